@@ -4,13 +4,14 @@ use std::sync::Arc;
 use arrow::array::{Array, new_empty_array};
 use arrow::datatypes::*;
 use arrow::record_batch::RecordBatch;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::io::AsyncReadExt;
 
 use super::builder::TypedBuilder;
 use super::deserialize::{ArrowDeserializerState, ClickHouseArrowDeserializer};
 use super::serialize::ClickHouseArrowSerializer;
 use super::types::arrow_to_ch_type;
 pub use super::types::{
+    DYNAMIC_NULL_FIELD_NAME, DYNAMIC_TYPES_METADATA_KEY, JSON_DYNAMIC_FIELD_NAME,
     LIST_ITEM_FIELD_NAME, MAP_FIELD_NAME, STRUCT_KEY_FIELD_NAME, STRUCT_VALUE_FIELD_NAME,
     TUPLE_FIELD_NAME_PREFIX,
 };
@@ -23,7 +24,9 @@ use crate::io::{ClickHouseBytesRead, ClickHouseBytesWrite, ClickHouseRead, Click
 use crate::native::block_info::BlockInfo;
 use crate::native::protocol::DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION;
 use crate::native::sparse::{
-    SparseDeserializeState, expand_sparse_array, read_sparse_offsets, read_sparse_offsets_sync,
+    SparseDeserializeState, expand_sparse_array, put_serialization_kind, put_sparse_offsets,
+    read_sparse_offsets, read_sparse_offsets_sync, sparse_array, write_serialization_kind,
+    write_sparse_offsets,
 };
 use crate::prelude::*;
 use crate::serialize::ClickHouseNativeSerializer;
@@ -67,8 +70,12 @@ impl ProtocolData<RecordBatch, ArrowDeserializerState> for RecordBatch {
         revision: u64,
         header: Option<&[(String, Type)]>,
         options: ArrowOptions,
+        sparse_ratio: Option<f64>,
     ) -> Result<()> {
         let schema = self.schema();
+        // Sparse serialization is negotiated along with custom serialization
+        let sparse_ratio = sparse_ratio
+            .filter(|_| revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION);
 
         if revision > 0 {
             BlockInfo::default().write_async(writer).await?;
@@ -83,7 +90,8 @@ impl ProtocolData<RecordBatch, ArrowDeserializerState> for RecordBatch {
             trace!(?header, columns, rows, "writing column data");
         }
 
-        let mut state = SerializerState::default().with_arrow_options(options);
+        let mut state =
+            SerializerState::default().with_arrow_options(options).with_revision(revision);
 
         // Convert and write each column
         for (i, field) in schema.fields().iter().enumerate() {
@@ -111,9 +119,8 @@ impl ProtocolData<RecordBatch, ArrowDeserializerState> for RecordBatch {
             // Write column type
             writer.write_string(type_.to_string()).await?;
 
-            if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION {
-                writer.write_u8(0).await?;
-            }
+            let sparse = sparse_array(type_, column, sparse_ratio)?;
+            write_serialization_kind(writer, revision, sparse.is_some()).await?;
 
             if column.is_empty() {
                 if debug_arrow() {
@@ -123,7 +130,12 @@ impl ProtocolData<RecordBatch, ArrowDeserializerState> for RecordBatch {
             }
 
             type_.serialize_prefix_async(writer, &mut state).await?;
-            type_.serialize_async(writer, column, data_type, &mut state).await?;
+            if let Some((positions, values)) = sparse {
+                write_sparse_offsets(writer, &positions, column.len()).await?;
+                type_.serialize_async(writer, &values, data_type, &mut state).await?;
+            } else {
+                type_.serialize_async(writer, column, data_type, &mut state).await?;
+            }
         }
 
         Ok(())
@@ -135,8 +147,12 @@ impl ProtocolData<RecordBatch, ArrowDeserializerState> for RecordBatch {
         revision: u64,
         header: Option<&[(String, Type)]>,
         options: ArrowOptions,
+        sparse_ratio: Option<f64>,
     ) -> Result<()> {
         let schema = self.schema();
+        // Sparse serialization is negotiated along with custom serialization
+        let sparse_ratio = sparse_ratio
+            .filter(|_| revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION);
 
         if revision > 0 {
             BlockInfo::default().write(writer)?;
@@ -151,7 +167,8 @@ impl ProtocolData<RecordBatch, ArrowDeserializerState> for RecordBatch {
             trace!(?header, columns, rows, "writing column data");
         }
 
-        let mut state = SerializerState::default().with_arrow_options(options);
+        let mut state =
+            SerializerState::default().with_arrow_options(options).with_revision(revision);
 
         // Convert and write each column
         for (i, field) in schema.fields().iter().enumerate() {
@@ -179,9 +196,8 @@ impl ProtocolData<RecordBatch, ArrowDeserializerState> for RecordBatch {
             // Write column type
             writer.put_string(type_.to_string())?;
 
-            if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION {
-                writer.put_u8(0);
-            }
+            let sparse = sparse_array(type_, column, sparse_ratio)?;
+            put_serialization_kind(writer, revision, sparse.is_some());
 
             if column.is_empty() {
                 if debug_arrow() {
@@ -191,7 +207,12 @@ impl ProtocolData<RecordBatch, ArrowDeserializerState> for RecordBatch {
             }
 
             type_.serialize_prefix(writer, &mut state);
-            type_.serialize(writer, column, data_type, &mut state)?;
+            if let Some((positions, values)) = sparse {
+                put_sparse_offsets(writer, &positions, column.len())?;
+                type_.serialize(writer, &values, data_type, &mut state)?;
+            } else {
+                type_.serialize(writer, column, data_type, &mut state)?;
+            }
         }
 
         Ok(())
@@ -310,7 +331,7 @@ impl ProtocolData<RecordBatch, ArrowDeserializerState> for RecordBatch {
                     let builder = if let Some(b) = builders.get_mut(i) {
                         b
                     } else {
-                        builders.push(TypedBuilder::try_new(&type_hint, dt)?);
+                        builders.push(TypedBuilder::try_new_column(&type_hint, dt, options)?);
                         builders.last_mut().unwrap()
                     };
 
@@ -335,7 +356,7 @@ impl ProtocolData<RecordBatch, ArrowDeserializerState> for RecordBatch {
                     let builder = if let Some(b) = builders.get_mut(i) {
                         b
                     } else {
-                        builders.push(TypedBuilder::try_new(&type_hint, dt)?);
+                        builders.push(TypedBuilder::try_new_column(&type_hint, dt, options)?);
                         builders.last_mut().unwrap()
                     };
 
@@ -350,6 +371,12 @@ impl ProtocolData<RecordBatch, ArrowDeserializerState> for RecordBatch {
                 new_empty_array(field.data_type())
             };
 
+            // The union of a `Dynamic` column lists the types found in this block
+            let field = if let Type::Dynamic { .. } = type_hint {
+                super::deserialize::dynamic::dynamic_field(field, &array)
+            } else {
+                field
+            };
             let _ = deser.push_array(array).push_field(Arc::new(field));
         }
 
@@ -452,7 +479,7 @@ impl ProtocolData<RecordBatch, ArrowDeserializerState> for RecordBatch {
                     let builder = if let Some(b) = builders.get_mut(i) {
                         b
                     } else {
-                        builders.push(TypedBuilder::try_new(&type_hint, dt)?);
+                        builders.push(TypedBuilder::try_new_column(&type_hint, dt, options)?);
                         builders.last_mut().unwrap()
                     };
 
@@ -470,7 +497,7 @@ impl ProtocolData<RecordBatch, ArrowDeserializerState> for RecordBatch {
                     let builder = if let Some(b) = builders.get_mut(i) {
                         b
                     } else {
-                        builders.push(TypedBuilder::try_new(&type_hint, dt)?);
+                        builders.push(TypedBuilder::try_new_column(&type_hint, dt, options)?);
                         builders.last_mut().unwrap()
                     };
 
@@ -527,7 +554,7 @@ mod tests {
         let mut buffer = Vec::new();
         batch
             .clone()
-            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options)
+            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
             .await
             .unwrap();
         assert!(!buffer.is_empty());
@@ -542,7 +569,7 @@ mod tests {
         let mut buffer = Vec::new();
         batch
             .clone()
-            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options)
+            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
             .await
             .unwrap();
 
@@ -567,6 +594,56 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_sparse_record_batch_roundtrip() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("count", DataType::Int64, false),
+            Field::new("label", DataType::Utf8, false),
+            Field::new("name", DataType::Utf8, true),
+        ]));
+        let batch = RecordBatch::try_new(schema, vec![
+            Arc::new(Int64Array::from(vec![0, 0, 5, 0, 0, 0, 3, 0])),
+            Arc::new(StringArray::from(vec!["", "", "", "x", "", "", "", ""])),
+            Arc::new(StringArray::from(vec![None, None, Some("a"), None, None, None, None, None])),
+        ])
+        .unwrap();
+
+        let arrow_options = ArrowOptions::default().with_strings_as_strings(true);
+        let mut full = Vec::new();
+        batch
+            .clone()
+            .write_async(&mut full, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
+            .await
+            .unwrap();
+        let mut sparse = Vec::new();
+        batch
+            .clone()
+            .write_async(&mut sparse, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, Some(0.5))
+            .await
+            .unwrap();
+        assert!(sparse.len() < full.len());
+
+        let mut synced = Vec::new();
+        batch
+            .clone()
+            .write(&mut synced, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, Some(0.5))
+            .unwrap();
+        assert_eq!(synced, sparse);
+
+        let mut state = DeserializerState::default().with_arrow_options(arrow_options);
+        let deserialized = RecordBatch::read_async(
+            &mut Cursor::new(sparse),
+            DBMS_TCP_PROTOCOL_VERSION,
+            arrow_options,
+            &mut state,
+        )
+        .await
+        .unwrap();
+        for i in 0..batch.num_columns() {
+            assert_eq!(deserialized.column(i).as_ref(), batch.column(i).as_ref());
+        }
+    }
+
     #[tokio::test]
     async fn test_serialize_empty_batch() {
         let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int32, false)]));
@@ -575,7 +652,13 @@ mod tests {
                 .unwrap();
         let mut buffer = Vec::new();
         batch
-            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, ArrowOptions::default())
+            .write_async(
+                &mut buffer,
+                DBMS_TCP_PROTOCOL_VERSION,
+                None,
+                ArrowOptions::default(),
+                None,
+            )
             .await
             .unwrap();
         assert!(!buffer.is_empty());
@@ -667,7 +750,7 @@ mod tests {
         let arrow_options = ArrowOptions::default();
         let mut buffer = Cursor::new(Vec::new());
         batch
-            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options)
+            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
             .await
             .unwrap();
 
@@ -716,7 +799,7 @@ mod tests {
         let arrow_options = ArrowOptions::default().with_strings_as_strings(true);
         let mut buffer = Cursor::new(Vec::new());
         batch
-            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options)
+            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
             .await
             .unwrap();
 
@@ -781,7 +864,7 @@ mod tests {
         let mut buffer = Cursor::new(Vec::new());
         batch
             .clone()
-            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options)
+            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
             .await
             .unwrap();
 
@@ -827,7 +910,7 @@ mod tests {
         let mut buffer = Cursor::new(Vec::new());
         batch
             .clone()
-            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options)
+            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
             .await
             .unwrap();
 
@@ -862,7 +945,7 @@ mod tests {
         let mut buffer = Cursor::new(Vec::new());
         batch
             .clone()
-            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options)
+            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
             .await
             .unwrap();
 
@@ -898,7 +981,7 @@ mod tests {
         let mut buffer = Cursor::new(Vec::new());
         batch
             .clone()
-            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, Some(&header), arrow_options)
+            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, Some(&header), arrow_options, None)
             .await
             .unwrap();
 
@@ -938,7 +1021,7 @@ mod tests {
         let mut buffer = Cursor::new(Vec::new());
         batch
             .clone()
-            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options)
+            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
             .await
             .unwrap();
 
@@ -976,7 +1059,7 @@ mod tests {
         let mut buffer = Cursor::new(Vec::new());
         batch
             .clone()
-            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options)
+            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
             .await
             .unwrap();
 
@@ -1018,7 +1101,7 @@ mod tests {
         let mut buffer = Cursor::new(Vec::new());
         batch
             .clone()
-            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options)
+            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
             .await
             .unwrap();
 
@@ -1062,7 +1145,7 @@ mod tests {
         let mut buffer = Cursor::new(Vec::new());
         batch
             .clone()
-            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options)
+            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
             .await
             .unwrap();
 
@@ -1109,7 +1192,7 @@ mod tests {
         let mut buffer = Cursor::new(Vec::new());
         batch
             .clone()
-            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options)
+            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
             .await
             .unwrap();
 
@@ -1152,7 +1235,7 @@ mod tests {
         let mut buffer = Cursor::new(Vec::new());
         batch
             .clone()
-            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options)
+            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
             .await
             .unwrap();
 
@@ -1211,7 +1294,7 @@ mod tests {
         let mut buffer = Cursor::new(Vec::new());
         batch
             .clone()
-            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, Some(&header), arrow_options)
+            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, Some(&header), arrow_options, None)
             .await
             .unwrap();
 
@@ -1275,7 +1358,7 @@ mod tests {
         let mut buffer = Cursor::new(Vec::new());
         batch
             .clone()
-            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, Some(&header), arrow_options)
+            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, Some(&header), arrow_options, None)
             .await
             .unwrap();
 
@@ -1324,7 +1407,7 @@ mod tests {
         let mut buffer = Cursor::new(Vec::new());
         batch
             .clone()
-            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options)
+            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
             .await
             .unwrap();
 
@@ -1374,7 +1457,7 @@ mod tests {
         let mut buffer = Cursor::new(Vec::new());
         batch
             .clone()
-            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options)
+            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
             .await
             .unwrap();
 
@@ -1427,7 +1510,7 @@ mod tests {
         let mut buffer = Cursor::new(Vec::new());
         batch
             .clone()
-            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options)
+            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
             .await
             .unwrap();
 
@@ -1469,7 +1552,7 @@ mod tests {
         let mut buffer = Cursor::new(Vec::new());
         batch
             .clone()
-            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options)
+            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
             .await
             .unwrap();
 
@@ -1508,7 +1591,7 @@ mod tests {
         let mut buffer = Cursor::new(Vec::new());
         batch
             .clone()
-            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options)
+            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
             .await
             .unwrap();
 
@@ -1549,7 +1632,7 @@ mod tests {
         let mut buffer = Cursor::new(Vec::new());
         batch
             .clone()
-            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options)
+            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
             .await
             .unwrap();
 
@@ -1587,7 +1670,7 @@ mod tests {
         let mut buffer = Cursor::new(Vec::new());
         let result = batch
             .clone()
-            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, Some(&header), arrow_options)
+            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, Some(&header), arrow_options, None)
             .await;
         assert!(matches!(
             result,
@@ -1617,7 +1700,7 @@ mod tests {
 
         let mut writer = Cursor::new(Vec::new());
         batch
-            .write_async(&mut writer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options)
+            .write_async(&mut writer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
             .await
             .unwrap();
         let output = writer.clone().into_inner();
@@ -1722,7 +1805,10 @@ mod tests_sync {
 
         let arrow_options = ArrowOptions::default();
         let mut buffer = Vec::new();
-        batch.clone().write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options).unwrap();
+        batch
+            .clone()
+            .write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
+            .unwrap();
         assert!(!buffer.is_empty());
     }
 
@@ -1733,7 +1819,10 @@ mod tests_sync {
 
         let arrow_options = ArrowOptions::default().with_strings_as_strings(true);
         let mut buffer = Vec::new();
-        batch.clone().write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options).unwrap();
+        batch
+            .clone()
+            .write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
+            .unwrap();
 
         // Deserialize back
         let mut state = DeserializerState::default().with_arrow_options(arrow_options);
@@ -1759,7 +1848,7 @@ mod tests_sync {
                 .unwrap();
         let mut buffer = Vec::new();
         batch
-            .write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, ArrowOptions::default())
+            .write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, ArrowOptions::default(), None)
             .unwrap();
         assert!(!buffer.is_empty());
     }
@@ -1842,7 +1931,7 @@ mod tests_sync {
 
         let arrow_options = ArrowOptions::default();
         let mut buffer = Vec::new();
-        batch.write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options).unwrap();
+        batch.write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None).unwrap();
 
         let mut state = DeserializerState::default().with_arrow_options(arrow_options);
         let mut reader = Cursor::new(buffer);
@@ -1883,7 +1972,7 @@ mod tests_sync {
 
         let arrow_options = ArrowOptions::default().with_strings_as_strings(true);
         let mut buffer = Vec::new();
-        batch.write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options).unwrap();
+        batch.write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None).unwrap();
 
         let mut state = DeserializerState::default().with_arrow_options(arrow_options);
         let mut reader = Cursor::new(buffer);
@@ -1939,7 +2028,10 @@ mod tests_sync {
 
         let arrow_options = ArrowOptions::default().with_strings_as_strings(true);
         let mut buffer = Vec::new();
-        batch.clone().write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options).unwrap();
+        batch
+            .clone()
+            .write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
+            .unwrap();
 
         let mut state = DeserializerState::default().with_arrow_options(arrow_options);
         let mut reader = Cursor::new(buffer);
@@ -1976,7 +2068,10 @@ mod tests_sync {
 
         let arrow_options = ArrowOptions::default().with_strings_as_strings(true);
         let mut buffer = Vec::new();
-        batch.clone().write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options).unwrap();
+        batch
+            .clone()
+            .write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
+            .unwrap();
 
         let mut state = DeserializerState::default().with_arrow_options(arrow_options);
         let mut reader = Cursor::new(buffer);
@@ -2002,7 +2097,10 @@ mod tests_sync {
 
         let arrow_options = ArrowOptions::default().with_strings_as_strings(true);
         let mut buffer = Vec::new();
-        batch.clone().write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options).unwrap();
+        batch
+            .clone()
+            .write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
+            .unwrap();
 
         let mut state = DeserializerState::default().with_arrow_options(arrow_options);
         let mut reader = Cursor::new(buffer);
@@ -2031,7 +2129,7 @@ mod tests_sync {
         let mut buffer = Vec::new();
         batch
             .clone()
-            .write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, Some(&header), arrow_options)
+            .write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, Some(&header), arrow_options, None)
             .unwrap();
 
         let mut state = DeserializerState::default().with_arrow_options(arrow_options);
@@ -2063,7 +2161,10 @@ mod tests_sync {
 
         let arrow_options = ArrowOptions::default().with_strings_as_strings(false);
         let mut buffer = Vec::new();
-        batch.clone().write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options).unwrap();
+        batch
+            .clone()
+            .write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
+            .unwrap();
 
         let mut state = DeserializerState::default().with_arrow_options(arrow_options);
         let mut reader = Cursor::new(buffer);
@@ -2092,7 +2193,10 @@ mod tests_sync {
 
         let arrow_options = ArrowOptions::default().with_strings_as_strings(true);
         let mut buffer = Vec::new();
-        batch.clone().write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options).unwrap();
+        batch
+            .clone()
+            .write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
+            .unwrap();
 
         let mut state = DeserializerState::default().with_arrow_options(arrow_options);
         let mut reader = Cursor::new(buffer);
@@ -2125,7 +2229,10 @@ mod tests_sync {
 
         let arrow_options = ArrowOptions::default().with_strings_as_strings(true);
         let mut buffer = Vec::new();
-        batch.clone().write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options).unwrap();
+        batch
+            .clone()
+            .write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
+            .unwrap();
 
         let mut state = DeserializerState::default().with_arrow_options(arrow_options);
         let mut reader = Cursor::new(buffer);
@@ -2160,7 +2267,10 @@ mod tests_sync {
 
         let arrow_options = ArrowOptions::default().with_strings_as_strings(true);
         let mut buffer = Vec::new();
-        batch.clone().write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options).unwrap();
+        batch
+            .clone()
+            .write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
+            .unwrap();
 
         let mut state = DeserializerState::default().with_arrow_options(arrow_options);
         let mut reader = Cursor::new(buffer);
@@ -2198,7 +2308,10 @@ mod tests_sync {
 
         let arrow_options = ArrowOptions::default().with_strings_as_strings(true);
         let mut buffer = Vec::new();
-        batch.clone().write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options).unwrap();
+        batch
+            .clone()
+            .write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
+            .unwrap();
 
         let mut state = DeserializerState::default().with_arrow_options(arrow_options);
         let mut reader = Cursor::new(buffer);
@@ -2232,7 +2345,10 @@ mod tests_sync {
 
         let arrow_options = ArrowOptions::default().with_strings_as_strings(true);
         let mut buffer = Vec::new();
-        batch.clone().write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options).unwrap();
+        batch
+            .clone()
+            .write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
+            .unwrap();
 
         let mut state = DeserializerState::default().with_arrow_options(arrow_options);
         let mut reader = Cursor::new(buffer);
@@ -2284,7 +2400,7 @@ mod tests_sync {
         let mut buffer = Vec::new();
         batch
             .clone()
-            .write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, Some(&header), arrow_options)
+            .write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, Some(&header), arrow_options, None)
             .unwrap();
 
         let mut state = DeserializerState::default().with_arrow_options(arrow_options);
@@ -2342,7 +2458,7 @@ mod tests_sync {
         let mut buffer = Vec::new();
         batch
             .clone()
-            .write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, Some(&header), arrow_options)
+            .write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, Some(&header), arrow_options, None)
             .unwrap();
 
         let mut state = DeserializerState::default().with_arrow_options(arrow_options);
@@ -2383,7 +2499,10 @@ mod tests_sync {
 
         let arrow_options = ArrowOptions::default().with_strings_as_strings(true);
         let mut buffer = Vec::new();
-        batch.clone().write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options).unwrap();
+        batch
+            .clone()
+            .write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
+            .unwrap();
 
         let mut state = DeserializerState::default().with_arrow_options(arrow_options);
         let mut reader = Cursor::new(buffer);
@@ -2424,7 +2543,10 @@ mod tests_sync {
 
         let arrow_options = ArrowOptions::default().with_strings_as_strings(true);
         let mut buffer = Vec::new();
-        batch.clone().write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options).unwrap();
+        batch
+            .clone()
+            .write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
+            .unwrap();
 
         let mut state = DeserializerState::default().with_arrow_options(arrow_options);
         let mut reader = Cursor::new(buffer);
@@ -2468,7 +2590,10 @@ mod tests_sync {
 
         let arrow_options = ArrowOptions::default().with_strings_as_strings(true);
         let mut buffer = Vec::new();
-        batch.clone().write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options).unwrap();
+        batch
+            .clone()
+            .write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
+            .unwrap();
 
         let mut state = DeserializerState::default().with_arrow_options(arrow_options);
         let mut reader = Cursor::new(buffer);
@@ -2501,7 +2626,10 @@ mod tests_sync {
 
         let arrow_options = ArrowOptions::default().with_strings_as_strings(true);
         let mut buffer = Vec::new();
-        batch.clone().write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options).unwrap();
+        batch
+            .clone()
+            .write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
+            .unwrap();
 
         let mut state = DeserializerState::default().with_arrow_options(arrow_options);
         let mut reader = Cursor::new(buffer);
@@ -2531,7 +2659,10 @@ mod tests_sync {
 
         let arrow_options = ArrowOptions::default().with_strings_as_strings(false);
         let mut buffer = Vec::new();
-        batch.clone().write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options).unwrap();
+        batch
+            .clone()
+            .write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
+            .unwrap();
 
         let mut state = DeserializerState::default().with_arrow_options(arrow_options);
         let mut reader = Cursor::new(buffer);
@@ -2563,7 +2694,10 @@ mod tests_sync {
 
         let arrow_options = ArrowOptions::default().with_strings_as_strings(true);
         let mut buffer = Vec::new();
-        batch.clone().write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options).unwrap();
+        batch
+            .clone()
+            .write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None)
+            .unwrap();
 
         let mut state = DeserializerState::default().with_arrow_options(arrow_options);
         let mut reader = Cursor::new(buffer);
@@ -2597,6 +2731,7 @@ mod tests_sync {
             DBMS_TCP_PROTOCOL_VERSION,
             Some(&header),
             arrow_options,
+            None,
         );
         assert!(matches!(
             result,
@@ -2625,7 +2760,7 @@ mod tests_sync {
         .expect("Failed to create RecordBatch");
 
         let mut writer = Vec::new();
-        batch.write(&mut writer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options).unwrap();
+        batch.write(&mut writer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options, None).unwrap();
         let output = writer.clone();
         let expected = vec![
             1, 2, 2, 255, 255, 255, 255, 0,  // BlockInfo
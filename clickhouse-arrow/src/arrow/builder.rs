@@ -8,6 +8,7 @@ use arrow::array::*;
 use arrow::datatypes::*;
 use strum::AsRefStr;
 
+use crate::ArrowOptions;
 use crate::constants::CLICKHOUSE_DEFAULT_CHUNK_ROWS;
 use crate::prelude::*;
 
@@ -122,9 +123,26 @@ pub(crate) enum TypedBuilder {
     // Complex types
     Map((Box<TypedBuilder>, Box<TypedBuilder>)),
     Tuple(Vec<TypedBuilder>),
+    /// Builders of the typed paths of a `JSON` column
+    Json(Vec<TypedBuilder>),
+    /// Options mapping the types found in a `Dynamic` column, builders are created per block
+    Dynamic(ArrowOptions),
 }
 
 impl TypedBuilder {
+    /// Create the builder of a block column. Types found in the data, like the variants of
+    /// `Dynamic`, are mapped with `options`.
+    pub(crate) fn try_new_column(
+        type_: &Type,
+        data_type: &DataType,
+        options: ArrowOptions,
+    ) -> Result<Self> {
+        if let Type::Dynamic { .. } = type_.strip_null() {
+            return Ok(Self::Dynamic(options));
+        }
+        Self::try_new(type_, data_type)
+    }
+
     #[expect(clippy::too_many_lines)]
     #[expect(clippy::cast_possible_wrap)]
     #[expect(clippy::cast_possible_truncation)]
@@ -165,6 +183,29 @@ impl TypedBuilder {
             ));
         }
 
+        if let Type::Json(json) = type_ {
+            let DataType::Struct(fields) = data_type else {
+                return Err(Error::ArrowDeserialize(format!(
+                    "Unexpected datatype for JSON: {data_type:?}",
+                )));
+            };
+            return Ok(Self::Json(
+                json.typed_paths
+                    .iter()
+                    .map(|(path, t)| {
+                        let (_, field) = fields.find(path).ok_or_else(|| {
+                            Error::ArrowDeserialize(format!("Missing field for JSON path {path}"))
+                        })?;
+                        TypedBuilder::try_new(t, field.data_type())
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+            ));
+        }
+
+        if let Type::Dynamic { .. } = type_ {
+            return Ok(Self::Dynamic(ArrowOptions::default()));
+        }
+
         if let Type::Map(key, value) = type_ {
             let (kfield, vfield) = map::get_map_fields(data_type)?;
             let kbuilder = Box::new(TypedBuilder::try_new(key, kfield.data_type())?);
@@ -297,6 +338,7 @@ impl std::fmt::Debug for TypedBuilder {
             Self::LowCardinality(l) => write!(f, "TypedBuilder::LowCardinality({l:?})"),
             Self::Map((k, v)) => write!(f, "TypedBuilder::Map({k:?}, {v:?})"),
             Self::Tuple(t) => write!(f, "TypedBuilder::Tuple({t:?})"),
+            Self::Json(t) => write!(f, "TypedBuilder::Json({t:?})"),
             Self::String(_) => write!(f, "TypedBuilder::String"),
             b => write!(f, "TypedBuilder::{}", b.as_ref()),
         }
@@ -11,7 +11,9 @@
 /// maps, and tuples. Each module processes the input data from a `ClickHouseRead` reader,
 /// respecting nullability and maintaining deserialization state.
 mod binary;
+pub(crate) mod dynamic;
 mod enums;
+mod json;
 mod list;
 mod low_cardinality;
 mod map;
@@ -302,6 +304,13 @@ impl ClickHouseArrowDeserializer for Type {
         nulls: &[u8],
         rbuffer: &mut Vec<u8>,
     ) -> Result<ArrayRef> {
+        // The prefix of these columns is read with the data, nested they are not supported yet
+        if self.has_dynamic_structure() && !matches!(self, Type::Json(_) | Type::Dynamic { .. }) {
+            return Err(Error::Unimplemented(format!(
+                "Arrow deserialization not implemented for {self}"
+            )));
+        }
+
         Ok(match self {
             // Primitive types
             Type::Int8
@@ -374,6 +383,12 @@ impl ClickHouseArrowDeserializer for Type {
             Type::Tuple(inner) => Box::pin(
                 tuple::deserialize_async(inner, builder, data_type, reader, rows, nulls, rbuffer)
             ).await?,
+            Type::Json(json) => Box::pin(
+                json::deserialize_async(json, builder, data_type, reader, rows, rbuffer)
+            ).await?,
+            Type::Dynamic { .. } => Box::pin(
+                dynamic::deserialize_async(builder, data_type, reader, rows, rbuffer)
+            ).await?,
             // Geo types
             Type::Polygon | Type::MultiPolygon | Type::Point | Type::Ring => {
                 // Geo types should be converted earlier, this is a fallback
@@ -390,7 +405,6 @@ impl ClickHouseArrowDeserializer for Type {
             }
             // DFE Fork: New types - Arrow deserialization not yet implemented
             Type::Variant(_)
-            | Type::Dynamic { .. }
             | Type::Nested(_)
             | Type::Time
             | Type::Time64(_)
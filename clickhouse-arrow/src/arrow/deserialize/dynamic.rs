@@ -0,0 +1,298 @@
+/// Deserialization logic for `ClickHouse` `Dynamic` types into Arrow arrays.
+///
+/// `Dynamic` columns are read either as `Utf8`, each value rendered as JSON text, or as a
+/// dense `Union` with one field per type found in the block, named by the `ClickHouse` type
+/// name. Values stored in the shared variant are decoded and added to the field of their type.
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use arrow::array::*;
+use arrow::buffer::ScalarBuffer;
+use arrow::datatypes::{DataType, Field, UnionFields, UnionMode};
+use tokio::io::AsyncReadExt;
+
+use super::ClickHouseArrowDeserializer;
+use crate::arrow::builder::TypedBuilder;
+use crate::arrow::types::{
+    DYNAMIC_NULL_FIELD_NAME, DYNAMIC_TYPES_METADATA_KEY, ch_to_arrow_type, normalize_type,
+};
+use crate::deserialize::ClickHouseNativeDeserializer;
+use crate::formats::{DeserializerState, SerializerState};
+use crate::io::ClickHouseRead;
+use crate::native::types::binary;
+use crate::native::types::deserialize::dynamic::{
+    DynamicPrefix, SHARED_VARIANT, StructurePrefix, read_data, read_dynamic_data,
+    read_dynamic_prefix,
+};
+use crate::native::types::json_type::{is_bool_type, write_json_string, write_json_value};
+use crate::native::types::serialize::variant::NULL_DISCRIMINATOR;
+use crate::serialize::ClickHouseNativeSerializer;
+use crate::{ArrowOptions, Error, Result, Type, Value};
+
+/// Deserializes a `ClickHouse` `Dynamic` column, prefix included, into an Arrow array.
+///
+/// # Errors
+/// - Returns `ArrowDeserialize` if the data type or builder do not match the `Dynamic` type, or a
+///   block holds more types than an Arrow union supports.
+/// - Returns `Io` if reading from the reader fails.
+pub(super) async fn deserialize_async<R: ClickHouseRead>(
+    builder: &mut TypedBuilder,
+    data_type: &DataType,
+    reader: &mut R,
+    rows: usize,
+    rbuffer: &mut Vec<u8>,
+) -> Result<ArrayRef> {
+    let TypedBuilder::Dynamic(options) = builder else {
+        return Err(Error::ArrowDeserialize(format!(
+            "Unexpected Dynamic builder: {}",
+            builder.as_ref()
+        )));
+    };
+    let options = *options;
+
+    let prefix = read_dynamic_prefix(reader).await?;
+    match data_type {
+        DataType::Utf8 => {
+            let mut array = StringBuilder::with_capacity(rows, rows * 8);
+            let mut text = String::new();
+            for value in read_dynamic_data(&prefix, reader, rows).await? {
+                let Value::Dynamic(name, value) = value else {
+                    array.append_null();
+                    continue;
+                };
+                text.clear();
+                write_json_value(&value, is_bool_type(&name), &mut text);
+                array.append_value(&text);
+            }
+            Ok(Arc::new(array.finish()))
+        }
+        DataType::Union(_, UnionMode::Dense) => {
+            deserialize_union(&prefix, reader, rows, options, rbuffer).await
+        }
+        _ => Err(Error::ArrowDeserialize(format!("Unsupported Dynamic datatype: {data_type:?}"))),
+    }
+}
+
+/// Field of a union column, collecting the values of one type.
+struct UnionChild {
+    name:  String,
+    parts: Vec<ArrayRef>,
+    len:   usize,
+}
+
+impl UnionChild {
+    fn new(name: String) -> Self { Self { name, parts: vec![], len: 0 } }
+
+    fn push(&mut self, array: ArrayRef) {
+        self.len += array.len();
+        self.parts.push(array);
+    }
+}
+
+async fn deserialize_union<R: ClickHouseRead>(
+    prefix: &DynamicPrefix,
+    reader: &mut R,
+    rows: usize,
+    options: ArrowOptions,
+    rbuffer: &mut Vec<u8>,
+) -> Result<ArrayRef> {
+    let mut discriminators = vec![0u8; rows];
+    let _ = reader.read_exact(&mut discriminators).await?;
+
+    let mut counts = vec![0usize; prefix.variants.len()];
+    for &discriminator in &discriminators {
+        if let Some(count) = counts.get_mut(usize::from(discriminator)) {
+            *count += 1;
+        } else if discriminator != NULL_DISCRIMINATOR {
+            return Err(Error::ArrowDeserialize(format!(
+                "invalid Dynamic discriminator {discriminator}"
+            )));
+        }
+    }
+
+    // Variants keep their order, types only found in the shared variant are appended
+    let mut children: Vec<UnionChild> = Vec::with_capacity(prefix.variants.len());
+    let mut variant_child = vec![None; prefix.variants.len()];
+    let mut shared = Vec::new();
+    for (i, ((name, type_, variant_prefix), count)) in
+        prefix.variants.iter().zip(&counts).enumerate()
+    {
+        let array = if name == SHARED_VARIANT {
+            for value in Type::String
+                .deserialize_column(reader, *count, &mut DeserializerState::default())
+                .await?
+            {
+                let Value::String(encoded) = value else {
+                    return Err(Error::ArrowDeserialize("invalid shared variant value".into()));
+                };
+                let mut encoded = encoded.as_slice();
+                let name = binary::decode_type(&mut encoded)?;
+                let value = binary::decode_value(&Type::from_str(&name)?, &mut encoded)?;
+                shared.push((name, value));
+            }
+            continue;
+        } else if matches!(variant_prefix, StructurePrefix::Plain) {
+            let (data_type, _) = ch_to_arrow_type(type_, Some(options))?;
+            let type_hint = normalize_type(type_, &data_type).unwrap_or_else(|| type_.clone());
+            let mut builder = TypedBuilder::try_new_column(&type_hint, &data_type, options)?;
+            type_hint
+                .deserialize_arrow_async(&mut builder, reader, &data_type, *count, &[], rbuffer)
+                .await?
+        } else {
+            let values = read_data(type_, variant_prefix, reader, *count).await?;
+            Box::pin(values_to_arrow(type_, values, options, rbuffer)).await?
+        };
+        variant_child[i] = Some(children.len());
+        children.push(UnionChild { name: name.clone(), parts: vec![array], len: *count });
+    }
+
+    // Values of the shared variant are grouped by type and appended to the matching child
+    let (shared_groups, shared_rows) = group_shared(shared);
+    let mut shared_rows = shared_rows.into_iter();
+    let mut group_start = Vec::with_capacity(shared_groups.len());
+    for (name, values) in shared_groups {
+        let type_ = Type::from_str(&name)?;
+        let child =
+            position_or_push(&mut children, |c| c.name == name, || UnionChild::new(name.clone()));
+        group_start.push((child, children[child].len));
+        let array = Box::pin(values_to_arrow(&type_, values, options, rbuffer)).await?;
+        children[child].push(array);
+    }
+
+    // NULL rows reference a null field, since dense unions have no validity of their own
+    let null_child = discriminators.contains(&NULL_DISCRIMINATOR).then(|| {
+        children.push(UnionChild::new(DYNAMIC_NULL_FIELD_NAME.into()));
+        children.len() - 1
+    });
+
+    let mut type_ids = Vec::with_capacity(rows);
+    let mut offsets = Vec::with_capacity(rows);
+    let mut positions = vec![0usize; prefix.variants.len()];
+    let mut nulls = 0;
+    for discriminator in discriminators {
+        let index = usize::from(discriminator);
+        let (child, offset) = if discriminator == NULL_DISCRIMINATOR {
+            nulls += 1;
+            (null_child.unwrap_or_default(), nulls - 1)
+        } else if let Some(child) = variant_child[index] {
+            positions[index] += 1;
+            (child, positions[index] - 1)
+        } else {
+            let (group, position) = shared_rows.next().unwrap_or_default();
+            let (child, start) = group_start[group];
+            (child, start + position)
+        };
+        type_ids.push(i8::try_from(child).map_err(|_| {
+            Error::ArrowDeserialize(format!(
+                "Dynamic block holds {} types, more than an Arrow union supports",
+                children.len()
+            ))
+        })?);
+        offsets.push(i32::try_from(offset).map_err(|_| {
+            Error::ArrowDeserialize(format!("Dynamic union offset {offset} overflows"))
+        })?);
+    }
+    if let Some(null_child) = null_child {
+        children[null_child].push(Arc::new(NullArray::new(nulls)));
+    }
+
+    finish_union(children, type_ids, offsets)
+}
+
+/// Type name and values of one type.
+type TypeGroup = (String, Vec<Value>);
+
+/// Group the values of the shared variant by type. Returns the groups and, per value, its group
+/// and position within the group.
+fn group_shared(shared: Vec<(String, Value)>) -> (Vec<TypeGroup>, Vec<(usize, usize)>) {
+    let mut groups: Vec<TypeGroup> = Vec::new();
+    let mut rows = Vec::with_capacity(shared.len());
+    for (name, value) in shared {
+        let group = position_or_push(&mut groups, |(n, _)| *n == name, || (name.clone(), vec![]));
+        rows.push((group, groups[group].1.len()));
+        groups[group].1.push(value);
+    }
+    (groups, rows)
+}
+
+fn position_or_push<T>(
+    items: &mut Vec<T>,
+    find: impl Fn(&T) -> bool,
+    new: impl FnOnce() -> T,
+) -> usize {
+    items.iter().position(find).unwrap_or_else(|| {
+        items.push(new());
+        items.len() - 1
+    })
+}
+
+fn finish_union(
+    children: Vec<UnionChild>,
+    type_ids: Vec<i8>,
+    offsets: Vec<i32>,
+) -> Result<ArrayRef> {
+    let mut fields = Vec::with_capacity(children.len());
+    let mut arrays = Vec::with_capacity(children.len());
+    for (type_id, child) in (0_i8..).zip(children) {
+        let array = match child.parts.as_slice() {
+            [array] => Arc::clone(array),
+            parts => {
+                let parts = parts.iter().map(AsRef::as_ref).collect::<Vec<_>>();
+                arrow::compute::concat(&parts)?
+            }
+        };
+        fields.push((type_id, Arc::new(Field::new(child.name, array.data_type().clone(), true))));
+        arrays.push(array);
+    }
+    Ok(Arc::new(UnionArray::try_new(
+        fields.into_iter().collect::<UnionFields>(),
+        ScalarBuffer::from(type_ids),
+        Some(ScalarBuffer::from(offsets)),
+        arrays,
+    )?))
+}
+
+/// Convert native values of a type into an Arrow array, by writing them in the native format and
+/// reading them back through the Arrow deserializer.
+async fn values_to_arrow(
+    type_: &Type,
+    values: Vec<Value>,
+    options: ArrowOptions,
+    rbuffer: &mut Vec<u8>,
+) -> Result<ArrayRef> {
+    let rows = values.len();
+    let mut state = SerializerState::default();
+    let mut buffer = Vec::new();
+    type_.serialize_prefix(&mut buffer, &mut state);
+    type_.serialize_column_sync(values, &mut buffer, &mut state)?;
+
+    let (data_type, _) = ch_to_arrow_type(type_, Some(options))?;
+    let type_hint = normalize_type(type_, &data_type).unwrap_or_else(|| type_.clone());
+    let mut reader = Cursor::new(buffer);
+    type_hint.deserialize_prefix_async(&mut reader, &mut DeserializerState::default()).await?;
+    let mut builder = TypedBuilder::try_new_column(&type_hint, &data_type, options)?;
+    type_hint
+        .deserialize_arrow_async(&mut builder, &mut reader, &data_type, rows, &[], rbuffer)
+        .await
+}
+
+/// Update the field of a `Dynamic` column to the union read from a block, recording its type list
+/// in the field metadata under [`DYNAMIC_TYPES_METADATA_KEY`].
+pub(crate) fn dynamic_field(field: Field, array: &ArrayRef) -> Field {
+    let DataType::Union(fields, _) = array.data_type() else {
+        return field;
+    };
+    let mut types = String::from("[");
+    for (_, union_field) in fields.iter().filter(|(_, f)| f.name() != DYNAMIC_NULL_FIELD_NAME) {
+        if types.len() > 1 {
+            types.push(',');
+        }
+        write_json_string(union_field.name().as_bytes(), &mut types);
+    }
+    types.push(']');
+    field
+        .with_data_type(array.data_type().clone())
+        .with_metadata(HashMap::from([(DYNAMIC_TYPES_METADATA_KEY.to_string(), types)]))
+}
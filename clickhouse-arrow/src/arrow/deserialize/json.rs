@@ -0,0 +1,71 @@
+/// Deserialization logic for `ClickHouse` `JSON` types into Arrow `StructArray`.
+///
+/// Typed paths are read into their own fields, the dynamic paths and shared data are rendered
+/// as JSON text into the [`JSON_DYNAMIC_FIELD_NAME`] field.
+use std::sync::Arc;
+
+use arrow::array::*;
+use arrow::datatypes::DataType;
+
+use super::ClickHouseArrowDeserializer;
+use crate::arrow::builder::TypedBuilder;
+use crate::arrow::types::JSON_DYNAMIC_FIELD_NAME;
+use crate::io::ClickHouseRead;
+use crate::native::types::JsonType;
+use crate::native::types::deserialize::json::{read_json_prefix, read_json_remainder};
+use crate::native::types::json_type::JsonObjectBuilder;
+use crate::{Error, Result};
+
+/// Deserializes a `ClickHouse` `JSON` column, prefix included, into an Arrow `StructArray`.
+///
+/// # Errors
+/// - Returns `ArrowDeserialize` if the data type or builder do not match the `JSON` type, or the
+///   server sent the documents as strings.
+/// - Returns `Io` if reading from the reader fails.
+pub(super) async fn deserialize_async<R: ClickHouseRead>(
+    json: &JsonType,
+    builder: &mut TypedBuilder,
+    data_type: &DataType,
+    reader: &mut R,
+    rows: usize,
+    rbuffer: &mut Vec<u8>,
+) -> Result<ArrayRef> {
+    let DataType::Struct(fields) = data_type else {
+        return Err(Error::ArrowDeserialize(format!("Unsupported JSON datatype: {data_type:?}")));
+    };
+    let TypedBuilder::Json(builders) = builder else {
+        return Err(Error::ArrowDeserialize(format!(
+            "Unexpected JSON builder: {}",
+            builder.as_ref()
+        )));
+    };
+
+    let prefix = read_json_prefix(json, reader).await?;
+    if prefix.as_string {
+        return Err(Error::ArrowDeserialize(
+            "JSON columns serialized as strings cannot be read into a struct, disable \
+             `output_format_native_write_json_as_string`"
+                .into(),
+        ));
+    }
+
+    let mut arrays = Vec::with_capacity(fields.len());
+    for (b, (path, inner_type)) in builders.iter_mut().zip(&json.typed_paths) {
+        let (_, field) = fields.find(path).ok_or_else(|| {
+            Error::ArrowDeserialize(format!("Missing field for JSON path {path}"))
+        })?;
+        let data_type = field.data_type();
+        arrays.push(
+            inner_type.deserialize_arrow_async(b, reader, data_type, rows, &[], rbuffer).await?,
+        );
+    }
+
+    let mut objects = (0..rows).map(|_| JsonObjectBuilder::default()).collect::<Vec<_>>();
+    read_json_remainder(&prefix, reader, &mut objects).await?;
+    arrays.push(Arc::new(StringArray::from_iter_values(
+        objects.into_iter().map(JsonObjectBuilder::finish),
+    )));
+
+    debug_assert_eq!(fields.last().map(|f| f.name().as_str()), Some(JSON_DYNAMIC_FIELD_NAME));
+    Ok(Arc::new(StructArray::try_new(fields.clone(), arrays, None)?))
+}
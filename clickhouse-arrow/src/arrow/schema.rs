@@ -2,10 +2,10 @@ use std::collections::HashMap;
 use std::str::FromStr;
 use std::sync::Arc;
 
-use arrow::array::AsArray;
+use arrow::array::{AsArray, RecordBatch};
 use arrow::compute::cast;
 use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
-use futures_util::stream::StreamExt;
+use futures_util::stream::{Stream, StreamExt};
 
 use super::utils::array_to_string_iter;
 use crate::ArrowOptions;
@@ -94,8 +94,17 @@ pub(crate) async fn fetch_schema(
     qid: Option<Qid>,
     options: ArrowOptions,
 ) -> Result<HashMap<String, SchemaRef>> {
-    let query = if tables.is_empty() {
-        format!("SELECT table, name, type FROM system.columns WHERE database = '{database}'")
+    let query = schema_query(Some(database), tables);
+    let stream = client.query(query, qid).await?;
+    collect_schemas(stream, options).await
+}
+
+/// Builds the `system.columns` query for [`fetch_schema`]. Without a database, the current
+/// database of the session is used.
+pub(crate) fn schema_query(database: Option<&str>, tables: &[&str]) -> String {
+    let database = database.map_or("currentDatabase()".to_string(), |db| format!("'{db}'"));
+    if tables.is_empty() {
+        format!("SELECT table, name, type FROM system.columns WHERE database = {database}")
     } else {
         let table_list = tables
             .iter()
@@ -103,12 +112,20 @@ pub(crate) async fn fetch_schema(
             .collect::<Vec<_>>()
             .join(",");
         format!(
-            "SELECT table, name, type FROM system.columns WHERE database = '{database}' AND table \
+            "SELECT table, name, type FROM system.columns WHERE database = {database} AND table \
              IN ({table_list})",
         )
-    };
+    }
+}
 
-    let mut stream = client.query(query, qid).await?;
+/// Collects the `(table, name, type)` rows of a [`schema_query`] into Arrow schemas per table.
+pub(crate) async fn collect_schemas<S>(
+    mut stream: S,
+    options: ArrowOptions,
+) -> Result<HashMap<String, SchemaRef>>
+where
+    S: Stream<Item = Result<RecordBatch>> + Unpin,
+{
     let mut schemas: HashMap<String, Vec<Field>> = HashMap::new();
 
     // Collect column metadata from the stream
@@ -150,3 +167,21 @@ pub(crate) async fn fetch_schema(
         .map(|(table, columns)| (table, Arc::new(Schema::new(columns))))
         .collect())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_query() {
+        assert_eq!(
+            schema_query(Some("db"), &[]),
+            "SELECT table, name, type FROM system.columns WHERE database = 'db'"
+        );
+        assert_eq!(
+            schema_query(None, &["`a`", "b"]),
+            "SELECT table, name, type FROM system.columns WHERE database = currentDatabase() AND \
+             table IN ('a','b')"
+        );
+    }
+}
@@ -1,5 +1,7 @@
 mod binary;
+mod dynamic;
 mod enums;
+mod json;
 mod list;
 mod low_cardinality;
 mod map;
@@ -105,6 +107,12 @@ impl ClickHouseArrowSerializer for Type {
     ) -> Result<()> {
         let base_type = self.strip_null();
 
+        if self.has_dynamic_structure() && !matches!(self, Type::Json(_) | Type::Dynamic { .. }) {
+            return Err(Error::Unimplemented(format!(
+                "Arrow serialization not implemented for {self}"
+            )));
+        }
+
         // v0.4.0: Use vectored I/O for nullable standard primitives (15-25% syscall reduction)
         // Combines null bitmap + values into single write_vectored call
         if self.is_nullable() {
@@ -203,6 +211,8 @@ impl ClickHouseArrowSerializer for Type {
             Type::Tuple(_) => {
                 Box::pin(tuple::serialize_async(self, writer, column, state)).await?;
             }
+            Type::Json(_) => json::serialize_async(self, writer, column, state).await?,
+            Type::Dynamic { .. } => dynamic::serialize_async(self, writer, column, state).await?,
             Type::Ring | Type::Polygon | Type::Point | Type::MultiPolygon => {
                 // Type should be converted earlier, if not this is a fallback
                 let normalized = normalize_geo_type(base_type).unwrap();
@@ -212,7 +222,6 @@ impl ClickHouseArrowSerializer for Type {
             Type::Nullable(_) => unreachable!(),
             // DFE Fork: New types - Arrow serialization not yet implemented
             Type::Variant(_)
-            | Type::Dynamic { .. }
             | Type::Nested(_)
             | Type::BFloat16
             | Type::Time
@@ -240,6 +249,12 @@ impl ClickHouseArrowSerializer for Type {
         // requires it.
         let base_type = self.strip_null();
 
+        if self.has_dynamic_structure() && !matches!(self, Type::Json(_) | Type::Dynamic { .. }) {
+            return Err(Error::Unimplemented(format!(
+                "Arrow serialization not implemented for {self}"
+            )));
+        }
+
         if self.is_nullable() {
             null::serialize_nulls(self, writer, column, state);
         }
@@ -299,6 +314,8 @@ impl ClickHouseArrowSerializer for Type {
             Type::Tuple(_) => {
                 tuple::serialize(self, writer, column, state)?;
             }
+            Type::Json(_) => json::serialize(self, writer, column, state)?,
+            Type::Dynamic { .. } => dynamic::serialize(self, writer, column, state)?,
             Type::Ring | Type::Polygon | Type::Point | Type::MultiPolygon => {
                 // Type should be converted earlier, if not this is a fallback
                 let normalized = normalize_geo_type(base_type).unwrap();
@@ -308,7 +325,6 @@ impl ClickHouseArrowSerializer for Type {
             Type::Nullable(_) => unreachable!(),
             // DFE Fork: New types - Arrow serialization not yet implemented
             Type::Variant(_)
-            | Type::Dynamic { .. }
             | Type::Nested(_)
             | Type::BFloat16
             | Type::Time
@@ -826,6 +842,133 @@ mod tests {
         cursor.read_exact(&mut bytes).unwrap();
         assert_eq!(u64::from_le_bytes(bytes), 4);
     }
+
+    /// Tests that `JSON` documents written from strings read back as a struct of typed paths and
+    /// the remainder, and that the struct can be written again.
+    #[cfg(feature = "serde")]
+    #[tokio::test]
+    async fn test_serialize_json_roundtrip() {
+        use std::str::FromStr;
+
+        use crate::arrow::builder::TypedBuilder;
+        use crate::arrow::types::JSON_DYNAMIC_FIELD_NAME;
+        use crate::deserialize::ClickHouseNativeDeserializer;
+
+        let type_ = Type::from_str("JSON(a UInt32)").unwrap();
+        let (data_type, _) = type_.arrow_type(None).unwrap();
+        let column =
+            Arc::new(StringArray::from(vec![r#"{"a":1,"b":{"c":"x"}}"#, r#"{"d":2}"#])) as ArrayRef;
+
+        let read = async |column: &ArrayRef| {
+            let mut buffer = Cursor::new(Vec::new());
+            let mut state = SerializerState::default();
+            type_
+                .serialize_async(&mut buffer, column, column.data_type(), &mut state)
+                .await
+                .unwrap();
+            let mut reader = Cursor::new(buffer.into_inner());
+            type_
+                .deserialize_prefix_async(
+                    &mut reader,
+                    &mut crate::formats::DeserializerState::default(),
+                )
+                .await
+                .unwrap();
+            let mut builder = TypedBuilder::try_new(&type_, &data_type).unwrap();
+            type_
+                .deserialize_arrow_async(&mut builder, &mut reader, &data_type, 2, &[], &mut vec![])
+                .await
+                .unwrap()
+        };
+
+        let array = read(&column).await;
+        let array = array.as_struct();
+        assert_eq!(array.column_by_name("a").unwrap().as_primitive::<UInt32Type>().values(), &[
+            1, 0
+        ]);
+        let remainder = array.column_by_name(JSON_DYNAMIC_FIELD_NAME).unwrap().as_string::<i32>();
+        assert_eq!(remainder.value(0), r#"{"b":{"c":"x"}}"#);
+        assert_eq!(remainder.value(1), r#"{"d":2}"#);
+
+        let struct_column = Arc::new(array.clone()) as ArrayRef;
+        let reread = read(&struct_column).await;
+        assert_eq!(reread.as_ref(), struct_column.as_ref());
+    }
+
+    /// Tests that a union written to `Dynamic` reads back as the same union, including values
+    /// moved to the shared variant, and as JSON text.
+    #[tokio::test]
+    async fn test_serialize_dynamic_roundtrip() {
+        use arrow::buffer::ScalarBuffer;
+        use arrow::datatypes::UnionFields;
+
+        use crate::arrow::builder::TypedBuilder;
+        use crate::arrow::deserialize::dynamic::dynamic_field;
+        use crate::arrow::types::{DYNAMIC_NULL_FIELD_NAME, DYNAMIC_TYPES_METADATA_KEY};
+        use crate::deserialize::ClickHouseNativeDeserializer;
+
+        // A single variant, `String` values go to the shared variant
+        let type_ = Type::Dynamic { max_types: Some(1) };
+        let fields = [
+            (0, Arc::new(Field::new("Int64", DataType::Int64, true))),
+            (1, Arc::new(Field::new("String", DataType::Utf8, true))),
+            (2, Arc::new(Field::new(DYNAMIC_NULL_FIELD_NAME, DataType::Null, true))),
+        ]
+        .into_iter()
+        .collect::<UnionFields>();
+        let column = Arc::new(
+            UnionArray::try_new(
+                fields,
+                ScalarBuffer::from(vec![0, 1, 2, 0, 1]),
+                Some(ScalarBuffer::from(vec![0, 0, 0, 1, 1])),
+                vec![
+                    Arc::new(Int64Array::from(vec![1, 2])),
+                    Arc::new(StringArray::from(vec!["a", "b"])),
+                    Arc::new(NullArray::new(1)),
+                ],
+            )
+            .unwrap(),
+        ) as ArrayRef;
+
+        let options = ArrowOptions::default().with_strings_as_strings(true);
+        let read = async |data_type: &DataType| {
+            let mut buffer = Cursor::new(Vec::new());
+            let mut state = SerializerState::default().with_arrow_options(options);
+            type_
+                .serialize_async(&mut buffer, &column, column.data_type(), &mut state)
+                .await
+                .unwrap();
+            let mut reader = Cursor::new(buffer.into_inner());
+            type_
+                .deserialize_prefix_async(
+                    &mut reader,
+                    &mut crate::formats::DeserializerState::default(),
+                )
+                .await
+                .unwrap();
+            let mut builder = TypedBuilder::try_new_column(&type_, data_type, options).unwrap();
+            type_
+                .deserialize_arrow_async(&mut builder, &mut reader, data_type, 5, &[], &mut vec![])
+                .await
+                .unwrap()
+        };
+
+        let (data_type, _) = type_.arrow_type(Some(options.with_dynamic_as_union(true))).unwrap();
+        let union = read(&data_type).await;
+        assert_eq!(union.as_ref(), column.as_ref());
+        let field = dynamic_field(Field::new("dyn", data_type, true), &union);
+        assert_eq!(field.data_type(), column.data_type());
+        assert_eq!(
+            field.metadata().get(DYNAMIC_TYPES_METADATA_KEY).map(String::as_str),
+            Some(r#"["Int64","String"]"#)
+        );
+
+        let (data_type, _) = type_.arrow_type(Some(options)).unwrap();
+        let text = read(&data_type).await;
+        let expected =
+            StringArray::from(vec![Some("1"), Some(r#""a""#), None, Some("2"), Some(r#""b""#)]);
+        assert_eq!(text.as_string::<i32>(), &expected);
+    }
 }
 
 #[cfg(test)]
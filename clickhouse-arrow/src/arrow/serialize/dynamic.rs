@@ -0,0 +1,135 @@
+//! Serialization of Arrow arrays into `ClickHouse` `Dynamic` columns.
+//!
+//! Accepts dense or sparse unions, as produced when reading `Dynamic` columns with
+//! [`crate::ArrowOptions::with_dynamic_as_union`]. Each union field holds the values of one type:
+//! fields named by a `ClickHouse` type name use that type, other fields the type mapped from their
+//! data type. Null values and fields of the `Null` data type are inserted as NULL. Arrays of any
+//! other data type are inserted as values of their mapped type.
+use std::str::FromStr;
+
+use arrow::array::*;
+use arrow::datatypes::{DataType, Field};
+use tokio::io::AsyncWriteExt;
+
+use super::ClickHouseArrowSerializer;
+use crate::arrow::types::arrow_to_ch_type;
+use crate::deserialize::ClickHouseNativeDeserializer;
+use crate::formats::{DeserializerState, SerializerState};
+use crate::io::{ClickHouseBytesWrite, ClickHouseWrite};
+use crate::serialize::ClickHouseNativeSerializer;
+use crate::{Error, Result, Type, Value};
+
+pub(super) async fn serialize_async<W: ClickHouseWrite>(
+    type_: &Type,
+    writer: &mut W,
+    column: &ArrayRef,
+    state: &mut SerializerState,
+) -> Result<()> {
+    // The prefix depends on the whole column, so it is assembled before writing
+    let mut buffer = Vec::new();
+    serialize(type_, &mut buffer, column, state)?;
+    writer.write_all(&buffer).await?;
+    Ok(())
+}
+
+pub(super) fn serialize<W: ClickHouseBytesWrite>(
+    type_: &Type,
+    writer: &mut W,
+    column: &ArrayRef,
+    state: &mut SerializerState,
+) -> Result<()> {
+    if !matches!(type_, Type::Dynamic { .. }) {
+        return Err(Error::ArrowSerialize(format!("Expected Dynamic type, got {type_}")));
+    }
+
+    let Some(union) = column.as_union_opt() else {
+        let field = Field::new("", column.data_type().clone(), true);
+        let values = typed_values(&field, column, state)?.map_or_else(
+            || vec![Value::Null; column.len()],
+            |(name, values)| dynamic_values(&name, values, column),
+        );
+        return type_.serialize_column_sync(values, writer, state);
+    };
+
+    let DataType::Union(fields, _) = union.data_type() else {
+        return Err(Error::ArrowSerialize("Expected union data type".into()));
+    };
+    let mut children = Vec::with_capacity(fields.len());
+    for (type_id, field) in fields.iter() {
+        let child = union.child(type_id);
+        let values = typed_values(field, child, state)?;
+        children.push((type_id, child, values));
+    }
+
+    let mut values = Vec::with_capacity(union.len());
+    for row in 0..union.len() {
+        let type_id = union.type_id(row);
+        let offset = union.value_offset(row);
+        let Some((_, child, Some((name, child_values)))) =
+            children.iter_mut().find(|(id, ..)| *id == type_id)
+        else {
+            values.push(Value::Null);
+            continue;
+        };
+        if child.is_null(offset) {
+            values.push(Value::Null);
+            continue;
+        }
+        let value = std::mem::replace(&mut child_values[offset], Value::Null);
+        values.push(Value::Dynamic(name.clone(), Box::new(value)));
+    }
+    type_.serialize_column_sync(values, writer, state)
+}
+
+/// Type name and native values of the field of a union, `None` for fields only holding NULL.
+///
+/// The values are converted by writing the array in the native format and reading it back.
+fn typed_values(
+    field: &Field,
+    array: &ArrayRef,
+    state: &mut SerializerState,
+) -> Result<Option<(String, Vec<Value>)>> {
+    if matches!(field.data_type(), DataType::Null) {
+        return Ok(None);
+    }
+
+    let (name, type_) = match Type::from_str(field.name()) {
+        Ok(type_) if !type_.is_nullable() => (field.name().clone(), type_),
+        _ => {
+            let type_ = arrow_to_ch_type(field.data_type(), false, state.options)?;
+            (type_.to_string(), type_)
+        }
+    };
+    if type_.has_dynamic_structure() {
+        return Err(Error::Unimplemented(format!(
+            "Arrow serialization of {type_} values into Dynamic is not supported"
+        )));
+    }
+
+    let mut buffer = Vec::new();
+    type_.serialize_prefix(&mut buffer, state);
+    ClickHouseArrowSerializer::serialize(&type_, &mut buffer, array, array.data_type(), state)?;
+    let mut reader = buffer.as_slice();
+    type_.deserialize_prefix(&mut reader)?;
+    let values = type_.deserialize_column_sync(
+        &mut reader,
+        array.len(),
+        &mut DeserializerState::default(),
+    )?;
+    Ok(Some((name, values)))
+}
+
+/// Wrap the values of a single typed array, keeping its nulls.
+fn dynamic_values(name: &str, values: Vec<Value>, array: &ArrayRef) -> Vec<Value> {
+    values
+        .into_iter()
+        .enumerate()
+        .map(|(i, value)| {
+            if array.is_null(i) {
+                Value::Null
+            } else {
+                Value::Dynamic(name.to_string(), Box::new(value))
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,99 @@
+//! Serialization of Arrow arrays into `ClickHouse` `JSON` columns.
+//!
+//! Accepts string or binary arrays holding one JSON document per row, or the struct produced when
+//! reading `JSON` columns: one field per typed path plus [`JSON_DYNAMIC_FIELD_NAME`] holding the
+//! remaining paths as JSON text.
+use arrow::array::*;
+use arrow::datatypes::DataType;
+use tokio::io::AsyncWriteExt;
+
+use super::ClickHouseArrowSerializer;
+use crate::arrow::types::JSON_DYNAMIC_FIELD_NAME;
+use crate::formats::SerializerState;
+use crate::io::{ClickHouseBytesWrite, ClickHouseWrite};
+use crate::native::types::serialize::json::JsonColumn;
+use crate::serialize::ClickHouseNativeSerializer;
+use crate::{Error, Result, Type, Value};
+
+pub(super) async fn serialize_async<W: ClickHouseWrite>(
+    type_: &Type,
+    writer: &mut W,
+    column: &ArrayRef,
+    state: &mut SerializerState,
+) -> Result<()> {
+    // The prefix depends on the whole column, so it is assembled before writing
+    let mut buffer = Vec::new();
+    serialize(type_, &mut buffer, column, state)?;
+    writer.write_all(&buffer).await?;
+    Ok(())
+}
+
+pub(super) fn serialize<W: ClickHouseBytesWrite>(
+    type_: &Type,
+    writer: &mut W,
+    column: &ArrayRef,
+    state: &mut SerializerState,
+) -> Result<()> {
+    let Type::Json(json) = type_ else {
+        return Err(Error::ArrowSerialize(format!("Expected JSON type, got {type_}")));
+    };
+
+    let Some(array) = column.as_struct_opt() else {
+        return type_.serialize_column_sync(documents(column)?, writer, state);
+    };
+
+    // Typed paths are written from their fields, so they are skipped in the remainder
+    let mut remainder_type = (**json).clone();
+    remainder_type
+        .skip_paths
+        .extend(remainder_type.typed_paths.drain(..).map(|(path, _)| path));
+    let remainder = match array.column_by_name(JSON_DYNAMIC_FIELD_NAME) {
+        Some(remainder) => documents(remainder)?,
+        None => vec![Value::Null; array.len()],
+    };
+    let remainder = JsonColumn::new(&remainder_type, remainder, state.revision)?;
+
+    let mut typed_fields = Vec::with_capacity(json.typed_paths.len());
+    for (path, inner_type) in &json.typed_paths {
+        let field = array
+            .column_by_name(path)
+            .ok_or_else(|| Error::ArrowSerialize(format!("Missing field for JSON path {path}")))?;
+        typed_fields.push((inner_type, field));
+    }
+
+    remainder.write_paths(writer)?;
+    for (inner_type, _) in &typed_fields {
+        inner_type.serialize_prefix(writer, state);
+    }
+    remainder.write_dynamic_prefix(writer, state)?;
+    for (inner_type, field) in typed_fields {
+        ClickHouseArrowSerializer::serialize(inner_type, writer, field, field.data_type(), state)?;
+    }
+    remainder.write_dynamic_data(writer, state)
+}
+
+/// Read JSON documents out of a string or binary array.
+fn documents(column: &ArrayRef) -> Result<Vec<Value>> {
+    fn document(value: Option<&[u8]>) -> Value {
+        value.map_or(Value::Null, |value| Value::Object(value.to_vec()))
+    }
+    Ok(match column.data_type() {
+        DataType::Utf8 => {
+            column.as_string::<i32>().iter().map(|v| document(v.map(str::as_bytes))).collect()
+        }
+        DataType::LargeUtf8 => {
+            column.as_string::<i64>().iter().map(|v| document(v.map(str::as_bytes))).collect()
+        }
+        DataType::Utf8View => {
+            column.as_string_view().iter().map(|v| document(v.map(str::as_bytes))).collect()
+        }
+        DataType::Binary => column.as_binary::<i32>().iter().map(document).collect(),
+        DataType::LargeBinary => column.as_binary::<i64>().iter().map(document).collect(),
+        DataType::BinaryView => column.as_binary_view().iter().map(document).collect(),
+        data_type => {
+            return Err(Error::ArrowSerialize(format!(
+                "Unsupported Arrow type for JSON column: {data_type}"
+            )));
+        }
+    })
+}
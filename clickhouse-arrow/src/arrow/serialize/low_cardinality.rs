@@ -541,7 +541,7 @@ async fn write_string_values<W: ClickHouseWrite>(
         DataType::BinaryView => handle_binary_array!(BinaryViewArray),
         DataType::LargeBinary => handle_binary_array!(LargeBinaryArray),
         dt => {
-            return Err(Error::ArrowSerialize(format!("Expected string-like array, got {dt}",)));
+            return Err(Error::ArrowSerialize(format!("Expected string-like array, got {dt}")));
         }
     }
 
@@ -639,7 +639,7 @@ fn put_string_values<W: ClickHouseBytesWrite>(
         DataType::BinaryView => handle_binary_array!(BinaryViewArray),
         DataType::LargeBinary => handle_binary_array!(LargeBinaryArray),
         dt => {
-            return Err(Error::ArrowSerialize(format!("Expected string-like array, got {dt}",)));
+            return Err(Error::ArrowSerialize(format!("Expected string-like array, got {dt}")));
         }
     }
 
@@ -19,6 +19,12 @@ pub const MAP_FIELD_NAME: &str = "entries";
 pub const STRUCT_KEY_FIELD_NAME: &str = "key";
 /// Consistent use of struct's value field name
 pub const STRUCT_VALUE_FIELD_NAME: &str = "value";
+/// Field of a `JSON` struct holding the paths without a declared type, as JSON text
+pub const JSON_DYNAMIC_FIELD_NAME: &str = "_dynamic";
+/// Field metadata of a `Dynamic` union, listing the types found in the block as a JSON array
+pub const DYNAMIC_TYPES_METADATA_KEY: &str = "clickhouse.dynamic.types";
+/// Field of a `Dynamic` union holding the NULL values, as `dynamicType` names them
+pub const DYNAMIC_NULL_FIELD_NAME: &str = "None";
 
 // From impl from Arrow's i256 to internal i256
 impl From<i256> for crate::i256 {
@@ -327,9 +333,13 @@ pub(crate) fn arrow_to_ch_type(
             // Invalid in Arrow; fallback to microsecond precision
             Type::DateTime64(6, chrono_tz::Tz::UTC)
         }
+        DataType::Union(_, _) => {
+            // Dynamic holds NULL itself and cannot be wrapped in Nullable
+            is_nullable = false;
+            Type::Dynamic { max_types: None }
+        }
         DataType::Null
         | DataType::Float16
-        | DataType::Union(_, _)
         // TODO: Support RunEndEncoded
         | DataType::RunEndEncoded(_, _) => {
             return Err(Error::ArrowUnsupportedType(format!(
@@ -499,8 +509,27 @@ pub fn ch_to_arrow_type(ch_type: &Type, options: Option<ArrowOptions>) -> Result
             DataType::Union(fields, UnionMode::Dense)
         }
         Type::Dynamic { .. } => {
-            // Dynamic can contain any type at runtime, represent as JSON string
-            DataType::Utf8
+            // Dynamic can hold NULL, types are only known once a block is read
+            is_null = true;
+            if options.is_some_and(|o| o.dynamic_as_union) {
+                DataType::Union(UnionFields::empty(), UnionMode::Dense)
+            } else {
+                // Represent each value as JSON text
+                DataType::Utf8
+            }
+        }
+        Type::Json(json) => {
+            // Typed paths become fields, the remaining paths are kept as JSON text
+            let mut fields = json
+                .typed_paths
+                .iter()
+                .map(|(path, t)| {
+                    ch_to_arrow_type(t, options)
+                        .map(|(arrow_type, is_null)| Field::new(path, arrow_type, is_null))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            fields.push(Field::new(JSON_DYNAMIC_FIELD_NAME, DataType::Utf8, false));
+            DataType::Struct(fields.into())
         }
         Type::Nested(fields) => {
             // Nested is essentially a struct of arrays
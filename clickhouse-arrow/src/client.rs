@@ -6,14 +6,18 @@
 /// This module is designed to be thread-safe, with [`Client`] instances that can be
 /// cloned and shared across threads. It supports querying, inserting data, managing
 /// database schemas, and handling `ClickHouse` events like progress and profiling.
+mod auth;
 mod builder;
 mod chunk;
 #[cfg(feature = "cloud")]
 mod cloud;
 pub(crate) mod connection;
+mod inserter;
 mod internal;
 mod options;
 mod reader;
+mod reconnect;
+mod replicas;
 mod response;
 mod tcp;
 mod writer;
@@ -29,23 +33,31 @@ use futures_util::{Stream, StreamExt, TryStreamExt, stream};
 use strum::AsRefStr;
 use tokio::sync::{broadcast, mpsc, oneshot};
 
+pub use self::auth::Auth;
+#[cfg(feature = "ssh")]
+pub use self::auth::SshKeySource;
 pub use self::builder::*;
 pub use self::connection::ConnectionStatus;
+pub use self::inserter::{Inserter, InserterOptions, InserterSink};
 pub(crate) use self::internal::{Message, Operation};
 pub use self::options::*;
+pub use self::reconnect::{ExponentialBackoff, ReconnectPolicy};
+pub use self::replicas::{LoadBalancing, ReplicaHealth, ReplicaOptions};
 pub use self::response::*;
 pub use self::tcp::Destination;
 use crate::arrow::utils::batch_to_rows;
 use crate::constants::*;
 use crate::formats::{ClientFormat, NativeFormat};
 use crate::native::block::Block;
-use crate::native::protocol::{CompressionMethod, ProfileEvent};
+use crate::native::protocol::{
+    CompressionMethod, CompressionOptions, LogData, ProfileEvent, ProfileInfo,
+};
 use crate::prelude::*;
 use crate::query::{ParsedQuery, QueryParams};
 use crate::schema::CreateOptions;
 use crate::{Error, Progress, Result, Row};
 
-static CLIENT_ID: AtomicU16 = AtomicU16::new(0);
+pub(crate) static CLIENT_ID: AtomicU16 = AtomicU16::new(0);
 
 /// A `ClickHouse` client configured for the native format.
 ///
@@ -69,12 +81,15 @@ pub type ArrowClient = Client<ArrowFormat>;
 /// # Fields
 /// - `trace`: Optional tracing context for logging and monitoring.
 /// - `cloud`: Optional cloud-specific configuration (requires the `cloud` feature).
+/// - `replicas`: Optional replica health tracking shared between clients, see [`ReplicaHealth`].
+/// - `tls_config`: Optional custom TLS configuration, replacing the one built from [`TlsOptions`].
 #[derive(Debug, Clone, Default)]
-#[cfg_attr(not(feature = "cloud"), derive(Copy))]
 pub struct ConnectionContext {
-    pub trace: Option<TraceContext>,
+    pub trace:      Option<TraceContext>,
     #[cfg(feature = "cloud")]
-    pub cloud: Option<Arc<std::sync::atomic::AtomicBool>>,
+    pub cloud:      Option<Arc<std::sync::atomic::AtomicBool>>,
+    pub replicas:   Option<Arc<ReplicaHealth>>,
+    pub tls_config: Option<Arc<tokio_rustls::rustls::ClientConfig>>,
 }
 
 /// Emitted clickhouse events from the underlying connection
@@ -85,11 +100,31 @@ pub struct Event {
     pub client_id: u16,
 }
 
-/// Profile and progress events from clickhouse
+/// Profile and progress events from clickhouse, as well as connection recovery events.
+///
+/// Connection events are emitted with a default [`Qid`] since they are not tied to a query.
 #[derive(Debug, Clone, AsRefStr)]
+#[non_exhaustive]
 pub enum ClickHouseEvent {
     Progress(Progress),
     Profile(Vec<ProfileEvent>),
+    /// A lost connection is being re-established, `attempt` starts at 1.
+    Reconnecting {
+        attempt: u32,
+    },
+    /// A lost connection was re-established after the given number of attempts.
+    Reconnected {
+        attempts: u32,
+    },
+    /// Reconnecting was abandoned after exhausting the [`ReconnectPolicy`].
+    ReconnectFailed {
+        attempts: u32,
+        error:    String,
+    },
+    /// Server log lines, sent when the `send_logs_level` setting is enabled.
+    Log(Vec<LogData>),
+    /// Execution summary of a query, including the row count before `LIMIT`.
+    ProfileInfo(ProfileInfo),
 }
 
 /// A thread-safe handle for interacting with a `ClickHouse` database over its native protocol.
@@ -188,6 +223,7 @@ impl<T: ClientFormat> Client<T> {
     /// # Errors
     /// - Fails if the destination cannot be resolved or the connection cannot be established.
     /// - Fails if authentication or TLS setup encounters an issue.
+    /// - Fails if the reconnect policy in `options` would retry forever.
     ///
     /// # Examples
     /// ```rust,ignore
@@ -220,11 +256,19 @@ impl<T: ClientFormat> Client<T> {
         let trace_ctx = context.trace.unwrap_or_default();
         let _ = trace_ctx.link(&Span::current());
 
+        if let Some(policy) = options.ext.reconnect.as_ref() {
+            policy.validate()?;
+        }
+
         let client_id = CLIENT_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
 
         // Resolve the destination
         let destination: Destination = destination.into();
-        let addrs = destination.resolve(options.ipv4_only).await?;
+        let replicas = replicas::ReplicaSet::new(
+            destination.resolve_replicas(options.ipv4_only).await?,
+            options.ext.replicas,
+            context.replicas.unwrap_or_default(),
+        );
 
         #[cfg(feature = "cloud")]
         {
@@ -235,7 +279,7 @@ impl<T: ClientFormat> Client<T> {
             }
         }
 
-        if let Some(addr) = addrs.first() {
+        if let Some(addr) = replicas.first_addr() {
             let _ = Span::current()
                 .record("server.address", tracing::field::debug(&addr.ip()))
                 .record("server.port", addr.port());
@@ -246,8 +290,15 @@ impl<T: ClientFormat> Client<T> {
         let events = Arc::new(event_tx);
         let conn_ev = Arc::clone(&events);
 
-        let conn =
-            connection::Connection::connect(client_id, addrs, options, conn_ev, trace_ctx).await?;
+        let conn = connection::Connection::connect(
+            client_id,
+            replicas,
+            options,
+            conn_ev,
+            trace_ctx,
+            context.tls_config,
+        )
+        .await?;
         let connection = Arc::new(conn);
 
         debug!("created connection successfully");
@@ -347,6 +398,98 @@ impl<T: ClientFormat> Client<T> {
         self.conn().await?.check_connection(ping).await
     }
 
+    /// Fetches the replication status of tables from the server this client is connected to.
+    ///
+    /// For each table using a `Replicated*` engine, the server reports how far its replica lags
+    /// behind (`absolute_delay`, in seconds) and whether it is read-only. This is the check the
+    /// `Distributed` engine uses to avoid stale replicas, so reads can be routed away from
+    /// replicas lagging more than a threshold. Tables that do not exist are omitted from the
+    /// result. If a query is executing on the connection, the request is sent once it finishes.
+    ///
+    /// # Parameters
+    /// - `tables`: `(database, table)` pairs to check.
+    ///
+    /// # Returns
+    /// A [`Result`] containing a [`TableStatus`] per existing table.
+    ///
+    /// # Errors
+    /// - Fails if the connection is closed.
+    /// - Fails if `ClickHouse` returns an exception.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let client = Client::builder()
+    ///     .with_endpoint("localhost:9000")
+    ///     .build_arrow()
+    ///     .await
+    ///     .unwrap();
+    ///
+    /// let statuses = client.tables_status(&[("default", "events")]).await.unwrap();
+    /// let lagging = statuses.iter().any(|s| s.is_replicated && s.absolute_delay > 300);
+    /// ```
+    #[instrument(
+        name = "clickhouse.tables_status",
+        skip_all,
+        fields(
+            db.system = "clickhouse",
+            db.operation = "tables_status",
+            clickhouse.client.id = self.client_id,
+        )
+    )]
+    pub async fn tables_status(&self, tables: &[(&str, &str)]) -> Result<Vec<TableStatus>> {
+        let tables = tables.iter().map(|(db, table)| ((*db).to_string(), (*table).to_string()));
+        self.conn().await?.tables_status(tables.collect()).await
+    }
+
+    /// Cancels a running or queued query by its query ID.
+    ///
+    /// If the query is executing, a `Cancel` packet is sent to `ClickHouse` and the remaining
+    /// response is drained so the connection stays usable. The query's response stream ends once
+    /// the server acknowledges the cancellation, either normally or with an exception. If the
+    /// query is still queued, it is removed and its caller receives an error. Unknown or
+    /// completed query IDs are ignored.
+    ///
+    /// Dropping a response stream returned by a query method (e.g. [`Client::query`]) before it
+    /// is exhausted cancels the query in the same way.
+    ///
+    /// # Parameters
+    /// - `qid`: The query ID of the query to cancel.
+    ///
+    /// # Returns
+    /// A [`Result`] indicating whether the cancellation was sent.
+    ///
+    /// # Errors
+    /// - Fails if the connection is closed.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let client = Client::builder()
+    ///     .with_endpoint("localhost:9000")
+    ///     .build_arrow()
+    ///     .await
+    ///     .unwrap();
+    ///
+    /// let qid = Qid::new();
+    /// let mut response = client.query("SELECT * FROM system.numbers", Some(qid)).await.unwrap();
+    /// let _ = response.next().await;
+    /// client.cancel(qid).await.unwrap();
+    /// ```
+    #[instrument(
+        name = "clickhouse.cancel",
+        skip_all,
+        fields(
+            db.system = "clickhouse",
+            db.operation = "cancel",
+            clickhouse.client.id = self.client_id,
+            clickhouse.query.id = %qid
+        )
+    )]
+    pub async fn cancel(&self, qid: Qid) -> Result<()> { self.conn().await?.cancel(qid).await }
+
     /// Shuts down the `ClickHouse` client and closes its connection.
     ///
     /// This method gracefully terminates the underlying connection, ensuring that any
@@ -439,7 +582,63 @@ impl<T: ClientFormat> Client<T> {
         block: T::Data,
         qid: Option<Qid>,
     ) -> Result<impl Stream<Item = Result<()>> + '_> {
-        let (query, qid) = record_query(qid, query.into(), self.client_id);
+        self.insert_with_options(query, block, QueryOptions { qid, ..Default::default() }).await
+    }
+
+    /// Inserts a block of data into `ClickHouse` with per-query options.
+    ///
+    /// This is [`Client::insert`] with [`QueryOptions`], allowing settings such as
+    /// `async_insert` or `insert_quorum` to be set for this insert only. Settings are
+    /// applied on top of the client's settings. Parameters and the query ID are also
+    /// taken from the options, limits and explain are ignored.
+    ///
+    /// # Parameters
+    /// - `query`: The insert query (e.g., `"INSERT INTO my_table VALUES"`).
+    /// - `block`: The data to insert, in the format specified by `T` ([`Block`] or
+    ///   [`RecordBatch`]).
+    /// - `options`: The query options, see [`QueryOptions`].
+    ///
+    /// # Returns
+    /// A [`Result`] containing a stream of [`Result<()>`], where each item indicates
+    /// the success or failure of processing response data.
+    ///
+    /// # Errors
+    /// - Fails if the query is malformed or the data format is invalid.
+    /// - Fails if the connection to `ClickHouse` is interrupted.
+    /// - Fails if `ClickHouse` returns an exception (e.g., schema mismatch).
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let options = QueryOptions::new()
+    ///     .with_setting("async_insert", 1)
+    ///     .with_setting("wait_for_async_insert", 1);
+    /// let stream = client.insert_with_options("INSERT INTO my_table VALUES", batch, options).await?;
+    /// while let Some(result) = stream.next().await {
+    ///     result?; // Check for errors
+    /// }
+    /// ```
+    #[instrument(
+        level = "trace",
+        name = "clickhouse.insert_with_options",
+        skip_all
+        fields(
+            db.system = "clickhouse",
+            db.operation = "insert",
+            db.format = T::FORMAT,
+            clickhouse.client.id = self.client_id,
+            clickhouse.query.id
+        ),
+    )]
+    pub async fn insert_with_options(
+        &self,
+        query: impl Into<ParsedQuery>,
+        block: T::Data,
+        options: QueryOptions,
+    ) -> Result<impl Stream<Item = Result<()>> + '_> {
+        let settings = options.merge_settings(self.settings.as_ref());
+        let (query, qid) = record_query(options.qid, query.into(), self.client_id);
 
         // Create metadata channel
         let (tx, rx) = oneshot::channel();
@@ -451,10 +650,16 @@ impl<T: ClientFormat> Client<T> {
             .send_operation(
                 Operation::Query {
                     query,
-                    settings: self.settings.clone(),
-                    params: None,
+                    settings,
+                    params: options.params,
                     response: tx,
                     header: None,
+                    cancel_on_drop: false,
+                    summary: None,
+                    compression: options.compression,
+                    external: Vec::new(),
+                    trace: TraceParent::resolve(options.trace.as_ref()),
+                    quota_key: options.quota_key,
                 },
                 qid,
                 false,
@@ -483,6 +688,57 @@ impl<T: ClientFormat> Client<T> {
         Ok(self.insert_response(responses, qid))
     }
 
+    /// Opens a streaming insert.
+    ///
+    /// The returned [`Inserter`] keeps a single `INSERT` open, buffering data written to it and
+    /// sending it to the server once a row, byte or time threshold in [`InserterOptions`] is
+    /// reached. Use this to insert long running streams, e.g. from a message queue, without
+    /// materializing them or issuing a query per batch. The insert is ended with
+    /// [`Inserter::commit`] or [`Inserter::abort`].
+    ///
+    /// The inserter uses this client's connection, which is occupied by the insert until it ends.
+    ///
+    /// # Parameters
+    /// - `query`: The insert query (e.g., `"INSERT INTO my_table VALUES"`).
+    /// - `options`: Flush thresholds and query options, see [`InserterOptions`].
+    ///
+    /// # Returns
+    /// A [`Result`] containing the [`Inserter`] once the server accepted the query.
+    ///
+    /// # Errors
+    /// - Fails if the query is malformed or the table does not exist.
+    /// - Fails if the connection to `ClickHouse` is interrupted.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let options = InserterOptions::default().with_max_rows(Some(100_000));
+    /// let mut inserter = client.inserter("INSERT INTO my_table VALUES", options).await?;
+    /// inserter.write_stream(batches).await?;
+    /// inserter.commit().await?;
+    /// ```
+    #[instrument(
+        name = "clickhouse.inserter",
+        skip_all,
+        fields(
+            db.system = "clickhouse",
+            db.operation = "insert",
+            db.format = T::FORMAT,
+            clickhouse.client.id = self.client_id,
+            clickhouse.query.id
+        ),
+    )]
+    pub async fn inserter(
+        &self,
+        query: impl Into<ParsedQuery>,
+        options: InserterOptions,
+    ) -> Result<Inserter<T>> {
+        let inserter = Inserter::new(self, query.into(), options).await?;
+        let _ = Span::current().record(ATT_QID, tracing::field::display(inserter.qid()));
+        Ok(inserter)
+    }
+
     /// Inserts multiple blocks of data into `ClickHouse` using the native protocol.
     ///
     /// This method sends an insert query with a collection of data blocks, formatted
@@ -545,7 +801,60 @@ impl<T: ClientFormat> Client<T> {
         batch: Vec<T::Data>,
         qid: Option<Qid>,
     ) -> Result<impl Stream<Item = Result<()>> + '_> {
-        let (query, qid) = record_query(qid, query.into(), self.client_id);
+        self.insert_many_with_options(query, batch, QueryOptions { qid, ..Default::default() })
+            .await
+    }
+
+    /// Inserts multiple blocks of data into `ClickHouse` with per-query options.
+    ///
+    /// This is [`Client::insert_many`] with [`QueryOptions`], see
+    /// [`Client::insert_with_options`].
+    ///
+    /// # Parameters
+    /// - `query`: The insert query (e.g., `"INSERT INTO my_table VALUES"`).
+    /// - `batch`: A vector of data blocks to insert, in the format specified by `T`.
+    /// - `options`: The query options, see [`QueryOptions`].
+    ///
+    /// # Returns
+    /// A [`Result`] containing a stream of [`Result<()>`], where each item indicates
+    /// the success or failure of processing response data.
+    ///
+    /// # Errors
+    /// - Fails if the query is malformed or any data block is invalid.
+    /// - Fails if the connection to `ClickHouse` is interrupted.
+    /// - Fails if `ClickHouse` returns an exception (e.g., schema mismatch).
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let options = QueryOptions::new().with_setting("insert_quorum", 2);
+    /// let stream = client
+    ///     .insert_many_with_options("INSERT INTO my_table VALUES", batches, options)
+    ///     .await?;
+    /// while let Some(result) = stream.next().await {
+    ///     result?; // Check for errors
+    /// }
+    /// ```
+    #[instrument(
+        name = "clickhouse.insert_many_with_options",
+        skip_all,
+        fields(
+            db.system = "clickhouse",
+            db.operation = "insert",
+            db.format = T::FORMAT,
+            clickhouse.client.id = self.client_id,
+            clickhouse.query.id
+        ),
+    )]
+    pub async fn insert_many_with_options(
+        &self,
+        query: impl Into<ParsedQuery>,
+        batch: Vec<T::Data>,
+        options: QueryOptions,
+    ) -> Result<impl Stream<Item = Result<()>> + '_> {
+        let settings = options.merge_settings(self.settings.as_ref());
+        let (query, qid) = record_query(options.qid, query.into(), self.client_id);
 
         // Create metadata channel
         let (tx, rx) = oneshot::channel();
@@ -556,10 +865,16 @@ impl<T: ClientFormat> Client<T> {
             .send_operation(
                 Operation::Query {
                     query,
-                    settings: self.settings.clone(),
-                    params: None,
+                    settings,
+                    params: options.params,
                     response: tx,
                     header: None,
+                    cancel_on_drop: false,
+                    summary: None,
+                    compression: options.compression,
+                    external: Vec::new(),
+                    trace: TraceParent::resolve(options.trace.as_ref()),
+                    quota_key: options.quota_key,
                 },
                 qid,
                 false,
@@ -604,8 +919,8 @@ impl<T: ClientFormat> Client<T> {
     /// - `qid`: A unique query ID for tracking and debugging.
     ///
     /// # Returns
-    /// A [`Result`] containing a stream of [`Result<T::Data>`], where each item is a
-    /// data block or an error.
+    /// A [`Result`] containing a [`ClickHouseResponse<T::Data>`] that streams data blocks.
+    /// Totals and extremes are available from the response once it has been consumed.
     ///
     /// # Errors
     /// - Fails if the query is malformed or unsupported by `ClickHouse`.
@@ -645,9 +960,32 @@ impl<T: ClientFormat> Client<T> {
         query: String,
         params: Option<P>,
         qid: Qid,
-    ) -> Result<impl Stream<Item = Result<T::Data>> + 'static> {
+    ) -> Result<ClickHouseResponse<T::Data>> {
+        let options = QueryOptions { params: params.map(Into::into), ..Default::default() };
+        self.send_query(query, options, qid, self.settings.clone(), true, vec![]).await
+    }
+
+    /// Sends a query and returns its response stream.
+    ///
+    /// When `cancel_on_drop` is set, dropping the returned stream before it is exhausted sends a
+    /// `Cancel` packet so the server stops executing the query. `external` blocks are sent as
+    /// temporary tables named by their key.
+    async fn send_query(
+        &self,
+        query: String,
+        options: QueryOptions,
+        qid: Qid,
+        settings: Option<Arc<Settings>>,
+        cancel_on_drop: bool,
+        external: Vec<(String, T::Data)>,
+    ) -> Result<ClickHouseResponse<T::Data>> {
+        if external.iter().any(|(name, _)| name.is_empty()) {
+            return Err(Error::Client("External table names must not be empty".into()));
+        }
+
         // Create metadata channel
         let (tx, rx) = oneshot::channel();
+        let summary = SummaryHandle::default();
         let connection = self.conn().await?;
 
         #[cfg_attr(not(feature = "inner_pool"), expect(unused_variables))]
@@ -655,10 +993,16 @@ impl<T: ClientFormat> Client<T> {
             .send_operation(
                 Operation::Query {
                     query,
-                    settings: self.settings.clone(),
-                    params: params.map(Into::into),
+                    settings,
+                    params: options.params,
                     response: tx,
                     header: None,
+                    cancel_on_drop,
+                    summary: Some(Arc::clone(&summary)),
+                    compression: None,
+                    external,
+                    trace: TraceParent::resolve(options.trace.as_ref()),
+                    quota_key: options.quota_key,
                 },
                 qid,
                 true,
@@ -677,7 +1021,8 @@ impl<T: ClientFormat> Client<T> {
         #[cfg(feature = "inner_pool")]
         connection.finish(conn_idx, Operation::<T::Data>::weight_query());
 
-        Ok(create_response_stream::<T>(responses, qid, self.client_id))
+        let stream = create_response_stream::<T>(responses, qid, self.client_id);
+        Ok(ClickHouseResponse::from_stream(stream).with_summary(Some(summary)))
     }
 
     /// Executes a `ClickHouse` query and discards all returned data.
@@ -756,7 +1101,7 @@ impl<T: ClientFormat> Client<T> {
     /// let params = Some(vec![
     ///     ("str", ParamValue::from("hello")),
     ///     ("num", ParamValue::from(42)),
-    ///     ("array", ParamValue::from("['a', 'b', 'c']")),
+    ///     ("array", ParamValue::from(vec!["a", "b", "c"])),
     /// ]);
     /// let query = "SELECT {num:Int64}, {str:String}, {array:Array(String)}";
     /// client.execute_params(query, params, None).await.unwrap();
@@ -779,9 +1124,54 @@ impl<T: ClientFormat> Client<T> {
         params: Option<P>,
         qid: Option<Qid>,
     ) -> Result<()> {
-        let (query, qid) = record_query(qid, query.into(), self.client_id);
-        let stream = self.query_raw(query, params, qid).await?;
-        tokio::pin!(stream);
+        let params = params.map(Into::into);
+        self.execute_with_options(query, QueryOptions { params, qid, ..Default::default() }).await
+    }
+
+    /// Executes a `ClickHouse` query with per-query options and discards all returned data.
+    ///
+    /// Settings in the options are applied on top of the client's settings, for example to
+    /// raise `max_execution_time` for a single maintenance statement. Parameters and the query
+    /// ID are also taken from the options, limits and explain are ignored.
+    ///
+    /// # Parameters
+    /// - `query`: The SQL query to execute (e.g., `"OPTIMIZE TABLE my_table FINAL"`).
+    /// - `options`: The query options, see [`QueryOptions`].
+    ///
+    /// # Returns
+    /// A [`Result`] indicating whether the query executed successfully.
+    ///
+    /// # Errors
+    /// - Fails if the query is malformed or unsupported by `ClickHouse`.
+    /// - Fails if the connection to `ClickHouse` is interrupted.
+    /// - Fails if `ClickHouse` returns an exception (e.g., permission denied).
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let options = QueryOptions::new().with_setting("max_execution_time", 3600);
+    /// client.execute_with_options("OPTIMIZE TABLE my_table FINAL", options).await.unwrap();
+    /// ```
+    #[instrument(
+        name = "clickhouse.execute_with_options",
+        skip_all,
+        fields(
+            db.system = "clickhouse",
+            db.format = T::FORMAT,
+            db.operation = "query",
+            clickhouse.client.id = self.client_id,
+            clickhouse.query.id
+        )
+    )]
+    pub async fn execute_with_options(
+        &self,
+        query: impl Into<ParsedQuery>,
+        options: QueryOptions,
+    ) -> Result<()> {
+        let settings = options.merge_settings(self.settings.as_ref());
+        let (query, qid) = record_query(options.qid, query.into(), self.client_id);
+        let mut stream = self.send_query(query, options, qid, settings, true, vec![]).await?;
         while let Some(next) = stream.next().await {
             drop(next?);
         }
@@ -882,7 +1272,9 @@ impl<T: ClientFormat> Client<T> {
         qid: Option<Qid>,
     ) -> Result<()> {
         let (query, qid) = record_query(qid, query.into(), self.client_id);
-        drop(self.query_raw(query, params, qid).await?);
+        let settings = self.settings.clone();
+        let options = QueryOptions { params: params.map(Into::into), ..Default::default() };
+        drop(self.send_query(query, options, qid, settings, false, vec![]).await?);
         Ok(())
     }
 
@@ -995,12 +1387,10 @@ impl<T: ClientFormat> Client<T> {
 }
 
 impl<T: ClientFormat> Client<T> {
-    /// Get a reference to the underlying connection.
-    ///
-    /// TODO: Support reconnect.
-    #[expect(clippy::unused_async)]
+    /// Get a reference to the underlying connection, re-establishing it first if it was lost and
+    /// a [`ReconnectPolicy`] is configured.
     async fn conn(&self) -> Result<&connection::Connection<T>> {
-        // TODO: Add reconnection logic here if configured
+        self.connection.ensure_connected().await?;
         Ok(self.connection.as_ref())
     }
 
@@ -1093,9 +1483,67 @@ impl Client<NativeFormat> {
         query: impl Into<ParsedQuery>,
         blocks: impl Iterator<Item = T> + Send + Sync + 'static,
         qid: Option<Qid>,
+    ) -> Result<ClickHouseResponse<()>> {
+        self.insert_rows_with_options(query, blocks, QueryOptions { qid, ..Default::default() })
+            .await
+    }
+
+    /// Inserts rows into `ClickHouse` with per-query options.
+    ///
+    /// This is [`Client::insert_rows`] with [`QueryOptions`], allowing settings such as
+    /// `async_insert` to be set for this insert only. Settings are applied on top of the
+    /// client's settings. Parameters and the query ID are also taken from the options,
+    /// limits and explain are ignored.
+    ///
+    /// # Parameters
+    /// - `query`: The insert query (e.g., `"INSERT INTO my_table VALUES"`).
+    /// - `blocks`: An iterator of rows to insert, where each row implements [`Row`].
+    /// - `options`: The query options, see [`QueryOptions`].
+    ///
+    /// # Returns
+    /// A [`Result`] containing a [`ClickHouseResponse<()>`] that streams the operation's
+    /// outcome.
+    ///
+    /// # Errors
+    /// - Fails if the query is malformed or the row data is invalid.
+    /// - Fails if the connection to `ClickHouse` is interrupted.
+    /// - Fails if `ClickHouse` returns an exception (e.g., schema mismatch).
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// // Assume `MyRow` implements `Row`
+    /// let rows = vec![MyRow { /* ... */ }, MyRow { /* ... */ }];
+    /// let options = QueryOptions::new().with_setting("async_insert", 1);
+    /// let response = client
+    ///     .insert_rows_with_options("INSERT INTO my_table VALUES", rows.into_iter(), options)
+    ///     .await
+    ///     .unwrap();
+    /// while let Some(result) = response.next().await {
+    ///     result.unwrap(); // Check for errors
+    /// }
+    /// ```
+    #[instrument(
+        name = "clickhouse.insert_rows_with_options",
+        fields(
+            db.system = "clickhouse",
+            db.operation = "insert",
+            db.format = NativeFormat::FORMAT,
+            clickhouse.client.id = self.client_id,
+            clickhouse.query.id
+        ),
+        skip_all
+    )]
+    pub async fn insert_rows_with_options<T: Row + Send + 'static>(
+        &self,
+        query: impl Into<ParsedQuery>,
+        blocks: impl Iterator<Item = T> + Send + Sync + 'static,
+        options: QueryOptions,
     ) -> Result<ClickHouseResponse<()>> {
         let cid = self.client_id;
-        let (query, qid) = record_query(qid, query.into(), cid);
+        let settings = options.merge_settings(self.settings.as_ref());
+        let (query, qid) = record_query(options.qid, query.into(), cid);
 
         // Create metadata channel
         let (tx, rx) = oneshot::channel();
@@ -1108,10 +1556,16 @@ impl Client<NativeFormat> {
             .send_operation(
                 Operation::Query {
                     query,
-                    settings: self.settings.clone(),
-                    params: None,
+                    settings,
+                    params: options.params,
                     response: tx,
                     header: Some(header_tx),
+                    cancel_on_drop: false,
+                    summary: None,
+                    compression: options.compression,
+                    external: Vec::new(),
+                    trace: TraceParent::resolve(options.trace.as_ref()),
+                    quota_key: options.quota_key,
                 },
                 qid,
                 false,
@@ -1244,21 +1698,139 @@ impl Client<NativeFormat> {
         params: Option<QueryParams>,
         qid: Option<Qid>,
     ) -> Result<ClickHouseResponse<T>> {
-        let (query, qid) = record_query(qid, query.into(), self.client_id);
-        let raw = self.query_raw(query, params, qid).await?;
-        Ok(ClickHouseResponse::new(Box::pin(raw.flat_map(|block| {
-            match block {
-                Ok(mut block) => stream::iter(
-                    block
-                        .take_iter_rows()
-                        .filter(|x| !x.is_empty())
-                        .map(T::deserialize_row)
-                        .map(|maybe| maybe.inspect_err(|error| error!(?error, "deserializing row")))
-                        .collect::<Vec<_>>(),
-                ),
-                Err(e) => stream::iter(vec![Err(e)]),
-            }
-        }))))
+        self.query_with_options(query, QueryOptions { params, qid, ..Default::default() }).await
+    }
+
+    /// Executes a `ClickHouse` query with per-query options and streams deserialized rows.
+    ///
+    /// This is [`Client::query_params`] with [`QueryOptions`]. Settings in the options are
+    /// applied on top of the client's settings for this query only, for example to set
+    /// `max_execution_time` or `max_threads`.
+    ///
+    /// Result limits and EXPLAIN are only supported by the arrow client's
+    /// `query_with_options`, setting either here returns an error.
+    ///
+    /// # Parameters
+    /// - `query`: The SQL query to execute (e.g., `"SELECT * FROM my_table"`).
+    /// - `options`: The query options, see [`QueryOptions`].
+    ///
+    /// # Returns
+    /// A [`Result`] containing a [`ClickHouseResponse<T>`] that streams deserialized
+    /// rows of type `T`.
+    ///
+    /// # Errors
+    /// - Fails if the options contain limits or explain configuration.
+    /// - Fails if the query is malformed or unsupported by `ClickHouse`.
+    /// - Fails if row deserialization fails (e.g., schema mismatch).
+    /// - Fails if the connection to `ClickHouse` is interrupted.
+    /// - Fails if `ClickHouse` returns an exception (e.g., table not found).
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let options = QueryOptions::new().with_setting("max_execution_time", 5);
+    /// let mut response = client
+    ///     .query_with_options::<MyRow>("SELECT * FROM my_table", options)
+    ///     .await
+    ///     .unwrap();
+    /// while let Some(row) = response.next().await {
+    ///     let row = row.unwrap();
+    ///     println!("Row: {:?}", row);
+    /// }
+    /// ```
+    #[instrument(
+        name = "clickhouse.query_with_options",
+        skip_all,
+        fields(
+            db.system = "clickhouse",
+            db.operation = "query",
+            db.format = NativeFormat::FORMAT,
+            clickhouse.query.id
+        )
+    )]
+    pub async fn query_with_options<T: Row + Send + 'static>(
+        &self,
+        query: impl Into<ParsedQuery>,
+        options: QueryOptions,
+    ) -> Result<ClickHouseResponse<T>> {
+        self.query_with_external_tables(query, Vec::<(String, Block)>::new(), options).await
+    }
+
+    /// Executes a `ClickHouse` query with external tables and streams deserialized rows.
+    ///
+    /// Each block is sent with the query as a temporary table named by its key, which the query
+    /// can read like any table, e.g. `WHERE id IN ids`. The columns of the table are those of the
+    /// block, blocks with the same name are appended to the same table. This avoids inlining
+    /// large lists of values into the query text.
+    ///
+    /// # Parameters
+    /// - `query`: The SQL query to execute.
+    /// - `external_tables`: `(name, block)` pairs sent as temporary tables.
+    /// - `options`: Per-query options, see [`Client::query_with_options`].
+    ///
+    /// # Returns
+    /// A [`Result`] containing a [`ClickHouseResponse<T>`] that streams deserialized rows.
+    ///
+    /// # Errors
+    /// - Fails if a table name is empty, or the options contain limits or explain configuration.
+    /// - Fails if a block cannot be serialized, which also closes the connection.
+    /// - Fails if the query is malformed or `ClickHouse` returns an exception.
+    /// - Fails if row deserialization fails (e.g., schema mismatch).
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// // Assume `ids` is a `Block` with a single `id UInt64` column
+    /// let mut response = client
+    ///     .query_with_external_tables::<MyRow>(
+    ///         "SELECT * FROM my_table WHERE id IN ids",
+    ///         [("ids", ids)],
+    ///         QueryOptions::new(),
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    /// ```
+    #[instrument(
+        name = "clickhouse.query_with_external_tables",
+        skip_all,
+        fields(
+            db.system = "clickhouse",
+            db.operation = "query",
+            db.format = NativeFormat::FORMAT,
+            clickhouse.query.id
+        )
+    )]
+    pub async fn query_with_external_tables<T: Row + Send + 'static>(
+        &self,
+        query: impl Into<ParsedQuery>,
+        external_tables: impl IntoIterator<Item = (impl Into<String>, Block)>,
+        options: QueryOptions,
+    ) -> Result<ClickHouseResponse<T>> {
+        if options.limits.is_some() || options.explain.is_some() {
+            return Err(Error::Client(
+                "Limits and explain are not supported by the native client".into(),
+            ));
+        }
+
+        let external = external_tables.into_iter().map(|(name, block)| (name.into(), block));
+        let settings = options.merge_settings(self.settings.as_ref());
+        let (query, qid) = record_query(options.qid, query.into(), self.client_id);
+        let raw = self.send_query(query, options, qid, settings, true, external.collect()).await?;
+        let summary = raw.summary_handle();
+        let rows = raw.flat_map(|block| match block {
+            Ok(mut block) => stream::iter(
+                block
+                    .take_iter_rows()
+                    .filter(|x| !x.is_empty())
+                    .map(T::deserialize_row)
+                    .map(|maybe| maybe.inspect_err(|error| error!(?error, "deserializing row")))
+                    .collect::<Vec<_>>(),
+            ),
+            Err(e) => stream::iter(vec![Err(e)]),
+        });
+        Ok(ClickHouseResponse::from_stream(rows).with_summary(summary))
     }
 
     /// Executes a `ClickHouse` query and returns the first row, discarding the rest.
@@ -1538,7 +2110,7 @@ impl Client<ArrowFormat> {
         qid: Option<Qid>,
     ) -> Result<ClickHouseResponse<RecordBatch>> {
         let (query, qid) = record_query(qid, query.into(), self.client_id);
-        Ok(ClickHouseResponse::new(Box::pin(self.query_raw(query, params, qid).await?)))
+        self.query_raw(query, params, qid).await
     }
 
     /// Executes a `ClickHouse` query with result limits and streams Arrow [`RecordBatch`] results.
@@ -1729,7 +2301,7 @@ impl Client<ArrowFormat> {
     pub async fn query_with_options(
         &self,
         query: impl Into<ParsedQuery>,
-        options: QueryOptions,
+        mut options: QueryOptions,
     ) -> Result<ClickHouseResponse<RecordBatch>> {
         use crate::explain::{ExplainFormat, ExplainResult};
 
@@ -1823,11 +2395,15 @@ impl Client<ArrowFormat> {
 
         // Execute the actual query
         let (query_str, recorded_qid) = record_query(Some(qid), parsed_query, self.client_id);
-        let stream = self.query_raw(query_str, options.params, recorded_qid).await?;
+        let settings = options.merge_settings(self.settings.as_ref());
+        let limits = options.limits.take();
+        let stream =
+            self.send_query(query_str, options, recorded_qid, settings, true, vec![]).await?;
+        let summary = stream.summary_handle();
 
         // Wrap in limited response if limits are configured
-        let response = if let Some(limits) = options.limits {
-            let limited = LimitedResponse::new(ClickHouseResponse::from_stream(stream), limits);
+        let response = if let Some(limits) = limits {
+            let limited = LimitedResponse::new(stream, limits);
             // Note: We lose the explain receiver here since LimitedResponse wraps
             // ClickHouseResponse For now, we'll handle this by not supporting limits +
             // explain together TODO: Consider wrapping LimitedResponse to preserve
@@ -1843,10 +2419,76 @@ impl Client<ArrowFormat> {
         } else if let Some(rx) = explain_receiver {
             ClickHouseResponse::with_explain(Box::pin(stream), rx)
         } else {
-            ClickHouseResponse::new(Box::pin(stream))
+            stream
         };
 
-        Ok(response)
+        Ok(response.with_summary(summary))
+    }
+
+    /// Executes a `ClickHouse` query with external tables and streams Arrow [`RecordBatch`]
+    /// results.
+    ///
+    /// Each batch is sent with the query as a temporary table named by its key, which the query
+    /// can read like any table, e.g. `WHERE id IN ids` or `JOIN ids USING (id)`. The columns of
+    /// the table are those of the batch schema, converted as for inserts. Batches with the same
+    /// name are appended to the same table. This avoids inlining large lists of values into the
+    /// query text.
+    ///
+    /// # Parameters
+    /// - `query`: The SQL query to execute.
+    /// - `external_tables`: `(name, batch)` pairs sent as temporary tables.
+    /// - `options`: Per-query parameters, query ID and settings. Limits and explain are not
+    ///   supported.
+    ///
+    /// # Returns
+    /// A [`Result`] containing a [`ClickHouseResponse<RecordBatch>`] that streams query results.
+    ///
+    /// # Errors
+    /// - Fails if a table name is empty, or the options contain limits or explain configuration.
+    /// - Fails if a batch cannot be serialized, which also closes the connection.
+    /// - Fails if the query is malformed or `ClickHouse` returns an exception.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::UInt64, false)]));
+    /// let ids = RecordBatch::try_new(schema, vec![Arc::new(UInt64Array::from(ids))])?;
+    ///
+    /// let mut response = client
+    ///     .query_with_external_tables(
+    ///         "SELECT * FROM my_table WHERE id IN ids",
+    ///         [("ids", ids)],
+    ///         QueryOptions::new(),
+    ///     )
+    ///     .await?;
+    /// ```
+    #[instrument(
+        name = "clickhouse.query_with_external_tables",
+        skip_all,
+        fields(
+            db.system = "clickhouse",
+            db.operation = "query",
+            db.format = ArrowFormat::FORMAT,
+            clickhouse.query.id
+        )
+    )]
+    pub async fn query_with_external_tables(
+        &self,
+        query: impl Into<ParsedQuery>,
+        external_tables: impl IntoIterator<Item = (impl Into<String>, RecordBatch)>,
+        options: QueryOptions,
+    ) -> Result<ClickHouseResponse<RecordBatch>> {
+        if options.limits.is_some() || options.explain.is_some() {
+            return Err(Error::Client(
+                "Limits and explain are not supported with external tables".into(),
+            ));
+        }
+
+        let external = external_tables.into_iter().map(|(name, batch)| (name.into(), batch));
+        let settings = options.merge_settings(self.settings.as_ref());
+        let (query, qid) = record_query(options.qid, query.into(), self.client_id);
+        self.send_query(query, options, qid, settings, true, external.collect()).await
     }
 
     /// Extract text from EXPLAIN result batches.
@@ -1931,6 +2573,7 @@ impl Client<ArrowFormat> {
         // Create metadata channel
         let (tx, rx) = oneshot::channel();
         let (header_tx, header_rx) = oneshot::channel();
+        let summary = SummaryHandle::default();
 
         #[cfg_attr(not(feature = "inner_pool"), expect(unused_variables))]
         let conn_idx = connection
@@ -1942,6 +2585,12 @@ impl Client<ArrowFormat> {
                     params: None,
                     response: tx,
                     header: Some(header_tx),
+                    cancel_on_drop: true,
+                    summary: Some(Arc::clone(&summary)),
+                    compression: None,
+                    external: Vec::new(),
+                    trace: TraceParent::resolve(None),
+                    quota_key: None,
                 },
                 qid,
                 true,
@@ -1971,7 +2620,7 @@ impl Client<ArrowFormat> {
         #[cfg(feature = "inner_pool")]
         connection.finish(conn_idx, Operation::<RecordBatch>::weight_insert_many());
 
-        Ok(ClickHouseResponse::from_stream(response))
+        Ok(ClickHouseResponse::from_stream(response).with_summary(Some(summary)))
     }
 
     /// Executes a `ClickHouse` query and returns the first column of the first batch.
@@ -3,12 +3,15 @@ use std::net::SocketAddr;
 use std::path::Path;
 use std::sync::Arc;
 
+use tokio_rustls::rustls;
 use tracing::error;
 
 use super::tcp::Destination;
 use super::{
-    ArrowOptions, Client, ClientFormat, CompressionMethod, ConnectionContext, Extension, Secret,
+    ArrowOptions, Auth, Client, ClientFormat, CompressionMethod, ConnectionContext, Extension,
+    LoadBalancing, ReconnectPolicy, ReplicaOptions, Secret,
 };
+use crate::native::protocol::ServerLogLevel;
 #[cfg(feature = "pool")]
 use crate::pool::ConnectionManager;
 use crate::prelude::SettingValue;
@@ -380,6 +383,124 @@ impl ClientBuilder {
         self
     }
 
+    /// Sets the client certificate and private key for mutual TLS.
+    ///
+    /// This method configures the client to authenticate itself to the `ClickHouse`
+    /// server with a certificate, for servers that require client certificates. Both
+    /// files must be PEM encoded. The key may be PKCS#1, PKCS#8, or SEC1. TLS must
+    /// be enabled (via [`ClientBuilder::with_tls`]).
+    ///
+    /// # Parameters
+    /// - `cert`: The path to the client certificate chain.
+    /// - `key`: The path to the client private key.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated client certificate setting.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("localhost:9440")
+    ///     .with_tls(true)
+    ///     .with_cafile("/path/to/ca.crt")
+    ///     .with_client_cert("/path/to/client.crt", "/path/to/client.key");
+    /// ```
+    #[must_use]
+    pub fn with_client_cert<P: AsRef<Path>>(mut self, cert: P, key: P) -> Self {
+        self.options.ext.tls = self.options.ext.tls.with_client_cert(cert, key);
+        self
+    }
+
+    /// Trusts the operating system's certificate store for TLS connections.
+    ///
+    /// This method adds the platform's native root certificates to the trusted roots,
+    /// alongside any CA file set via [`ClientBuilder::with_cafile`]. When neither is
+    /// configured, the bundled webpki roots are used.
+    ///
+    /// # Parameters
+    /// - `enabled`: If `true`, loads the native root certificates.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated native roots setting.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("clickhouse.internal:9440")
+    ///     .with_tls(true)
+    ///     .with_native_roots(true);
+    /// ```
+    #[must_use]
+    pub fn with_native_roots(mut self, enabled: bool) -> Self {
+        self.options.ext.tls = self.options.ext.tls.with_native_roots(enabled);
+        self
+    }
+
+    /// Sets the server name (SNI) used for TLS connections.
+    ///
+    /// This method overrides the name sent during the TLS handshake and used to verify
+    /// the server's certificate. It takes precedence over [`ClientBuilder::with_domain`]
+    /// and the host of the destination, which is useful when connecting by IP address
+    /// or through a proxy.
+    ///
+    /// # Parameters
+    /// - `server_name`: The server name to send and verify.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated server name.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("10.0.0.5:9440")
+    ///     .with_tls(true)
+    ///     .with_tls_server_name("clickhouse.example.com");
+    /// ```
+    #[must_use]
+    pub fn with_tls_server_name(mut self, server_name: impl Into<String>) -> Self {
+        self.options.ext.tls = self.options.ext.tls.with_server_name(server_name);
+        self
+    }
+
+    /// Sets a custom `rustls` client configuration for TLS connections.
+    ///
+    /// This method replaces the configuration otherwise built from the CA file, client
+    /// certificate, and native roots settings, giving full control over verification,
+    /// protocol versions, and ALPN. TLS is enabled automatically. The server name is
+    /// still taken from [`ClientBuilder::with_tls_server_name`], the domain, or the
+    /// destination.
+    ///
+    /// # Parameters
+    /// - `config`: The `rustls` client configuration.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the custom TLS configuration.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use std::sync::Arc;
+    /// use clickhouse_arrow::prelude::*;
+    /// use clickhouse_arrow::rustls;
+    ///
+    /// let config = rustls::ClientConfig::builder()
+    ///     .with_root_certificates(rustls::RootCertStore::empty())
+    ///     .with_no_client_auth();
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("localhost:9440")
+    ///     .with_tls_config(Arc::new(config));
+    /// ```
+    #[must_use]
+    pub fn with_tls_config(mut self, config: Arc<rustls::ClientConfig>) -> Self {
+        self.context.get_or_insert_default().tls_config = Some(config);
+        self.with_tls(true)
+    }
+
     /// Forces the use of IPv4-only resolution for the `ClickHouse` server address.
     ///
     /// This method configures whether the destination address resolution (during
@@ -471,6 +592,90 @@ impl ClientBuilder {
         self
     }
 
+    /// Sets the minimum level of server log lines sent to the client.
+    ///
+    /// This method sets the `send_logs_level` setting for all queries executed by the
+    /// client. Log lines are delivered as [`crate::ClickHouseEvent::Log`] events (see
+    /// [`Client::subscribe_events`]) and, if enabled via
+    /// [`ClientBuilder::with_trace_server_logs`], re-emitted as `tracing` events. Use
+    /// [`crate::explain::QueryOptions::with_send_logs_level`] to override it per query.
+    ///
+    /// # Parameters
+    /// - `level`: The minimum server log level, [`ServerLogLevel::None`] disables logs.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated `send_logs_level` setting.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("localhost:9000")
+    ///     .with_send_logs_level(ServerLogLevel::Debug)
+    ///     .with_trace_server_logs(true);
+    /// ```
+    #[must_use]
+    pub fn with_send_logs_level(self, level: ServerLogLevel) -> Self {
+        self.with_setting("send_logs_level", level)
+    }
+
+    /// Re-emits server log lines as `tracing` events.
+    ///
+    /// When enabled, each server log line is emitted with the target
+    /// `clickhouse_arrow::server` at the `tracing` level matching its priority, tagged
+    /// with the query id. Server logs are only sent when `send_logs_level` is set (via
+    /// [`ClientBuilder::with_send_logs_level`] or per query).
+    ///
+    /// # Parameters
+    /// - `enabled`: If `true`, server log lines are re-emitted as `tracing` events.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated setting.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("localhost:9000")
+    ///     .with_send_logs_level(ServerLogLevel::Trace)
+    ///     .with_trace_server_logs(true);
+    /// ```
+    #[must_use]
+    pub fn with_trace_server_logs(mut self, enabled: bool) -> Self {
+        self.options.ext.trace_server_logs = enabled;
+        self
+    }
+
+    /// Inserts columns made up mostly of default values with sparse serialization.
+    ///
+    /// A column is sent sparse, as the positions and values of its non-default rows, when the
+    /// ratio of default values (zeros, empty strings, etc.) in a block reaches `ratio`. This
+    /// mirrors the server setting `ratio_of_defaults_for_sparse_serialization`: a ratio of `1.0`
+    /// or more disables it. Only supported for non-nullable numbers, strings, dates, UUIDs and IP
+    /// addresses, and only with servers supporting custom serialization (revision 54454+).
+    ///
+    /// # Parameters
+    /// - `ratio`: The minimum ratio of default values, for example `0.9`.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated setting.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("localhost:9000")
+    ///     .with_sparse_serialization_ratio(0.9);
+    /// ```
+    #[must_use]
+    pub fn with_sparse_serialization_ratio(mut self, ratio: f64) -> Self {
+        self.options.ext.sparse_serialization_ratio = Some(ratio);
+        self
+    }
+
     /// Sets the username for authenticating with `ClickHouse`.
     ///
     /// This method configures the username used to authenticate the client with the
@@ -527,6 +732,34 @@ impl ClientBuilder {
         self
     }
 
+    /// Sets how the client authenticates with `ClickHouse`.
+    ///
+    /// By default the username and password are sent in the handshake. With the `ssh` feature,
+    /// [`Auth::SshKey`] authenticates the username with an SSH private key instead: the server
+    /// sends a challenge during the handshake, which the client signs with the key. The password
+    /// is not sent.
+    ///
+    /// # Parameters
+    /// - `auth`: The authentication method.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated authentication method.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("localhost:9000")
+    ///     .with_username("ssh_user")
+    ///     .with_auth(Auth::ssh_key_file("/home/me/.ssh/id_ed25519", None::<&str>));
+    /// ```
+    #[must_use]
+    pub fn with_auth(mut self, auth: Auth) -> Self {
+        self.options.ext.auth = auth;
+        self
+    }
+
     /// Sets the default database for the `ClickHouse` connection.
     ///
     /// This method configures the default database used by the client for queries and
@@ -610,6 +843,63 @@ impl ClientBuilder {
         self
     }
 
+    /// Sets the compression level for data sent to `ClickHouse`.
+    ///
+    /// Higher levels trade compression speed for smaller data, e.g. for inserts over slow
+    /// links. For [`CompressionMethod::ZSTD`] levels range up to 22 (default 1), negative
+    /// levels compress faster. For `LZ4HC`, with the `lz4hc` feature, levels range from 1 to 12
+    /// (default 9). [`CompressionMethod::LZ4`] has no levels. The `ZSTD` level is also
+    /// requested for data sent by the server. Use
+    /// [`crate::explain::QueryOptions::with_compression`] to override it per insert.
+    ///
+    /// # Parameters
+    /// - `level`: The compression level.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated compression level.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("localhost:9000")
+    ///     .with_compression(CompressionMethod::ZSTD)
+    ///     .with_compression_level(9);
+    /// ```
+    #[must_use]
+    pub fn with_compression_level(mut self, level: i32) -> Self {
+        self.options.ext.compression_options.level = Some(level);
+        self
+    }
+
+    /// Sets the maximum size of the data compressed into a single block.
+    ///
+    /// Data packets larger than `size` bytes are split into several compressed blocks, as done
+    /// by `ClickHouse` with its `max_compress_block_size` setting (1 MiB by default). Smaller
+    /// blocks lower the memory used to decompress them, larger blocks may improve the ratio. By
+    /// default each data packet is compressed into a single block.
+    ///
+    /// # Parameters
+    /// - `size`: The maximum uncompressed size of a block, in bytes.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated block size.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("localhost:9000")
+    ///     .with_max_compressed_block_size(1024 * 1024);
+    /// ```
+    #[must_use]
+    pub fn with_max_compressed_block_size(mut self, size: usize) -> Self {
+        self.options.ext.compression_options.max_block_size = Some(size);
+        self
+    }
+
     /// Sets the Arrow-specific options for `ClickHouse` connections.
     ///
     /// This method configures options specific to the Arrow format (used by
@@ -638,6 +928,93 @@ impl ClientBuilder {
         self
     }
 
+    /// Enables automatic reconnection using the provided [`ReconnectPolicy`].
+    ///
+    /// When the underlying connection errors or closes, the client re-establishes it before
+    /// sending the next operation, retrying with exponential backoff as configured by the
+    /// policy. The handshake is performed again on each attempt and client settings are re-sent
+    /// with every query. Progress of the recovery is reported through
+    /// [`Client::subscribe_events`] and reflected in [`Client::status`]. Queries in flight when
+    /// the connection was lost are not retried.
+    ///
+    /// # Parameters
+    /// - `policy`: The reconnect policy (attempts and backoff).
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with reconnection enabled.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("localhost:9000")
+    ///     .with_reconnect(ReconnectPolicy::default().with_max_attempts(Some(10)));
+    /// ```
+    #[must_use]
+    pub fn with_reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.options.ext.reconnect = Some(policy);
+        self
+    }
+
+    /// Sets the strategy used to choose between replicas.
+    ///
+    /// This only has an effect when the destination lists multiple hosts, either through
+    /// [`Destination::replicas`] or a comma separated endpoint. Connections fail over to the
+    /// remaining replicas regardless of strategy. See [`LoadBalancing`] for the available
+    /// strategies and [`ClientBuilder::with_replica_options`] to configure health tracking.
+    ///
+    /// # Parameters
+    /// - `strategy`: The load balancing strategy.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated strategy.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("replica-1:9000,replica-2:9000,replica-3:9000")
+    ///     .with_load_balancing(LoadBalancing::RoundRobin);
+    /// ```
+    #[must_use]
+    pub fn with_load_balancing(mut self, strategy: LoadBalancing) -> Self {
+        self.options.ext.replicas.strategy = strategy;
+        self
+    }
+
+    /// Sets the options used when the destination lists multiple replicas.
+    ///
+    /// Besides the [`LoadBalancing`] strategy, this configures how long a replica that failed
+    /// to connect is considered down. Health is shared by every client built from this builder,
+    /// including pooled connections.
+    ///
+    /// # Parameters
+    /// - `options`: The replica options.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated replica options.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use std::time::Duration;
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_destination(Destination::replicas(["replica-1:9000", "replica-2:9000"]))
+    ///     .with_replica_options(
+    ///         ReplicaOptions::default()
+    ///             .with_strategy(LoadBalancing::FirstHealthy)
+    ///             .with_retry_after(Duration::from_secs(10)),
+    ///     );
+    /// ```
+    #[must_use]
+    pub fn with_replica_options(mut self, options: ReplicaOptions) -> Self {
+        self.options.ext.replicas = options;
+        self
+    }
+
     /// Sets a tracing context for `ClickHouse` connections and queries.
     ///
     /// This method configures a [`TraceContext`] to enable distributed tracing for
@@ -697,34 +1074,38 @@ impl ClientBuilder {
     /// println!("Destination verified!");
     /// ```
     pub async fn verify(mut self) -> Result<Self> {
-        let (addrs, domain) = {
-            let destination =
-                self.destination.as_ref().ok_or(Error::MissingConnectionInformation)?;
-            let addrs = destination
-                .resolve(self.options.ipv4_only)
-                .await
-                .inspect_err(|error| error!(?error, "Failed to resolve destination"))?;
-            if addrs.is_empty() {
-                return Err(Error::MalformedConnectionInformation(
-                    "Socket addresses cannot be empty".into(),
-                ));
-            }
+        let destination = self.destination.take().ok_or(Error::MissingConnectionInformation)?;
+        let mut replicas = destination
+            .resolve_replicas(self.options.ipv4_only)
+            .await
+            .inspect_err(|error| error!(?error, "Failed to resolve destination"))?;
+        if replicas.iter().all(|r| r.addrs.is_empty()) {
+            return Err(Error::MalformedConnectionInformation(
+                "Socket addresses cannot be empty".into(),
+            ));
+        }
 
+        if self.options.use_tls
+            && self.options.domain.is_none()
+            && self.options.ext.tls.server_name.is_none()
+            && replicas.iter().any(|r| r.domain.is_empty())
+        {
+            return Err(Error::MalformedConnectionInformation(
+                "Domain required for TLS, couldn't be determined from destination".into(),
+            ));
+        }
+
+        // Replicas are resolved again on connect, each using its own host for TLS
+        if destination.is_replicas() {
+            self.destination = Some(destination);
+            let _ = self.context.get_or_insert_default().replicas.get_or_insert_default();
+        } else {
+            let replica = replicas.remove(0);
             if self.options.use_tls && self.options.domain.is_none() {
-                let domain = destination.domain();
-                if domain.is_empty() {
-                    return Err(Error::MalformedConnectionInformation(
-                        "Domain required for TLS, couldn't be determined from destination".into(),
-                    ));
-                }
-                (addrs, Some(domain))
-            } else {
-                (addrs, self.options.domain)
+                self.options.domain = Some(replica.domain);
             }
-        };
-
-        self.options.domain = domain;
-        self.destination = Some(Destination::from(addrs));
+            self.destination = Some(Destination::from(replica.addrs));
+        }
         self.verified = true;
 
         Ok(self)
@@ -813,6 +1194,7 @@ impl ClientBuilder {
     /// - `endpoint` → Base URL (use `http://` or `https://` scheme)
     /// - `username` → X-ClickHouse-User header
     /// - `database` → X-ClickHouse-Database header
+    /// - `settings` → Settings sent with every request
     ///
     /// Note: For password authentication, use [`HttpOptions`](crate::http::HttpOptions)
     /// directly with [`HttpClient::new`](crate::http::HttpClient::new).
@@ -863,6 +1245,8 @@ impl ClientBuilder {
             Some(self.options.default_database)
         };
 
+        options.settings = self.settings;
+
         HttpClient::new(options)
     }
 
@@ -1058,6 +1442,7 @@ mod tests {
     use std::path::PathBuf;
 
     use super::*;
+    use crate::CompressionOptions;
 
     fn default_builder() -> ClientBuilder { ClientBuilder::new() }
 
@@ -1131,6 +1516,61 @@ mod tests {
         assert_eq!(builder.options().cafile, Some(cafile));
     }
 
+    #[test]
+    fn test_with_client_cert() {
+        let builder = default_builder()
+            .with_client_cert("/path/to/client.crt", "/path/to/client.key")
+            .with_native_roots(true)
+            .with_tls_server_name("example.com");
+        let tls = &builder.options().ext.tls;
+        assert_eq!(tls.client_cert, Some(PathBuf::from("/path/to/client.crt")));
+        assert_eq!(tls.client_key, Some(PathBuf::from("/path/to/client.key")));
+        assert!(tls.native_roots);
+        assert_eq!(tls.server_name.as_deref(), Some("example.com"));
+    }
+
+    #[test]
+    fn test_with_tls_config() {
+        let _ = rustls::crypto::aws_lc_rs::default_provider().install_default().ok();
+        let config = rustls::ClientConfig::builder()
+            .with_root_certificates(rustls::RootCertStore::empty())
+            .with_no_client_auth();
+        let builder = default_builder().with_tls_config(Arc::new(config));
+        assert!(builder.options().use_tls);
+        assert!(builder.context.as_ref().and_then(|c| c.tls_config.as_ref()).is_some());
+    }
+
+    #[test]
+    fn test_with_send_logs_level() {
+        let builder = default_builder()
+            .with_send_logs_level(ServerLogLevel::Debug)
+            .with_trace_server_logs(true);
+        let settings = builder.settings().unwrap().encode_to_key_value_strings();
+        assert_eq!(settings, vec![("send_logs_level".to_string(), "debug".to_string())]);
+        assert!(builder.options().ext.trace_server_logs);
+    }
+
+    #[test]
+    fn test_with_compression_options() {
+        let builder = default_builder()
+            .with_compression(CompressionMethod::ZSTD)
+            .with_compression_level(12)
+            .with_max_compressed_block_size(1024);
+        assert_eq!(builder.options().compression, CompressionMethod::ZSTD);
+        assert_eq!(
+            builder.options().ext.compression_options,
+            CompressionOptions::new().with_level(12).with_max_block_size(1024)
+        );
+    }
+
+    #[test]
+    fn test_with_sparse_serialization_ratio() {
+        let builder = default_builder();
+        assert_eq!(builder.options().ext.sparse_serialization_ratio, None);
+        let builder = builder.with_sparse_serialization_ratio(0.9);
+        assert_eq!(builder.options().ext.sparse_serialization_ratio, Some(0.9));
+    }
+
     #[test]
     fn test_with_settings() {
         let settings = Settings::default();
@@ -1151,6 +1591,14 @@ mod tests {
         assert!(!builder.verified());
     }
 
+    #[test]
+    fn test_with_reconnect() {
+        assert_eq!(default_builder().options().ext.reconnect, None);
+        let policy = ReconnectPolicy::default().with_max_attempts(Some(3));
+        let builder = default_builder().with_reconnect(policy);
+        assert_eq!(builder.options().ext.reconnect, Some(policy));
+    }
+
     #[test]
     fn test_with_trace_context() {
         let trace_context = TraceContext::default();
@@ -1,22 +1,24 @@
-use std::collections::VecDeque;
-use std::net::SocketAddr;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
-use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::time::Instant;
 
-#[cfg(feature = "inner_pool")]
 use arc_swap::ArcSwap;
 use parking_lot::Mutex;
 use strum::Display;
 use tokio::io::{AsyncWriteExt, BufReader, BufWriter};
-use tokio::sync::{broadcast, mpsc};
+use tokio::sync::{broadcast, mpsc, watch};
 use tokio::task::{AbortHandle, JoinSet};
 use tokio_rustls::rustls;
 
 use super::internal::{InternalConn, PendingQuery};
-use super::{ArrowOptions, CompressionMethod, Event};
+use super::replicas::ReplicaSet;
+use super::{ArrowOptions, ClickHouseEvent, CompressionMethod, Event, ReconnectPolicy};
 use crate::client::chunk::{ChunkReader, ChunkWriter};
+use crate::compression::DEFAULT_ZSTD_LEVEL;
 use crate::flags::{conn_read_buffer_size, conn_write_buffer_size};
 use crate::io::{ClickHouseRead, ClickHouseWrite};
+use crate::metrics::record_reconnect;
 use crate::native::protocol::{
     ClientHello, DBMS_MIN_PROTOCOL_VERSION_WITH_ADDENDUM, DBMS_TCP_PROTOCOL_VERSION, ServerHello,
 };
@@ -32,6 +34,8 @@ pub enum ConnectionStatus {
     Open,
     Closed,
     Error,
+    /// The connection was lost and is being re-established, see [`ReconnectPolicy`].
+    Reconnecting,
 }
 
 impl From<u8> for ConnectionStatus {
@@ -39,6 +43,7 @@ impl From<u8> for ConnectionStatus {
         match value {
             0 => Self::Open,
             1 => Self::Closed,
+            3 => Self::Reconnecting,
             _ => Self::Error,
         }
     }
@@ -51,30 +56,38 @@ impl From<ConnectionStatus> for u8 {
 /// Client metadata passed around the internal client
 #[derive(Debug, Clone, Copy)]
 pub(crate) struct ClientMetadata {
-    pub(crate) client_id:     u16,
-    pub(crate) compression:   CompressionMethod,
-    pub(crate) arrow_options: ArrowOptions,
+    pub(crate) client_id:           u16,
+    pub(crate) compression:         CompressionMethod,
+    pub(crate) compression_options: CompressionOptions,
+    pub(crate) arrow_options:       ArrowOptions,
+    /// Whether server log lines are re-emitted as `tracing` events.
+    pub(crate) trace_server_logs:   bool,
+    /// Minimum ratio of default values for columns to be inserted with sparse serialization.
+    pub(crate) sparse_ratio:        Option<f64>,
 }
 
 impl ClientMetadata {
     /// Helper function to disable compression on the metadata.
     pub(crate) fn disable_compression(self) -> Self {
-        Self {
-            client_id:     self.client_id,
-            compression:   CompressionMethod::None,
-            arrow_options: self.arrow_options,
-        }
+        Self { compression: CompressionMethod::None, ..self }
+    }
+
+    /// Helper function to override the compression options, e.g. for a single insert.
+    pub(crate) fn with_compression_options(self, compression_options: CompressionOptions) -> Self {
+        Self { compression_options, ..self }
     }
 
     /// Helper function to provide settings for compression
     pub(crate) fn compression_settings(self) -> Settings {
         match self.compression {
             CompressionMethod::None | CompressionMethod::LZ4 => Settings::default(),
-            CompressionMethod::ZSTD => vec![
-                ("network_compression_method", "zstd"),
-                ("network_zstd_compression_level", "1"),
-            ]
-            .into(),
+            #[cfg(feature = "lz4hc")]
+            CompressionMethod::LZ4HC => vec![("network_compression_method", "lz4hc")].into(),
+            CompressionMethod::ZSTD => {
+                let level = self.compression_options.level.unwrap_or(DEFAULT_ZSTD_LEVEL);
+                Settings::from(vec![("network_compression_method", "zstd")])
+                    .with_setting("network_zstd_compression_level", level)
+            }
         }
     }
 }
@@ -82,27 +95,62 @@ impl ClientMetadata {
 /// A struct defining the information needed to connect over TCP.
 #[derive(Debug)]
 struct ConnectState<T: Send + Sync + 'static> {
-    status:  Arc<AtomicU8>,
-    channel: mpsc::Sender<Message<T>>,
-    #[expect(unused)]
-    handle:  AbortHandle,
+    status:     Arc<AtomicU8>,
+    channel:    mpsc::Sender<Message<T>>,
+    handle:     AbortHandle,
+    /// Incremented each time the inner connection is re-established.
+    generation: u64,
 }
 
-// NOTE: ArcSwaps are used to support reconnects in the future.
+impl<T: Send + Sync + 'static> ConnectState<T> {
+    /// Whether the io task is running and accepting operations.
+    fn is_open(&self) -> bool {
+        self.status.load(Ordering::Acquire) == u8::from(ConnectionStatus::Open)
+            && !self.channel.is_closed()
+    }
+}
+
+/// Outcome of a reconnect, `None` while in progress. Failures carry the error message.
+type ReconnectOutcome = Option<std::result::Result<(), String>>;
+
+/// Reconnects in progress by connection index.
+type Reconnects = Mutex<HashMap<usize, watch::Receiver<ReconnectOutcome>>>;
+
+/// Reconnects that gave up by connection index, with when they did and the error.
+type ReconnectFailures = Mutex<HashMap<usize, (Instant, String)>>;
+
+/// Ends a reconnect in progress when the leading task finishes or is dropped.
+struct ReconnectGuard<'a> {
+    reconnecting: &'a Reconnects,
+    idx:          usize,
+}
+
+impl Drop for ReconnectGuard<'_> {
+    fn drop(&mut self) { drop(self.reconnecting.lock().remove(&self.idx)); }
+}
+
+// NOTE: ArcSwaps are used to support reconnects.
 #[derive(Debug)]
 pub(super) struct Connection<T: ClientFormat> {
-    #[expect(unused)]
-    addrs:         Arc<[SocketAddr]>,
+    replicas:      ReplicaSet,
+    tls:           Option<Arc<rustls::ClientConfig>>,
     options:       Arc<ClientOptions>,
     io_task:       Arc<Mutex<IoHandle<T::Data>>>,
+    events:        Arc<broadcast::Sender<Event>>,
     metadata:      ClientMetadata,
     #[cfg(not(feature = "inner_pool"))]
-    state:         Arc<ConnectState<T::Data>>,
+    state:         ArcSwap<ConnectState<T::Data>>,
     /// NOTE: Max connections must remain at 4, unless algorithm changes
     #[cfg(feature = "inner_pool")]
     state:         Vec<ArcSwap<ConnectState<T::Data>>>,
     #[cfg(feature = "inner_pool")]
     load_balancer: Arc<load::AtomicLoad>,
+    /// Reconnects in progress, so a lost connection is only re-established once.
+    reconnecting:  Reconnects,
+    /// Failed reconnects, operations fail fast until the policy's cooldown has passed.
+    failures:      ReconnectFailures,
+    /// Set on shutdown, closed connections are not re-established afterwards.
+    shutdown:      AtomicBool,
 }
 
 impl<T: ClientFormat> Connection<T> {
@@ -120,38 +168,52 @@ impl<T: ClientFormat> Connection<T> {
     )]
     pub(crate) async fn connect(
         client_id: u16,
-        addrs: Vec<SocketAddr>,
+        replicas: ReplicaSet,
         options: ClientOptions,
         events: Arc<broadcast::Sender<Event>>,
         trace_ctx: TraceContext,
+        tls_config: Option<Arc<rustls::ClientConfig>>,
     ) -> Result<Self> {
         let span = Span::current();
         span.in_scope(|| trace!({ {ATT_CID} = client_id }, "connecting stream"));
         let _ = trace_ctx.link(&span);
 
         // Create joinset
-        let mut io_task = JoinSet::new();
+        let io_task = Mutex::new(JoinSet::new());
 
         // Construct connection metadata
         let metadata = ClientMetadata {
             client_id,
             compression: options.compression,
+            compression_options: options.ext.compression_options,
             arrow_options: options.ext.arrow.unwrap_or_default(),
+            trace_server_logs: options.ext.trace_server_logs,
+            sparse_ratio: options.ext.sparse_serialization_ratio,
         };
 
-        // Install rustls provider if using tls
-        if options.use_tls {
+        // Install rustls provider and build the tls config (once, to share resumption) if using tls
+        let tls = if options.use_tls {
             drop(rustls::crypto::aws_lc_rs::default_provider().install_default());
-        }
+            Some(tls_config.map_or_else(|| super::tcp::tls_config(&options), Ok)?)
+        } else {
+            None
+        };
 
         // Establish tcp connection, perform handshake, and spawn io task
-        let state = Arc::new(
-            Self::connect_inner(&addrs, &mut io_task, Arc::clone(&events), &options, metadata)
-                .await?,
+        let state = ArcSwap::from_pointee(
+            Self::connect_inner(
+                &replicas,
+                tls.as_ref(),
+                &io_task,
+                Arc::clone(&events),
+                &options,
+                metadata,
+            )
+            .await?,
         );
 
         #[cfg(feature = "inner_pool")]
-        let mut state = vec![ArcSwap::from(state)];
+        let mut state = vec![state];
 
         // Inner pool: Spawn additional connections for improved concurrency.
         // Default is 4, max is 16. User can configure via fast_mode_size option.
@@ -164,41 +226,88 @@ impl<T: ClientFormat> Connection<T> {
         #[cfg(feature = "inner_pool")]
         for _ in 0..inner_pool_size.saturating_sub(1) {
             let events = Arc::clone(&events);
-            state.push(ArcSwap::from(Arc::new(
-                Self::connect_inner(&addrs, &mut io_task, events, &options, metadata).await?,
-            )));
+            state.push(ArcSwap::from_pointee(
+                Self::connect_inner(&replicas, tls.as_ref(), &io_task, events, &options, metadata)
+                    .await?,
+            ));
         }
 
         Ok(Self {
-            addrs: Arc::from(addrs.as_slice()),
-            io_task: Arc::new(Mutex::new(io_task)),
+            replicas,
+            tls,
+            io_task: Arc::new(io_task),
+            events,
             options: Arc::new(options),
             metadata,
             state,
             #[cfg(feature = "inner_pool")]
             load_balancer: Arc::new(load::AtomicLoad::new(inner_pool_size)),
+            reconnecting: Mutex::default(),
+            failures: Mutex::default(),
+            shutdown: AtomicBool::new(false),
         })
     }
 
     async fn connect_inner(
-        addrs: &[SocketAddr],
-        io_task: &mut IoHandle<T::Data>,
+        replicas: &ReplicaSet,
+        tls: Option<&Arc<rustls::ClientConfig>>,
+        io_task: &Mutex<IoHandle<T::Data>>,
         events: Arc<broadcast::Sender<Event>>,
         options: &ClientOptions,
         metadata: ClientMetadata,
     ) -> Result<ConnectState<T::Data>> {
-        if options.use_tls {
-            let tls_stream = super::tcp::connect_tls(addrs, options.domain.as_deref()).await?;
-            Self::establish_connection(tls_stream, io_task, events, options, metadata).await
+        if let Some(tls) = tls {
+            // Server name precedence: explicit override, configured domain, then replica host
+            let server_name = options.ext.tls.server_name.as_deref().or(options.domain.as_deref());
+            Self::connect_replica(replicas, |addr, domain| {
+                let domain = server_name.unwrap_or(domain);
+                let events = Arc::clone(&events);
+                async move {
+                    let stream = super::tcp::connect_tls(addr, domain, Arc::clone(tls)).await?;
+                    Self::establish_connection(stream, io_task, events, options, metadata).await
+                }
+            })
+            .await
         } else {
-            let tcp_stream = super::tcp::connect_socket(addrs).await?;
-            Self::establish_connection(tcp_stream, io_task, events, options, metadata).await
+            Self::connect_replica(replicas, |addr, _| {
+                let events = Arc::clone(&events);
+                async move {
+                    let stream = super::tcp::connect_socket(addr).await?;
+                    Self::establish_connection(stream, io_task, events, options, metadata).await
+                }
+            })
+            .await
+        }
+    }
+
+    /// Connects to the first replica address that accepts the connection and completes the
+    /// handshake, in the order given by the configured [`super::LoadBalancing`] strategy,
+    /// tracking the health of each address.
+    async fn connect_replica<'a, S, F, Fut>(replicas: &'a ReplicaSet, connect: F) -> Result<S>
+    where
+        F: Fn(std::net::SocketAddr, &'a str) -> Fut,
+        Fut: Future<Output = Result<S>>,
+    {
+        let mut last_error = None;
+        for (addr, domain) in replicas.candidates() {
+            match connect(addr, domain).await {
+                Ok(state) => {
+                    replicas.mark_up(&addr);
+                    return Ok(state);
+                }
+                Err(error) => {
+                    warn!(?error, %addr, "Failed to connect to replica, trying next");
+                    replicas.mark_down(addr);
+                    last_error = Some(error);
+                }
+            }
         }
+        Err(last_error.unwrap_or(Error::MissingConnectionInformation))
     }
 
     async fn establish_connection<RW: ClickHouseRead + ClickHouseWrite + Send + 'static>(
         mut stream: RW,
-        io_task: &mut IoHandle<T::Data>,
+        io_task: &Mutex<IoHandle<T::Data>>,
         events: Arc<broadcast::Sender<Event>>,
         options: &ClientOptions,
         metadata: ClientMetadata,
@@ -219,7 +328,7 @@ impl<T: ClientFormat> Connection<T> {
         let (reader, writer) = tokio::io::split(stream);
 
         // Spawn read loop
-        let handle = io_task.spawn(
+        let handle = io_task.lock().spawn(
             async move {
                 let chunk_send = server_hello.supports_chunked_send();
                 let chunk_recv = server_hello.supports_chunked_recv();
@@ -269,9 +378,24 @@ impl<T: ClientFormat> Connection<T> {
         );
 
         trace!({ ATT_CID } = cid, "spawned connection loop");
-        Ok(ConnectState { status, channel: operations, handle })
+        Ok(ConnectState { status, channel: operations, handle, generation: 0 })
     }
 
+    pub(crate) async fn send_operation(
+        &self,
+        op: Operation<T::Data>,
+        qid: Qid,
+        finished: bool,
+    ) -> Result<usize> {
+        self.send_bound_operation(op, qid, finished, None).await.map(|(conn_idx, _)| conn_idx)
+    }
+
+    /// Sends an operation, returning the index and generation of the inner connection it was
+    /// sent on.
+    ///
+    /// Operations continuing a query, ie data for an open insert, pass the generation the query
+    /// was sent on. They fail rather than reach a connection re-established since, which does
+    /// not know the query.
     #[instrument(
         level = "trace",
         skip_all,
@@ -282,18 +406,24 @@ impl<T: ClientFormat> Connection<T> {
             clickhouse.query.id = %qid,
         )
     )]
-    pub(crate) async fn send_operation(
+    pub(crate) async fn send_bound_operation(
         &self,
         op: Operation<T::Data>,
         qid: Qid,
         finished: bool,
-    ) -> Result<usize> {
+        generation: Option<u64>,
+    ) -> Result<(usize, u64)> {
         #[cfg(not(feature = "inner_pool"))]
         let conn_idx = 0; // Dummy for non-fast mode
         #[cfg(feature = "inner_pool")]
         let conn_idx = {
             let key = (matches!(op, Operation::Query { .. } if !finished)
-                || matches!(op, Operation::Insert { .. } | Operation::InsertMany { .. }))
+                || matches!(
+                    op,
+                    Operation::Insert { .. }
+                        | Operation::InsertMany { .. }
+                        | Operation::InsertPartial { .. }
+                ))
             .then(|| qid.key());
             self.load_balancer.assign(key, op.weight(finished) as usize)
         };
@@ -308,21 +438,17 @@ impl<T: ClientFormat> Connection<T> {
         );
 
         // Get the current state
-        #[cfg(not(feature = "inner_pool"))]
-        let state = &self.state;
-        #[cfg(feature = "inner_pool")]
-        let state = self.state[conn_idx].load();
-
-        // Get the current status
-        #[cfg(not(feature = "inner_pool"))]
-        let status = self.state.status.load(Ordering::Acquire);
-        #[cfg(feature = "inner_pool")]
-        let status = state.status.load(Ordering::Acquire);
+        let state = self.load_state(conn_idx);
 
-        // First check if the underlying connection is ok (until re-connects are impelemented)
-        if status > 0 {
+        // First check if the underlying connection is ok, reconnects happen before this point
+        if state.status.load(Ordering::Acquire) > 0 {
             return Err(Error::Client("No active connection".into()));
         }
+        if generation.is_some_and(|generation| generation != state.generation) {
+            return Err(Error::ConnectionGone(
+                "Connection re-established since the query was sent",
+            ));
+        }
 
         let result = state.channel.send(Message::Operation { qid, op }).instrument(span).await;
         if result.is_err() {
@@ -331,7 +457,7 @@ impl<T: ClientFormat> Connection<T> {
             return Err(Error::ChannelClosed);
         }
 
-        Ok(conn_idx)
+        Ok((conn_idx, state.generation))
     }
 
     #[instrument(
@@ -341,9 +467,10 @@ impl<T: ClientFormat> Connection<T> {
     )]
     pub(crate) async fn shutdown(&self) -> Result<()> {
         trace!({ ATT_CID } = self.metadata.client_id, "Shutting down connections");
+        self.shutdown.store(true, Ordering::Release);
         #[cfg(not(feature = "inner_pool"))]
         {
-            if self.state.channel.send(Message::Shutdown).await.is_err() {
+            if self.state.load().channel.send(Message::Shutdown).await.is_err() {
                 error!("Failed to shutdown connection");
             }
         }
@@ -362,6 +489,62 @@ impl<T: ClientFormat> Connection<T> {
         Ok(())
     }
 
+    /// Cancels the query identified by `qid`.
+    ///
+    /// The query may be owned by any inner connection, so each is notified and those not owning
+    /// the query ignore it.
+    pub(crate) async fn cancel(&self, qid: Qid) -> Result<()> {
+        trace!({ ATT_CID } = self.metadata.client_id, { ATT_QID } = %qid, "Cancelling query");
+        #[cfg(not(feature = "inner_pool"))]
+        {
+            self.state
+                .load()
+                .channel
+                .send(Message::Operation { qid, op: Operation::Cancel })
+                .await
+                .map_err(|_| Error::ChannelClosed)?;
+        }
+        #[cfg(feature = "inner_pool")]
+        {
+            for (i, conn_state) in self.state.iter().enumerate() {
+                let state = conn_state.load();
+                if state
+                    .channel
+                    .send(Message::Operation { qid, op: Operation::Cancel })
+                    .await
+                    .is_err()
+                {
+                    warn!({ ATT_QID } = %qid, "Failed to send cancel to connection {i}");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Cancels the query identified by `qid` without waiting for channel capacity.
+    ///
+    /// Used where awaiting isn't possible, e.g. on drop. Best effort, connections with a full
+    /// operation queue are skipped.
+    pub(crate) fn try_cancel(&self, qid: Qid) {
+        trace!({ ATT_CID } = self.metadata.client_id, { ATT_QID } = %qid, "Cancelling query");
+        #[cfg(not(feature = "inner_pool"))]
+        {
+            let message = Message::Operation { qid, op: Operation::Cancel };
+            if self.state.load().channel.try_send(message).is_err() {
+                warn!({ ATT_QID } = %qid, "Failed to send cancel");
+            }
+        }
+        #[cfg(feature = "inner_pool")]
+        {
+            for (i, conn_state) in self.state.iter().enumerate() {
+                let message = Message::Operation { qid, op: Operation::Cancel };
+                if conn_state.load().channel.try_send(message).is_err() {
+                    warn!({ ATT_QID } = %qid, "Failed to send cancel to connection {i}");
+                }
+            }
+        }
+    }
+
     pub(crate) async fn check_connection(&self, ping: bool) -> Result<()> {
         // First check that internal channels are ok
         self.check_channel()?;
@@ -397,15 +580,188 @@ impl<T: ClientFormat> Connection<T> {
         Ok(())
     }
 
+    /// Requests the replication status of `tables` from the server.
+    pub(crate) async fn tables_status(
+        &self,
+        tables: Vec<(String, String)>,
+    ) -> Result<Vec<TableStatus>> {
+        let (response, rx) = tokio::sync::oneshot::channel();
+        let op = Operation::TablesStatus { tables, response };
+        let _ = self.send_operation(op, Qid::default(), true).await?;
+        rx.await.map_err(|_| Error::ChannelClosed)?
+    }
+
     fn update_status(&self, idx: usize, status: ConnectionStatus) {
         trace!({ ATT_CID } = self.metadata.client_id, ?status, "Updating status conn {idx}");
 
+        self.load_state(idx).status.store(status.into(), Ordering::Release);
+    }
+
+    /// Re-establishes any lost inner connection, if a [`ReconnectPolicy`] is configured.
+    ///
+    /// Connections that are open are left untouched, so this is cheap to call before every
+    /// operation. Nothing is done once the connection has been shut down.
+    pub(crate) async fn ensure_connected(&self) -> Result<()> {
+        let Some(policy) = self.options.ext.reconnect else { return Ok(()) };
+        if self.shutdown.load(Ordering::Acquire) {
+            return Ok(());
+        }
+
+        #[cfg(not(feature = "inner_pool"))]
+        let indices = 0..1;
+        #[cfg(feature = "inner_pool")]
+        let indices = 0..self.state.len();
+
+        for idx in indices {
+            if !self.load_state(idx).is_open() {
+                self.reconnect(idx, policy).await?;
+            }
+        }
+        Ok(())
+    }
+
+    #[instrument(
+        level = "trace",
+        name = "clickhouse.connection.reconnect",
+        skip_all,
+        fields(
+            db.system = "clickhouse",
+            db.operation = "connect",
+            clickhouse.client.id = self.metadata.client_id,
+            clickhouse.connection.id = idx,
+        ),
+        err
+    )]
+    async fn reconnect(&self, idx: usize, policy: ReconnectPolicy) -> Result<()> {
+        // Fail fast rather than starting another round of attempts against a server that was
+        // just given up on
+        if let Some((failed_at, error)) = self.failures.lock().get(&idx)
+            && failed_at.elapsed() < policy.cooldown
+        {
+            return Err(Error::Network(format!("Connection {idx} is down: {error}")));
+        }
+
+        // Lead the reconnect, or wait for the one in progress. The lock is not held while
+        // attempting, so waiting callers are not serialized behind the backoff.
+        let leader = {
+            let mut reconnecting = self.reconnecting.lock();
+            if let Some(outcome) = reconnecting.get(&idx) {
+                Err(outcome.clone())
+            } else {
+                // Another task may have reconnected in the meantime
+                if self.load_state(idx).is_open() {
+                    return Ok(());
+                }
+                let (tx, rx) = watch::channel(None);
+                drop(reconnecting.insert(idx, rx));
+                Ok(tx)
+            }
+        };
+
+        let outcome = match leader {
+            Ok(outcome) => outcome,
+            Err(mut rx) => {
+                let result = rx.wait_for(Option::is_some).await.map(|outcome| outcome.clone());
+                return match result {
+                    Ok(Some(Ok(()))) => Ok(()),
+                    Ok(Some(Err(error))) => {
+                        Err(Error::Client(format!("Reconnect failed: {error}")))
+                    }
+                    _ => Err(Error::ConnectionGone("Reconnect abandoned")),
+                };
+            }
+        };
+
+        // Released even if this task is dropped mid reconnect, waiters then see it abandoned
+        let _guard = ReconnectGuard { reconnecting: &self.reconnecting, idx };
+        let result = self.reconnect_with_backoff(idx, policy).await;
+        let message = result.as_ref().err().map(ToString::to_string);
+        match &message {
+            Some(error) => drop(self.failures.lock().insert(idx, (Instant::now(), error.clone()))),
+            None => drop(self.failures.lock().remove(&idx)),
+        }
+        let _ = outcome.send(Some(message.map_or(Ok(()), Err))).ok();
+        result
+    }
+
+    async fn reconnect_with_backoff(&self, idx: usize, policy: ReconnectPolicy) -> Result<()> {
+        let cid = self.metadata.client_id;
+        warn!({ ATT_CID } = cid, "Connection {idx} lost, reconnecting");
+        self.update_status(idx, ConnectionStatus::Reconnecting);
+
+        // Reap finished io tasks
+        {
+            let mut io_task = self.io_task.lock();
+            while io_task.try_join_next().is_some() {}
+        }
+
+        let mut backoff = policy.backoff();
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            self.emit(ClickHouseEvent::Reconnecting { attempt });
+
+            let result = Self::connect_inner(
+                &self.replicas,
+                self.tls.as_ref(),
+                &self.io_task,
+                Arc::clone(&self.events),
+                &self.options,
+                self.metadata,
+            )
+            .await;
+
+            let error = match result {
+                Ok(mut state) => {
+                    state.generation = self.load_state(idx).generation + 1;
+                    let previous = self.swap_state(idx, state);
+                    previous.handle.abort();
+                    info!({ ATT_CID } = cid, attempt, "Connection {idx} re-established");
+                    record_reconnect(cid, true);
+                    self.emit(ClickHouseEvent::Reconnected { attempts: attempt });
+                    return Ok(());
+                }
+                Err(error) => error,
+            };
+
+            warn!(?error, { ATT_CID } = cid, attempt, "Reconnect attempt failed");
+            let Some(delay) = backoff.next_backoff().filter(|_| policy.allows(attempt)) else {
+                error!(?error, { ATT_CID } = cid, "Giving up reconnecting connection {idx}");
+                self.update_status(idx, ConnectionStatus::Error);
+                record_reconnect(cid, false);
+                self.emit(ClickHouseEvent::ReconnectFailed {
+                    attempts: attempt,
+                    error:    error.to_string(),
+                });
+                return Err(error);
+            };
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    fn emit(&self, event: ClickHouseEvent) {
+        let client_id = self.metadata.client_id;
+        let _ = self.events.send(Event { event, qid: Qid::default(), client_id }).ok();
+    }
+
+    #[cfg_attr(not(feature = "inner_pool"), expect(unused_variables))]
+    fn load_state(&self, idx: usize) -> arc_swap::Guard<Arc<ConnectState<T::Data>>> {
         #[cfg(not(feature = "inner_pool"))]
-        let state = &self.state;
+        let state = self.state.load();
         #[cfg(feature = "inner_pool")]
         let state = self.state[idx].load();
 
-        state.status.store(status.into(), Ordering::Release);
+        state
+    }
+
+    #[cfg_attr(not(feature = "inner_pool"), expect(unused_variables))]
+    fn swap_state(&self, idx: usize, state: ConnectState<T::Data>) -> Arc<ConnectState<T::Data>> {
+        #[cfg(not(feature = "inner_pool"))]
+        let previous = self.state.swap(Arc::new(state));
+        #[cfg(feature = "inner_pool")]
+        let previous = self.state[idx].swap(Arc::new(state));
+
+        previous
     }
 
     async fn perform_handshake<RW: ClickHouseRead + ClickHouseWrite + Send + 'static>(
@@ -422,11 +778,45 @@ impl<T: ClientFormat> Connection<T> {
             password:         options.password.get().to_string(),
         };
 
+        // The username is marked and no password is sent when authenticating with an SSH key
+        #[cfg(feature = "ssh")]
+        let ssh_key = options.ext.auth.ssh_private_key()?;
+        #[cfg(feature = "ssh")]
+        let client_hello = if ssh_key.is_some() {
+            ClientHello {
+                username: format!(
+                    "{}{}",
+                    super::auth::SSH_KEY_AUTHENTICATION_MARKER,
+                    options.username
+                ),
+                password: String::new(),
+                ..client_hello
+            }
+        } else {
+            client_hello
+        };
+
         // Send client hello
         Writer::send_hello(stream, client_hello)
             .await
             .inspect_err(|error| error!(?error, { ATT_CID } = client_id, "Failed to send hello"))?;
 
+        // Sign the server's challenge before the server hello
+        #[cfg(feature = "ssh")]
+        if let Some(key) = ssh_key {
+            Writer::send_ssh_challenge_request(stream).await?;
+            let challenge = Reader::receive_ssh_challenge(stream).await.inspect_err(|error| {
+                error!(?error, { ATT_CID } = client_id, "Failed to receive SSH challenge");
+            })?;
+            let signature = super::auth::sign_ssh_challenge(
+                &key,
+                &options.default_database,
+                &options.username,
+                &challenge,
+            )?;
+            Writer::send_ssh_challenge_response(stream, &signature).await?;
+        }
+
         // Receive server hello
         let chunked_modes = (options.ext.chunked_send, options.ext.chunked_recv);
         let server_hello =
@@ -453,22 +843,19 @@ impl<T: ClientFormat> Connection<T> {
         self.load_balancer.finish(usize::from(weight), conn_idx);
     }
 
-    pub(crate) fn status(&self) -> ConnectionStatus {
-        #[cfg(not(feature = "inner_pool"))]
-        let status = ConnectionStatus::from(self.state.status.load(Ordering::Acquire));
+    /// The generation of an inner connection, incremented each time it is re-established.
+    pub(crate) fn generation(&self, conn_idx: usize) -> u64 { self.load_state(conn_idx).generation }
 
+    pub(crate) fn status(&self) -> ConnectionStatus {
         // TODO: Status is strange if we have an internal pool. Figure this out.
         // Just use the first channel for now
-        #[cfg(feature = "inner_pool")]
-        let status = ConnectionStatus::from(self.state[0].load().status.load(Ordering::Acquire));
-
-        status
+        ConnectionStatus::from(self.load_state(0).status.load(Ordering::Acquire))
     }
 
     fn check_channel(&self) -> Result<()> {
         #[cfg(not(feature = "inner_pool"))]
         {
-            if self.state.channel.is_closed() {
+            if self.state.load().channel.is_closed() {
                 self.update_status(0, ConnectionStatus::Closed);
                 Err(Error::ChannelClosed)
             } else {
@@ -0,0 +1,604 @@
+//! Streaming inserts.
+//!
+//! An [`Inserter`] keeps a single `INSERT` open and sends data to the server as it arrives,
+//! buffering it until a row, byte or time threshold is reached. This avoids both materializing a
+//! long running stream in memory and paying a query round trip per batch.
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, ready};
+use std::time::{Duration, Instant};
+
+use futures_util::future::BoxFuture;
+use futures_util::{Sink, Stream, StreamExt, pin_mut};
+use tokio::sync::{mpsc, oneshot};
+
+use super::Client;
+use super::connection::Connection;
+use super::internal::Operation;
+use super::response::handle_insert_response;
+use crate::constants::{INSERTER_MAX_BYTES, INSERTER_MAX_ROWS};
+use crate::formats::{ClientFormat, DataSize, NativeFormat};
+use crate::native::block::Block;
+use crate::prelude::*;
+use crate::query::ParsedQuery;
+use crate::{Error, Result, Row, Type};
+
+/// Options controlling when an [`Inserter`] sends buffered data to the server.
+///
+/// Data is flushed once any threshold is reached. A threshold of `None` is never reached, so with
+/// all thresholds disabled data is only sent on [`Inserter::flush`] or [`Inserter::commit`].
+///
+/// The defaults match the server's `min_insert_block_size_rows` and
+/// `min_insert_block_size_bytes`, with no time threshold.
+#[derive(Debug, Clone)]
+pub struct InserterOptions {
+    /// Flush once this many rows are buffered.
+    pub max_rows:  Option<usize>,
+    /// Flush once the buffered data is estimated to use this many bytes.
+    pub max_bytes: Option<usize>,
+    /// Flush when data is written this long after the previous flush.
+    pub period:    Option<Duration>,
+    /// Settings, parameters and query id for the `INSERT` query.
+    pub query:     QueryOptions,
+}
+
+impl Default for InserterOptions {
+    fn default() -> Self {
+        Self {
+            max_rows:  Some(INSERTER_MAX_ROWS),
+            max_bytes: Some(INSERTER_MAX_BYTES),
+            period:    None,
+            query:     QueryOptions::default(),
+        }
+    }
+}
+
+impl InserterOptions {
+    #[must_use]
+    pub fn with_max_rows(mut self, max_rows: Option<usize>) -> Self {
+        self.max_rows = max_rows;
+        self
+    }
+
+    #[must_use]
+    pub fn with_max_bytes(mut self, max_bytes: Option<usize>) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    #[must_use]
+    pub fn with_period(mut self, period: Option<Duration>) -> Self {
+        self.period = period;
+        self
+    }
+
+    #[must_use]
+    pub fn with_query_options(mut self, query: QueryOptions) -> Self {
+        self.query = query;
+        self
+    }
+}
+
+/// A handle to a single open `INSERT`, created with [`Client::inserter`].
+///
+/// Data written with [`Inserter::write`] is buffered and sent to the server once a threshold in
+/// [`InserterOptions`] is reached. Sending waits for the connection to write the data, so a
+/// producer writing faster than the server accepts data is slowed down accordingly.
+///
+/// The insert must be ended with [`Inserter::commit`], which sends any remaining data and waits
+/// for the server to acknowledge the insert, or [`Inserter::abort`]. Dropping an inserter without
+/// either cancels the insert. Note that the server writes data as it receives it, so aborting
+/// only discards data the server has not yet written to the table.
+///
+/// The insert is bound to the connection it was started on. If that connection is lost, the
+/// insert is lost with it and writing fails, even once the client has reconnected.
+///
+/// [`Inserter::write`] only checks the time threshold when data is written, while
+/// [`Inserter::write_stream`] also flushes once the period elapses while waiting on the stream.
+/// To flush an otherwise idle inserter, wait on [`Inserter::time_left`] and call
+/// [`Inserter::flush`]. An inserter can also be used as a [`Sink`] with [`Inserter::into_sink`].
+///
+/// # Examples
+/// ```rust,ignore
+/// use clickhouse_arrow::prelude::*;
+///
+/// let options = InserterOptions::default()
+///     .with_max_rows(Some(100_000))
+///     .with_period(Some(Duration::from_secs(5)));
+/// let mut inserter = client.inserter("INSERT INTO my_table VALUES", options).await?;
+/// while let Some(batch) = consumer.next().await {
+///     inserter.write(batch).await?;
+/// }
+/// inserter.commit().await?;
+/// ```
+pub struct Inserter<T: ClientFormat> {
+    connection:    Arc<Connection<T>>,
+    client_id:     u16,
+    qid:           Qid,
+    conn_idx:      usize,
+    /// Generation of the connection the insert was started on, data is only sent there
+    generation:    u64,
+    #[cfg(feature = "inner_pool")]
+    query_weight:  u8,
+    header:        Vec<(String, Type)>,
+    responses:     Option<mpsc::Receiver<Result<T::Data>>>,
+    options:       InserterOptions,
+    buffer:        Vec<T::Data>,
+    pending_rows:  usize,
+    pending_bytes: usize,
+    rows_sent:     u64,
+    last_flush:    Instant,
+}
+
+impl<T: ClientFormat> Inserter<T> {
+    pub(super) async fn new(
+        client: &Client<T>,
+        query: ParsedQuery,
+        options: InserterOptions,
+    ) -> Result<Self> {
+        let settings = options.query.merge_settings(client.settings.as_ref());
+        let client_id = client.client_id;
+        let qid = options.query.qid.unwrap_or_default();
+        let query = query.0;
+        trace!(query, { ATT_CID } = client_id, { ATT_QID } = %qid, "Opening inserter");
+
+        let connection = Arc::clone(&client.connection);
+        connection.ensure_connected().await?;
+
+        let (tx, rx) = oneshot::channel();
+        let (header_tx, header_rx) = oneshot::channel();
+        let op = Operation::Query {
+            query,
+            settings,
+            params: options.query.params.clone(),
+            response: tx,
+            header: Some(header_tx),
+            cancel_on_drop: false,
+            summary: None,
+            compression: options.query.compression,
+            external: Vec::new(),
+            trace: TraceParent::resolve(options.query.trace.as_ref()),
+            quota_key: options.query.quota_key.clone(),
+        };
+        #[cfg(feature = "inner_pool")]
+        let query_weight = op.weight(false);
+        let (conn_idx, generation) = connection.send_bound_operation(op, qid, false, None).await?;
+
+        let responses = rx
+            .await
+            .map_err(|_| Error::Protocol(format!("Failed to receive response for query {qid}")))?
+            .inspect_err(|error| error!(?error, { ATT_QID } = %qid, "Error receiving header"))?;
+        let header = header_rx
+            .await
+            .map_err(|_| Error::Protocol(format!("Failed to receive header for query {qid}")))?;
+
+        Ok(Self {
+            connection,
+            client_id,
+            qid,
+            conn_idx,
+            generation,
+            #[cfg(feature = "inner_pool")]
+            query_weight,
+            header,
+            responses: Some(responses),
+            options,
+            buffer: Vec::new(),
+            pending_rows: 0,
+            pending_bytes: 0,
+            rows_sent: 0,
+            last_flush: Instant::now(),
+        })
+    }
+
+    /// The query id of the insert.
+    pub fn qid(&self) -> Qid { self.qid }
+
+    /// The columns of the table being inserted into, as reported by the server.
+    pub fn header(&self) -> &[(String, Type)] { &self.header }
+
+    /// Rows buffered and not yet sent to the server.
+    pub fn pending_rows(&self) -> usize { self.pending_rows }
+
+    /// Estimated size in bytes of the buffered data.
+    pub fn pending_bytes(&self) -> usize { self.pending_bytes }
+
+    /// Rows sent to the server so far.
+    pub fn rows_sent(&self) -> u64 { self.rows_sent }
+
+    /// Time until the period threshold is reached, if one is configured.
+    ///
+    /// Returns [`Duration::ZERO`] once the period has elapsed.
+    pub fn time_left(&self) -> Option<Duration> {
+        self.options.period.map(|period| period.saturating_sub(self.last_flush.elapsed()))
+    }
+
+    /// Buffers data, flushing if a threshold is reached.
+    ///
+    /// # Errors
+    /// - Fails if the server ended the insert with an exception.
+    /// - Fails if the connection to `ClickHouse` is interrupted.
+    pub async fn write(&mut self, data: T::Data) -> Result<()> {
+        if self.push(data) {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// Writes every item of a stream, flushing as thresholds are reached.
+    ///
+    /// Buffered data is also flushed when the period elapses while waiting for the next item, so
+    /// a slow stream does not hold data back. The insert remains open once the stream ends.
+    ///
+    /// # Errors
+    /// - Fails on the first error yielded by the stream.
+    /// - Fails if the server ended the insert with an exception.
+    /// - Fails if the connection to `ClickHouse` is interrupted.
+    pub async fn write_stream<S>(&mut self, stream: S) -> Result<()>
+    where
+        S: Stream<Item = Result<T::Data>>,
+    {
+        pin_mut!(stream);
+        loop {
+            let next = match self.time_left() {
+                Some(left) if !self.buffer.is_empty() => {
+                    // Waiting on the next item is cancel safe, so it can be abandoned to flush
+                    if let Ok(next) = tokio::time::timeout(left, stream.next()).await {
+                        next
+                    } else {
+                        self.flush().await?;
+                        continue;
+                    }
+                }
+                _ => stream.next().await,
+            };
+            let Some(data) = next else { return Ok(()) };
+            self.write(data?).await?;
+        }
+    }
+
+    /// Sends buffered data to the server.
+    ///
+    /// # Errors
+    /// - Fails if the server ended the insert with an exception.
+    /// - Fails if the connection to `ClickHouse` is interrupted.
+    pub async fn flush(&mut self) -> Result<()> {
+        self.check_responses()?;
+        self.last_flush = Instant::now();
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+
+        let data = std::mem::take(&mut self.buffer);
+        let rows = std::mem::take(&mut self.pending_rows);
+        self.pending_bytes = 0;
+        trace!({ ATT_CID } = self.client_id, { ATT_QID } = %self.qid, rows, "Flushing inserter");
+
+        let (tx, rx) = oneshot::channel();
+        self.send(Operation::InsertPartial { data, response: tx }, false, rx).await?;
+        self.rows_sent += rows as u64;
+        Ok(())
+    }
+
+    /// Sends any remaining data and ends the insert, waiting for the server to acknowledge it.
+    ///
+    /// # Errors
+    /// - Fails if the server ended the insert with an exception.
+    /// - Fails if the connection to `ClickHouse` is interrupted.
+    pub async fn commit(mut self) -> Result<()> {
+        self.check_responses()?;
+
+        let data = std::mem::take(&mut self.buffer);
+        let rows = std::mem::take(&mut self.pending_rows);
+        let (tx, rx) = oneshot::channel();
+        self.send(Operation::InsertMany { data, response: tx }, true, rx).await?;
+        self.rows_sent += rows as u64;
+
+        let result = self.finish().await;
+        debug!(
+            { ATT_CID } = self.client_id,
+            { ATT_QID } = %self.qid,
+            rows = self.rows_sent,
+            "Inserter committed"
+        );
+        result
+    }
+
+    /// Cancels the insert, discarding buffered data.
+    ///
+    /// Data already written to the table by the server is not rolled back.
+    ///
+    /// # Errors
+    /// - Fails if the connection to `ClickHouse` is interrupted.
+    pub async fn abort(mut self) -> Result<()> {
+        self.buffer.clear();
+        if self.responses.is_none() {
+            return Ok(());
+        }
+        self.connection.cancel(self.qid).await?;
+        // The server ends a cancelled query with an exception or end of stream, either is fine
+        if let Err(error) = self.finish().await {
+            debug!(?error, { ATT_QID } = %self.qid, "Inserter aborted");
+        }
+        Ok(())
+    }
+
+    /// Converts the inserter into a [`Sink`], see [`InserterSink`].
+    pub fn into_sink(self) -> InserterSink<T> {
+        InserterSink { state: SinkState::Idle(Box::new(self)) }
+    }
+
+    /// Buffers data, returns whether a threshold is reached.
+    fn push(&mut self, data: T::Data) -> bool {
+        self.pending_rows += data.num_rows();
+        self.pending_bytes += data.data_size();
+        self.buffer.push(data);
+        self.should_flush()
+    }
+
+    fn should_flush(&self) -> bool {
+        self.options.max_rows.is_some_and(|max| self.pending_rows >= max)
+            || self.options.max_bytes.is_some_and(|max| self.pending_bytes >= max)
+            || self.time_left().is_some_and(|left| left.is_zero())
+    }
+
+    /// Surfaces an exception the server sent while data was being streamed, before sending more.
+    fn check_responses(&mut self) -> Result<()> {
+        if self.responses.is_some() && self.connection.generation(self.conn_idx) != self.generation
+        {
+            drop(self.release());
+            return Err(Error::ConnectionGone(
+                "Connection re-established since the insert started",
+            ));
+        }
+        let Some(responses) = self.responses.as_mut() else {
+            return Err(Error::Client(format!("Insert {} already ended", self.qid)));
+        };
+        loop {
+            match responses.try_recv() {
+                Ok(Ok(_)) => {}
+                Ok(Err(error)) => {
+                    drop(self.release());
+                    return Err(error);
+                }
+                Err(mpsc::error::TryRecvError::Empty) => return Ok(()),
+                Err(mpsc::error::TryRecvError::Disconnected) => {
+                    drop(self.release());
+                    return Err(Error::Protocol(format!("Insert {} ended by server", self.qid)));
+                }
+            }
+        }
+    }
+
+    async fn send(
+        &mut self,
+        op: Operation<T::Data>,
+        finished: bool,
+        rx: oneshot::Receiver<Result<()>>,
+    ) -> Result<()> {
+        let qid = self.qid;
+        #[cfg(feature = "inner_pool")]
+        let weight = op.weight(finished);
+        let generation = Some(self.generation);
+        let sent = self.connection.send_bound_operation(op, qid, finished, generation).await;
+        if let Err(Error::ConnectionGone(_)) = &sent {
+            // The insert is lost with its connection, nothing is left to commit or cancel
+            drop(self.release());
+        }
+        let _ = sent?;
+        let result = rx.await.map_err(|_| {
+            Error::Protocol(format!("Failed to receive response from insert {qid}"))
+        })?;
+
+        #[cfg(feature = "inner_pool")]
+        self.connection.finish(self.conn_idx, weight);
+
+        result
+    }
+
+    /// Takes the response channel once the insert has ended, releasing the query's load.
+    fn release(&mut self) -> Option<mpsc::Receiver<Result<T::Data>>> {
+        let responses = self.responses.take()?;
+        #[cfg(feature = "inner_pool")]
+        self.connection.finish(self.conn_idx, self.query_weight);
+        Some(responses)
+    }
+
+    /// Waits for the server to end the insert, returning the first error.
+    async fn finish(&mut self) -> Result<()> {
+        let Some(responses) = self.release() else {
+            return Ok(());
+        };
+
+        let stream = handle_insert_response::<T>(responses, self.qid, self.client_id);
+        pin_mut!(stream);
+        let mut result = Ok(());
+        while let Some(next) = stream.next().await {
+            if let Err(error) = next
+                && result.is_ok()
+            {
+                result = Err(error);
+            }
+        }
+        result
+    }
+}
+
+impl Inserter<NativeFormat> {
+    /// Buffers rows, flushing if a threshold is reached.
+    ///
+    /// Rows are converted into a [`Block`] using the columns of the table being inserted into.
+    ///
+    /// # Errors
+    /// - Fails if the rows don't match the table's columns.
+    /// - Fails if the server ended the insert with an exception.
+    /// - Fails if the connection to `ClickHouse` is interrupted.
+    pub async fn write_rows<R: Row>(&mut self, rows: impl IntoIterator<Item = R>) -> Result<()> {
+        let block = Block::from_rows(rows.into_iter().collect(), self.header.clone())?;
+        self.write(block).await
+    }
+}
+
+impl<T: ClientFormat> Drop for Inserter<T> {
+    fn drop(&mut self) {
+        if self.release().is_some() {
+            debug!({ ATT_QID } = %self.qid, "Inserter dropped before commit, cancelling insert");
+            self.connection.try_cancel(self.qid);
+        }
+    }
+}
+
+impl<T: ClientFormat> std::fmt::Debug for Inserter<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Inserter")
+            .field("client_id", &self.client_id)
+            .field("qid", &self.qid)
+            .field("options", &self.options)
+            .field("pending_rows", &self.pending_rows)
+            .field("pending_bytes", &self.pending_bytes)
+            .field("rows_sent", &self.rows_sent)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A [`Sink`] writing into an [`Inserter`], created with [`Inserter::into_sink`].
+///
+/// Sent data is buffered and flushed once a threshold in [`InserterOptions`] is reached, the
+/// same as [`Inserter::write`]. Flushing the sink sends buffered data regardless of thresholds,
+/// and closing it commits the insert. Dropping the sink without closing it cancels the insert.
+///
+/// Note that [`StreamExt::forward`] flushes whenever the stream has no item ready, prefer
+/// [`Inserter::write_stream`] to batch data from a stream.
+///
+/// # Examples
+/// ```rust,ignore
+/// use futures_util::SinkExt;
+/// use clickhouse_arrow::prelude::*;
+///
+/// let inserter = client.inserter("INSERT INTO my_table VALUES", options).await?;
+/// let mut sink = inserter.into_sink();
+/// sink.send(batch).await?;
+/// sink.close().await?;
+/// ```
+pub struct InserterSink<T: ClientFormat> {
+    state: SinkState<T>,
+}
+
+enum SinkState<T: ClientFormat> {
+    Idle(Box<Inserter<T>>),
+    Flushing(BoxFuture<'static, (Box<Inserter<T>>, Result<()>)>),
+    Committing(BoxFuture<'static, Result<()>>),
+    Closed,
+}
+
+impl<T: ClientFormat> InserterSink<T> {
+    /// Waits for a pending flush, returning its result.
+    fn poll_idle(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        match &mut self.state {
+            SinkState::Idle(_) => Poll::Ready(Ok(())),
+            SinkState::Flushing(flush) => {
+                let (inserter, result) = ready!(flush.as_mut().poll(cx));
+                self.state = SinkState::Idle(inserter);
+                Poll::Ready(result)
+            }
+            SinkState::Committing(_) | SinkState::Closed => {
+                Poll::Ready(Err(Error::Client("Inserter sink is closed".into())))
+            }
+        }
+    }
+
+    fn start_flush(&mut self) {
+        if let SinkState::Idle(mut inserter) = std::mem::replace(&mut self.state, SinkState::Closed)
+        {
+            self.state = SinkState::Flushing(Box::pin(async move {
+                let result = inserter.flush().await;
+                (inserter, result)
+            }));
+        }
+    }
+}
+
+impl<T: ClientFormat> Sink<T::Data> for InserterSink<T> {
+    type Error = Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.get_mut().poll_idle(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, data: T::Data) -> Result<()> {
+        let this = self.get_mut();
+        let SinkState::Idle(inserter) = &mut this.state else {
+            return Err(Error::Client("Inserter sink is not ready".into()));
+        };
+        if inserter.push(data) {
+            this.start_flush();
+        }
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_idle(cx))?;
+        if matches!(&this.state, SinkState::Idle(inserter) if !inserter.buffer.is_empty()) {
+            this.start_flush();
+            ready!(this.poll_idle(cx))?;
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.get_mut();
+        if matches!(this.state, SinkState::Closed) {
+            return Poll::Ready(Ok(()));
+        }
+        if !matches!(this.state, SinkState::Committing(_)) {
+            ready!(this.poll_idle(cx))?;
+            if let SinkState::Idle(inserter) = std::mem::replace(&mut this.state, SinkState::Closed)
+            {
+                this.state = SinkState::Committing(Box::pin(inserter.commit()));
+            }
+        }
+        let SinkState::Committing(commit) = &mut this.state else {
+            return Poll::Ready(Ok(()));
+        };
+        let result = ready!(commit.as_mut().poll(cx));
+        this.state = SinkState::Closed;
+        Poll::Ready(result)
+    }
+}
+
+impl<T: ClientFormat> std::fmt::Debug for InserterSink<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = match &self.state {
+            SinkState::Idle(_) => "idle",
+            SinkState::Flushing(_) => "flushing",
+            SinkState::Committing(_) => "committing",
+            SinkState::Closed => "closed",
+        };
+        f.debug_struct("InserterSink").field("state", &state).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inserter_options() {
+        let options = InserterOptions::default();
+        assert_eq!(options.max_rows, Some(INSERTER_MAX_ROWS));
+        assert_eq!(options.max_bytes, Some(INSERTER_MAX_BYTES));
+        assert_eq!(options.period, None);
+
+        let qid = Qid::new();
+        let options = options
+            .with_max_rows(Some(10))
+            .with_max_bytes(None)
+            .with_period(Some(Duration::from_secs(1)))
+            .with_query_options(QueryOptions::new().with_qid(qid));
+        assert_eq!(options.max_rows, Some(10));
+        assert_eq!(options.max_bytes, None);
+        assert_eq!(options.period, Some(Duration::from_secs(1)));
+        assert_eq!(options.query.qid, Some(qid));
+    }
+}
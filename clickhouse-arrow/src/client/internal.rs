@@ -1,32 +1,41 @@
 use std::collections::VecDeque;
 use std::sync::Arc;
 use std::sync::atomic::AtomicU16;
+use std::time::Instant;
 
 use strum::{AsRefStr, IntoStaticStr};
+use tokio::io::AsyncWriteExt;
 use tokio::sync::{broadcast, mpsc, oneshot};
 
 use super::Event;
 use super::chunk::ChunkWriter;
 use super::connection::ClientMetadata;
 use super::reader::Reader;
+use super::response::SummaryHandle;
 use super::writer::{Query, Writer};
 use crate::ClickHouseEvent;
 use crate::errors::*;
 use crate::formats::DeserializerState;
 use crate::io::{ClickHouseRead, ClickHouseWrite};
+use crate::metrics::{ConnectionMetrics, OperationKind};
 use crate::native::block::Block;
 use crate::native::block_info::BlockInfo;
 use crate::native::client_info::ClientInfo;
-use crate::native::protocol::{QueryProcessingStage, ServerData, ServerHello, ServerPacket};
+use crate::native::protocol::{
+    QueryProcessingStage, ServerData, ServerHello, ServerPacket, TableStatus,
+};
 use crate::prelude::*;
 use crate::query::QueryParams;
 use crate::settings::Settings;
 
 type ResponseReceiver<T> = mpsc::Receiver<Result<T>>;
 type ResponseSender<T> = mpsc::Sender<Result<T>>;
+type TablesStatusSender = oneshot::Sender<Result<Vec<TableStatus>>>;
 
 static CONN_ID: AtomicU16 = AtomicU16::new(0);
 
+// Nearly all messages are operations, boxing them would add an allocation per message
+#[expect(clippy::large_enum_variant)]
 pub(crate) enum Message<Data: Send + Sync> {
     Operation { qid: Qid, op: Operation<Data> },
     Shutdown,
@@ -36,18 +45,38 @@ pub(crate) enum Message<Data: Send + Sync> {
 pub(crate) enum Operation<Data: Send + Sync> {
     #[strum(serialize = "Ping")]
     Ping { response: oneshot::Sender<Result<()>> },
+    /// Sent once no query is executing, queries queued after it wait for the response.
+    #[strum(serialize = "TablesStatus")]
+    TablesStatus { tables: Vec<(String, String)>, response: TablesStatusSender },
     #[strum(serialize = "Query")]
     Query {
-        query:    String,
-        settings: Option<Arc<Settings>>,
-        params:   Option<QueryParams>,
-        response: oneshot::Sender<Result<ResponseReceiver<Data>>>,
-        header:   Option<oneshot::Sender<Vec<(String, Type)>>>,
+        query:          String,
+        settings:       Option<Arc<Settings>>,
+        params:         Option<QueryParams>,
+        response:       oneshot::Sender<Result<ResponseReceiver<Data>>>,
+        header:         Option<oneshot::Sender<Vec<(String, Type)>>>,
+        /// Whether dropping the response receiver cancels the query server-side.
+        cancel_on_drop: bool,
+        /// Receives totals and extremes, if the caller exposes them.
+        summary:        Option<SummaryHandle>,
+        /// Overrides the client's compression options for the data of an insert.
+        compression:    Option<CompressionOptions>,
+        /// Named blocks sent as temporary tables the query can read.
+        external:       Vec<(String, Data)>,
+        /// W3C trace context sent in the client info.
+        trace:          Option<TraceParent>,
+        /// Quota key sent in the client info.
+        quota_key:      Option<String>,
     },
+    #[strum(serialize = "Cancel")]
+    Cancel,
     #[strum(serialize = "Insert")]
     Insert { data: Data, response: oneshot::Sender<Result<()>> },
     #[strum(serialize = "InsertMany")]
     InsertMany { data: Vec<Data>, response: oneshot::Sender<Result<()>> },
+    /// Sends data blocks without ending the insert, more data or an end of data follows.
+    #[strum(serialize = "InsertPartial")]
+    InsertPartial { data: Vec<Data>, response: oneshot::Sender<Result<()>> },
 }
 
 // Track operation tasks
@@ -55,6 +84,7 @@ pub(crate) enum Operation<Data: Send + Sync> {
 enum OperationTask {
     Chunk(ChunkBoundary),
     Ping(oneshot::Sender<Result<()>>),
+    TablesStatus(TablesStatusSender),
     Shutdown,
 }
 
@@ -84,6 +114,7 @@ pub(super) enum QueryState {
 pub(super) enum InsertState<T> {
     Data(T),
     Batch(Vec<T>),
+    Partial(Vec<T>),
 }
 
 pub(super) struct ExecutingQuery<T: Send + Sync> {
@@ -92,25 +123,49 @@ pub(super) struct ExecutingQuery<T: Send + Sync> {
     header:          Option<Vec<(String, Type)>>,
     header_response: Option<oneshot::Sender<Vec<(String, Type)>>>,
     response:        ResponseSender<T>,
+    cancel_on_drop:  bool,
+    /// Set once a `Cancel` packet has been sent, the query is drained until the server ends it.
+    cancelled:       bool,
+    summary:         Option<SummaryHandle>,
+    /// Compression options of the data of an insert, if overridden.
+    compression:     Option<CompressionOptions>,
+    started:         Instant,
+    operation:       OperationKind,
+}
+
+impl<T: Send + Sync> ExecutingQuery<T> {
+    /// Whether the caller dropped the response before the query finished.
+    fn is_abandoned(&self) -> bool {
+        self.cancel_on_drop && !self.cancelled && self.response.is_closed()
+    }
 }
 
 pub(super) struct PendingQuery<T: Send + Sync> {
-    qid:      Qid,
-    query:    String,
-    settings: Option<Arc<Settings>>,
-    params:   Option<QueryParams>,
-    response: oneshot::Sender<Result<ResponseReceiver<T>>>,
-    header:   Option<oneshot::Sender<Vec<(String, Type)>>>,
+    qid:            Qid,
+    query:          String,
+    settings:       Option<Arc<Settings>>,
+    params:         Option<QueryParams>,
+    response:       oneshot::Sender<Result<ResponseReceiver<T>>>,
+    header:         Option<oneshot::Sender<Vec<(String, Type)>>>,
+    cancel_on_drop: bool,
+    summary:        Option<SummaryHandle>,
+    compression:    Option<CompressionOptions>,
+    external:       Vec<(String, T)>,
+    trace:          Option<TraceParent>,
+    quota_key:      Option<String>,
 }
 
 pub(super) struct InternalConn<T: ClientFormat> {
-    cid:          &'static str,
-    server_hello: Arc<ServerHello>,
-    pending:      VecDeque<PendingQuery<T::Data>>,
-    executing:    Option<ExecutingQuery<T::Data>>,
-    events:       Arc<broadcast::Sender<Event>>,
-    metadata:     ClientMetadata,
-    state:        DeserializerState<T::Deser>,
+    cid:            &'static str,
+    server_hello:   Arc<ServerHello>,
+    pending:        VecDeque<PendingQuery<T::Data>>,
+    /// Tables status requests waiting for the executing query to finish.
+    pending_status: VecDeque<(Vec<(String, String)>, TablesStatusSender)>,
+    executing:      Option<ExecutingQuery<T::Data>>,
+    events:         Arc<broadcast::Sender<Event>>,
+    metadata:       ClientMetadata,
+    state:          DeserializerState<T::Deser>,
+    metrics:        ConnectionMetrics,
 }
 
 impl<T: ClientFormat> InternalConn<T> {
@@ -126,14 +181,17 @@ impl<T: ClientFormat> InternalConn<T> {
         let conn_id = CONN_ID.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
         let cid = Box::leak(format!("{}.{conn_id}", metadata.client_id).into_boxed_str());
         let state = DeserializerState::default().with_arrow_options(metadata.arrow_options);
+        let metrics = ConnectionMetrics::new(metadata.client_id, cid);
         InternalConn {
             cid,
             server_hello,
             pending: VecDeque::with_capacity(Self::CAPACITY),
+            pending_status: VecDeque::new(),
             executing: None,
             metadata,
             events,
             state,
+            metrics,
         }
     }
 
@@ -151,7 +209,9 @@ impl<T: ClientFormat> InternalConn<T> {
         mut operations: mpsc::Receiver<Message<T::Data>>,
     ) -> Result<()> {
         loop {
-            match self.run_inner(&mut reader, &mut writer, &mut operations).await? {
+            let task = self.run_inner(&mut reader, &mut writer, &mut operations).await?;
+            self.metrics.in_flight(self.pending.len() + usize::from(self.executing.is_some()));
+            match task {
                 OperationTask::Shutdown => return Ok(()),
                 OperationTask::Ping(response) => {
                     let cid = self.cid;
@@ -160,6 +220,10 @@ impl<T: ClientFormat> InternalConn<T> {
                         Self::receive_ping(&mut reader, revision, self.metadata, cid).await;
                     let _ = response.send(result).ok();
                 }
+                OperationTask::TablesStatus(response) => {
+                    let result = self.receive_tables_status(&mut reader).await;
+                    let _ = response.send(result).ok();
+                }
                 OperationTask::Chunk(_) => {}
             }
         }
@@ -179,7 +243,9 @@ impl<T: ClientFormat> InternalConn<T> {
         mut operations: mpsc::Receiver<Message<T::Data>>,
     ) -> Result<()> {
         loop {
-            match self.run_inner(&mut reader, &mut writer, &mut operations).await? {
+            let task = self.run_inner(&mut reader, &mut writer, &mut operations).await?;
+            self.metrics.in_flight(self.pending.len() + usize::from(self.executing.is_some()));
+            match task {
                 OperationTask::Ping(response) => {
                     // Be sure to flush the Ping
                     writer.finish_chunk().await?;
@@ -189,6 +255,11 @@ impl<T: ClientFormat> InternalConn<T> {
                         Self::receive_ping(&mut reader, revision, self.metadata, cid).await;
                     let _ = response.send(result).ok();
                 }
+                OperationTask::TablesStatus(response) => {
+                    writer.finish_chunk().await?;
+                    let result = self.receive_tables_status(&mut reader).await;
+                    let _ = response.send(result).ok();
+                }
                 // Logical chunk boundary, flush
                 OperationTask::Chunk(ChunkBoundary::Flush) => writer.finish_chunk().await?,
                 OperationTask::Chunk(ChunkBoundary::None) => {}
@@ -205,10 +276,43 @@ impl<T: ClientFormat> InternalConn<T> {
     ) -> Result<OperationTask> {
         let cid = self.cid;
 
+        // Cancel the executing query if its response was dropped before the query completed
+        if let Some(qid) = self.executing.as_ref().filter(|e| e.is_abandoned()).map(|e| e.qid) {
+            debug!({ ATT_CON } = cid, { ATT_QID } = %qid, "Response dropped, cancelling query");
+            return self.cancel_query(writer, qid).await;
+        }
+
+        // Send tables status requests queued behind a query, then the queries queued behind them
+        if self.executing.is_none() {
+            if let Some((tables, response)) = self.pending_status.pop_front() {
+                Writer::send_tables_status(writer, &tables).await?;
+                return Ok(OperationTask::TablesStatus(response));
+            }
+            if let Some(query) = self.pending.pop_front() {
+                self.send_query(writer, query).await?;
+                return Ok(OperationTask::Chunk(ChunkBoundary::Flush));
+            }
+        }
+
         // Track whether logical chunk boundaries are encountered
         let mut flush = OperationTask::default();
 
+        // Watch the executing query's response so a dropped response cancels it promptly
+        let watched = self
+            .executing
+            .as_ref()
+            .filter(|e| e.cancel_on_drop && !e.cancelled)
+            .map(|e| (e.qid, e.response.clone()));
+
         tokio::select! {
+            // Response dropped while the query executes
+            () = async { if let Some((_, tx)) = &watched { tx.closed().await } }, if watched.is_some() => {
+                if let Some((qid, _)) = watched {
+                    debug!({ ATT_CON } = cid, { ATT_QID } = %qid, "Response dropped, cancelling query");
+                    return self.cancel_query(writer, qid).await;
+                }
+            }
+
             // Write loop
             Some(op) = operations.recv() => {
                 trace!(message = ?op, { ATT_CON } = cid, "Received operation");
@@ -227,10 +331,20 @@ impl<T: ClientFormat> InternalConn<T> {
 
             // Read loop
             result = self.receive_packet(reader), if self.executing.is_some() => {
-                result.inspect_err(|error| error!(?error, { ATT_CID } = cid, "Fatal error"))?;
+                if let Err(error) = result {
+                    error!(?error, { ATT_CID } = cid, "Fatal error");
+                    // Fail the query rather than ending its response as if it completed
+                    if let Some(exec) = self.executing.take() {
+                        self.metrics.finished(exec.operation, exec.started, false);
+                        let error = Error::ConnectionGone("Connection lost during query");
+                        let _ = exec.response.send(Err(error)).await.ok();
+                    }
+                    return Err(error);
+                }
 
-                // Queue up next query if any
+                // Queue up next query if any, tables status requests go first
                 if self.executing.is_none()
+                    && self.pending_status.is_empty()
                     && let Some(query) = self.pending.pop_front() {
                         self.send_query(writer, query).await?;
                         flush = OperationTask::Chunk(ChunkBoundary::Flush);
@@ -259,6 +373,17 @@ impl<T: ClientFormat> InternalConn<T> {
         op: Operation<T::Data>,
         qid: Qid,
     ) -> Result<OperationTask> {
+        // Data sent for the executing query makes it an insert
+        if matches!(
+            op,
+            Operation::Insert { .. }
+                | Operation::InsertMany { .. }
+                | Operation::InsertPartial { .. }
+        ) && let Some(exec) = self.executing.as_mut()
+        {
+            exec.operation = OperationKind::Insert;
+        }
+
         // Track logical chunk boundaries
         let (result, response) = match op {
             // Ping
@@ -269,9 +394,43 @@ impl<T: ClientFormat> InternalConn<T> {
                 }
                 return Ok(OperationTask::default());
             }
+            // Tables status
+            Operation::TablesStatus { tables, response } => {
+                if self.pending.is_empty() && self.executing.is_none() {
+                    Writer::send_tables_status(writer, &tables).await?;
+                    return Ok(OperationTask::TablesStatus(response));
+                }
+                self.pending_status.push_back((tables, response));
+                return Ok(OperationTask::default());
+            }
             // Query - NOTE: May be any type of query, ie DDL, DML, Settings, etc.
-            Operation::Query { query, settings, params, response, header } => {
-                let pending = PendingQuery { qid, query, settings, params, response, header };
+            Operation::Query {
+                query,
+                settings,
+                params,
+                response,
+                header,
+                cancel_on_drop,
+                summary,
+                compression,
+                external,
+                trace,
+                quota_key,
+            } => {
+                let pending = PendingQuery {
+                    qid,
+                    query,
+                    settings,
+                    params,
+                    response,
+                    header,
+                    cancel_on_drop,
+                    summary,
+                    compression,
+                    external,
+                    trace,
+                    quota_key,
+                };
                 if self.pending.is_empty() && self.executing.is_none() {
                     self.send_query(writer, pending).await?;
                     return Ok(OperationTask::Chunk(ChunkBoundary::Flush));
@@ -279,6 +438,17 @@ impl<T: ClientFormat> InternalConn<T> {
                 self.pending.push_back(pending);
                 return Ok(OperationTask::default());
             }
+            // Cancel - NOTE: Ignored if the query is not owned by this connection
+            Operation::Cancel => return self.cancel_query(writer, qid).await,
+            // Inserts into a cancelled query would be read by the server as new packets
+            Operation::Insert { response, .. }
+            | Operation::InsertMany { response, .. }
+            | Operation::InsertPartial { response, .. }
+                if self.executing.as_ref().is_some_and(|e| e.cancelled) =>
+            {
+                let _ = response.send(Err(Error::Client(format!("Query {qid} cancelled")))).ok();
+                return Ok(OperationTask::default());
+            }
             // Inserts
             Operation::Insert { data, response } => {
                 let insert = InsertState::Data(data);
@@ -292,6 +462,12 @@ impl<T: ClientFormat> InternalConn<T> {
                 let result = self.send_insert(writer, insert, header, qid).await;
                 (result, response)
             }
+            Operation::InsertPartial { data, response } => {
+                let insert = InsertState::Partial(data);
+                let header = self.executing.as_ref().and_then(|e| e.header.as_deref());
+                let result = self.send_insert(writer, insert, header, qid).await;
+                (result, response)
+            }
         };
 
         // Return result to caller
@@ -310,6 +486,36 @@ impl<T: ClientFormat> InternalConn<T> {
         Ok(OperationTask::Chunk(ChunkBoundary::Flush))
     }
 
+    /// Cancels the query identified by `qid`, if this connection owns it.
+    ///
+    /// A pending query is removed from the queue and its caller notified. An executing query is
+    /// sent a `Cancel` packet and remains executing, discarding data, until the server responds
+    /// with `EndOfStream` or an `Exception`. This keeps the connection usable afterwards.
+    async fn cancel_query<W: ClickHouseWrite>(
+        &mut self,
+        writer: &mut W,
+        qid: Qid,
+    ) -> Result<OperationTask> {
+        if let Some(pending) =
+            self.pending.iter().position(|p| p.qid == qid).and_then(|i| self.pending.remove(i))
+        {
+            debug!({ ATT_CON } = self.cid, { ATT_QID } = %qid, "Cancelled pending query");
+            let error = Error::Client(format!("Query {qid} cancelled"));
+            let _ = pending.response.send(Err(error)).ok();
+            return Ok(OperationTask::default());
+        }
+
+        let Some(exec) = self.executing.as_mut().filter(|e| e.qid == qid && !e.cancelled) else {
+            return Ok(OperationTask::default());
+        };
+        exec.cancelled = true;
+
+        Writer::send_cancel(writer).await?;
+        debug!({ ATT_CON } = self.cid, { ATT_QID } = %qid, "Sent cancel, draining query");
+
+        Ok(OperationTask::Chunk(ChunkBoundary::Flush))
+    }
+
     // READ
 
     #[instrument(
@@ -359,11 +565,21 @@ impl<T: ClientFormat> InternalConn<T> {
             ServerPacket::Data(ServerData { block }) => {
                 let _ = exec.response.send(Ok(block)).await.ok();
             }
+            ServerPacket::Log(logs) => {
+                if self.metadata.trace_server_logs {
+                    for log in &logs {
+                        log.emit(qid);
+                    }
+                }
+                let event = ClickHouseEvent::Log(logs);
+                let _ = self.events.send(Event { event, qid, client_id }).ok();
+            }
             ServerPacket::ProfileEvents(info) => {
                 let event = ClickHouseEvent::Profile(info);
                 let _ = self.events.send(Event { event, qid, client_id }).ok();
             }
             ServerPacket::Progress(progress) => {
+                self.metrics.progress(exec.operation, &progress);
                 let event = ClickHouseEvent::Progress(progress);
                 let _ = self.events.send(Event { event, qid, client_id }).ok();
             }
@@ -371,6 +587,7 @@ impl<T: ClientFormat> InternalConn<T> {
                 let error = exception.emit();
                 error!({ ATT_QID } = %exec.qid, { ATT_CON } = cid, "EXCEPTION: {error}");
                 let _ = exec.response.send(Err(error.clone().into())).await.ok();
+                self.metrics.finished(exec.operation, exec.started, false);
                 drop(self.executing.take());
                 if error.is_fatal() {
                     return Err(error.into());
@@ -379,16 +596,30 @@ impl<T: ClientFormat> InternalConn<T> {
             }
             ServerPacket::EndOfStream => {
                 debug!({ ATT_CON } = cid, { ATT_QID } = %qid, "END OF STREAM");
+                self.metrics.finished(exec.operation, exec.started, true);
                 drop(self.executing.take());
                 T::finish_deser(&mut self.state);
             }
             ServerPacket::Hello(_) => {
                 return Err(Error::Protocol("Unexpected Server Hello".to_string()));
             }
-            // Ignored
-            // TODO: Should profile info be returned to caller?
+            ServerPacket::Totals(ServerData { block }) => {
+                if let Some(summary) = exec.summary.as_ref() {
+                    summary.lock().totals = Some(block.into());
+                }
+            }
+            ServerPacket::Extremes(ServerData { block }) => {
+                if let Some(summary) = exec.summary.as_ref() {
+                    summary.lock().extremes = Some(block.into());
+                }
+            }
             ServerPacket::ProfileInfo(info) => {
                 debug!(?info, "Profile info");
+                if let Some(summary) = exec.summary.as_ref() {
+                    summary.lock().profile_info = Some(info);
+                }
+                let event = ClickHouseEvent::ProfileInfo(info);
+                let _ = self.events.send(Event { event, qid, client_id }).ok();
             }
             ServerPacket::Ignore(ignored) => trace!(ignored = ignored.as_ref(), "Ignored packet"),
 
@@ -417,6 +648,25 @@ impl<T: ClientFormat> InternalConn<T> {
         Ok(())
     }
 
+    async fn receive_tables_status<R: ClickHouseRead + 'static>(
+        &mut self,
+        reader: &mut R,
+    ) -> Result<Vec<TableStatus>> {
+        let revision = self.server_hello.revision_version;
+        let packet = Reader::receive_packet::<T>(reader, revision, self.metadata, &mut self.state)
+            .await
+            .inspect_err(|error| error!(?error, { ATT_CON } = self.cid, "Failed tables status"))?;
+
+        match packet {
+            ServerPacket::TablesStatusResponse(response) => Ok(response.tables),
+            ServerPacket::Exception(exception) => Err(exception.emit().into()),
+            packet => Err(Error::Protocol(format!(
+                "Expected TablesStatusResponse, got {}",
+                packet.as_ref()
+            ))),
+        }
+    }
+
     // WRITE
 
     #[instrument(skip_all, fields(clickhouse.query.id = %query.qid), err)]
@@ -425,9 +675,45 @@ impl<T: ClientFormat> InternalConn<T> {
         writer: &mut W,
         query: PendingQuery<T::Data>,
     ) -> Result<()> {
-        let PendingQuery { qid, query, settings, params, response, header } = query;
+        let PendingQuery {
+            qid,
+            query,
+            settings,
+            params,
+            response,
+            header,
+            cancel_on_drop,
+            summary,
+            compression,
+            external,
+            trace,
+            quota_key,
+        } = query;
         debug!({ ATT_CON } = self.cid, { ATT_QID } = %qid, query, "sending query");
 
+        // Serialize external tables up front, a failure part way through the packets would leave
+        // the server waiting on the rest of the query, only this query fails instead
+        let revision = self.server_hello.revision_version;
+        let mut tables = Vec::with_capacity(external.len());
+        for (name, data) in external {
+            let mut buffer = Vec::new();
+            if let Err(error) = Writer::send_external_table::<T>(
+                &mut buffer,
+                &name,
+                data,
+                qid,
+                revision,
+                self.metadata,
+            )
+            .await
+            {
+                error!(?error, { ATT_CON } = self.cid, { ATT_QID } = %qid, "External table failed");
+                drop(response.send(Err(error)));
+                return Ok(());
+            }
+            tables.push(buffer);
+        }
+
         // Send initial query
         if let Err(error) = Writer::send_query(
             writer,
@@ -437,7 +723,11 @@ impl<T: ClientFormat> InternalConn<T> {
                 settings,
                 params,
                 stage: QueryProcessingStage::Complete,
-                info: ClientInfo::default(),
+                info: ClientInfo {
+                    open_telemetry: trace.as_ref().map(Into::into),
+                    quota_key: quota_key.as_deref().unwrap_or_default(),
+                    ..ClientInfo::default()
+                },
             },
             self.server_hello.settings.as_ref(),
             self.server_hello.revision_version,
@@ -462,8 +752,19 @@ impl<T: ClientFormat> InternalConn<T> {
             header: None,
             header_response: header,
             response: sender,
+            cancel_on_drop,
+            cancelled: false,
+            summary,
+            compression,
+            started: Instant::now(),
+            operation: OperationKind::Query,
         });
 
+        // External tables precede the delimiter
+        for table in tables {
+            writer.write_all(&table).await?;
+        }
+
         self.send_delimiter(writer, qid).await?;
         trace!({ ATT_CON } = self.cid, { ATT_QID } = %qid, "sent query and delimiter");
 
@@ -487,10 +788,15 @@ impl<T: ClientFormat> InternalConn<T> {
         qid: Qid,
     ) -> Result<()> {
         let revision = self.server_hello.revision_version;
+        let metadata = self
+            .executing
+            .as_ref()
+            .and_then(|e| e.compression)
+            .map_or(self.metadata, |c| self.metadata.with_compression_options(c));
         trace!({ ATT_CID } = self.cid, { ATT_QID } = %qid, insert = insert.as_ref(), "Inserting");
         match insert {
             InsertState::Data(data) => {
-                Writer::send_data::<T>(writer, data, qid, header, revision, self.metadata).await?;
+                Writer::send_data::<T>(writer, data, qid, header, revision, metadata).await?;
                 self.send_delimiter(writer, qid).await?;
             }
             InsertState::Batch(data) => {
@@ -501,12 +807,7 @@ impl<T: ClientFormat> InternalConn<T> {
                     trace!({ ATT_QID } = %qid, blocks = block_count, "Batch insert with deferred flush");
                     for block in data {
                         Writer::send_data_no_flush::<T>(
-                            writer,
-                            block,
-                            qid,
-                            header,
-                            revision,
-                            self.metadata,
+                            writer, block, qid, header, revision, metadata,
                         )
                         .await?;
                     }
@@ -514,6 +815,18 @@ impl<T: ClientFormat> InternalConn<T> {
                 // Delimiter includes the final flush for all accumulated data
                 self.send_delimiter(writer, qid).await?;
             }
+            InsertState::Partial(data) => {
+                // Same deferred flush as batches, but the insert stays open for more data
+                for block in data {
+                    Writer::send_data_no_flush::<T>(writer, block, qid, header, revision, metadata)
+                        .await?;
+                }
+                writer
+                    .flush()
+                    .instrument(trace_span!("flush_data", { ATT_QID } = %qid))
+                    .await
+                    .inspect_err(|error| error!(?error, { ATT_QID } = %qid, "send_insert"))?;
+            }
         }
 
         Ok(())
@@ -551,12 +864,12 @@ impl<Data: Send + Sync + 'static + crate::formats::DataSize> Operation<Data> {
                 // Skip load balancing for small inserts
                 if data.data_size() < SMALL_INSERT_THRESHOLD { 0 } else { 2 }
             }
-            Operation::InsertMany { data, .. } => {
+            Operation::InsertMany { data, .. } | Operation::InsertPartial { data, .. } => {
                 // Calculate total size for batch inserts
                 let total_size: usize = data.iter().map(crate::formats::DataSize::data_size).sum();
                 if total_size < SMALL_INSERT_THRESHOLD { 0 } else { 3 }
             }
-            Operation::Ping { .. } => 0,
+            Operation::Ping { .. } | Operation::TablesStatus { .. } | Operation::Cancel => 0,
         }
     }
 
@@ -581,9 +894,10 @@ impl<T: Send + Sync + 'static> std::fmt::Debug for ExecutingQuery<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "ExecutingQuery(qid={}, header={:?}, header_response={:?}, response={})",
+            "ExecutingQuery(qid={}, header={:?}, cancelled={}, header_response={:?}, response={})",
             self.qid,
             self.header,
+            self.cancelled,
             if self.header_response.as_ref().is_some_and(|h| !h.is_closed()) {
                 &"CHANNEL_OPEN"
             } else {
@@ -2,7 +2,7 @@ use std::path::PathBuf;
 
 use tracing::warn;
 
-use super::CompressionMethod;
+use super::{Auth, CompressionMethod, CompressionOptions, ReconnectPolicy, ReplicaOptions};
 use crate::native::protocol::ChunkedProtocolMode;
 use crate::prelude::Secret;
 
@@ -161,22 +161,44 @@ impl ClientOptions {
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Extension {
     /// Options specific to (de)serializing arrow data.
-    pub arrow:          Option<ArrowOptions>,
+    pub arrow:                      Option<ArrowOptions>,
     /// Options specific to communicating with `ClickHouse` over their cloud offering.
     #[cfg(feature = "cloud")]
-    pub cloud:          CloudOptions,
+    pub cloud:                      CloudOptions,
     /// Options related to server/client protocol send chunking.
     /// This may be removed, as it may be defaulted.
     #[cfg_attr(feature = "serde", serde(default))]
-    pub chunked_send:   ChunkedProtocolMode,
+    pub chunked_send:               ChunkedProtocolMode,
     /// Options related to server/client protocol recv chunking.
     /// This may be removed, as it may be defaulted
     #[cfg_attr(feature = "serde", serde(default))]
-    pub chunked_recv:   ChunkedProtocolMode,
+    pub chunked_recv:               ChunkedProtocolMode,
     /// Related to `inner_pool`, how many 'inner clients' to spawn. Currently capped at 4.
     #[cfg(feature = "inner_pool")]
     #[cfg_attr(feature = "serde", serde(default))]
-    pub fast_mode_size: Option<u8>,
+    pub fast_mode_size:             Option<u8>,
+    /// How lost connections are re-established, `None` disables reconnecting.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub reconnect:                  Option<ReconnectPolicy>,
+    /// How replicas are chosen when the destination lists several hosts.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub replicas:                   ReplicaOptions,
+    /// Options for TLS connections beyond [`ClientOptions::cafile`] and [`ClientOptions::domain`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tls:                        TlsOptions,
+    /// Re-emit server log lines (see the `send_logs_level` setting) as `tracing` events.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub trace_server_logs:          bool,
+    /// Minimum ratio of default values for a column to be inserted with sparse serialization,
+    /// `None` (the default) always inserts columns in full.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub sparse_serialization_ratio: Option<f64>,
+    /// Level and block size of the compression of data sent to the server.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub compression_options:        CompressionOptions,
+    /// How the client authenticates, username and password by default.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub auth:                       Auth,
 }
 
 /// Configuration extensions for specialized `ClickHouse` client behavior.
@@ -221,6 +243,110 @@ impl Extension {
         self.fast_mode_size = Some(size);
         self
     }
+
+    #[must_use]
+    pub fn with_reconnect(mut self, policy: Option<ReconnectPolicy>) -> Self {
+        self.reconnect = policy;
+        self
+    }
+
+    #[must_use]
+    pub fn with_replicas(mut self, options: ReplicaOptions) -> Self {
+        self.replicas = options;
+        self
+    }
+
+    #[must_use]
+    pub fn with_tls(mut self, options: TlsOptions) -> Self {
+        self.tls = options;
+        self
+    }
+
+    #[must_use]
+    pub fn with_trace_server_logs(mut self, enabled: bool) -> Self {
+        self.trace_server_logs = enabled;
+        self
+    }
+
+    #[must_use]
+    pub fn with_sparse_serialization_ratio(mut self, ratio: Option<f64>) -> Self {
+        self.sparse_serialization_ratio = ratio;
+        self
+    }
+
+    #[must_use]
+    pub fn with_compression_options(mut self, options: CompressionOptions) -> Self {
+        self.compression_options = options;
+        self
+    }
+
+    #[must_use]
+    pub fn with_auth(mut self, auth: Auth) -> Self {
+        self.auth = auth;
+        self
+    }
+}
+
+/// TLS configuration for connections to `ClickHouse`.
+///
+/// The server certificate is verified against the roots in [`ClientOptions::cafile`] (a PEM
+/// bundle) and, if `native_roots` is set, the operating system's trust store. If neither is
+/// configured, the bundled Mozilla roots are used.
+///
+/// For mutual TLS, `client_cert` and `client_key` must both be set. The certificate is presented
+/// to the server, which is required for `ClickHouse` users authenticated by certificate.
+///
+/// For full control, a custom [`tokio_rustls::rustls::ClientConfig`] can be provided through
+/// [`super::builder::ClientBuilder::with_tls_config`], in which case these options are ignored.
+///
+/// # Examples
+/// ```rust,ignore
+/// use clickhouse_arrow::prelude::*;
+///
+/// let tls = TlsOptions::default()
+///     .with_client_cert("/etc/clickhouse/client.crt", "/etc/clickhouse/client.key")
+///     .with_server_name("clickhouse.internal");
+/// let options = ClientOptions::default()
+///     .with_use_tls(true)
+///     .with_cafile("/etc/clickhouse/ca.pem")
+///     .extend(|ext| ext.with_tls(tls.clone()));
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TlsOptions {
+    /// PEM file containing the client certificate chain, for mutual TLS.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub client_cert:  Option<PathBuf>,
+    /// PEM file containing the client private key, for mutual TLS.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub client_key:   Option<PathBuf>,
+    /// Whether to trust the certificates in the operating system's trust store.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub native_roots: bool,
+    /// Server name used for SNI and certificate verification, overriding the domain.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub server_name:  Option<String>,
+}
+
+impl TlsOptions {
+    #[must_use]
+    pub fn with_client_cert<P: AsRef<std::path::Path>>(mut self, cert: P, key: P) -> Self {
+        self.client_cert = Some(cert.as_ref().into());
+        self.client_key = Some(key.as_ref().into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_native_roots(mut self, native_roots: bool) -> Self {
+        self.native_roots = native_roots;
+        self
+    }
+
+    #[must_use]
+    pub fn with_server_name(mut self, server_name: impl Into<String>) -> Self {
+        self.server_name = Some(server_name.into());
+        self
+    }
 }
 
 // TODO: Remove - make the properties public!
@@ -287,6 +413,8 @@ pub struct ArrowOptions {
     pub strict_schema:                bool,
     pub disable_strict_schema_ddl:    bool,
     pub nullable_array_default_empty: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub dynamic_as_union:             bool,
 }
 
 impl Default for ArrowOptions {
@@ -341,6 +469,7 @@ impl ArrowOptions {
             strict_schema:                false,
             disable_strict_schema_ddl:    false,
             nullable_array_default_empty: true,
+            dynamic_as_union:             false,
         }
     }
 
@@ -370,6 +499,7 @@ impl ArrowOptions {
             strict_schema:                true,
             disable_strict_schema_ddl:    false,
             nullable_array_default_empty: false,
+            dynamic_as_union:             false,
         }
     }
 
@@ -409,6 +539,7 @@ impl ArrowOptions {
         Self {
             strings_as_strings: self.strings_as_strings,
             use_date32_for_date: self.use_date32_for_date,
+            dynamic_as_union: self.dynamic_as_union,
             ..Self::strict()
         }
     }
@@ -560,6 +691,39 @@ impl ArrowOptions {
         self
     }
 
+    /// Sets whether `ClickHouse` `Dynamic` types are deserialized as Arrow dense unions.
+    ///
+    /// By default, `Dynamic` values are deserialized as Arrow `Utf8`, holding each value rendered
+    /// as JSON text. When this option is enabled (`true`), they are deserialized as a dense
+    /// `Union` with one field per type found in the block, named by the `ClickHouse` type name.
+    /// The type list is also stored in the field metadata under
+    /// [`crate::arrow::block::DYNAMIC_TYPES_METADATA_KEY`]. Since types are discovered per block,
+    /// the union fields can differ between `RecordBatch`es.
+    ///
+    /// Arrow unions can be inserted into `Dynamic` columns regardless of this option.
+    ///
+    /// # Parameters
+    /// - `enabled`: If `true`, maps [`crate::Type::Dynamic`] to a dense
+    ///   [`arrow::datatypes::DataType::Union`]; if `false`, maps to
+    ///   [`arrow::datatypes::DataType::Utf8`].
+    ///
+    /// # Returns
+    /// A new [`ArrowOptions`] with the updated setting.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::arrow::ArrowOptions;
+    ///
+    /// let arrow_options = ArrowOptions::new()
+    ///     .with_dynamic_as_union(true);
+    /// assert!(arrow_options.dynamic_as_union);
+    /// ```
+    #[must_use]
+    pub fn with_dynamic_as_union(mut self, enabled: bool) -> Self {
+        self.dynamic_as_union = enabled;
+        self
+    }
+
     /// Sets an Arrow option by name and value.
     ///
     /// This method updates a specific option identified by `name` to the given boolean
@@ -570,6 +734,7 @@ impl ArrowOptions {
     /// - `"disable_strict_schema_ddl"`: Disables strict mode for schema creation.
     /// - `"nullable_array_default_empty"`: Maps `Nullable(Array(...))` to `Array(...)` with `[]`
     ///   for nulls.
+    /// - `"dynamic_as_union"`: Maps `ClickHouse` `Dynamic` to an Arrow dense `Union`.
     ///
     /// If an unrecognized name is provided, a warning is logged, and the options are
     /// returned unchanged. Use this for dynamic configuration or when options are
@@ -600,6 +765,7 @@ impl ArrowOptions {
             "strict_schema" => self.with_strict_schema(value),
             "disable_strict_schema_ddl" => self.with_disable_strict_schema_ddl(value),
             "nullable_array_default_empty" => self.with_nullable_array_default_empty(value),
+            "dynamic_as_union" => self.with_dynamic_as_union(value),
             k => {
                 warn!("Unrecognized option for ArrowOptions: {k}");
                 self
@@ -18,13 +18,14 @@ use crate::native::protocol::{
     DBMS_MIN_REVISION_WITH_QUERY_PLAN_SERIALIZATION,
     DBMS_MIN_REVISION_WITH_ROWS_BEFORE_AGGREGATION, DBMS_MIN_REVISION_WITH_SERVER_DISPLAY_NAME,
     DBMS_MIN_REVISION_WITH_SERVER_LOGS, DBMS_MIN_REVISION_WITH_SERVER_SETTINGS,
-    DBMS_MIN_REVISION_WITH_SERVER_TIMEZONE, DBMS_MIN_REVISION_WITH_VERSION_PATCH,
+    DBMS_MIN_REVISION_WITH_SERVER_TIMEZONE, DBMS_MIN_REVISION_WITH_TABLE_READ_ONLY_CHECK,
+    DBMS_MIN_REVISION_WITH_VERSION_PATCH,
     DBMS_MIN_REVISION_WITH_VERSIONED_PARALLEL_REPLICAS_PROTOCOL, LogData, MAX_STRING_SIZE,
     ProfileEvent, ProfileInfo, ServerData, ServerException, ServerHello, ServerPacket,
     ServerPacketId, TableColumns, TableStatus, TablesStatusResponse,
 };
 use crate::prelude::*;
-use crate::{Error, FxIndexMap, Result};
+use crate::{Error, Result};
 
 #[derive(Debug, Clone, Copy)]
 pub(super) struct Reader<R: ClickHouseRead> {
@@ -54,6 +55,20 @@ impl<R: ClickHouseRead + 'static> Reader<R> {
         }
     }
 
+    /// Receive the challenge to sign for SSH key authentication.
+    #[cfg(feature = "ssh")]
+    pub(super) async fn receive_ssh_challenge(reader: &mut R) -> Result<Vec<u8>> {
+        let packet = ServerPacketId::from_u64(reader.read_var_uint().await?)
+            .inspect_err(|error| error!(?error, "Failed to read packet ID"))?;
+        match packet {
+            ServerPacketId::SSHChallenge => reader.read_string().await,
+            ServerPacketId::Exception => Err(Self::read_exception(reader).await?.emit().into()),
+            packet => Err(Error::Protocol(format!(
+                "Unexpected packet {packet:?}, expected SSH challenge"
+            ))),
+        }
+    }
+
     /// Receive header packet (empty native block)
     pub(super) async fn receive_header<T: ClientFormat>(
         reader: &mut R,
@@ -125,9 +140,11 @@ impl<R: ClickHouseRead + 'static> Reader<R> {
             ServerPacketId::Extremes => Ok(Self::read_data::<T>(reader, revision, metadata, state)
                 .await?
                 .map_or(ServerPacket::Ignore(ServerPacketId::Extremes), ServerPacket::Extremes)),
-            ServerPacketId::TablesStatusResponse => Self::read_table_status_response(reader)
-                .await
-                .map(ServerPacket::TablesStatusResponse),
+            ServerPacketId::TablesStatusResponse => {
+                Self::read_table_status_response(reader, revision)
+                    .await
+                    .map(ServerPacket::TablesStatusResponse)
+            }
             ServerPacketId::Log => {
                 Self::read_log_data(reader, revision, metadata).await.map(ServerPacket::Log)
             }
@@ -403,8 +420,10 @@ impl<R: ClickHouseRead + 'static> Reader<R> {
             .unwrap_or_default())
     }
 
-    async fn read_table_status_response(reader: &mut R) -> Result<TablesStatusResponse> {
-        let mut response = TablesStatusResponse { database_tables: FxIndexMap::default() };
+    async fn read_table_status_response(
+        reader: &mut R,
+        revision: u64,
+    ) -> Result<TablesStatusResponse> {
         let size = reader.read_var_uint().await?;
 
         #[expect(clippy::cast_possible_truncation)]
@@ -413,18 +432,22 @@ impl<R: ClickHouseRead + 'static> Reader<R> {
                 "table status response size too large. {size} > {MAX_STRING_SIZE}"
             )));
         }
+        #[expect(clippy::cast_possible_truncation)]
+        let mut response = TablesStatusResponse { tables: Vec::with_capacity(size as usize) };
         for _ in 0..size {
-            let database_name = reader.read_utf8_string().await?;
-            let table_name = reader.read_utf8_string().await?;
-            let is_replicated = reader.read_u8().await? != 0;
-            #[expect(clippy::cast_possible_truncation)]
-            let absolute_delay =
-                if is_replicated { reader.read_var_uint().await? as u32 } else { 0 };
-            let _ = response
-                .database_tables
-                .entry(database_name)
-                .or_default()
-                .insert(table_name, TableStatus { is_replicated, absolute_delay });
+            let database = reader.read_utf8_string().await?;
+            let table = reader.read_utf8_string().await?;
+            let mut status = TableStatus { database, table, ..Default::default() };
+            status.is_replicated = reader.read_u8().await? != 0;
+            if status.is_replicated {
+                #[expect(clippy::cast_possible_truncation)]
+                let absolute_delay = reader.read_var_uint().await? as u32;
+                status.absolute_delay = absolute_delay;
+                if revision >= DBMS_MIN_REVISION_WITH_TABLE_READ_ONLY_CHECK {
+                    status.is_readonly = reader.read_var_uint().await? != 0;
+                }
+            }
+            response.tables.push(status);
         }
         Ok(response)
     }
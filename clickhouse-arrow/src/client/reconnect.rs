@@ -0,0 +1,335 @@
+use std::time::{Duration, Instant};
+
+use crate::{Error, Result};
+
+/// Policy controlling how a [`crate::Client`] recovers a lost connection.
+///
+/// When configured (see [`super::builder::ClientBuilder::with_reconnect`]), a connection that has
+/// errored or closed is re-established before the next operation is sent. Each attempt opens a
+/// new socket and performs the full handshake. Client settings are sent with every query, so they
+/// are re-applied automatically once the connection is restored. Queries in flight when the
+/// connection was lost are not retried and fail with the original error.
+///
+/// Attempts are spaced using an [`ExponentialBackoff`] built from this policy. At least one of
+/// `max_attempts` and `max_elapsed_time` must be set, connecting fails otherwise. Once attempts
+/// are exhausted, operations fail immediately with [`Error::Network`] until `cooldown` has passed,
+/// rather than each starting a new round of attempts.
+///
+/// # Examples
+/// ```rust,ignore
+/// use std::time::Duration;
+/// use clickhouse_arrow::prelude::*;
+///
+/// let policy = ReconnectPolicy::new()
+///     .with_max_attempts(Some(10))
+///     .with_initial_interval(Duration::from_millis(50))
+///     .with_max_interval(Duration::from_secs(5));
+///
+/// let client = Client::builder()
+///     .with_endpoint("localhost:9000")
+///     .with_reconnect(policy)
+///     .build_arrow()
+///     .await
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReconnectPolicy {
+    /// Maximum number of attempts before giving up, `None` retries until the backoff is exhausted.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_attempts:     Option<u32>,
+    /// Delay before the second attempt. The first attempt is made immediately.
+    pub initial_interval: Duration,
+    /// Upper bound on the delay between attempts.
+    pub max_interval:     Duration,
+    /// Multiplier applied to the delay after each failed attempt.
+    pub factor:           f64,
+    /// Total time after which attempts stop, measured from the first attempt and regardless of
+    /// `max_attempts`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_elapsed_time: Option<Duration>,
+    /// Time after giving up during which operations fail without attempting to reconnect.
+    #[cfg_attr(feature = "serde", serde(default = "default_cooldown"))]
+    pub cooldown:         Duration,
+}
+
+fn default_cooldown() -> Duration { Duration::from_secs(10) }
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts:     Some(5),
+            initial_interval: Duration::from_millis(100),
+            max_interval:     Duration::from_secs(30),
+            factor:           2.0,
+            max_elapsed_time: Some(Duration::from_secs(300)),
+            cooldown:         default_cooldown(),
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Create a new `ReconnectPolicy` with default values.
+    #[must_use]
+    pub fn new() -> Self { Self::default() }
+
+    #[must_use]
+    pub fn with_max_attempts(mut self, max_attempts: Option<u32>) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    #[must_use]
+    pub fn with_initial_interval(mut self, interval: Duration) -> Self {
+        self.initial_interval = interval;
+        self
+    }
+
+    #[must_use]
+    pub fn with_max_interval(mut self, interval: Duration) -> Self {
+        self.max_interval = interval;
+        self
+    }
+
+    #[must_use]
+    pub fn with_factor(mut self, factor: f64) -> Self {
+        self.factor = factor;
+        self
+    }
+
+    #[must_use]
+    pub fn with_max_elapsed_time(mut self, max_elapsed_time: Option<Duration>) -> Self {
+        self.max_elapsed_time = max_elapsed_time;
+        self
+    }
+
+    #[must_use]
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Reject policies that would retry forever or whose delays would not grow.
+    pub(crate) fn validate(&self) -> Result<()> {
+        if self.max_attempts.is_none() && self.max_elapsed_time.is_none() {
+            return Err(Error::Configuration(
+                "Reconnect policy needs max_attempts or max_elapsed_time, it would retry forever"
+                    .into(),
+            ));
+        }
+        if !self.factor.is_finite() || self.factor < 1.0 {
+            return Err(Error::Configuration(format!(
+                "Reconnect policy factor must be a finite number of at least 1, got {}",
+                self.factor
+            )));
+        }
+        Ok(())
+    }
+
+    /// Whether another attempt is allowed after `attempts` failed attempts.
+    pub(crate) fn allows(&self, attempts: u32) -> bool {
+        self.max_attempts.is_none_or(|max| attempts < max)
+    }
+
+    /// Create the backoff used to space attempts, call when the first attempt starts.
+    pub fn backoff(&self) -> ExponentialBackoff {
+        ExponentialBackoff::new()
+            .with_initial_interval(self.initial_interval)
+            .with_max_interval(self.max_interval)
+            .with_factor(self.factor)
+            .with_max_elapsed_time(self.max_elapsed_time)
+    }
+}
+
+/// Exponentially increasing delays, capped at a maximum interval.
+///
+/// The elapsed time is measured from the creation of the backoff, or its last reset.
+#[derive(Debug, Clone, Copy)]
+pub struct ExponentialBackoff {
+    current_interval: Duration,
+    factor:           f64,
+    max_interval:     Duration,
+    max_elapsed_time: Option<Duration>,
+    attempts:         u32,
+    started:          Instant,
+}
+
+impl ExponentialBackoff {
+    pub fn new() -> Self {
+        ExponentialBackoff {
+            current_interval: Duration::from_millis(10), // Start with 100ms
+            factor:           2.0,
+            max_interval:     Duration::from_secs(60),
+            max_elapsed_time: Some(Duration::from_secs(900)), // 15 minutes
+            attempts:         0,
+            started:          Instant::now(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_initial_interval(mut self, interval: Duration) -> Self {
+        self.current_interval = interval;
+        self
+    }
+
+    #[must_use]
+    pub fn with_factor(mut self, factor: f64) -> Self {
+        self.factor = factor;
+        self
+    }
+
+    #[must_use]
+    pub fn with_max_interval(mut self, interval: Duration) -> Self {
+        self.max_interval = interval;
+        self
+    }
+
+    #[must_use]
+    pub fn with_max_elapsed_time(mut self, max_elapsed_time: Option<Duration>) -> Self {
+        self.max_elapsed_time = max_elapsed_time;
+        self
+    }
+
+    /// The number of backoffs handed out so far.
+    pub fn attempts(&self) -> u32 { self.attempts }
+
+    /// Start over from the initial interval, restarting the elapsed time.
+    pub fn reset(&mut self) {
+        self.attempts = 0;
+        self.started = Instant::now();
+    }
+
+    /// The delay before the next attempt, `None` once waiting it out would exceed the maximum
+    /// elapsed time.
+    ///
+    /// Delays that cannot be represented, such as from a factor that is negative or not finite,
+    /// are replaced by the maximum interval.
+    pub fn next_backoff(&mut self) -> Option<Duration> {
+        self.attempts = self.attempts.saturating_add(1);
+
+        // Computed in f64 and clamped before converting, the product overflows `Duration` after
+        // enough attempts
+        let exponent = i32::try_from(self.attempts - 1).unwrap_or(i32::MAX);
+        let secs = self.current_interval.as_secs_f64() * self.factor.powi(exponent);
+        let next_interval = if secs < self.max_interval.as_secs_f64() {
+            Duration::try_from_secs_f64(secs).unwrap_or(self.max_interval)
+        } else {
+            self.max_interval
+        };
+
+        if let Some(max_time) = self.max_elapsed_time
+            && self.started.elapsed().saturating_add(next_interval) > max_time
+        {
+            return None;
+        }
+
+        Some(next_interval)
+    }
+}
+
+impl Default for ExponentialBackoff {
+    fn default() -> Self { Self::new() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_grows_and_caps() {
+        let mut backoff = ExponentialBackoff::new()
+            .with_initial_interval(Duration::from_millis(100))
+            .with_max_interval(Duration::from_millis(350))
+            .with_max_elapsed_time(None);
+
+        assert_eq!(backoff.next_backoff(), Some(Duration::from_millis(100)));
+        assert_eq!(backoff.next_backoff(), Some(Duration::from_millis(200)));
+        assert_eq!(backoff.next_backoff(), Some(Duration::from_millis(350)));
+        assert_eq!(backoff.attempts(), 3);
+
+        backoff.reset();
+        assert_eq!(backoff.next_backoff(), Some(Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn test_backoff_max_elapsed_time() {
+        let mut backoff = ExponentialBackoff::new()
+            .with_initial_interval(Duration::from_secs(1))
+            .with_max_elapsed_time(Some(Duration::from_secs(3)));
+
+        // Delays of 1s and 2s fit, 4s would end after the limit
+        assert!(backoff.next_backoff().is_some());
+        assert!(backoff.next_backoff().is_some());
+        assert!(backoff.next_backoff().is_none());
+
+        // Measured against the time actually elapsed, not the delays handed out
+        let mut backoff = ExponentialBackoff::new()
+            .with_initial_interval(Duration::from_millis(10))
+            .with_max_elapsed_time(Some(Duration::from_secs(60)));
+        assert!(backoff.next_backoff().is_some());
+        backoff.started = Instant::now().checked_sub(Duration::from_secs(61)).unwrap();
+        assert!(backoff.next_backoff().is_none());
+
+        backoff.reset();
+        assert!(backoff.next_backoff().is_some());
+    }
+
+    #[test]
+    fn test_reconnect_policy_attempts() {
+        let policy = ReconnectPolicy::new().with_max_attempts(Some(2));
+        assert!(policy.allows(0));
+        assert!(policy.allows(1));
+        assert!(!policy.allows(2));
+
+        let policy = policy.with_max_attempts(None);
+        assert!(policy.allows(u32::MAX));
+        assert!(policy.validate().is_ok());
+
+        let unbounded = policy.with_max_elapsed_time(None);
+        assert!(matches!(unbounded.validate(), Err(Error::Configuration(_))));
+
+        for factor in [0.5, 0.0, -2.0, f64::NAN, f64::INFINITY] {
+            let policy = ReconnectPolicy::new().with_factor(factor);
+            assert!(matches!(policy.validate(), Err(Error::Configuration(_))), "{factor}");
+        }
+        assert!(ReconnectPolicy::new().with_factor(1.0).validate().is_ok());
+    }
+
+    #[test]
+    fn test_backoff_does_not_overflow() {
+        let policy = ReconnectPolicy::new()
+            .with_max_attempts(None)
+            .with_max_interval(Duration::from_secs(1))
+            .with_max_elapsed_time(Some(Duration::from_secs(300)));
+        let mut backoff = policy.backoff();
+        for _ in 0..200 {
+            assert!(backoff.next_backoff().is_some_and(|delay| delay <= Duration::from_secs(1)));
+        }
+        assert_eq!(backoff.next_backoff(), Some(Duration::from_secs(1)));
+
+        // Factors rejected by the policy still hand out bounded delays
+        for factor in [0.0, -2.0, f64::NAN, f64::INFINITY] {
+            let mut backoff = ExponentialBackoff::new()
+                .with_factor(factor)
+                .with_max_interval(Duration::from_secs(1))
+                .with_max_elapsed_time(None);
+            for _ in 0..200 {
+                assert!(
+                    backoff.next_backoff().is_some_and(|delay| delay <= Duration::from_secs(1))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_reconnect_policy_backoff() {
+        let policy = ReconnectPolicy::new()
+            .with_initial_interval(Duration::from_millis(10))
+            .with_factor(3.0)
+            .with_max_interval(Duration::from_millis(50));
+        let mut backoff = policy.backoff();
+        assert_eq!(backoff.next_backoff(), Some(Duration::from_millis(10)));
+        assert_eq!(backoff.next_backoff(), Some(Duration::from_millis(30)));
+        assert_eq!(backoff.next_backoff(), Some(Duration::from_millis(50)));
+    }
+}
@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+use std::hash::{BuildHasher, RandomState};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+use crate::constants::REPLICA_RETRY_AFTER_SECS;
+
+/// Strategy used to choose a replica when a [`super::Destination`] lists several hosts.
+///
+/// Every strategy falls back to the remaining replicas when a connection attempt fails, so the
+/// strategy only decides the order replicas are tried in. Replicas that recently failed are
+/// tracked per address (see [`ReplicaOptions::retry_after`]).
+///
+/// - `InOrder`: Replicas are always tried in the order given, ignoring health. The first replica is
+///   preferred again as soon as it accepts connections.
+/// - `Random`: Replicas are tried in random order, healthy replicas first.
+/// - `RoundRobin`: Each connection starts at the next replica, healthy replicas first.
+/// - `FirstHealthy`: Replicas are tried in the order given, skipping replicas that recently failed
+///   until they are due to be retried. Failed replicas are only tried when no healthy replica
+///   accepts the connection.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LoadBalancing {
+    #[default]
+    InOrder,
+    Random,
+    RoundRobin,
+    FirstHealthy,
+}
+
+/// Options for connecting to destinations with multiple replicas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReplicaOptions {
+    /// The order replicas are tried in.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub strategy:    LoadBalancing,
+    /// How long a replica that failed to connect is considered down.
+    pub retry_after: Duration,
+}
+
+impl Default for ReplicaOptions {
+    fn default() -> Self {
+        Self {
+            strategy:    LoadBalancing::default(),
+            retry_after: Duration::from_secs(REPLICA_RETRY_AFTER_SECS),
+        }
+    }
+}
+
+impl ReplicaOptions {
+    #[must_use]
+    pub fn with_strategy(mut self, strategy: LoadBalancing) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    #[must_use]
+    pub fn with_retry_after(mut self, retry_after: Duration) -> Self {
+        self.retry_after = retry_after;
+        self
+    }
+}
+
+/// Tracks replica addresses that recently failed to connect.
+///
+/// A single instance is shared by every client built from the same
+/// [`super::builder::ClientBuilder`], including clients created by a connection pool, so a down
+/// replica is skipped by all of them until it is due to be retried.
+#[derive(Debug, Default)]
+pub struct ReplicaHealth {
+    down: Mutex<HashMap<SocketAddr, Instant>>,
+    next: AtomicUsize,
+}
+
+impl ReplicaHealth {
+    pub fn new() -> Self { Self::default() }
+
+    /// Whether the address is not currently considered down.
+    pub fn is_healthy(&self, addr: &SocketAddr) -> bool {
+        self.down.lock().get(addr).is_none_or(|until| *until <= Instant::now())
+    }
+
+    pub(crate) fn mark_down(&self, addr: SocketAddr, retry_after: Duration) {
+        let _ = self.down.lock().insert(addr, Instant::now() + retry_after);
+    }
+
+    pub(crate) fn mark_up(&self, addr: &SocketAddr) { let _ = self.down.lock().remove(addr); }
+
+    fn next_offset(&self) -> usize { self.next.fetch_add(1, Ordering::Relaxed) }
+}
+
+/// A resolved replica, ie one host of a [`super::Destination`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Replica {
+    pub(crate) addrs:  Vec<SocketAddr>,
+    /// The host, used as the TLS server name unless a domain is configured.
+    pub(crate) domain: String,
+}
+
+/// The replicas a connection may be established to.
+#[derive(Debug, Clone)]
+pub(crate) struct ReplicaSet {
+    replicas: Arc<[Replica]>,
+    options:  ReplicaOptions,
+    health:   Arc<ReplicaHealth>,
+}
+
+impl ReplicaSet {
+    pub(crate) fn new(
+        replicas: Vec<Replica>,
+        options: ReplicaOptions,
+        health: Arc<ReplicaHealth>,
+    ) -> Self {
+        Self { replicas: replicas.into(), options, health }
+    }
+
+    pub(crate) fn first_addr(&self) -> Option<&SocketAddr> {
+        self.replicas.iter().find_map(|r| r.addrs.first())
+    }
+
+    /// The addresses to try, in order, paired with their replica's domain.
+    pub(crate) fn candidates(&self) -> Vec<(SocketAddr, &str)> {
+        let len = self.replicas.len();
+        let mut order = (0..len).collect::<Vec<_>>();
+
+        match self.options.strategy {
+            LoadBalancing::InOrder | LoadBalancing::FirstHealthy => {}
+            LoadBalancing::Random => shuffle(&mut order),
+            LoadBalancing::RoundRobin => order.rotate_left(self.health.next_offset() % len.max(1)),
+        }
+
+        // Healthy replicas first, keeping the strategy's order otherwise
+        if self.options.strategy != LoadBalancing::InOrder {
+            order.sort_by_key(|&i| {
+                !self.replicas[i].addrs.iter().any(|a| self.health.is_healthy(a))
+            });
+        }
+
+        order
+            .into_iter()
+            .flat_map(|i| {
+                let replica = &self.replicas[i];
+                replica.addrs.iter().map(|addr| (*addr, replica.domain.as_str()))
+            })
+            .collect()
+    }
+
+    pub(crate) fn mark_down(&self, addr: SocketAddr) {
+        self.health.mark_down(addr, self.options.retry_after);
+    }
+
+    pub(crate) fn mark_up(&self, addr: &SocketAddr) { self.health.mark_up(addr); }
+}
+
+/// Fisher-Yates shuffle seeded from std's randomly keyed hasher.
+fn shuffle(order: &mut [usize]) {
+    let mut state = RandomState::new().hash_one(Instant::now()) | 1;
+    for i in (1..order.len()).rev() {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        #[expect(clippy::cast_possible_truncation)]
+        let j = (state % (i as u64 + 1)) as usize;
+        order.swap(i, j);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr};
+
+    use super::*;
+
+    fn addr(port: u16) -> SocketAddr { SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port) }
+
+    fn replica_set(strategy: LoadBalancing) -> ReplicaSet {
+        let replicas = (1..=3)
+            .map(|i| Replica { addrs: vec![addr(9000 + i)], domain: format!("host{i}") })
+            .collect();
+        let options = ReplicaOptions::default().with_strategy(strategy);
+        ReplicaSet::new(replicas, options, Arc::new(ReplicaHealth::new()))
+    }
+
+    fn ports(set: &ReplicaSet) -> Vec<u16> {
+        set.candidates().into_iter().map(|(addr, _)| addr.port()).collect()
+    }
+
+    #[test]
+    fn test_in_order_ignores_health() {
+        let set = replica_set(LoadBalancing::InOrder);
+        set.mark_down(addr(9001));
+        assert_eq!(ports(&set), vec![9001, 9002, 9003]);
+    }
+
+    #[test]
+    fn test_first_healthy_skips_down_replicas() {
+        let set = replica_set(LoadBalancing::FirstHealthy);
+        assert_eq!(ports(&set), vec![9001, 9002, 9003]);
+
+        set.mark_down(addr(9001));
+        assert_eq!(ports(&set), vec![9002, 9003, 9001]);
+
+        set.mark_up(&addr(9001));
+        assert_eq!(ports(&set), vec![9001, 9002, 9003]);
+    }
+
+    #[test]
+    fn test_down_replica_recovers_after_retry() {
+        let health = Arc::new(ReplicaHealth::new());
+        health.mark_down(addr(9001), Duration::ZERO);
+        assert!(health.is_healthy(&addr(9001)));
+
+        health.mark_down(addr(9001), Duration::from_secs(60));
+        assert!(!health.is_healthy(&addr(9001)));
+    }
+
+    #[test]
+    fn test_round_robin_rotates() {
+        let set = replica_set(LoadBalancing::RoundRobin);
+        assert_eq!(ports(&set), vec![9001, 9002, 9003]);
+        assert_eq!(ports(&set), vec![9002, 9003, 9001]);
+        assert_eq!(ports(&set), vec![9003, 9001, 9002]);
+        assert_eq!(ports(&set), vec![9001, 9002, 9003]);
+    }
+
+    #[test]
+    fn test_random_includes_every_replica() {
+        let set = replica_set(LoadBalancing::Random);
+        for _ in 0..10 {
+            let mut ports = ports(&set);
+            ports.sort_unstable();
+            assert_eq!(ports, vec![9001, 9002, 9003]);
+        }
+    }
+
+    #[test]
+    fn test_candidates_carry_domain() {
+        let set = replica_set(LoadBalancing::InOrder);
+        let domains = set.candidates().into_iter().map(|(_, d)| d.to_string()).collect::<Vec<_>>();
+        assert_eq!(domains, vec!["host1", "host2", "host3"]);
+    }
+}
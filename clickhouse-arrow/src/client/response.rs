@@ -1,5 +1,7 @@
 use std::pin::Pin;
+use std::sync::Arc;
 
+use arrow::array::RecordBatch;
 use futures_util::stream::StreamExt;
 use futures_util::{Stream, TryStreamExt};
 use tokio::sync::{mpsc, oneshot};
@@ -8,9 +10,40 @@ use tracing::{error, trace};
 
 use super::ClientFormat;
 use crate::explain::ExplainResult;
+use crate::native::block::Block;
+use crate::native::protocol::ProfileInfo;
 use crate::prelude::{ATT_CID, ATT_QID};
 use crate::{Qid, Result};
 
+/// Shared between the connection receiving a query's packets and its [`ClickHouseResponse`].
+pub(crate) type SummaryHandle = Arc<parking_lot::Mutex<ResponseSummary>>;
+
+/// A totals or extremes block, in the data type of the format that received it.
+#[derive(Debug, Clone)]
+pub(crate) enum SummaryBlock {
+    Native(Block),
+    Arrow(RecordBatch),
+}
+
+impl From<Block> for SummaryBlock {
+    fn from(block: Block) -> Self { Self::Native(block) }
+}
+
+impl From<RecordBatch> for SummaryBlock {
+    fn from(batch: RecordBatch) -> Self { Self::Arrow(batch) }
+}
+
+/// Results `ClickHouse` sends alongside a query's data blocks.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ResponseSummary {
+    /// The `WITH TOTALS` block.
+    pub(crate) totals:       Option<SummaryBlock>,
+    /// The minimum and maximum values of each column, sent when `extremes = 1`.
+    pub(crate) extremes:     Option<SummaryBlock>,
+    /// Execution summary, sent after the data blocks.
+    pub(crate) profile_info: Option<ProfileInfo>,
+}
+
 pub(crate) fn create_response_stream<T: ClientFormat>(
     rx: mpsc::Receiver<Result<T::Data>>,
     qid: Qid,
@@ -47,12 +80,14 @@ pub struct ClickHouseResponse<T> {
     stream:           Pin<Box<dyn Stream<Item = Result<T>> + Send + 'static>>,
     /// Receiver for the parallel EXPLAIN result, if configured.
     explain_receiver: Option<oneshot::Receiver<Result<ExplainResult>>>,
+    /// Totals and extremes, filled in by the connection as the query runs.
+    summary:          Option<SummaryHandle>,
 }
 
 impl<T> ClickHouseResponse<T> {
     /// Create a new response wrapping a stream.
     pub fn new(stream: Pin<Box<dyn Stream<Item = Result<T>> + Send + 'static>>) -> Self {
-        Self { stream, explain_receiver: None }
+        Self { stream, explain_receiver: None, summary: None }
     }
 
     /// Create a new response with an explain receiver.
@@ -60,7 +95,7 @@ impl<T> ClickHouseResponse<T> {
         stream: Pin<Box<dyn Stream<Item = Result<T>> + Send + 'static>>,
         explain_receiver: oneshot::Receiver<Result<ExplainResult>>,
     ) -> Self {
-        Self { stream, explain_receiver: Some(explain_receiver) }
+        Self { stream, explain_receiver: Some(explain_receiver), summary: None }
     }
 
     /// Create a response from a stream.
@@ -117,6 +152,95 @@ impl<T> ClickHouseResponse<T> {
     }
 }
 
+impl<T> ClickHouseResponse<T> {
+    /// Attach the summary the connection fills in for this response.
+    #[must_use]
+    pub(crate) fn with_summary(mut self, summary: Option<SummaryHandle>) -> Self {
+        self.summary = summary;
+        self
+    }
+
+    /// The summary handle, used to carry it over when mapping the response into another.
+    pub(crate) fn summary_handle(&self) -> Option<SummaryHandle> { self.summary.clone() }
+
+    /// Get the totals block of a `WITH TOTALS` query, for clients using [`crate::ArrowFormat`].
+    ///
+    /// Totals are sent after all data blocks, so this returns `None` until the response has been
+    /// fully consumed, as well as for queries without totals and for native clients (see
+    /// [`ClickHouseResponse::totals_block`]).
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let mut response = client
+    ///     .query("SELECT number % 3 AS n, count() FROM numbers(10) GROUP BY n WITH TOTALS", None)
+    ///     .await?;
+    /// while let Some(batch) = response.next().await { ... }
+    ///
+    /// if let Some(totals) = response.totals() {
+    ///     println!("Totals: {} rows", totals.num_rows());
+    /// }
+    /// ```
+    pub fn totals(&self) -> Option<RecordBatch> {
+        match self.summary.as_ref()?.lock().totals.as_ref()? {
+            SummaryBlock::Arrow(batch) => Some(batch.clone()),
+            SummaryBlock::Native(_) => None,
+        }
+    }
+
+    /// Get the extremes block of a query run with the `extremes` setting enabled, for clients
+    /// using [`crate::ArrowFormat`].
+    ///
+    /// The block contains two rows, the minimum and maximum of each column. Like
+    /// [`ClickHouseResponse::totals`], it's only available once the response has been consumed.
+    pub fn extremes(&self) -> Option<RecordBatch> {
+        match self.summary.as_ref()?.lock().extremes.as_ref()? {
+            SummaryBlock::Arrow(batch) => Some(batch.clone()),
+            SummaryBlock::Native(_) => None,
+        }
+    }
+
+    /// Get the execution summary of the query, available once the response has been consumed.
+    ///
+    /// Useful for pagination, [`ProfileInfo::rows_before_limit`] holds the number of rows the
+    /// query would have returned without its `LIMIT`, so a single round trip yields both a page
+    /// and the total count. The same information is also sent as a
+    /// [`crate::ClickHouseEvent::ProfileInfo`] event.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let mut response = client.query("SELECT * FROM events LIMIT 50 OFFSET 100", None).await?;
+    /// while let Some(batch) = response.next().await { ... }
+    ///
+    /// if let Some(info) = response.profile_info() {
+    ///     println!("Total rows: {}", info.rows_before_limit);
+    /// }
+    /// ```
+    pub fn profile_info(&self) -> Option<ProfileInfo> { self.summary.as_ref()?.lock().profile_info }
+
+    /// Get the totals block of a `WITH TOTALS` query, for clients using [`crate::NativeFormat`].
+    ///
+    /// See [`ClickHouseResponse::totals`].
+    pub fn totals_block(&self) -> Option<Block> {
+        match self.summary.as_ref()?.lock().totals.as_ref()? {
+            SummaryBlock::Native(block) => Some(block.clone()),
+            SummaryBlock::Arrow(_) => None,
+        }
+    }
+
+    /// Get the extremes block of a query run with the `extremes` setting enabled, for clients
+    /// using [`crate::NativeFormat`].
+    ///
+    /// See [`ClickHouseResponse::extremes`].
+    pub fn extremes_block(&self) -> Option<Block> {
+        match self.summary.as_ref()?.lock().extremes.as_ref()? {
+            SummaryBlock::Native(block) => Some(block.clone()),
+            SummaryBlock::Arrow(_) => None,
+        }
+    }
+}
+
 impl<T> Stream for ClickHouseResponse<T>
 where
     T: Send + 'static,
@@ -130,3 +254,41 @@ where
         self.project().stream.poll_next(cx)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::array::{ArrayRef, Int32Array};
+
+    use super::*;
+
+    fn batch() -> RecordBatch {
+        let array: ArrayRef = Arc::new(Int32Array::from(vec![1, 2]));
+        RecordBatch::try_from_iter([("n", array)]).unwrap()
+    }
+
+    #[test]
+    fn test_summary_accessors() {
+        let response = ClickHouseResponse::<()>::from_stream(futures_util::stream::empty());
+        assert!(response.totals().is_none());
+        assert!(response.extremes_block().is_none());
+
+        let summary = SummaryHandle::default();
+        let response = response.with_summary(Some(Arc::clone(&summary)));
+        assert!(response.totals().is_none());
+
+        summary.lock().totals = Some(batch().into());
+        summary.lock().extremes = Some(Block::default().into());
+        assert_eq!(response.totals().map(|b| b.num_rows()), Some(2));
+        assert!(response.totals_block().is_none());
+        assert!(response.extremes().is_none());
+        assert!(response.extremes_block().is_some());
+
+        assert!(response.profile_info().is_none());
+        let info =
+            ProfileInfo { rows_before_limit: 100, applied_limit: true, ..Default::default() };
+        summary.lock().profile_info = Some(info);
+        assert_eq!(response.profile_info(), Some(info));
+    }
+}
@@ -1,18 +1,29 @@
 use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::Path;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{Context, Poll};
 use std::time::Duration;
 
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 use tokio::net::TcpStream;
 use tokio_rustls::TlsConnector;
 use tokio_rustls::client::TlsStream;
-use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
 use tokio_rustls::rustls::{self, ClientConfig, RootCertStore};
 
+use super::replicas::Replica;
 use crate::constants::*;
 use crate::prelude::*;
-use crate::{Error, Result};
-
-// Custom Destination type
+use crate::{ClientOptions, Error, Result};
+
+/// The `ClickHouse` server(s) to connect to.
+///
+/// A destination is usually a single host, but may list several replicas (see
+/// [`Destination::replicas`]), in which case connections are spread across and fail over between
+/// them according to the configured [`super::LoadBalancing`] strategy. Endpoint strings containing
+/// commas, ie `"host1:9000,host2:9000"`, are parsed as replicas.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Destination {
     inner: DestinationInner,
@@ -24,12 +35,88 @@ enum DestinationInner {
     SocketAddr(SocketAddr),       // Direct SocketAddr (e.g., 127.0.0.1:9000)
     HostPort(String, u16),        // Hostname and port (e.g., "localhost", 9000)
     Endpoint(String),             // String to parse (e.g., "localhost:9000")
+    Replicas(Vec<Destination>),   // Multiple hosts (e.g., "host1:9000,host2:9000")
 }
 
 impl Destination {
-    /// Resolve to Vec<SocketAddr> using [`tokio::net::lookup_host`]
+    /// Create a destination from multiple replicas, each a single host.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let destination = Destination::replicas([("replica-1", 9000), ("replica-2", 9000)]);
+    /// let builder = ClientBuilder::new()
+    ///     .with_destination(destination)
+    ///     .with_load_balancing(LoadBalancing::RoundRobin);
+    /// ```
+    pub fn replicas<D: Into<Destination>>(replicas: impl IntoIterator<Item = D>) -> Self {
+        // Nested replicas are flattened
+        let mut replicas = replicas
+            .into_iter()
+            .map(Into::into)
+            .flat_map(|d: Destination| match d.inner {
+                DestinationInner::Replicas(replicas) => replicas,
+                inner => vec![Destination { inner }],
+            })
+            .collect::<Vec<_>>();
+        if replicas.len() == 1 {
+            return replicas.remove(0);
+        }
+        Destination { inner: DestinationInner::Replicas(replicas) }
+    }
+
+    /// Whether this destination lists multiple replicas.
+    pub fn is_replicas(&self) -> bool { matches!(self.inner, DestinationInner::Replicas(_)) }
+
+    // Parse an endpoint, splitting comma separated hosts into replicas
+    fn endpoint(endpoint: String) -> Self {
+        if endpoint.contains(',') {
+            return Self::replicas(
+                endpoint.split(',').map(str::trim).filter(|e| !e.is_empty()).map(str::to_string),
+            );
+        }
+        Destination { inner: DestinationInner::Endpoint(endpoint) }
+    }
+
+    /// Resolve each replica using [`tokio::net::lookup_host`].
+    ///
+    /// Replicas that fail to resolve are skipped, an error is only returned if none resolve.
+    pub(crate) async fn resolve_replicas(&self, ipv4_only: bool) -> Result<Vec<Replica>> {
+        let DestinationInner::Replicas(replicas) = &self.inner else {
+            let addrs = self.resolve(ipv4_only).await?;
+            return Ok(vec![Replica { addrs, domain: self.domain() }]);
+        };
+
+        let mut resolved = Vec::with_capacity(replicas.len());
+        for replica in replicas {
+            match replica.resolve(ipv4_only).await {
+                Ok(addrs) if !addrs.is_empty() => {
+                    resolved.push(Replica { addrs, domain: replica.domain() });
+                }
+                Ok(_) => warn!(%replica, "Replica resolved to no addresses, skipping"),
+                Err(error) => warn!(?error, %replica, "Failed to resolve replica, skipping"),
+            }
+        }
+
+        if resolved.is_empty() {
+            return Err(Error::MalformedConnectionInformation(
+                "Could not resolve any replica".into(),
+            ));
+        }
+        Ok(resolved)
+    }
+
+    /// Resolve a single host to Vec<SocketAddr> using [`tokio::net::lookup_host`]
+    ///
+    /// Use [`Destination::resolve_replicas`] for destinations that may list multiple replicas.
     pub(crate) async fn resolve(&self, ipv4_only: bool) -> Result<Vec<SocketAddr>> {
         let addrs: Vec<SocketAddr> = match &self.inner {
+            DestinationInner::Replicas(_) => {
+                return Err(Error::MalformedConnectionInformation(
+                    "Replicas must be resolved individually".into(),
+                ));
+            }
             DestinationInner::SocketAddrs(addrs) => return Ok(addrs.clone()),
             DestinationInner::SocketAddr(addr) => return Ok(vec![*addr]),
             DestinationInner::HostPort(host, port) => {
@@ -60,27 +147,95 @@ impl Destination {
             DestinationInner::Endpoint(endpoint) => {
                 endpoint.split(':').next().map(ToString::to_string).unwrap_or(endpoint.clone())
             }
+            DestinationInner::Replicas(replicas) => {
+                replicas.first().map(Destination::domain).unwrap_or_default()
+            }
         }
     }
 }
 
 /// Connects to `ClickHouse`'s native server port over TLS.
 pub(super) async fn connect_tls(
-    addrs: &[SocketAddr],
-    domain: Option<&str>,
-) -> Result<TlsStream<TcpStream>> {
-    let domain: String =
-        domain.as_ref().map_or_else(|| addrs[0].ip().to_string(), ToString::to_string);
+    addr: SocketAddr,
+    domain: &str,
+    config: Arc<ClientConfig>,
+) -> Result<TlsStream<TcpTransport>> {
+    let domain = if domain.is_empty() { addr.ip().to_string() } else { domain.to_string() };
     debug!(%domain, "Initiating TLS connection");
-    let stream = connect_socket(addrs).await?;
-    tls_stream(domain, stream).await
+    let stream = connect_socket(addr).await?;
+    tls_stream(domain, stream, config).await
+}
+
+/// Builds the TLS configuration from the client's options.
+///
+/// Server certificates are verified against the CA bundle and/or the OS trust store when
+/// configured, otherwise the bundled webpki roots. A client certificate is presented when both a
+/// certificate and key are configured.
+pub(super) fn tls_config(options: &ClientOptions) -> Result<Arc<ClientConfig>> {
+    let tls = &options.ext.tls;
+
+    let mut root_store = RootCertStore::empty();
+    if let Some(cafile) = options.cafile.as_deref() {
+        for cert in load_certs(cafile)? {
+            root_store.add(cert).map_err(|error| {
+                Error::Configuration(format!("Invalid CA in {}: {error}", cafile.display()))
+            })?;
+        }
+    }
+    if tls.native_roots {
+        let native = rustls_native_certs::load_native_certs();
+        for error in &native.errors {
+            warn!(?error, "Failed to load native root certificate");
+        }
+        let (added, ignored) = root_store.add_parsable_certificates(native.certs);
+        debug!(added, ignored, "Loaded native root certificates");
+    }
+    if root_store.is_empty() {
+        root_store.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    }
+
+    let builder = ClientConfig::builder().with_root_certificates(root_store);
+    let mut tls_config = match (tls.client_cert.as_deref(), tls.client_key.as_deref()) {
+        (Some(cert), Some(key)) => {
+            let certs = load_certs(cert)?;
+            let key = PrivateKeyDer::from_pem_file(key).map_err(|error| {
+                Error::Configuration(format!("Invalid client key {}: {error}", key.display()))
+            })?;
+            builder.with_client_auth_cert(certs, key).map_err(|error| {
+                Error::Configuration(format!("Invalid client certificate: {error}"))
+            })?
+        }
+        (None, None) => builder.with_no_client_auth(),
+        _ => {
+            return Err(Error::Configuration(
+                "Both a client certificate and key are required for mutual TLS".into(),
+            ));
+        }
+    };
+
+    // Enable session resumption by default
+    tls_config.resumption = rustls::client::Resumption::in_memory_sessions(256);
+
+    Ok(Arc::new(tls_config))
+}
+
+// Load all certificates from a PEM file
+fn load_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>> {
+    let certs = CertificateDer::pem_file_iter(path)
+        .and_then(Iterator::collect::<Result<Vec<_>, _>>)
+        .map_err(|error| {
+            Error::Configuration(format!("Invalid certificates {}: {error}", path.display()))
+        })?;
+    if certs.is_empty() {
+        return Err(Error::Configuration(format!("No certificates found in {}", path.display())));
+    }
+    Ok(certs)
 }
 
 /// Connects to `ClickHouse`'s native server port and configures common socket options.
 #[instrument(level = "trace", name = "clickhouse._connect_socket", skip_all)]
-pub(crate) async fn connect_socket(addrs: &[SocketAddr]) -> Result<TcpStream> {
-    debug!(?addrs, "Initiating TCP connection");
-    let addr = addrs.first().ok_or(Error::MissingConnectionInformation)?;
+pub(crate) async fn connect_socket(addr: SocketAddr) -> Result<TcpTransport> {
+    debug!(?addr, "Initiating TCP connection");
     let domain = if addr.is_ipv4() { socket2::Domain::IPV4 } else { socket2::Domain::IPV6 };
     let socket = socket2::Socket::new(domain, socket2::Type::STREAM, Some(socket2::Protocol::TCP))?;
     socket.set_nonblocking(true)?;
@@ -94,30 +249,92 @@ pub(crate) async fn connect_socket(addrs: &[SocketAddr]) -> Result<TcpStream> {
         .with_retries(TCP_KEEP_ALIVE_RETRIES);
     socket.set_tcp_keepalive(&keepalive)?;
 
-    // Connect with a timeout
-    let sock_addr = socket2::SockAddr::from(*addr);
-    socket.connect_timeout(&sock_addr, Duration::from_secs(TCP_CONNECT_TIMEOUT))?;
+    // Connect with a timeout, without blocking the runtime
+    let socket = tokio::net::TcpSocket::from_std_stream(std::net::TcpStream::from(socket));
+    let stream =
+        tokio::time::timeout(Duration::from_secs(TCP_CONNECT_TIMEOUT), socket.connect(addr))
+            .await
+            .map_err(|_| Error::ConnectionTimeout(format!("Connecting to {addr} timed out")))??;
+    stream.set_nodelay(true)?;
     trace!("Connected socket for {addr}");
 
-    // Convert to TcpStream
-    let stream = std::net::TcpStream::from(socket);
-    stream.set_nodelay(true)?;
-    stream.set_nonblocking(true)?;
+    // Prefer io_uring when the kernel supports it, falling back to the tokio socket
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    if crate::io_uring::is_iouring_available() {
+        return match crate::io_uring::UringStream::try_from_std(stream.into_std()?) {
+            Ok(stream) => {
+                trace!("Using io_uring transport for {addr}");
+                Ok(TcpTransport::Uring(Box::new(stream)))
+            }
+            Err(stream) => Ok(TcpTransport::Tokio(TcpStream::from_std(stream)?)),
+        };
+    }
 
-    Ok(TcpStream::from_std(stream)?)
+    Ok(TcpTransport::Tokio(stream))
 }
 
-// Helper function to facilitate TLS connection setup
-async fn tls_stream(domain: String, stream: TcpStream) -> Result<TlsStream<TcpStream>> {
-    let root_store = RootCertStore { roots: webpki_roots::TLS_SERVER_ROOTS.into() };
+/// The socket a native connection runs over.
+///
+/// With the `io-uring` feature enabled, sockets are driven by io_uring when the kernel supports
+/// it (see [`crate::io_uring`]), otherwise by tokio.
+#[derive(Debug)]
+pub(crate) enum TcpTransport {
+    Tokio(TcpStream),
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    Uring(Box<crate::io_uring::UringStream>),
+}
 
-    let mut tls_config =
-        ClientConfig::builder().with_root_certificates(root_store).with_no_client_auth();
+impl AsyncRead for TcpTransport {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Self::Tokio(stream) => Pin::new(stream).poll_read(cx, buf),
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            Self::Uring(stream) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
 
-    // Enable session resumption by default
-    tls_config.resumption = rustls::client::Resumption::in_memory_sessions(256);
+impl AsyncWrite for TcpTransport {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        match self.get_mut() {
+            Self::Tokio(stream) => Pin::new(stream).poll_write(cx, buf),
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            Self::Uring(stream) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
 
-    let connector = TlsConnector::from(Arc::new(tls_config));
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Self::Tokio(stream) => Pin::new(stream).poll_flush(cx),
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            Self::Uring(stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Self::Tokio(stream) => Pin::new(stream).poll_shutdown(cx),
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            Self::Uring(stream) => Pin::new(stream).poll_shutdown(cx),
+        }
+    }
+}
+
+// Helper function to facilitate TLS connection setup
+async fn tls_stream(
+    domain: String,
+    stream: TcpTransport,
+    config: Arc<ClientConfig>,
+) -> Result<TlsStream<TcpTransport>> {
+    let connector = TlsConnector::from(config);
     let dnsname =
         ServerName::try_from(domain.clone()).map_err(|e| Error::InvalidDnsName(e.to_string()))?;
     Ok(connector.connect(dnsname, stream).await?)
@@ -132,6 +349,10 @@ impl std::fmt::Display for Destination {
             DestinationInner::SocketAddr(addr) => write!(f, "{addr}"),
             DestinationInner::HostPort(host, port) => write!(f, "{host}:{port}"),
             DestinationInner::Endpoint(endpoint) => write!(f, "{endpoint}"),
+            DestinationInner::Replicas(replicas) => {
+                let replicas = replicas.iter().map(ToString::to_string).collect::<Vec<_>>();
+                write!(f, "{}", replicas.join(","))
+            }
         }
     }
 }
@@ -173,26 +394,20 @@ impl From<(&str, u16)> for Destination {
 }
 
 impl From<String> for Destination {
-    fn from(endpoint: String) -> Self {
-        Destination { inner: DestinationInner::Endpoint(endpoint) }
-    }
+    fn from(endpoint: String) -> Self { Destination::endpoint(endpoint) }
 }
 
 impl From<&String> for Destination {
-    fn from(endpoint: &String) -> Self {
-        Destination { inner: DestinationInner::Endpoint(endpoint.clone()) }
-    }
+    fn from(endpoint: &String) -> Self { Destination::endpoint(endpoint.clone()) }
 }
 
 impl From<&str> for Destination {
-    fn from(endpoint: &str) -> Self {
-        Destination { inner: DestinationInner::Endpoint(endpoint.to_string()) }
-    }
+    fn from(endpoint: &str) -> Self { Destination::endpoint(endpoint.to_string()) }
 }
 
 impl From<std::borrow::Cow<'_, str>> for Destination {
     fn from(endpoint: std::borrow::Cow<'_, str>) -> Self {
-        Destination { inner: DestinationInner::Endpoint(endpoint.into_owned()) }
+        Destination::endpoint(endpoint.into_owned())
     }
 }
 
@@ -217,6 +432,28 @@ mod tests {
     // Helper to create Destination variants
     fn socket_addr() -> SocketAddr { SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 9000) }
 
+    #[tokio::test]
+    async fn test_connect_socket_round_trip() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // Whichever transport is chosen, io_uring or tokio
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = crate::spawn::SpawnedTask::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let (mut reader, mut writer) = socket.split();
+            let _ = tokio::io::copy(&mut reader, &mut writer).await.unwrap();
+        });
+
+        let mut stream = connect_socket(addr).await.unwrap();
+        stream.write_all(b"ping").await.unwrap();
+        stream.shutdown().await.unwrap();
+        let mut echoed = Vec::new();
+        let _ = stream.read_to_end(&mut echoed).await.unwrap();
+        assert_eq!(echoed, b"ping");
+        server.await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_resolve_socket_addrs() {
         let addrs = vec![socket_addr()];
@@ -318,4 +555,65 @@ mod tests {
         let dest = Destination { inner: DestinationInner::Endpoint("localhost".to_string()) };
         assert_eq!(dest.domain(), "localhost");
     }
+
+    #[test]
+    fn test_endpoint_replicas() {
+        let dest = Destination::from("host1:9000, host2:9001,");
+        assert!(dest.is_replicas());
+        assert_eq!(dest.to_string(), "host1:9000,host2:9001");
+        assert_eq!(dest.domain(), "host1");
+
+        // A single replica is a plain destination
+        let dest = Destination::replicas(["localhost:9000"]);
+        assert!(!dest.is_replicas());
+        assert_eq!(dest, Destination::from("localhost:9000"));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_replicas() {
+        let addr = socket_addr();
+        let dest = Destination::replicas(vec![
+            Destination::from(addr),
+            Destination::from(("invalid-host-xyz", 9000)),
+            Destination::from(("localhost", 9001)),
+        ]);
+        let replicas = dest.resolve_replicas(true).await.unwrap();
+        assert_eq!(replicas.len(), 2);
+        assert_eq!(replicas[0].addrs, vec![addr]);
+        assert_eq!(replicas[0].domain, "127.0.0.1");
+        assert_eq!(replicas[1].domain, "localhost");
+        assert!(replicas[1].addrs.iter().all(|a| a.port() == 9001));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_replicas_none_resolve() {
+        let dest = Destination::from("invalid-host-xyz:9000,invalid-host-abc:9000");
+        let result = dest.resolve_replicas(false).await;
+        assert!(matches!(result, Err(Error::MalformedConnectionInformation(_))));
+    }
+
+    #[test]
+    fn test_tls_config_default_roots() {
+        let _ = rustls::crypto::aws_lc_rs::default_provider().install_default().ok();
+        let config = tls_config(&ClientOptions::default()).unwrap();
+        assert!(!config.client_auth_cert_resolver.has_certs());
+    }
+
+    #[test]
+    fn test_tls_config_requires_cert_and_key() {
+        let _ = rustls::crypto::aws_lc_rs::default_provider().install_default().ok();
+        let mut options = ClientOptions::default();
+        options.ext.tls.client_cert = Some("/path/to/client.crt".into());
+        let result = tls_config(&options);
+        assert!(matches!(result, Err(Error::Configuration(_))));
+    }
+
+    #[test]
+    fn test_tls_config_missing_cafile() {
+        let _ = rustls::crypto::aws_lc_rs::default_provider().install_default().ok();
+        let options =
+            ClientOptions { cafile: Some("/nonexistent/ca.pem".into()), ..Default::default() };
+        let result = tls_config(&options);
+        assert!(matches!(result, Err(Error::Configuration(_))));
+    }
 }
@@ -51,6 +51,26 @@ impl<W: ClickHouseWrite> Writer<W> {
         Ok(())
     }
 
+    /// Request the challenge to sign for SSH key authentication, sent right after the hello.
+    #[cfg(feature = "ssh")]
+    pub(super) async fn send_ssh_challenge_request(writer: &mut W) -> Result<()> {
+        writer.write_var_uint(ClientPacketId::SSHChallengeRequest as u64).await?;
+        writer.flush().await?;
+        Ok(())
+    }
+
+    /// Send the signature of the SSH challenge.
+    #[cfg(feature = "ssh")]
+    pub(super) async fn send_ssh_challenge_response(
+        writer: &mut W,
+        signature: &[u8],
+    ) -> Result<()> {
+        writer.write_var_uint(ClientPacketId::SSHChallengeResponse as u64).await?;
+        writer.write_string(signature).await?;
+        writer.flush().await?;
+        Ok(())
+    }
+
     pub(super) async fn send_query(
         writer: &mut W,
         params: Query<'_>,
@@ -163,6 +183,23 @@ impl<W: ClickHouseWrite> Writer<W> {
         Ok(())
     }
 
+    /// Sends a block of an external table, a temporary table the query can read by `name`.
+    /// Blocks with the same name are appended to the same table. Not flushed, the delimiter
+    /// that ends the external tables is.
+    pub(super) async fn send_external_table<T: ClientFormat>(
+        writer: &mut W,
+        name: &str,
+        data: T::Data,
+        qid: Qid,
+        revision: u64,
+        metadata: ClientMetadata,
+    ) -> Result<()> {
+        writer.write_var_uint(ClientPacketId::Data as u64).await?;
+        writer.write_string(name).await?;
+        T::write(writer, data, qid, None, revision, metadata).await?;
+        Ok(())
+    }
+
     pub(super) async fn send_addendum(
         writer: &mut W,
         revision: u64,
@@ -196,13 +233,25 @@ impl<W: ClickHouseWrite> Writer<W> {
         Ok(())
     }
 
-    // NOTE: Not used currently
-    #[expect(unused)]
     pub(super) async fn send_cancel(writer: &mut W) -> Result<()> {
         writer.write_var_uint(ClientPacketId::Cancel as u64).await?;
         writer.flush().instrument(trace_span!("flush_cancel")).await?;
         Ok(())
     }
+
+    pub(super) async fn send_tables_status(
+        writer: &mut W,
+        tables: &[(String, String)],
+    ) -> Result<()> {
+        writer.write_var_uint(ClientPacketId::TablesStatusRequest as u64).await?;
+        writer.write_var_uint(tables.len() as u64).await?;
+        for (database, table) in tables {
+            writer.write_string(database).await?;
+            writer.write_string(table).await?;
+        }
+        writer.flush().instrument(trace_span!("flush_tables_status")).await?;
+        Ok(())
+    }
 }
 
 /// A wrapper around a [`ClickHouseWrite`] that logs all writes. Useful for testing.
@@ -2,7 +2,7 @@
 //!
 //! LZ4 and ZSTD support w/ ClickHouse's custom frame format:
 //! - 16 bytes: CityHash128 checksum
-//! - 1 byte: compression method (0x82=LZ4, 0x90=ZSTD)
+//! - 1 byte: compression method (0x82=LZ4 and LZ4HC, 0x90=ZSTD)
 //! - 4 bytes: compressed size (incl. 9-byte header)
 //! - 4 bytes: decompressed size
 //! - N bytes: payload
@@ -16,105 +16,112 @@ use futures_util::FutureExt;
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt, ReadBuf};
 
 use crate::io::{ClickHouseRead, ClickHouseWrite};
-use crate::native::protocol::CompressionMethod;
+use crate::native::protocol::{CompressionMethod, CompressionOptions};
 use crate::{Error, Result};
 
+/// Default level of `ZSTD` compression.
+pub(crate) const DEFAULT_ZSTD_LEVEL: i32 = 1;
+/// Default level of `LZ4HC` compression, as used by `ClickHouse`.
+#[cfg(feature = "lz4hc")]
+pub(crate) const DEFAULT_LZ4HC_LEVEL: i32 = 9;
+
 /// Compress and write in ClickHouse chunk format.
-#[expect(clippy::cast_possible_truncation)]
 #[cfg_attr(not(test), expect(unused))]
 pub(crate) async fn compress_data<W: ClickHouseWrite>(
     writer: &mut W,
     raw: Vec<u8>,
     compression: CompressionMethod,
+    options: CompressionOptions,
 ) -> Result<()> {
-    let decompressed_size = raw.len();
-    let mut out = match compression {
-        // ZSTD with default compression level (1)
-        CompressionMethod::ZSTD => zstd::bulk::compress(&raw, 1)
-            .map_err(|e| Error::SerializeError(format!("ZSTD compress error: {e}")))?,
-        // LZ4
-        CompressionMethod::LZ4 => lz4_flex::compress(&raw),
-        // None
-        CompressionMethod::None => return Ok(()),
-    };
-
-    let mut new_out = Vec::with_capacity(out.len() + 13);
-    new_out.push(compression.byte());
-    new_out.extend_from_slice(&(out.len() as u32 + 9).to_le_bytes()[..]);
-    new_out.extend_from_slice(&(decompressed_size as u32).to_le_bytes()[..]);
-    new_out.append(&mut out);
-
-    let hash = cityhash_rs::cityhash_102_128(&new_out[..]);
-    writer.write_u64_le((hash >> 64) as u64).await?;
-    writer.write_u64_le(hash as u64).await?;
-    writer.write_all(&new_out[..]).await?;
-
+    let out = compress_chunks(&raw, compression, options)?;
+    writer.write_all(&out).await?;
     Ok(())
 }
 
-#[expect(clippy::cast_possible_truncation)]
+/// Compress and write `raw`, returning the number of compressed bytes written.
 pub(crate) async fn compress_data_sync<W: ClickHouseWrite>(
     writer: &mut W,
     raw: bytes::Bytes,
     compression: CompressionMethod,
-) -> Result<()> {
-    let decompressed_size = raw.len();
-    let mut out = match compression {
-        // ZSTD with default compression level (1)
-        CompressionMethod::ZSTD => zstd::bulk::compress(&raw, 1)
-            .map_err(|e| Error::SerializeError(format!("ZSTD compress error: {e}")))?,
-        // LZ4
-        CompressionMethod::LZ4 => lz4_flex::compress(&raw),
-        // None
-        CompressionMethod::None => return Ok(()),
-    };
-
-    let mut new_out = Vec::with_capacity(out.len() + 13);
-    new_out.push(compression.byte());
-    new_out.extend_from_slice(&(out.len() as u32 + 9).to_le_bytes()[..]);
-    new_out.extend_from_slice(&(decompressed_size as u32).to_le_bytes()[..]);
-    new_out.append(&mut out);
-
-    let hash = cityhash_rs::cityhash_102_128(&new_out[..]);
-    writer.write_u64_le((hash >> 64) as u64).await?;
-    writer.write_u64_le(hash as u64).await?;
-    writer.write_all(&new_out[..]).await?;
-
-    Ok(())
+    options: CompressionOptions,
+) -> Result<usize> {
+    let out = compress_chunks(&raw, compression, options)?;
+    writer.write_all(&out).await?;
+    Ok(out.len())
 }
 
-/// Compress from pooled buffer – reduces malloc churn for high-throughput inserts.
-#[expect(clippy::cast_possible_truncation)]
+/// Compress from pooled buffer – reduces malloc churn for high-throughput inserts. Returns the
+/// number of compressed bytes written.
 pub(crate) async fn compress_data_pooled<W: ClickHouseWrite>(
     writer: &mut W,
     raw: crate::simd::PooledBuffer,
     compression: CompressionMethod,
-) -> Result<()> {
-    let decompressed_size = raw.len();
-    let mut out = match compression {
-        // ZSTD with default compression level (1)
-        CompressionMethod::ZSTD => zstd::bulk::compress(&raw, 1)
-            .map_err(|e| Error::SerializeError(format!("ZSTD compress error: {e}")))?,
-        // LZ4
-        CompressionMethod::LZ4 => lz4_flex::compress(&raw),
-        // None
-        CompressionMethod::None => return Ok(()),
-    };
+    options: CompressionOptions,
+) -> Result<usize> {
+    let out = compress_chunks(&raw, compression, options)?;
 
     // Drop the input buffer early to return it to the pool
     drop(raw);
 
-    let mut new_out = Vec::with_capacity(out.len() + 13);
-    new_out.push(compression.byte());
-    new_out.extend_from_slice(&(out.len() as u32 + 9).to_le_bytes()[..]);
-    new_out.extend_from_slice(&(decompressed_size as u32).to_le_bytes()[..]);
-    new_out.append(&mut out);
+    writer.write_all(&out).await?;
+    Ok(out.len())
+}
+
+/// Compress into one chunk per `max_block_size` bytes of input, or a single chunk if unset.
+fn compress_chunks(
+    raw: &[u8],
+    compression: CompressionMethod,
+    options: CompressionOptions,
+) -> Result<Vec<u8>> {
+    if matches!(compression, CompressionMethod::None) {
+        return Ok(vec![]);
+    }
+    let block_size = options.max_block_size.filter(|size| *size > 0).unwrap_or(raw.len().max(1));
+    let mut out = Vec::with_capacity(raw.len() / 2 + 25);
+    for chunk in raw.chunks(block_size) {
+        compress_chunk(chunk, compression, options.level, &mut out)?;
+    }
+    // Empty data is still sent as a single (empty) chunk
+    if raw.is_empty() {
+        compress_chunk(raw, compression, options.level, &mut out)?;
+    }
+    Ok(out)
+}
+
+#[expect(clippy::cast_possible_truncation)]
+fn compress_chunk(
+    raw: &[u8],
+    compression: CompressionMethod,
+    level: Option<i32>,
+    out: &mut Vec<u8>,
+) -> Result<()> {
+    let mut compressed = match compression {
+        CompressionMethod::ZSTD => {
+            zstd::bulk::compress(raw, level.unwrap_or(DEFAULT_ZSTD_LEVEL))
+                .map_err(|e| Error::SerializeError(format!("ZSTD compress error: {e}")))?
+        }
+        CompressionMethod::LZ4 => lz4_flex::compress(raw),
+        // LZ4HC produces regular LZ4 blocks, decompressed like LZ4
+        #[cfg(feature = "lz4hc")]
+        CompressionMethod::LZ4HC => {
+            let mode =
+                lz4::block::CompressionMode::HIGHCOMPRESSION(level.unwrap_or(DEFAULT_LZ4HC_LEVEL));
+            lz4::block::compress(raw, Some(mode), false)
+                .map_err(|e| Error::SerializeError(format!("LZ4HC compress error: {e}")))?
+        }
+        CompressionMethod::None => return Ok(()),
+    };
 
-    let hash = cityhash_rs::cityhash_102_128(&new_out[..]);
-    writer.write_u64_le((hash >> 64) as u64).await?;
-    writer.write_u64_le(hash as u64).await?;
-    writer.write_all(&new_out[..]).await?;
+    let mut chunk = Vec::with_capacity(compressed.len() + 9);
+    chunk.push(compression.byte());
+    chunk.extend_from_slice(&(compressed.len() as u32 + 9).to_le_bytes()[..]);
+    chunk.extend_from_slice(&(raw.len() as u32).to_le_bytes()[..]);
+    chunk.append(&mut compressed);
 
+    let hash = cityhash_rs::cityhash_102_128(&chunk[..]);
+    out.extend_from_slice(&((hash >> 64) as u64).to_le_bytes());
+    out.extend_from_slice(&(hash as u64).to_le_bytes());
+    out.append(&mut chunk);
     Ok(())
 }
 
@@ -179,6 +186,11 @@ pub(crate) async fn decompress_data_async(
 
     // Decompress based on compression method
     match compression {
+        #[cfg(feature = "lz4hc")]
+        CompressionMethod::LZ4HC => {
+            lz4_flex::decompress(&compressed[9..], decompressed_size as usize)
+                .map_err(|e| Error::DeserializeError(format!("LZ4 decompress error: {e}")))
+        }
         CompressionMethod::LZ4 => {
             lz4_flex::decompress(&compressed[9..], decompressed_size as usize)
                 .map_err(|e| Error::DeserializeError(format!("LZ4 decompress error: {e}")))
@@ -288,7 +300,14 @@ mod tests {
         let data = b"test data for compression".to_vec();
         let mut buffer = Vec::new();
 
-        compress_data(&mut buffer, data.clone(), CompressionMethod::LZ4).await.unwrap();
+        compress_data(
+            &mut buffer,
+            data.clone(),
+            CompressionMethod::LZ4,
+            CompressionOptions::default(),
+        )
+        .await
+        .unwrap();
         assert!(!buffer.is_empty());
         assert!(buffer.len() >= 25); // 16 checksum + 9 header + payload
 
@@ -304,7 +323,14 @@ mod tests {
         let data = b"test data for ZSTD compression".to_vec();
         let mut buffer = Vec::new();
 
-        compress_data(&mut buffer, data.clone(), CompressionMethod::ZSTD).await.unwrap();
+        compress_data(
+            &mut buffer,
+            data.clone(),
+            CompressionMethod::ZSTD,
+            CompressionOptions::default(),
+        )
+        .await
+        .unwrap();
         assert!(!buffer.is_empty());
         assert!(buffer.len() >= 25); // 16 checksum + 9 header + payload
 
@@ -320,7 +346,14 @@ mod tests {
         let data = b"test data no compression".to_vec();
         let mut buffer = Vec::new();
 
-        compress_data(&mut buffer, data.clone(), CompressionMethod::None).await.unwrap();
+        compress_data(
+            &mut buffer,
+            data.clone(),
+            CompressionMethod::None,
+            CompressionOptions::default(),
+        )
+        .await
+        .unwrap();
         assert!(buffer.is_empty());
 
         // For None compression, the data should be in the same chunk format
@@ -335,7 +368,14 @@ mod tests {
 
         // First compress the data
         let mut buffer = Vec::new();
-        compress_data(&mut buffer, data.clone(), CompressionMethod::LZ4).await.unwrap();
+        compress_data(
+            &mut buffer,
+            data.clone(),
+            CompressionMethod::LZ4,
+            CompressionOptions::default(),
+        )
+        .await
+        .unwrap();
 
         // Then decompress it
         let mut reader = Cursor::new(buffer);
@@ -350,7 +390,14 @@ mod tests {
 
         // First compress the data
         let mut buffer = Vec::new();
-        compress_data(&mut buffer, data.clone(), CompressionMethod::ZSTD).await.unwrap();
+        compress_data(
+            &mut buffer,
+            data.clone(),
+            CompressionMethod::ZSTD,
+            CompressionOptions::default(),
+        )
+        .await
+        .unwrap();
 
         // Then decompress it
         let mut reader = Cursor::new(buffer);
@@ -366,7 +413,14 @@ mod tests {
 
         // Prepare compressed data
         let mut buffer = Vec::new();
-        compress_data(&mut buffer, data.clone(), CompressionMethod::LZ4).await.unwrap();
+        compress_data(
+            &mut buffer,
+            data.clone(),
+            CompressionMethod::LZ4,
+            CompressionOptions::default(),
+        )
+        .await
+        .unwrap();
 
         // Create decompression reader
         let mut reader = Cursor::new(buffer);
@@ -387,9 +441,14 @@ mod tests {
         for compression in [CompressionMethod::LZ4, CompressionMethod::ZSTD] {
             // Compress
             let mut compressed_buffer = Vec::new();
-            compress_data(&mut compressed_buffer, original_data.clone(), compression)
-                .await
-                .unwrap();
+            compress_data(
+                &mut compressed_buffer,
+                original_data.clone(),
+                compression,
+                CompressionOptions::default(),
+            )
+            .await
+            .unwrap();
 
             // Decompress
             let mut reader = Cursor::new(compressed_buffer);
@@ -405,7 +464,14 @@ mod tests {
 
         // Create properly compressed data
         let mut buffer = Vec::new();
-        compress_data(&mut buffer, data.clone(), CompressionMethod::LZ4).await.unwrap();
+        compress_data(
+            &mut buffer,
+            data.clone(),
+            CompressionMethod::LZ4,
+            CompressionOptions::default(),
+        )
+        .await
+        .unwrap();
 
         // Corrupt the checksum (first 8 bytes)
         buffer[0] ^= 0xFF;
@@ -417,4 +483,80 @@ mod tests {
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("Checksum mismatch"));
     }
+
+    #[tokio::test]
+    async fn test_round_trip_compression_levels() {
+        let original_data =
+            b"Data compressed at a configured level, repeated data repeated data".repeat(8);
+
+        for (compression, level) in [
+            (CompressionMethod::ZSTD, 19),
+            #[cfg(feature = "lz4hc")]
+            (CompressionMethod::LZ4HC, 12),
+            #[cfg(feature = "lz4hc")]
+            (CompressionMethod::LZ4HC, 1),
+        ] {
+            let mut compressed_buffer = Vec::new();
+            compress_data(
+                &mut compressed_buffer,
+                original_data.clone(),
+                compression,
+                CompressionOptions::new().with_level(level),
+            )
+            .await
+            .unwrap();
+            assert!(compressed_buffer.len() < original_data.len());
+
+            let mut reader = Cursor::new(compressed_buffer);
+            let decompressed = decompress_data_async(&mut reader, compression).await.unwrap();
+            assert_eq!(decompressed, original_data, "Round trip failed for {compression:?}");
+        }
+    }
+
+    #[cfg(feature = "lz4hc")]
+    #[tokio::test]
+    async fn test_lz4hc_decompresses_as_lz4() {
+        let data = b"LZ4HC writes regular LZ4 blocks".to_vec();
+
+        let mut buffer = Vec::new();
+        compress_data(
+            &mut buffer,
+            data.clone(),
+            CompressionMethod::LZ4HC,
+            CompressionOptions::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(buffer[16], CompressionMethod::LZ4.byte());
+
+        let mut reader = Cursor::new(buffer);
+        let decompressed =
+            decompress_data_async(&mut reader, CompressionMethod::LZ4).await.unwrap();
+        assert_eq!(decompressed, data);
+    }
+
+    #[tokio::test]
+    async fn test_max_block_size_splits_chunks() {
+        let data = (0..1000_u32).flat_map(u32::to_le_bytes).collect::<Vec<_>>();
+        let options = CompressionOptions::new().with_max_block_size(1024);
+
+        let mut buffer = Vec::new();
+        compress_data(&mut buffer, data.clone(), CompressionMethod::LZ4, options).await.unwrap();
+
+        // 4000 bytes in chunks of at most 1024 bytes
+        let mut reader = Cursor::new(buffer.clone());
+        let mut chunks = vec![];
+        while reader.position() < buffer.len() as u64 {
+            chunks.push(decompress_data_async(&mut reader, CompressionMethod::LZ4).await.unwrap());
+        }
+        assert_eq!(chunks.iter().map(Vec::len).collect::<Vec<_>>(), vec![1024, 1024, 1024, 928]);
+        assert_eq!(chunks.concat(), data);
+
+        let mut reader = Cursor::new(buffer);
+        let mut decompression_reader =
+            DecompressionReader::new(CompressionMethod::LZ4, &mut reader).await.unwrap();
+        let mut result = vec![0u8; data.len()];
+        let _ = decompression_reader.read_exact(&mut result).await.unwrap();
+        assert_eq!(result, data);
+    }
 }
@@ -15,6 +15,12 @@ pub(super) const TCP_CONNECT_TIMEOUT: u64 = 30;
 pub(super) const TCP_KEEP_ALIVE_SECS: u64 = 60;
 pub(super) const TCP_KEEP_ALIVE_INTERVAL: u64 = 10;
 pub(super) const TCP_KEEP_ALIVE_RETRIES: u32 = 6;
+// How long a replica that failed to connect is skipped
+pub(super) const REPLICA_RETRY_AFTER_SECS: u64 = 30;
+
+// Inserter flush thresholds, matching the server's `min_insert_block_size_rows/bytes` defaults
+pub(super) const INSERTER_MAX_ROWS: usize = 1_048_449;
+pub(super) const INSERTER_MAX_BYTES: usize = 256 * 1024 * 1024;
 
 // Maximum number of progress and profile statuses to keep in memory. New statuses evict old ones.
 pub(super) const EVENTS_CAPACITY: usize = 8;
@@ -28,11 +28,16 @@
 //! ```
 
 use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
 
 use arrow::record_batch::RecordBatch;
 
 use crate::limits::QueryLimits;
+use crate::native::protocol::{CompressionOptions, ServerLogLevel};
 use crate::query::{Qid, QueryParams};
+use crate::settings::{SettingValue, Settings};
+use crate::telemetry::TraceParent;
 
 /// Type of EXPLAIN operation to run.
 ///
@@ -401,6 +406,11 @@ impl ExplainEstimateRow {
 /// - Result limits (memory, rows, batches)
 /// - EXPLAIN execution
 /// - Query ID
+/// - Settings, applied on top of the client's settings
+/// - W3C trace context, continued by the server
+///
+/// Options are accepted by [`crate::Client::query_with_options`] as well as the insert, execute
+/// and native row entry points (`*_with_options`), which ignore limits and explain.
 ///
 /// # Example
 ///
@@ -417,18 +427,33 @@ impl ExplainEstimateRow {
 ///     .with_params(vec![("id", ParamValue::from(42))].into())
 ///     .with_limits(QueryLimits::none().with_max_rows(1000))
 ///     .with_explain(ExplainOptions::plan().with_json())
+///     .with_setting("max_execution_time", 30)
 ///     .with_qid(Qid::new());
 /// ```
 #[derive(Debug, Clone, Default)]
 pub struct QueryOptions {
     /// Query parameters for parameterized queries.
-    pub params:  Option<QueryParams>,
+    pub params:          Option<QueryParams>,
     /// Result limits (memory, rows, batches).
-    pub limits:  Option<QueryLimits>,
+    pub limits:          Option<QueryLimits>,
     /// EXPLAIN configuration.
-    pub explain: Option<ExplainOptions>,
+    pub explain:         Option<ExplainOptions>,
     /// Query ID for tracking and debugging.
-    pub qid:     Option<Qid>,
+    pub qid:             Option<Qid>,
+    /// Server log level for this query, overriding the client's `send_logs_level` setting.
+    pub send_logs_level: Option<ServerLogLevel>,
+    /// Settings for this query, applied on top of the client's settings.
+    pub settings:        Option<Settings>,
+    /// Compression level and block size of the data of an insert, overriding the client's.
+    pub compression:     Option<CompressionOptions>,
+    /// W3C trace context continued by the server, overriding the current `OpenTelemetry` one.
+    pub trace:           Option<TraceParent>,
+    /// Quota key the query is accounted to, overriding the HTTP client's.
+    pub quota_key:       Option<String>,
+    /// HTTP session of the query, overriding the HTTP client's. Native connections are sessions.
+    pub session_id:      Option<String>,
+    /// Timeout of the HTTP session of the query, overriding the HTTP client's.
+    pub session_timeout: Option<Duration>,
 }
 
 impl QueryOptions {
@@ -464,6 +489,76 @@ impl QueryOptions {
         self
     }
 
+    /// Set settings for this query, applied on top of the client's settings.
+    ///
+    /// Settings with the same name replace the client's, so a single client can serve queries
+    /// with different settings profiles (`max_execution_time`, `max_threads`, `async_insert`...).
+    #[must_use]
+    pub fn with_settings(mut self, settings: impl Into<Settings>) -> Self {
+        self.settings = Some(settings.into());
+        self
+    }
+
+    /// Set a single setting for this query, applied on top of the client's settings.
+    #[must_use]
+    pub fn with_setting(mut self, name: impl Into<String>, value: impl Into<SettingValue>) -> Self {
+        let value: SettingValue = value.into();
+        self.settings = Some(self.settings.unwrap_or_default().with_setting(name, value));
+        self
+    }
+
+    /// Set the server log level, delivered as [`crate::ClickHouseEvent::Log`] events.
+    #[must_use]
+    pub fn with_send_logs_level(mut self, level: ServerLogLevel) -> Self {
+        self.send_logs_level = Some(level);
+        self
+    }
+
+    /// Set the compression level and block size of the data sent by an insert.
+    ///
+    /// Only applies if the client compresses data, the compression method is the client's.
+    #[must_use]
+    pub fn with_compression(mut self, options: CompressionOptions) -> Self {
+        self.compression = Some(options);
+        self
+    }
+
+    /// Set the W3C trace context of the query, see [`TraceParent`].
+    ///
+    /// Without one, the current `OpenTelemetry` context is sent if the `opentelemetry` feature is
+    /// enabled.
+    #[must_use]
+    pub fn with_trace_parent(mut self, trace: TraceParent) -> Self {
+        self.trace = Some(trace);
+        self
+    }
+
+    /// Set the quota key the query is accounted to, see `quota_key` in the `ClickHouse` docs.
+    #[must_use]
+    pub fn with_quota_key(mut self, quota_key: impl Into<String>) -> Self {
+        self.quota_key = Some(quota_key.into());
+        self
+    }
+
+    /// Run the query in an HTTP session, sharing temporary tables and settings with the other
+    /// queries of the session.
+    ///
+    /// Only applies over HTTP, a native connection is a session already.
+    #[must_use]
+    pub fn with_session(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self
+    }
+
+    /// Set how long the HTTP session of the query is kept after its last query.
+    ///
+    /// Only applies over HTTP, with a session set on the query or the client.
+    #[must_use]
+    pub fn with_session_timeout(mut self, timeout: Duration) -> Self {
+        self.session_timeout = Some(timeout);
+        self
+    }
+
     /// Check if any options are set.
     #[must_use]
     pub fn has_options(&self) -> bool {
@@ -471,6 +566,29 @@ impl QueryOptions {
             || self.limits.is_some()
             || self.explain.is_some()
             || self.qid.is_some()
+            || self.send_logs_level.is_some()
+            || self.settings.is_some()
+            || self.compression.is_some()
+            || self.trace.is_some()
+            || self.quota_key.is_some()
+            || self.session_id.is_some()
+            || self.session_timeout.is_some()
+    }
+
+    /// Resolve the settings sent with the query, the client's `defaults` with these options'
+    /// overrides applied. The defaults are shared as is when nothing is overridden.
+    pub(crate) fn merge_settings(&self, defaults: Option<&Arc<Settings>>) -> Option<Arc<Settings>> {
+        if self.settings.is_none() && self.send_logs_level.is_none() {
+            return defaults.cloned();
+        }
+        let mut settings = defaults.map(|s| Settings::clone(s)).unwrap_or_default();
+        if let Some(overrides) = self.settings.as_ref() {
+            settings = settings.with_overrides(overrides);
+        }
+        if let Some(level) = self.send_logs_level {
+            settings.add_setting("send_logs_level", level);
+        }
+        Some(Arc::new(settings))
     }
 
     /// Check if explain is configured.
@@ -562,6 +680,28 @@ mod tests {
         assert!(explain_only.is_explain_only());
     }
 
+    #[test]
+    fn test_query_options_merge_settings() {
+        let defaults = Some(Arc::new(Settings::from([("max_threads", 8_i32)])));
+
+        let opts = QueryOptions::new();
+        let merged = opts.merge_settings(defaults.as_ref()).unwrap();
+        assert!(Arc::ptr_eq(&merged, defaults.as_ref().unwrap()));
+        assert!(opts.merge_settings(None).is_none());
+
+        let opts = QueryOptions::new()
+            .with_setting("max_threads", 2_i32)
+            .with_setting("max_execution_time", 30)
+            .with_send_logs_level(ServerLogLevel::Debug);
+        assert!(opts.has_options());
+        let merged = opts.merge_settings(defaults.as_ref()).unwrap();
+        assert_eq!(merged.encode_to_strings(), vec![
+            "max_threads = 2",
+            "max_execution_time = 30",
+            "send_logs_level = debug"
+        ]);
+    }
+
     #[test]
     fn test_explain_result_display() {
         let text = ExplainResult::Text("Expression\n  ReadFromStorage".to_string());
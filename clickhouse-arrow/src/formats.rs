@@ -7,13 +7,18 @@ pub use arrow::ArrowFormat;
 pub use native::NativeFormat;
 
 use crate::ArrowOptions;
+use crate::client::SummaryBlock;
 
 /// Trait for estimating the in-memory size of data.
 ///
-/// This is used by the load balancer to skip load balancing overhead for small inserts.
+/// This is used by the load balancer to skip load balancing overhead for small inserts, and by
+/// the [`crate::Inserter`] to decide when to flush.
 pub(crate) trait DataSize {
     /// Returns the estimated size of the data in bytes.
     fn data_size(&self) -> usize;
+
+    /// Returns the number of rows in the data.
+    fn num_rows(&self) -> usize;
 }
 
 /// Threshold for "small" inserts that skip load balancing (1MB).
@@ -28,7 +33,7 @@ pub(crate) const SMALL_INSERT_THRESHOLD: usize = 1024 * 1024;
 /// overhead and a fullblown serde implementation.
 #[expect(private_bounds)]
 pub trait ClientFormat: sealed::ClientFormatImpl<Self::Data> + Send + Sync + 'static {
-    type Data: std::fmt::Debug + Clone + Send + Sync + DataSize + 'static;
+    type Data: std::fmt::Debug + Clone + Send + Sync + DataSize + Into<SummaryBlock> + 'static;
 
     const FORMAT: &'static str;
 }
@@ -94,6 +99,8 @@ impl<T: Default> DeserializerState<T> {
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub(crate) struct SerializerState<T: Default = ()> {
     pub(crate) options:    Option<ArrowOptions>,
+    /// Negotiated protocol revision, selects the serialization version of `JSON` and `Dynamic`
+    pub(crate) revision:   u64,
     pub(crate) serializer: T,
 }
 
@@ -104,6 +111,12 @@ impl<T: Default> SerializerState<T> {
         self
     }
 
+    #[must_use]
+    pub(crate) fn with_revision(mut self, revision: u64) -> Self {
+        self.revision = revision;
+        self
+    }
+
     #[expect(unused)]
     #[must_use]
     pub(crate) fn serializer(&mut self) -> &mut T { &mut self.serializer }
@@ -8,6 +8,7 @@ use crate::arrow::ArrowDeserializerState;
 use crate::compression::{DecompressionReader, compress_data_pooled};
 use crate::connection::ClientMetadata;
 use crate::io::{ClickHouseRead, ClickHouseWrite};
+use crate::metrics::record_compression;
 use crate::native::protocol::CompressionMethod;
 use crate::prelude::*;
 use crate::simd::PooledBuffer;
@@ -15,6 +16,9 @@ use crate::simd::PooledBuffer;
 impl DataSize for RecordBatch {
     #[inline]
     fn data_size(&self) -> usize { self.get_array_memory_size() }
+
+    #[inline]
+    fn num_rows(&self) -> usize { RecordBatch::num_rows(self) }
 }
 
 /// Marker trait for Arrow format.
@@ -59,7 +63,13 @@ impl super::sealed::ClientFormatImpl<RecordBatch> for ArrowFormat {
     ) -> Result<()> {
         if let CompressionMethod::None = metadata.compression {
             batch
-                .write_async(writer, revision, header, metadata.arrow_options)
+                .write_async(
+                    writer,
+                    revision,
+                    header,
+                    metadata.arrow_options,
+                    metadata.sparse_ratio,
+                )
                 .instrument(trace_span!("serialize_block"))
                 .await
                 .inspect_err(|error| error!(?error, { ATT_QID } = %qid, "serialize"))?;
@@ -69,11 +79,24 @@ impl super::sealed::ClientFormatImpl<RecordBatch> for ArrowFormat {
             let capacity_hint = batch.get_array_memory_size();
             let mut raw = PooledBuffer::with_capacity(capacity_hint);
             batch
-                .write(raw.buffer_mut(), revision, header, metadata.arrow_options)
+                .write(
+                    raw.buffer_mut(),
+                    revision,
+                    header,
+                    metadata.arrow_options,
+                    metadata.sparse_ratio,
+                )
                 .inspect_err(|error| error!(?error, { ATT_QID } = %qid, "serialize"))?;
-            compress_data_pooled(writer, raw, metadata.compression)
-                .await
-                .inspect_err(|error| error!(?error, { ATT_QID } = %qid, "compressing"))?;
+            let raw_len = raw.len();
+            let compressed_len = compress_data_pooled(
+                writer,
+                raw,
+                metadata.compression,
+                metadata.compression_options,
+            )
+            .await
+            .inspect_err(|error| error!(?error, { ATT_QID } = %qid, "compressing"))?;
+            record_compression(metadata.client_id, metadata.compression, raw_len, compressed_len);
         }
 
         Ok(())
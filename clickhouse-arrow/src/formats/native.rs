@@ -6,6 +6,7 @@ use crate::Type;
 use crate::client::connection::ClientMetadata;
 use crate::compression::{DecompressionReader, compress_data_sync};
 use crate::io::{ClickHouseRead, ClickHouseWrite};
+use crate::metrics::record_compression;
 use crate::native::block::Block;
 use crate::native::protocol::CompressionMethod;
 use crate::prelude::*;
@@ -13,6 +14,9 @@ use crate::prelude::*;
 impl DataSize for Block {
     #[inline]
     fn data_size(&self) -> usize { self.estimate_size() }
+
+    #[inline]
+    fn num_rows(&self) -> usize { usize::try_from(self.rows).unwrap_or(usize::MAX) }
 }
 
 /// Marker for Native format.
@@ -56,7 +60,7 @@ impl super::sealed::ClientFormatImpl<Block> for NativeFormat {
         metadata: ClientMetadata,
     ) -> Result<()> {
         if let CompressionMethod::None = metadata.compression {
-            data.write_async(writer, revision, header, ())
+            data.write_async(writer, revision, header, (), metadata.sparse_ratio)
                 .instrument(trace_span!("serialize_block"))
                 .await
                 .inspect_err(|error| error!(?error, { ATT_QID } = %qid, "(block:uncompressed)"))
@@ -65,13 +69,21 @@ impl super::sealed::ClientFormatImpl<Block> for NativeFormat {
             let estimated_size = data.estimate_size();
             let mut buffer = BytesMut::with_capacity(estimated_size);
 
-            data.write(&mut buffer, revision, header, ())
+            data.write(&mut buffer, revision, header, (), metadata.sparse_ratio)
                 .inspect_err(|error| error!(?error, {ATT_QID} = %qid, "(block:compressed)"))?;
 
-            compress_data_sync(writer, buffer.freeze(), metadata.compression)
-                .instrument(trace_span!("compress_block"))
-                .await
-                .inspect_err(|error| error!(?error, {ATT_QID} = %qid, "compressing"))
+            let raw_len = buffer.len();
+            let compressed_len = compress_data_sync(
+                writer,
+                buffer.freeze(),
+                metadata.compression,
+                metadata.compression_options,
+            )
+            .instrument(trace_span!("compress_block"))
+            .await
+            .inspect_err(|error| error!(?error, {ATT_QID} = %qid, "compressing"))?;
+            record_compression(metadata.client_id, metadata.compression, raw_len, compressed_len);
+            Ok(())
         }
     }
 }
@@ -20,6 +20,8 @@ pub(crate) trait ProtocolData<Return, Deser: Default> {
     /// # Arguments
     /// - `writer`: The async writer to serialize the block to (e.g., a TCP stream).
     /// - `header`: Optional column name and type mappings for type disambiguation.
+    /// - `sparse_ratio`: Minimum ratio of default values for a column to be written with sparse
+    ///   serialization, `None` to always write columns in full.
     ///
     /// # Returns
     /// A `Future` resolving to a `Result` indicating success or a `Error` if
@@ -30,6 +32,7 @@ pub(crate) trait ProtocolData<Return, Deser: Default> {
         revision: u64,
         header: Option<&[(String, Type)]>,
         options: Self::Options,
+        sparse_ratio: Option<f64>,
     ) -> impl Future<Output = Result<()>> + Send;
 
     fn write<W: ClickHouseBytesWrite>(
@@ -38,6 +41,7 @@ pub(crate) trait ProtocolData<Return, Deser: Default> {
         _revision: u64,
         _header: Option<&[(String, Type)]>,
         _options: Self::Options,
+        _sparse_ratio: Option<f64>,
     ) -> Result<()>
     where
         Self: Sized;
@@ -1,57 +1,155 @@
 //! `ArrowStream` IPC format helpers.
 //!
-//! Uses the `arrow-ipc` crate to serialize and deserialize Arrow data
-//! in `ClickHouse`'s `ArrowStream` format.
+//! Uses the `arrow-ipc` crate to encode and decode Arrow data in `ClickHouse`'s `ArrowStream`
+//! format incrementally, one IPC message at a time, so only a single batch is held in memory
+//! while streaming a request or response body.
 
-use std::io::Cursor;
+use std::collections::VecDeque;
 
 use arrow::array::RecordBatch;
-use arrow::ipc::reader::StreamReader;
+use arrow::buffer::Buffer;
+use arrow::ipc::reader::StreamDecoder;
 use arrow::ipc::writer::StreamWriter;
 use bytes::Bytes;
+use futures_util::{Stream, StreamExt, stream};
 
+use super::response::parse_exception;
 use crate::Error;
 use crate::errors::Result;
 
-/// Serialize a `RecordBatch` to `ArrowStream` IPC format.
-pub(super) fn serialize_batch(batch: &RecordBatch) -> Result<Bytes> {
-    let schema = batch.schema();
-    let mut buffer = Vec::with_capacity(batch.get_array_memory_size());
-
-    let mut writer = StreamWriter::try_new(&mut buffer, &schema)
-        .map_err(|e| Error::ArrowSerialize(format!("Failed to create ArrowStream writer: {e}")))?;
+/// Trailing bytes of a response body searched for an exception that ended the stream early.
+const EXCEPTION_TAIL: usize = 64 * 1024;
+
+/// Encode `RecordBatch`es to `ArrowStream` IPC format, yielding the bytes of each batch.
+///
+/// The schema is taken from the first batch. An empty stream encodes to no bytes at all.
+pub(super) fn encode_batches<S>(batches: S) -> impl Stream<Item = Result<Bytes>> + Send + 'static
+where
+    S: Stream<Item = Result<RecordBatch>> + Send + 'static,
+{
+    let state = (Box::pin(batches), None::<StreamWriter<Vec<u8>>>, false);
+    stream::try_unfold(state, |(mut batches, writer, finished)| async move {
+        if finished {
+            return Ok(None);
+        }
+
+        let Some(batch) = batches.next().await.transpose()? else {
+            // End of the batches, write the end of stream marker
+            let Some(writer) = writer else { return Ok(None) };
+            let buffer = writer
+                .into_inner()
+                .map_err(|e| Error::ArrowSerialize(format!("Failed to finish ArrowStream: {e}")))?;
+            return Ok(Some((Bytes::from(buffer), (batches, None, true))));
+        };
+
+        let mut writer = if let Some(writer) = writer {
+            writer
+        } else {
+            let buffer = Vec::with_capacity(batch.get_array_memory_size());
+            StreamWriter::try_new(buffer, &batch.schema()).map_err(|e| {
+                Error::ArrowSerialize(format!("Failed to create ArrowStream writer: {e}"))
+            })?
+        };
+        writer.write(&batch).map_err(|e| {
+            Error::ArrowSerialize(format!("Failed to write batch to ArrowStream: {e}"))
+        })?;
 
-    writer
-        .write(batch)
-        .map_err(|e| Error::ArrowSerialize(format!("Failed to write batch to ArrowStream: {e}")))?;
+        // Hand over the encoded batch, the writer keeps an empty buffer for the next one
+        let buffer = std::mem::take(writer.get_mut());
+        Ok(Some((Bytes::from(buffer), (batches, Some(writer), false))))
+    })
+}
 
-    writer
-        .finish()
-        .map_err(|e| Error::ArrowSerialize(format!("Failed to finish ArrowStream: {e}")))?;
+/// Decode `ArrowStream` IPC format to `RecordBatch`es as the bytes arrive.
+///
+/// Chunks may split IPC messages at any offset. An empty body decodes to no batches. An exception
+/// raised after the response started is written in place of the rest of the stream, it is
+/// returned as [`Error::ServerException`] rather than a decoding error.
+pub(super) fn decode_batches<S>(
+    body: S,
+) -> impl Stream<Item = Result<RecordBatch>> + Send + Unpin + 'static
+where
+    S: Stream<Item = Result<Bytes>> + Send + 'static,
+{
+    let state =
+        (Box::pin(body), StreamDecoder::new(), Buffer::from(Vec::<u8>::new()), BodyTail::default());
+    Box::pin(stream::try_unfold(
+        state,
+        |(mut body, mut decoder, mut buffer, mut tail)| async move {
+            loop {
+                if buffer.is_empty() {
+                    let Some(chunk) = body.next().await.transpose()? else {
+                        if let Err(e) = decoder.finish() {
+                            let error =
+                                Error::ArrowDeserialize(format!("Truncated ArrowStream: {e}"));
+                            return Err(tail.into_exception(body, error).await);
+                        }
+                        return Ok(None);
+                    };
+                    tail.push(chunk.clone());
+                    buffer = Buffer::from(chunk);
+                    continue;
+                }
+
+                match decoder.decode(&mut buffer) {
+                    Ok(Some(batch)) => return Ok(Some((batch, (body, decoder, buffer, tail)))),
+                    Ok(None) => {}
+                    Err(e) => {
+                        let error = Error::ArrowDeserialize(format!(
+                            "Failed to read batch from ArrowStream: {e}"
+                        ));
+                        return Err(tail.into_exception(body, error).await);
+                    }
+                }
+            }
+        },
+    ))
+}
 
-    Ok(Bytes::from(buffer))
+/// The most recent chunks of a response body, at least the last [`EXCEPTION_TAIL`] bytes.
+#[derive(Default)]
+struct BodyTail {
+    chunks: VecDeque<Bytes>,
+    len:    usize,
 }
 
-/// Deserialize `ArrowStream` IPC format to `RecordBatch`es.
-pub(super) fn deserialize_batches(data: Bytes) -> Result<Vec<RecordBatch>> {
-    if data.is_empty() {
-        return Ok(Vec::new());
+impl BodyTail {
+    fn push(&mut self, chunk: Bytes) {
+        self.len += chunk.len();
+        self.chunks.push_back(chunk);
+        while let Some(front) = self.chunks.front()
+            && self.len - front.len() >= EXCEPTION_TAIL
+        {
+            self.len -= front.len();
+            drop(self.chunks.pop_front());
+        }
     }
 
-    let cursor = Cursor::new(data);
-    let reader = StreamReader::try_new(cursor, None).map_err(|e| {
-        Error::ArrowDeserialize(format!("Failed to create ArrowStream reader: {e}"))
-    })?;
-
-    let mut batches = Vec::new();
-    for batch_result in reader {
-        let batch = batch_result.map_err(|e| {
-            Error::ArrowDeserialize(format!("Failed to read batch from ArrowStream: {e}"))
-        })?;
-        batches.push(batch);
+    /// Find the exception `ClickHouse` wrote after the last complete batch, reading what is left
+    /// of the body. Returns `error` if there is none.
+    async fn into_exception<S>(mut self, mut body: S, error: Error) -> Error
+    where
+        S: Stream<Item = Result<Bytes>> + Unpin,
+    {
+        let mut read = 0;
+        while read < EXCEPTION_TAIL {
+            let Some(Ok(chunk)) = body.next().await else { break };
+            read += chunk.len();
+            self.push(chunk);
+        }
+
+        // Code: <code>. DB::Exception: <message>, possibly followed by an `__exception__` marker
+        let tail = self.chunks.into_iter().collect::<Vec<_>>().concat();
+        let exception = (0..tail.len())
+            .filter(|&i| tail[i..].starts_with(b"Code: "))
+            .map(|i| String::from_utf8_lossy(&tail[i..]))
+            .find(|text| {
+                text[6..].trim_start_matches(|c: char| c.is_ascii_digit()).starts_with(". DB::")
+            });
+        let Some(text) = exception else { return error };
+        let text = text.split("__exception__").next().unwrap_or_default();
+        parse_exception(None, text).map_or(error, Into::into)
     }
-
-    Ok(batches)
 }
 
 #[cfg(test)]
@@ -60,9 +158,32 @@ mod tests {
 
     use arrow::array::{Array, Float64Array, Int32Array, Int64Array, StringArray};
     use arrow::datatypes::{DataType, Field, Schema};
+    use futures_util::TryStreamExt;
 
     use super::*;
 
+    async fn serialize_batches(batches: Vec<RecordBatch>) -> Result<Bytes> {
+        let chunks: Vec<Bytes> =
+            encode_batches(stream::iter(batches.into_iter().map(Ok))).try_collect().await?;
+        Ok(Bytes::from(chunks.concat()))
+    }
+
+    async fn serialize_batch(batch: &RecordBatch) -> Result<Bytes> {
+        serialize_batches(vec![batch.clone()]).await
+    }
+
+    /// Decode `data` as if it was received in chunks of `chunk_size` bytes.
+    async fn deserialize_chunked(data: Bytes, chunk_size: usize) -> Result<Vec<RecordBatch>> {
+        let chunks =
+            data.chunks(chunk_size).map(|c| Ok(Bytes::copy_from_slice(c))).collect::<Vec<_>>();
+        decode_batches(stream::iter(chunks)).try_collect().await
+    }
+
+    async fn deserialize_batches(data: Bytes) -> Result<Vec<RecordBatch>> {
+        let chunk_size = data.len().max(1);
+        deserialize_chunked(data, chunk_size).await
+    }
+
     fn create_test_schema() -> Arc<Schema> {
         Arc::new(Schema::new(vec![
             Field::new("id", DataType::Int64, false),
@@ -78,31 +199,31 @@ mod tests {
         RecordBatch::try_new(schema, vec![Arc::new(id_array), Arc::new(name_array)]).unwrap()
     }
 
-    #[test]
-    fn test_round_trip() {
+    #[tokio::test]
+    async fn test_round_trip() {
         let original = create_test_batch();
-        let serialized = serialize_batch(&original).unwrap();
-        let deserialized = deserialize_batches(serialized).unwrap();
+        let serialized = serialize_batch(&original).await.unwrap();
+        let deserialized = deserialize_batches(serialized).await.unwrap();
 
         assert_eq!(deserialized.len(), 1);
         assert_eq!(deserialized[0], original);
     }
 
-    #[test]
-    fn test_empty_data() {
-        let result = deserialize_batches(Bytes::new()).unwrap();
+    #[tokio::test]
+    async fn test_empty_data() {
+        let result = deserialize_batches(Bytes::new()).await.unwrap();
         assert!(result.is_empty());
     }
 
-    #[test]
-    fn test_nullable_values() {
+    #[tokio::test]
+    async fn test_nullable_values() {
         let schema = Arc::new(Schema::new(vec![Field::new("value", DataType::Utf8, true)]));
 
         let array = StringArray::from(vec![Some("a"), None, Some("c"), None, None]);
         let batch = RecordBatch::try_new(schema, vec![Arc::new(array)]).unwrap();
 
-        let serialized = serialize_batch(&batch).unwrap();
-        let deserialized = deserialize_batches(serialized).unwrap();
+        let serialized = serialize_batch(&batch).await.unwrap();
+        let deserialized = deserialize_batches(serialized).await.unwrap();
 
         assert_eq!(deserialized.len(), 1);
         assert_eq!(deserialized[0].num_rows(), 5);
@@ -115,8 +236,8 @@ mod tests {
         assert!(result_col.is_null(4));
     }
 
-    #[test]
-    fn test_various_types() {
+    #[tokio::test]
+    async fn test_various_types() {
         let schema = Arc::new(Schema::new(vec![
             Field::new("int32_col", DataType::Int32, false),
             Field::new("int64_col", DataType::Int64, false),
@@ -132,17 +253,17 @@ mod tests {
         ])
         .unwrap();
 
-        let serialized = serialize_batch(&batch).unwrap();
-        let deserialized = deserialize_batches(serialized).unwrap();
+        let serialized = serialize_batch(&batch).await.unwrap();
+        let deserialized = deserialize_batches(serialized).await.unwrap();
 
         assert_eq!(deserialized.len(), 1);
         assert_eq!(deserialized[0].num_rows(), 3);
         assert_eq!(deserialized[0].num_columns(), 4);
     }
 
-    #[test]
+    #[tokio::test]
     #[allow(clippy::cast_possible_wrap, clippy::cast_precision_loss)]
-    fn test_large_batch() {
+    async fn test_large_batch() {
         const ROW_COUNT: usize = 10_000;
 
         let schema = Arc::new(Schema::new(vec![
@@ -159,20 +280,112 @@ mod tests {
         ])
         .unwrap();
 
-        let serialized = serialize_batch(&batch).unwrap();
+        let serialized = serialize_batch(&batch).await.unwrap();
         assert!(!serialized.is_empty());
 
-        let deserialized = deserialize_batches(serialized).unwrap();
+        let deserialized = deserialize_batches(serialized).await.unwrap();
         assert_eq!(deserialized.len(), 1);
         assert_eq!(deserialized[0].num_rows(), ROW_COUNT);
     }
 
-    #[test]
-    fn test_serialized_bytes_not_empty() {
+    #[tokio::test]
+    async fn test_serialized_bytes_not_empty() {
         let batch = create_test_batch();
-        let serialized = serialize_batch(&batch).unwrap();
+        let serialized = serialize_batch(&batch).await.unwrap();
 
         // ArrowStream format should produce non-trivial output
         assert!(serialized.len() > 50, "Serialized data too small: {} bytes", serialized.len());
     }
+
+    #[tokio::test]
+    async fn test_multiple_batches_split_chunks() {
+        let batches = vec![create_test_batch(), create_test_batch(), create_test_batch()];
+        let serialized = serialize_batches(batches.clone()).await.unwrap();
+
+        // Messages split at arbitrary offsets are reassembled
+        for chunk_size in [1, 7, 64, serialized.len()] {
+            let deserialized = deserialize_chunked(serialized.clone(), chunk_size).await.unwrap();
+            assert_eq!(deserialized, batches, "chunk size {chunk_size}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_encode_one_chunk_per_batch() {
+        let batches = vec![create_test_batch(), create_test_batch()];
+        let chunks: Vec<Bytes> =
+            encode_batches(stream::iter(batches.into_iter().map(Ok))).try_collect().await.unwrap();
+
+        // Schema and first batch, second batch, end of stream marker
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks.iter().all(|chunk| !chunk.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn test_encode_empty_stream() {
+        let chunks: Vec<Bytes> = encode_batches(stream::iter(Vec::<Result<RecordBatch>>::new()))
+            .try_collect()
+            .await
+            .unwrap();
+        assert!(chunks.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_truncated_stream() {
+        let serialized = serialize_batch(&create_test_batch()).await.unwrap();
+        let truncated = serialized.slice(..serialized.len() / 2);
+
+        let result = deserialize_chunked(truncated, 16).await;
+        assert!(matches!(result, Err(Error::ArrowDeserialize(_))));
+    }
+
+    #[tokio::test]
+    async fn test_body_error_ends_stream() {
+        let serialized = serialize_batch(&create_test_batch()).await.unwrap();
+        let body = stream::iter(vec![
+            Ok(serialized.slice(..10)),
+            Err(Error::Network("connection reset".into())),
+            Ok(serialized.slice(10..)),
+        ]);
+
+        let results = decode_batches(body).collect::<Vec<_>>().await;
+        assert_eq!(results.len(), 1);
+        assert!(matches!(results[0], Err(Error::Network(_))));
+    }
+
+    #[tokio::test]
+    async fn test_exception_after_batches() {
+        // The batches sent before the exception, without the end of stream marker
+        let batch = create_test_batch();
+        let mut chunks: Vec<Bytes> =
+            encode_batches(stream::iter(vec![Ok(batch.clone())])).try_collect().await.unwrap();
+        drop(chunks.pop());
+        let mut body = chunks.concat();
+        body.extend_from_slice(
+            b"Code: 241. DB::Exception: Memory limit (total) exceeded. (MEMORY_LIMIT_EXCEEDED) \
+              (version 25.3.1.2703 (official build))\n",
+        );
+
+        for chunk_size in [1, 7, 64, body.len()] {
+            let chunks =
+                body.chunks(chunk_size).map(|c| Ok(Bytes::copy_from_slice(c))).collect::<Vec<_>>();
+            let results = decode_batches(stream::iter(chunks)).collect::<Vec<_>>().await;
+            assert_eq!(results.len(), 2, "chunk size {chunk_size}");
+            assert_eq!(results[0].as_ref().unwrap(), &batch);
+            let Err(Error::ServerException(error)) = &results[1] else {
+                panic!("expected a server exception, got {:?}", results[1]);
+            };
+            assert_eq!(error.code, 241);
+            assert_eq!(error.message, "Memory limit (total) exceeded. (MEMORY_LIMIT_EXCEEDED)");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_exception_with_marker() {
+        let body = Bytes::from_static(
+            b"__exception__\r\nCode: 159. DB::Exception: Timeout exceeded. (TIMEOUT_EXCEEDED)\r\n\
+              __exception__\r\n",
+        );
+        let result = deserialize_batches(body).await;
+        assert!(matches!(result, Err(Error::ServerException(e)) if e.code == 159));
+    }
 }
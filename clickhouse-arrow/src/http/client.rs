@@ -4,15 +4,32 @@
 //! your network team insists on HTTP-only egress. Native protocol is faster
 //! and more CPU-efficient at both ends.
 
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+
 use arrow::array::RecordBatch;
-use bytes::Bytes;
+use arrow::datatypes::SchemaRef;
+use futures_util::{Stream, StreamExt, TryStreamExt, stream};
 use reqwest::header::{CONTENT_TYPE, HeaderMap, HeaderValue};
+use tokio::sync::broadcast;
 use tracing::{Instrument, debug, instrument, trace_span};
 
-use super::arrow_stream::{deserialize_batches, serialize_batch};
+use super::arrow_stream::{decode_batches, encode_batches};
 use super::config::HttpOptions;
-use crate::Error;
+use super::response::{
+    EXCEPTION_CODE_HEADER, PROGRESS_HEADER, SUMMARY_HEADER, parse_exception, parse_progress,
+    progress_delta,
+};
+use crate::arrow::schema::{collect_schemas, schema_query};
+use crate::client::CLIENT_ID;
+use crate::constants::EVENTS_CAPACITY;
 use crate::errors::Result;
+use crate::explain::QueryOptions;
+use crate::query::{Qid, QueryParams};
+use crate::settings::Settings;
+use crate::telemetry::TraceParent;
+use crate::{ArrowOptions, ClickHouseEvent, Error, Event, Progress};
 
 /// HTTP client using ClickHouse's ArrowStream format.
 ///
@@ -20,8 +37,12 @@ use crate::errors::Result;
 /// Simpler but slightly higher latency than native protocol.
 #[derive(Debug, Clone)]
 pub struct HttpClient {
-    client:  reqwest::Client,
-    options: HttpOptions,
+    client:    reqwest::Client,
+    options:   HttpOptions,
+    /// The settings of `options`, shared with the per-request settings they are merged into.
+    settings:  Option<Arc<Settings>>,
+    client_id: u16,
+    events:    Arc<broadcast::Sender<Event>>,
 }
 
 impl HttpClient {
@@ -37,11 +58,68 @@ impl HttpClient {
             .build()
             .map_err(|e| Error::Configuration(format!("Failed to build HTTP client: {e}")))?;
 
-        Ok(Self { client, options })
+        let settings = options.settings.clone().map(Arc::new);
+        let client_id = CLIENT_ID.fetch_add(1, Ordering::Relaxed);
+        let (event_tx, _) = broadcast::channel(EVENTS_CAPACITY);
+        Ok(Self { client, options, settings, client_id, events: Arc::new(event_tx) })
+    }
+
+    /// Subscribe to the progress of requests, as [`ClickHouseEvent::Progress`] events.
+    ///
+    /// Progress is read from the `X-ClickHouse-Progress` headers, sent when the
+    /// `send_progress_in_http_headers` setting is enabled, and the `X-ClickHouse-Summary` header.
+    /// Like the native protocol, each event is the progress made since the previous one. Headers
+    /// are only received before the response body, so the summary of a streamed query may not
+    /// cover the whole query, unless the `wait_end_of_query` setting is enabled.
+    pub fn subscribe_events(&self) -> broadcast::Receiver<Event> { self.events.subscribe() }
+
+    /// Emit the progress reported by the headers of a response.
+    fn emit_progress(&self, qid: Qid, headers: &HeaderMap) {
+        let mut reported = Progress::default();
+        let summary = headers.get(SUMMARY_HEADER);
+        for value in headers.get_all(PROGRESS_HEADER).iter().chain(summary) {
+            let Some(progress) = value.to_str().ok().and_then(parse_progress) else {
+                continue;
+            };
+            let delta = progress_delta(progress, reported);
+            reported = progress;
+            if delta != Progress::default() {
+                let event = ClickHouseEvent::Progress(delta);
+                let _ = self.events.send(Event { event, qid, client_id: self.client_id }).ok();
+            }
+        }
+    }
+
+    /// Check the status of a response, emitting its progress and turning failures into errors.
+    ///
+    /// `ClickHouse` exceptions become [`Error::ServerException`], like over the native protocol.
+    async fn check_response(
+        &self,
+        qid: Qid,
+        response: reqwest::Response,
+    ) -> Result<reqwest::Response> {
+        self.emit_progress(qid, response.headers());
+
+        let status = response.status();
+        if !status.is_success() {
+            let code = response
+                .headers()
+                .get(EXCEPTION_CODE_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse().ok());
+            let body = response.text().await.unwrap_or_default();
+            if let Some(error) = parse_exception(code, &body) {
+                return Err(error.into());
+            }
+            return Err(Error::Server(format!("HTTP {status}: {body}")));
+        }
+        Ok(response)
     }
 
-    /// Build default headers for requests.
-    fn default_headers(&self) -> HeaderMap {
+    /// Build the headers of a request: credentials, database, quota key and trace context.
+    ///
+    /// The quota key of `options` overrides the client's.
+    fn headers(&self, options: &QueryOptions) -> HeaderMap {
         let mut headers = HeaderMap::new();
 
         if let Some(ref user) = self.options.user
@@ -62,27 +140,113 @@ impl HttpClient {
             drop(headers.insert("X-ClickHouse-Database", value));
         }
 
+        if let Some(quota_key) = options.quota_key.as_ref().or(self.options.quota_key.as_ref())
+            && let Ok(value) = HeaderValue::from_str(quota_key)
+        {
+            drop(headers.insert("X-ClickHouse-Quota", value));
+        }
+
+        if let Some(trace) = TraceParent::resolve(options.trace.as_ref()) {
+            if let Ok(value) = HeaderValue::from_str(&trace.to_string()) {
+                drop(headers.insert("traceparent", value));
+            }
+            if !trace.tracestate.is_empty()
+                && let Ok(value) = HeaderValue::from_str(&trace.tracestate)
+            {
+                drop(headers.insert("tracestate", value));
+            }
+        }
+
         headers
     }
 
-    /// Build the query URL with the given SQL and format.
-    fn build_query_url(&self, sql: &str, format: &str) -> url::Url {
+    /// Build the URL of a request: the SQL, query ID, session, settings and parameters.
+    ///
+    /// Settings of `options` are applied on top of the client's, parameters are sent as
+    /// `param_<name>` in `ClickHouse`'s text format, as the native protocol does. The session of
+    /// `options` overrides the client's.
+    fn build_url(&self, sql: &str, options: &QueryOptions) -> url::Url {
         let mut url = self.options.url.clone();
+        {
+            let mut pairs = url.query_pairs_mut();
+            let _ = pairs.append_pair("query", sql);
+
+            if let Some(qid) = options.qid {
+                let _ = pairs.append_pair("query_id", &qid.to_string());
+            }
+
+            if let Some(session_id) =
+                options.session_id.as_ref().or(self.options.session_id.as_ref())
+            {
+                let _ = pairs.append_pair("session_id", session_id);
+                if let Some(timeout) = options.session_timeout.or(self.options.session_timeout) {
+                    let _ = pairs.append_pair("session_timeout", &timeout.as_secs().to_string());
+                }
+            }
+
+            if let Some(settings) = options.merge_settings(self.settings.as_ref()) {
+                for (name, value) in settings.encode_to_key_value_strings() {
+                    let _ = pairs.append_pair(&name, &value);
+                }
+            }
+
+            if let Some(ref params) = options.params {
+                for (name, value) in &params.0 {
+                    let _ = pairs.append_pair(&format!("param_{name}"), &value.to_string());
+                }
+            }
+        }
+        url
+    }
 
-        // Append FORMAT to the query
-        let query_with_format = format!("{sql} FORMAT {format}");
-
-        let _ = url.query_pairs_mut().append_pair("query", &query_with_format);
+    /// Execute SELECT query, returns a stream of Arrow RecordBatches.
+    ///
+    /// Batches are decoded as the response body arrives, so only about one batch is held in
+    /// memory at a time. Errors reported by the server before the first byte of data fail the
+    /// call itself, errors while streaming are yielded by the stream.
+    ///
+    /// # Errors
+    /// Returns an error if the request fails or the server rejects the query.
+    pub async fn query(
+        &self,
+        sql: &str,
+    ) -> Result<impl Stream<Item = Result<RecordBatch>> + Send + Unpin + 'static> {
+        self.query_with_options(sql, QueryOptions::new()).await
+    }
 
-        url
+    /// Execute SELECT query with parameters, returns a stream of Arrow RecordBatches.
+    ///
+    /// See [`HttpClient::query`].
+    ///
+    /// # Errors
+    /// Returns an error if the request fails or the server rejects the query.
+    pub async fn query_params(
+        &self,
+        sql: &str,
+        params: Option<QueryParams>,
+        qid: Option<Qid>,
+    ) -> Result<impl Stream<Item = Result<RecordBatch>> + Send + Unpin + 'static> {
+        let options = QueryOptions { params, qid, ..Default::default() };
+        self.query_with_options(sql, options).await
     }
 
-    /// Execute SELECT query, returns Arrow RecordBatches.
-    #[must_use = "query results should be used"]
-    #[instrument(skip(self), fields(sql = %sql))]
-    pub async fn query(&self, sql: &str) -> Result<Vec<RecordBatch>> {
-        let url = self.build_query_url(sql, "ArrowStream");
-        let headers = self.default_headers();
+    /// Execute SELECT query with per-request options, returns a stream of Arrow RecordBatches.
+    ///
+    /// Parameters, query ID, settings and trace context of `options` are sent with the request, a
+    /// query ID is generated if not set. Limits, explain and compression options only apply to the
+    /// native protocol and are ignored.
+    ///
+    /// # Errors
+    /// Returns an error if the request fails or the server rejects the query.
+    #[instrument(skip(self, options), fields(sql = %sql))]
+    pub async fn query_with_options(
+        &self,
+        sql: &str,
+        mut options: QueryOptions,
+    ) -> Result<impl Stream<Item = Result<RecordBatch>> + Send + Unpin + 'static> {
+        let qid = *options.qid.get_or_insert_default();
+        let url = self.build_url(&format!("{sql} FORMAT ArrowStream"), &options);
+        let headers = self.headers(&options);
 
         debug!(url = %url, "Executing HTTP query");
 
@@ -95,16 +259,43 @@ impl HttpClient {
             .await
             .map_err(|e| Error::Network(e.to_string()))?;
 
-        self.handle_response(response).await
+        let response = self.check_response(qid, response).await?;
+        let body = response
+            .bytes_stream()
+            .map_err(|e| Error::Network(format!("Failed to read response body: {e}")));
+        Ok(decode_batches(body))
     }
 
     /// Execute DDL or non-returning query (CREATE, DROP, ALTER, etc).
-    #[instrument(skip(self), fields(sql = %sql))]
     pub async fn execute(&self, sql: &str) -> Result<()> {
-        let mut url = self.options.url.clone();
-        let _ = url.query_pairs_mut().append_pair("query", sql);
+        self.execute_with_options(sql, QueryOptions::new()).await
+    }
 
-        let headers = self.default_headers();
+    /// Execute DDL or non-returning query with parameters.
+    ///
+    /// # Errors
+    /// Returns an error if the request fails or the server rejects the query.
+    pub async fn execute_params(
+        &self,
+        sql: &str,
+        params: Option<QueryParams>,
+        qid: Option<Qid>,
+    ) -> Result<()> {
+        let options = QueryOptions { params, qid, ..Default::default() };
+        self.execute_with_options(sql, options).await
+    }
+
+    /// Execute DDL or non-returning query with per-request options.
+    ///
+    /// See [`HttpClient::query_with_options`] for the options that apply.
+    ///
+    /// # Errors
+    /// Returns an error if the request fails or the server rejects the query.
+    #[instrument(skip(self, options), fields(sql = %sql))]
+    pub async fn execute_with_options(&self, sql: &str, mut options: QueryOptions) -> Result<()> {
+        let qid = *options.qid.get_or_insert_default();
+        let url = self.build_url(sql, &options);
+        let headers = self.headers(&options);
 
         debug!(url = %url, "Executing HTTP DDL");
 
@@ -117,128 +308,236 @@ impl HttpClient {
             .await
             .map_err(|e| Error::Network(e.to_string()))?;
 
-        let status = response.status();
-        if !status.is_success() {
-            let body = response.text().await.unwrap_or_default();
-            return Err(Error::Server(format!("HTTP {status}: {body}")));
-        }
-
+        drop(self.check_response(qid, response).await?);
         Ok(())
     }
 
+    /// Fetch the Arrow schemas of the tables in a database, or of a subset of its tables.
+    ///
+    /// Without a database, the default database of the client is used. Types are converted with
+    /// the default [`ArrowOptions`], as the HTTP interface does not negotiate them.
+    ///
+    /// # Errors
+    /// Returns an error if the request fails or a column type cannot be converted.
+    #[instrument(skip(self, tables, qid))]
+    pub async fn fetch_schema(
+        &self,
+        database: Option<&str>,
+        tables: &[&str],
+        qid: Option<Qid>,
+    ) -> Result<HashMap<String, SchemaRef>> {
+        let query = schema_query(database, tables);
+        let options = QueryOptions { qid, ..Default::default() };
+        let stream = self.query_with_options(&query, options).await?;
+        collect_schemas(stream, ArrowOptions::default()).await
+    }
+
     /// Insert Arrow RecordBatch into a table.
     #[instrument(skip(self, batch), fields(table = %table, rows = batch.num_rows()))]
     pub async fn insert(&self, table: &str, batch: RecordBatch) -> Result<()> {
-        let sql = format!("INSERT INTO {table} FORMAT ArrowStream");
-        let mut url = self.options.url.clone();
-        let _ = url.query_pairs_mut().append_pair("query", &sql);
-
-        let mut headers = self.default_headers();
-        drop(headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/octet-stream")));
-
-        let body = serialize_batch(&batch)?;
-
-        debug!(url = %url, body_size = body.len(), "Executing HTTP insert");
-
-        let response = self
-            .client
-            .post(url)
-            .headers(headers)
-            .body(body)
-            .send()
-            .instrument(trace_span!("http_request"))
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let body = response.text().await.unwrap_or_default();
-            return Err(Error::Server(format!("HTTP {status}: {body}")));
-        }
-
-        Ok(())
+        self.insert_stream(table, stream::iter([Ok(batch)])).await
     }
 
     /// Insert multiple Arrow batches (all must have same schema).
     #[instrument(skip(self, batches), fields(table = %table, batch_count = batches.len()))]
     pub async fn insert_batches(&self, table: &str, batches: Vec<RecordBatch>) -> Result<()> {
-        if batches.is_empty() {
-            return Ok(());
-        }
+        self.insert_stream(table, stream::iter(batches.into_iter().map(Ok))).await
+    }
 
-        let sql = format!("INSERT INTO {table} FORMAT ArrowStream");
-        let mut url = self.options.url.clone();
-        let _ = url.query_pairs_mut().append_pair("query", &sql);
+    /// Insert a stream of Arrow batches (all must have same schema) into a table.
+    ///
+    /// The request body is encoded as the batches are produced, so only about one batch is held
+    /// in memory at a time. An empty stream sends no request. An error yielded by the stream
+    /// aborts the request, the server then discards the insert.
+    ///
+    /// # Errors
+    /// Returns an error if a batch fails to encode, the stream yields an error, or the server
+    /// rejects the insert.
+    pub async fn insert_stream<S>(&self, table: &str, batches: S) -> Result<()>
+    where
+        S: Stream<Item = Result<RecordBatch>> + Send + 'static,
+    {
+        self.insert_stream_with_options(table, batches, QueryOptions::new()).await
+    }
+
+    /// Insert a stream of Arrow batches into a table with per-request options.
+    ///
+    /// See [`HttpClient::insert_stream`], and [`HttpClient::query_with_options`] for the options
+    /// that apply, e.g. `async_insert` settings.
+    ///
+    /// # Errors
+    /// Returns an error if a batch fails to encode, the stream yields an error, or the server
+    /// rejects the insert.
+    #[instrument(skip(self, batches, options), fields(table = %table))]
+    pub async fn insert_stream_with_options<S>(
+        &self,
+        table: &str,
+        batches: S,
+        mut options: QueryOptions,
+    ) -> Result<()>
+    where
+        S: Stream<Item = Result<RecordBatch>> + Send + 'static,
+    {
+        // Wait for the first batch, the schema of the stream
+        let mut batches = Box::pin(batches);
+        let Some(first) = batches.next().await.transpose()? else {
+            return Ok(());
+        };
 
-        let mut headers = self.default_headers();
+        let qid = *options.qid.get_or_insert_default();
+        let url = self.build_url(&format!("INSERT INTO {table} FORMAT ArrowStream"), &options);
+        let mut headers = self.headers(&options);
         drop(headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/octet-stream")));
 
-        let body = serialize_batches(&batches)?;
+        let body = encode_batches(stream::iter([Ok(first)]).chain(batches));
 
-        debug!(url = %url, body_size = body.len(), "Executing HTTP batch insert");
+        debug!(url = %url, "Executing HTTP streaming insert");
 
         let response = self
             .client
             .post(url)
             .headers(headers)
-            .body(body)
+            .body(reqwest::Body::wrap_stream(body))
             .send()
             .instrument(trace_span!("http_request"))
             .await
             .map_err(|e| Error::Network(e.to_string()))?;
 
-        let status = response.status();
-        if !status.is_success() {
-            let body = response.text().await.unwrap_or_default();
-            return Err(Error::Server(format!("HTTP {status}: {body}")));
-        }
-
+        drop(self.check_response(qid, response).await?);
         Ok(())
     }
+}
 
-    /// Handle an HTTP response, checking for errors and deserializing `ArrowStream`.
-    async fn handle_response(&self, response: reqwest::Response) -> Result<Vec<RecordBatch>> {
-        let status = response.status();
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
 
-        if !status.is_success() {
-            let body = response.text().await.unwrap_or_default();
-            return Err(Error::Server(format!("HTTP {status}: {body}")));
-        }
+    use super::*;
+    use crate::query::ParamValue;
 
-        let body = response
-            .bytes()
-            .instrument(trace_span!("read_response"))
-            .await
-            .map_err(|e| Error::Network(format!("Failed to read response body: {e}")))?;
+    fn query_pairs(url: &url::Url) -> Vec<(String, String)> {
+        url.query_pairs().map(|(k, v)| (k.into_owned(), v.into_owned())).collect()
+    }
 
-        deserialize_batches(body)
+    #[test]
+    fn test_build_url_query_only() {
+        let client = HttpClient::new(HttpOptions::default()).unwrap();
+        let url = client.build_url("SELECT 1", &QueryOptions::new());
+        assert_eq!(query_pairs(&url), vec![("query".into(), "SELECT 1".into())]);
     }
-}
 
-/// Serialize multiple batches to `ArrowStream` format.
-fn serialize_batches(batches: &[RecordBatch]) -> Result<Bytes> {
-    use arrow::ipc::writer::StreamWriter;
+    #[test]
+    fn test_build_url_options() {
+        let options = HttpOptions::default()
+            .with_session("my_session")
+            .with_session_timeout(Duration::from_secs(90))
+            .with_setting("max_threads", 8)
+            .with_setting("max_execution_time", 60);
+        let client = HttpClient::new(options).unwrap();
+
+        let qid = Qid::new();
+        let params = QueryParams::from(vec![
+            ("name", ParamValue::from("it's")),
+            ("ids", ParamValue::from(vec![1_i64, 2])),
+        ]);
+        let query_options =
+            QueryOptions::new().with_qid(qid).with_params(params).with_setting("max_threads", 2);
+        let url = client.build_url("SELECT {name:String}, {ids:Array(Int64)}", &query_options);
+
+        assert_eq!(query_pairs(&url), vec![
+            ("query".into(), "SELECT {name:String}, {ids:Array(Int64)}".into()),
+            ("query_id".into(), qid.to_string()),
+            ("session_id".into(), "my_session".into()),
+            ("session_timeout".into(), "90".into()),
+            ("max_threads".into(), "2".into()),
+            ("max_execution_time".into(), "60".into()),
+            ("param_name".into(), "it's".into()),
+            ("param_ids".into(), "[1,2]".into()),
+        ]);
+    }
 
-    if batches.is_empty() {
-        return Ok(Bytes::new());
+    #[test]
+    fn test_build_url_session_override() {
+        let options = HttpOptions::default()
+            .with_session("client_session")
+            .with_session_timeout(Duration::from_secs(90));
+        let client = HttpClient::new(options).unwrap();
+
+        let query_options = QueryOptions::new().with_session("query_session");
+        let url = client.build_url("SELECT 1", &query_options);
+        assert_eq!(query_pairs(&url)[1..], [
+            ("session_id".into(), "query_session".into()),
+            ("session_timeout".into(), "90".into()),
+        ]);
+
+        let query_options = QueryOptions::new().with_session_timeout(Duration::from_secs(5));
+        let url = client.build_url("SELECT 1", &query_options);
+        assert_eq!(query_pairs(&url)[1..], [
+            ("session_id".into(), "client_session".into()),
+            ("session_timeout".into(), "5".into()),
+        ]);
+
+        // No session without an ID
+        let client = HttpClient::new(HttpOptions::default()).unwrap();
+        let url = client.build_url("SELECT 1", &query_options);
+        assert_eq!(query_pairs(&url).len(), 1);
     }
 
-    let schema = batches[0].schema();
-    let total_size: usize = batches.iter().map(RecordBatch::get_array_memory_size).sum();
-    let mut buffer = Vec::with_capacity(total_size);
+    #[test]
+    fn test_headers_quota_key_override() {
+        let options = HttpOptions::default().with_quota_key("client");
+        let client = HttpClient::new(options).unwrap();
+        let headers = client.headers(&QueryOptions::new().with_quota_key("query"));
+        assert_eq!(headers["X-ClickHouse-Quota"], "query");
+    }
 
-    let mut writer = StreamWriter::try_new(&mut buffer, &schema)
-        .map_err(|e| Error::ArrowSerialize(format!("Failed to create ArrowStream writer: {e}")))?;
+    #[test]
+    fn test_default_headers_quota_key() {
+        let options = HttpOptions::default().with_credentials("user", "pass").with_quota_key("q");
+        let client = HttpClient::new(options).unwrap();
+        let headers = client.headers(&QueryOptions::new());
+        assert_eq!(headers["X-ClickHouse-User"], "user");
+        assert_eq!(headers["X-ClickHouse-Quota"], "q");
+        assert!(!headers.contains_key("traceparent"));
+    }
 
-    for batch in batches {
-        writer.write(batch).map_err(|e| {
-            Error::ArrowSerialize(format!("Failed to write batch to ArrowStream: {e}"))
-        })?;
+    #[test]
+    fn test_headers_trace_parent() {
+        let client = HttpClient::new(HttpOptions::default()).unwrap();
+        let trace = TraceParent::new(1, 2, true).with_tracestate("a=b");
+        let headers = client.headers(&QueryOptions::new().with_trace_parent(trace.clone()));
+        assert_eq!(headers["traceparent"], trace.to_string().as_str());
+        assert_eq!(headers["tracestate"], "a=b");
     }
 
-    writer
-        .finish()
-        .map_err(|e| Error::ArrowSerialize(format!("Failed to finish ArrowStream: {e}")))?;
+    #[test]
+    fn test_emit_progress_deltas() {
+        let client = HttpClient::new(HttpOptions::default()).unwrap();
+        let mut events = client.subscribe_events();
 
-    Ok(Bytes::from(buffer))
+        let mut headers = HeaderMap::new();
+        let progress = |rows: u64| {
+            HeaderValue::from_str(&format!(
+                r#"{{"read_rows":"{rows}","read_bytes":"{}"}}"#,
+                rows * 8
+            ))
+            .unwrap()
+        };
+        let _ = headers.append(PROGRESS_HEADER, progress(10));
+        let _ = headers.append(PROGRESS_HEADER, progress(25));
+        let _ = headers.append(SUMMARY_HEADER, progress(30));
+
+        let qid = Qid::new();
+        client.emit_progress(qid, &headers);
+
+        let mut read_rows = vec![];
+        while let Ok(event) = events.try_recv() {
+            assert_eq!(event.qid, qid);
+            let ClickHouseEvent::Progress(progress) = event.event else {
+                panic!("Expected progress event");
+            };
+            read_rows.push(progress.read_rows);
+        }
+        assert_eq!(read_rows, vec![10, 15, 5]);
+    }
 }
@@ -2,6 +2,8 @@
 
 use std::time::Duration;
 
+use crate::settings::{SettingValue, Settings};
+
 /// Default request timeout (60 seconds).
 ///
 /// This value is chosen to accommodate large queries while avoiding indefinite hangs.
@@ -28,7 +30,8 @@ pub struct HttpOptions {
 
     /// Request timeout.
     ///
-    /// Controls how long to wait for the entire request/response cycle.
+    /// Controls how long to wait for the entire request/response cycle, including streaming
+    /// the batches of a query response or of a streaming insert.
     /// Default: 60 seconds ([`DEFAULT_TIMEOUT_SECS`]).
     ///
     /// # Recommendations
@@ -36,6 +39,21 @@ pub struct HttpOptions {
     /// - For OLAP workloads (large analytical queries): 60-300 seconds
     /// - For bulk inserts: Consider timeout based on expected data size
     pub timeout: Duration,
+
+    /// Settings applied to every request, per-request settings are applied on top.
+    pub settings: Option<Settings>,
+
+    /// Session of the requests, keeping temporary tables and `SET` settings between requests.
+    ///
+    /// `ClickHouse` runs one request per session at a time, concurrent requests in the same
+    /// session fail with `SESSION_IS_LOCKED`.
+    pub session_id: Option<String>,
+
+    /// How long the server keeps an idle session, 60 seconds by default on the server.
+    pub session_timeout: Option<Duration>,
+
+    /// Quota key, to account for the quota of the user per key (X-ClickHouse-Quota header).
+    pub quota_key: Option<String>,
 }
 
 impl Default for HttpOptions {
@@ -49,6 +67,10 @@ impl Default for HttpOptions {
             password:           None,
             enable_compression: true,
             timeout:            Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+            settings:           None,
+            session_id:         None,
+            session_timeout:    None,
+            quota_key:          None,
         }
     }
 }
@@ -94,6 +116,42 @@ impl HttpOptions {
         self.timeout = timeout;
         self
     }
+
+    /// Set the settings applied to every request.
+    #[must_use]
+    pub fn with_settings(mut self, settings: impl Into<Settings>) -> Self {
+        self.settings = Some(settings.into());
+        self
+    }
+
+    /// Set a single setting applied to every request.
+    #[must_use]
+    pub fn with_setting(mut self, name: impl Into<String>, value: impl Into<SettingValue>) -> Self {
+        let value: SettingValue = value.into();
+        self.settings = Some(self.settings.unwrap_or_default().with_setting(name, value));
+        self
+    }
+
+    /// Run requests in the session with the given ID, created by the server on first use.
+    #[must_use]
+    pub fn with_session(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self
+    }
+
+    /// Set how long the server keeps the session alive between requests.
+    #[must_use]
+    pub fn with_session_timeout(mut self, timeout: Duration) -> Self {
+        self.session_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the quota key.
+    #[must_use]
+    pub fn with_quota_key(mut self, quota_key: impl Into<String>) -> Self {
+        self.quota_key = Some(quota_key.into());
+        self
+    }
 }
 
 #[cfg(test)]
@@ -109,6 +167,10 @@ mod tests {
         assert!(options.password.is_none());
         assert!(options.enable_compression);
         assert_eq!(options.timeout, Duration::from_secs(60));
+        assert!(options.settings.is_none());
+        assert!(options.session_id.is_none());
+        assert!(options.session_timeout.is_none());
+        assert!(options.quota_key.is_none());
     }
 
     #[test]
@@ -167,4 +229,27 @@ mod tests {
         assert!(options.enable_compression);
         assert_eq!(options.timeout, Duration::from_secs(30));
     }
+
+    #[test]
+    fn test_with_settings() {
+        let options = HttpOptions::default()
+            .with_settings(vec![("max_threads", 4)])
+            .with_setting("max_execution_time", 30)
+            .with_setting("max_threads", 8);
+        assert_eq!(options.settings.unwrap().encode_to_strings(), vec![
+            "max_threads = 8",
+            "max_execution_time = 30"
+        ]);
+    }
+
+    #[test]
+    fn test_with_session() {
+        let options = HttpOptions::default()
+            .with_session("my_session")
+            .with_session_timeout(Duration::from_secs(90))
+            .with_quota_key("tenant_1");
+        assert_eq!(options.session_id.as_deref(), Some("my_session"));
+        assert_eq!(options.session_timeout, Some(Duration::from_secs(90)));
+        assert_eq!(options.quota_key.as_deref(), Some("tenant_1"));
+    }
 }
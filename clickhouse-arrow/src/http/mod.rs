@@ -14,17 +14,25 @@
 //!     .build_http()
 //!     .await?;
 //!
-//! // Query returns Arrow RecordBatches
-//! let batches: Vec<RecordBatch> = client.query("SELECT * FROM my_table").await?;
+//! // Query returns a stream of Arrow RecordBatches, decoded as they arrive
+//! let mut batches = client.query("SELECT * FROM my_table").await?;
+//! while let Some(batch) = batches.try_next().await? {
+//!     println!("{} rows", batch.num_rows());
+//! }
 //!
 //! // Insert Arrow data
 //! client.insert("my_table", batch).await?;
+//!
+//! // Or stream batches into a table, e.g. from another query
+//! let batches = client.query("SELECT * FROM my_table").await?;
+//! client.insert_stream("my_table_copy", batches).await?;
 //! ```
 
 mod arrow_stream;
 mod client;
 mod config;
 pub mod escape;
+mod response;
 
 pub use client::HttpClient;
 pub use config::{DEFAULT_TIMEOUT_SECS, HttpOptions};
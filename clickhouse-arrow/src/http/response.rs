@@ -0,0 +1,162 @@
+//! Parsing of `ClickHouse` HTTP response headers and exception bodies.
+//!
+//! Exceptions are mapped to the same [`ServerError`] as the native protocol, progress and
+//! summary headers to [`Progress`] deltas.
+
+use std::collections::HashMap;
+
+use crate::Progress;
+use crate::native::ServerError;
+use crate::native::error_codes::map_error_code;
+
+/// Error code of a failed request, also sent when the body is not an exception.
+pub(super) const EXCEPTION_CODE_HEADER: &str = "X-ClickHouse-Exception-Code";
+/// Cumulative progress, sent while the query runs with `send_progress_in_http_headers = 1`.
+pub(super) const PROGRESS_HEADER: &str = "X-ClickHouse-Progress";
+/// Cumulative progress when the response starts, final unless the response was streamed.
+pub(super) const SUMMARY_HEADER: &str = "X-ClickHouse-Summary";
+
+/// Parse an exception body into a [`ServerError`].
+///
+/// The body has the form
+/// `Code: 60. DB::Exception: Table default.t does not exist. (UNKNOWN_TABLE) (version 25.3.1)`,
+/// optionally followed by a stack trace. `code` is the exception code header, if sent. Returns
+/// `None` if no code is known, e.g. for errors from a proxy.
+pub(super) fn parse_exception(code: Option<i32>, body: &str) -> Option<ServerError> {
+    let body = body.trim();
+
+    // Code: <code>. <name>: <message>
+    let (body_code, rest) = match body.strip_prefix("Code: ").and_then(|r| r.split_once(". ")) {
+        Some((code, rest)) => (code.parse::<i32>().ok(), rest),
+        None => (None, body),
+    };
+    let code = code.or(body_code)?;
+
+    let (text, stack_trace) = match rest.split_once("Stack trace") {
+        Some((text, trace)) => (text.trim_end(), format!("Stack trace{trace}")),
+        None => (rest, String::new()),
+    };
+    let (name, message) = match text.split_once(": ") {
+        Some((name, message)) if !name.contains(' ') => (name.to_string(), message),
+        _ => ("DB::Exception".to_string(), text),
+    };
+    // The server version is not part of the message
+    let message = match message.rfind(" (version ") {
+        Some(pos) if message.ends_with(')') => &message[..pos],
+        _ => message,
+    };
+
+    Some(ServerError {
+        error: map_error_code(code),
+        code,
+        name,
+        message: message.to_string(),
+        stack_trace,
+    })
+}
+
+/// Parse a progress or summary header, a JSON object of (quoted) counters.
+pub(super) fn parse_progress(value: &str) -> Option<Progress> {
+    let fields: HashMap<String, serde_json::Value> = serde_json::from_str(value).ok()?;
+    let field = |name: &str| {
+        fields.get(name).and_then(|value| match value {
+            serde_json::Value::String(s) => s.parse::<u64>().ok(),
+            value => value.as_u64(),
+        })
+    };
+    Some(Progress {
+        read_rows:           field("read_rows").unwrap_or_default(),
+        read_bytes:          field("read_bytes").unwrap_or_default(),
+        total_rows_to_read:  field("total_rows_to_read").unwrap_or_default(),
+        total_bytes_to_read: field("total_bytes_to_read"),
+        written_rows:        field("written_rows"),
+        written_bytes:       field("written_bytes"),
+        elapsed_ns:          field("elapsed_ns"),
+    })
+}
+
+/// The progress made between two cumulative progress headers, as the native protocol reports.
+pub(super) fn progress_delta(current: Progress, previous: Progress) -> Progress {
+    let sub_opt = |a: Option<u64>, b: Option<u64>| a.map(|a| a.saturating_sub(b.unwrap_or(0)));
+    Progress {
+        read_rows:           current.read_rows.saturating_sub(previous.read_rows),
+        read_bytes:          current.read_bytes.saturating_sub(previous.read_bytes),
+        total_rows_to_read:  current.total_rows_to_read.saturating_sub(previous.total_rows_to_read),
+        total_bytes_to_read: sub_opt(current.total_bytes_to_read, previous.total_bytes_to_read),
+        written_rows:        sub_opt(current.written_rows, previous.written_rows),
+        written_bytes:       sub_opt(current.written_bytes, previous.written_bytes),
+        elapsed_ns:          sub_opt(current.elapsed_ns, previous.elapsed_ns),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::native::error_codes::{ClickHouseError, Severity};
+
+    #[test]
+    fn test_parse_exception() {
+        let body = "Code: 60. DB::Exception: Table default.missing does not exist. \
+                    (UNKNOWN_TABLE) (version 25.3.1.2703 (official build))\n";
+        let error = parse_exception(Some(60), body).unwrap();
+        assert_eq!(error.code, 60);
+        assert_eq!(error.name, "DB::Exception");
+        assert_eq!(error.message, "Table default.missing does not exist. (UNKNOWN_TABLE)");
+        assert!(error.stack_trace.is_empty());
+        assert!(matches!(error.error, Severity::Query(ClickHouseError::UnknownTable)));
+    }
+
+    #[test]
+    fn test_parse_exception_code_from_body() {
+        let body = "Code: 62. DB::Exception: Syntax error: failed at position 8. (SYNTAX_ERROR) \
+                    (version 24.8.1.1)";
+        let error = parse_exception(None, body).unwrap();
+        assert_eq!(error.code, 62);
+        assert_eq!(error.message, "Syntax error: failed at position 8. (SYNTAX_ERROR)");
+    }
+
+    #[test]
+    fn test_parse_exception_stack_trace() {
+        let body = "Code: 159. DB::Exception: Timeout exceeded. (TIMEOUT_EXCEEDED)\nStack trace \
+                    (when copying this message, always include the lines below):\n\n0. foo\n";
+        let error = parse_exception(None, body).unwrap();
+        assert_eq!(error.code, 159);
+        assert_eq!(error.message, "Timeout exceeded. (TIMEOUT_EXCEEDED)");
+        assert!(error.stack_trace.starts_with("Stack trace"));
+        assert!(error.stack_trace.contains("0. foo"));
+    }
+
+    #[test]
+    fn test_parse_exception_without_code() {
+        assert!(parse_exception(None, "502 Bad Gateway").is_none());
+        let error = parse_exception(Some(516), "Authentication failed").unwrap();
+        assert_eq!(error.code, 516);
+        assert_eq!(error.message, "Authentication failed");
+    }
+
+    #[test]
+    fn test_parse_progress() {
+        let header = r#"{"read_rows":"10","read_bytes":"80","written_rows":"5","written_bytes":"40","total_rows_to_read":"100","result_rows":"0","result_bytes":"0","elapsed_ns":"1234"}"#;
+        let progress = parse_progress(header).unwrap();
+        assert_eq!(progress, Progress {
+            read_rows:           10,
+            read_bytes:          80,
+            total_rows_to_read:  100,
+            total_bytes_to_read: None,
+            written_rows:        Some(5),
+            written_bytes:       Some(40),
+            elapsed_ns:          Some(1234),
+        });
+        assert!(parse_progress("not json").is_none());
+    }
+
+    #[test]
+    fn test_progress_delta() {
+        let previous = Progress { read_rows: 10, written_rows: Some(5), ..Default::default() };
+        let current = Progress { read_rows: 25, written_rows: Some(5), ..Default::default() };
+        let delta = progress_delta(current, previous);
+        assert_eq!(delta.read_rows, 15);
+        assert_eq!(delta.written_rows, Some(0));
+        assert_eq!(previous + delta, current);
+    }
+}
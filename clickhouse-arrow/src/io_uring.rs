@@ -3,6 +3,12 @@
 //! This module provides io_uring-based async I/O when available, with automatic
 //! fallback to standard epoll-based I/O on unsupported systems.
 //!
+//! When the feature is enabled and the probe succeeds, each native TCP connection owns a small
+//! ring with a read and a write buffer registered with the kernel. Reads and writes are submitted
+//! to the ring directly from the connection's task, a pending read and write going to the kernel
+//! in a single `io_uring_enter`, and completions are awaited through tokio's reactor. If the ring
+//! cannot be set up, the connection uses the standard tokio socket.
+//!
 //! Part of HyperSec DFE optimisations ported to clickhouse-arrow for high-throughput
 //! ClickHouse workloads where syscall overhead becomes significant.
 //!
@@ -21,7 +27,18 @@
 //!
 //! Use [`is_iouring_available`] to check availability before using io_uring APIs.
 
-use std::sync::OnceLock;
+use std::io;
+use std::mem::ManuallyDrop;
+use std::net::Shutdown;
+use std::os::fd::AsRawFd;
+use std::pin::Pin;
+use std::sync::{Arc, OnceLock};
+use std::task::{Context, Poll, Wake, Waker, ready};
+
+use ::io_uring::{IoUring, opcode, squeue, types};
+use parking_lot::Mutex;
+use tokio::io::unix::AsyncFd;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 
 /// Cached result of io_uring availability check.
 static IOURING_AVAILABLE: OnceLock<bool> = OnceLock::new();
@@ -195,6 +212,397 @@ pub fn iouring_status() -> &'static str {
     }
 }
 
+/// Size of each of a connection's registered buffers, the most bytes read or written in a single
+/// operation.
+const URING_BUFFER_SIZE: u32 = 64 * 1024;
+/// Submission queue depth of a connection's ring, at most one read and one write are in flight.
+const URING_ENTRIES: u32 = 4;
+
+/// Registered buffer index of reads, also identifying their completions.
+const READ: u16 = 0;
+/// Registered buffer index of writes, also identifying their completions.
+const WRITE: u16 = 1;
+
+/// One direction of a [`UringStream`], the bytes in `buf[pos..len]` are yet to be consumed by
+/// `poll_read`, or yet to be written to the socket.
+struct Half {
+    buf:       Box<[u8]>,
+    pos:       usize,
+    len:       usize,
+    in_flight: bool,
+    error:     Option<io::Error>,
+}
+
+impl Half {
+    fn new() -> Self {
+        Self {
+            buf:       vec![0_u8; URING_BUFFER_SIZE as usize].into_boxed_slice(),
+            pos:       0,
+            len:       0,
+            in_flight: false,
+            error:     None,
+        }
+    }
+
+    fn iovec(&mut self) -> libc::iovec {
+        libc::iovec { iov_base: self.buf.as_mut_ptr().cast(), iov_len: self.buf.len() }
+    }
+
+    // Both bounded by the buffer size
+    #[expect(clippy::cast_possible_truncation)]
+    fn remaining(&self) -> u32 { (self.len - self.pos) as u32 }
+}
+
+/// Wakers of the tasks waiting on either direction of a [`UringStream`].
+///
+/// The ring's `AsyncFd` keeps a single waker, so it is given one waking both directions. Halves
+/// polled from different tasks, ie after `tokio::io::split`, are both woken once completions are
+/// queued, and the half reaping a completion wakes the direction it belongs to.
+#[derive(Default)]
+struct RingWakers {
+    read:  Mutex<Option<Waker>>,
+    write: Mutex<Option<Waker>>,
+}
+
+impl RingWakers {
+    fn slot(&self, op: u16) -> &Mutex<Option<Waker>> {
+        if op == READ { &self.read } else { &self.write }
+    }
+
+    fn register(&self, op: u16, waker: &Waker) {
+        let mut slot = self.slot(op).lock();
+        if !slot.as_ref().is_some_and(|w| w.will_wake(waker)) {
+            *slot = Some(waker.clone());
+        }
+    }
+
+    fn wake_op(&self, op: u16) {
+        if let Some(waker) = self.slot(op).lock().take() {
+            waker.wake();
+        }
+    }
+}
+
+impl Wake for RingWakers {
+    fn wake(self: Arc<Self>) { self.wake_by_ref(); }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.wake_op(READ);
+        self.wake_op(WRITE);
+    }
+}
+
+/// Operations still in flight when a [`UringStream`] is dropped, with the ring and buffers they
+/// use. Dropping it waits for the operations to complete before freeing the buffers.
+struct Drain {
+    ring:      IoUring,
+    buffers:   [Box<[u8]>; 2],
+    in_flight: usize,
+}
+
+impl Drop for Drain {
+    fn drop(&mut self) {
+        while self.in_flight > 0 {
+            // Retried operations are submitted here, and fail on the shut down socket
+            if self.ring.submit_and_wait(1).is_err() {
+                // The kernel may still write to the buffers, leak them rather than free them
+                std::mem::forget(std::mem::take(&mut self.buffers));
+                break;
+            }
+            self.in_flight = self.in_flight.saturating_sub(self.ring.completion().count());
+        }
+    }
+}
+
+/// A TCP stream whose I/O is performed through its own io_uring instance.
+///
+/// Reads fill the registered read buffer and are copied out of it by `poll_read`. Writes are
+/// copied into the registered write buffer and sent once it is full or flushed. If the buffers
+/// cannot be registered, ie when exceeding `RLIMIT_MEMLOCK`, the same buffers are used with plain
+/// `recv`/`send` operations.
+pub(crate) struct UringStream {
+    // Dropped before the buffers it may have registered, or handed to a `Drain`
+    ring:       ManuallyDrop<AsyncFd<IoUring>>,
+    socket:     std::net::TcpStream,
+    registered: bool,
+    /// Operations pushed to the submission queue but not yet submitted
+    queued:     bool,
+    eof:        bool,
+    read:       Half,
+    write:      Half,
+    wakers:     Arc<RingWakers>,
+    /// Wakes both directions, registered with the ring
+    ring_waker: Waker,
+}
+
+impl UringStream {
+    /// Set up a ring for a connected socket.
+    ///
+    /// The socket is returned if the ring cannot be created, so the caller can fall back to a
+    /// standard socket.
+    pub(crate) fn try_from_std(socket: std::net::TcpStream) -> Result<Self, std::net::TcpStream> {
+        let ring = match IoUring::new(URING_ENTRIES).and_then(AsyncFd::new) {
+            Ok(ring) => ring,
+            Err(error) => {
+                tracing::warn!(?error, "io_uring: failed to set up ring, falling back to epoll");
+                return Err(socket);
+            }
+        };
+        // The ring waits on the socket itself, operations on a non-blocking socket would fail
+        // with `EAGAIN` rather than completing once it is ready
+        if let Err(error) = socket.set_nonblocking(false) {
+            tracing::warn!(?error, "io_uring: failed to configure socket, falling back to epoll");
+            return Err(socket);
+        }
+
+        let mut read = Half::new();
+        let mut write = Half::new();
+        let buffers = [read.iovec(), write.iovec()];
+        // SAFETY: The buffers are heap allocations owned by the stream, they are not moved or
+        // freed before the ring is dropped
+        let registered = match unsafe { ring.get_ref().submitter().register_buffers(&buffers) } {
+            Ok(()) => true,
+            Err(error) => {
+                tracing::debug!(?error, "io_uring: failed to register buffers");
+                false
+            }
+        };
+
+        let wakers = Arc::new(RingWakers::default());
+        let ring_waker = Waker::from(Arc::clone(&wakers));
+        Ok(Self {
+            ring: ManuallyDrop::new(ring),
+            socket,
+            registered,
+            queued: false,
+            eof: false,
+            read,
+            write,
+            wakers,
+            ring_waker,
+        })
+    }
+
+    fn push(&mut self, entry: &squeue::Entry) {
+        // SAFETY: Operations only reference the stream's buffers, which outlive them, and the
+        // queue has room as at most one read and one write are in flight
+        let pushed = unsafe { self.ring.get_mut().submission().push(entry) };
+        debug_assert!(pushed.is_ok(), "io_uring submission queue is full");
+        self.queued = true;
+    }
+
+    fn start_read(&mut self) {
+        let fd = types::Fd(self.socket.as_raw_fd());
+        let buf = self.read.buf.as_mut_ptr();
+        let entry = if self.registered {
+            opcode::ReadFixed::new(fd, buf, URING_BUFFER_SIZE, READ).build()
+        } else {
+            opcode::Recv::new(fd, buf, URING_BUFFER_SIZE).build()
+        };
+        self.push(&entry.user_data(READ.into()));
+        self.read.in_flight = true;
+    }
+
+    fn start_write(&mut self) {
+        let fd = types::Fd(self.socket.as_raw_fd());
+        let buf = self.write.buf[self.write.pos..].as_ptr();
+        let len = self.write.remaining();
+        let entry = if self.registered {
+            opcode::WriteFixed::new(fd, buf, len, WRITE).build()
+        } else {
+            opcode::Send::new(fd, buf, len).build()
+        };
+        self.push(&entry.user_data(WRITE.into()));
+        self.write.in_flight = true;
+    }
+
+    fn complete_read(&mut self, result: i32) {
+        self.read.in_flight = false;
+        match usize::try_from(result) {
+            Ok(0) => self.eof = true,
+            Ok(len) => {
+                self.read.pos = 0;
+                self.read.len = len;
+            }
+            Err(_) if result == -libc::EAGAIN || result == -libc::EINTR => self.start_read(),
+            Err(_) => self.read.error = Some(io::Error::from_raw_os_error(-result)),
+        }
+        self.wakers.wake_op(READ);
+    }
+
+    fn complete_write(&mut self, result: i32) {
+        self.write.in_flight = false;
+        match usize::try_from(result) {
+            Ok(0) => self.write.error = Some(io::ErrorKind::WriteZero.into()),
+            Ok(len) => {
+                self.write.pos += len;
+                if self.write.pos == self.write.len {
+                    self.write.pos = 0;
+                    self.write.len = 0;
+                } else {
+                    // Short write, send the rest
+                    self.start_write();
+                }
+            }
+            Err(_) if result == -libc::EAGAIN || result == -libc::EINTR => self.start_write(),
+            Err(_) => self.write.error = Some(io::Error::from_raw_os_error(-result)),
+        }
+        self.wakers.wake_op(WRITE);
+    }
+
+    // Handle the completed operations, returns whether any completed
+    fn reap(&mut self) -> bool {
+        let mut completed = false;
+        loop {
+            // Releases the queue before handling the entry, which may push to the ring
+            let Some(entry) = self.ring.get_mut().completion().next() else {
+                break;
+            };
+            completed = true;
+            if entry.user_data() == u64::from(READ) {
+                self.complete_read(entry.result());
+            } else {
+                self.complete_write(entry.result());
+            }
+        }
+        completed
+    }
+
+    // Submit queued operations and wait until at least one operation completes, `op` being the
+    // direction the caller waits on
+    fn poll_complete(&mut self, op: u16, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        // Registered before reaping, so a completion reaped by the other half wakes this one
+        self.wakers.register(op, cx.waker());
+        if self.queued {
+            let _ = self.ring.get_ref().submit()?;
+            self.queued = false;
+        }
+        loop {
+            if self.reap() {
+                return Poll::Ready(Ok(()));
+            }
+            // The ring's fd is readable while completions are queued, readiness is cleared
+            // before reaping so a completion posted in between is not missed
+            let mut ring_cx = Context::from_waker(&self.ring_waker);
+            ready!(self.ring.poll_read_ready(&mut ring_cx))?.clear_ready();
+        }
+    }
+}
+
+impl Drop for UringStream {
+    fn drop(&mut self) {
+        // SAFETY: The ring is not used past this point
+        let ring = unsafe { ManuallyDrop::take(&mut self.ring) };
+        let in_flight = usize::from(self.read.in_flight) + usize::from(self.write.in_flight);
+        if in_flight == 0 {
+            return;
+        }
+
+        // The kernel may still access the buffers, shutting down the socket completes pending
+        // operations so they can be waited on before the buffers are freed
+        let _ = self.socket.shutdown(Shutdown::Both).ok();
+        let drain = Drain {
+            ring: ring.into_inner(),
+            buffers: [std::mem::take(&mut self.read.buf), std::mem::take(&mut self.write.buf)],
+            in_flight,
+        };
+        // Waiting would block a runtime thread. If the blocking task never runs, ie during
+        // runtime shutdown, dropping it drains the operations instead.
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            drop(handle.spawn_blocking(move || drop(drain)));
+        }
+    }
+}
+
+impl std::fmt::Debug for UringStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UringStream")
+            .field("registered", &self.registered)
+            .field("read_buf", &self.read.remaining())
+            .field("write_buf", &self.write.remaining())
+            .field("reading", &self.read.in_flight)
+            .field("writing", &self.write.in_flight)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AsyncRead for UringStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            let read = &mut this.read;
+            if read.pos < read.len {
+                let len = (read.len - read.pos).min(buf.remaining());
+                buf.put_slice(&read.buf[read.pos..read.pos + len]);
+                read.pos += len;
+                return Poll::Ready(Ok(()));
+            }
+            if let Some(error) = read.error.take() {
+                return Poll::Ready(Err(error));
+            }
+            if this.eof {
+                return Poll::Ready(Ok(()));
+            }
+            if !this.read.in_flight {
+                this.start_read();
+            }
+            ready!(this.poll_complete(READ, cx))?;
+        }
+    }
+}
+
+impl AsyncWrite for UringStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        loop {
+            let write = &mut this.write;
+            if let Some(error) = write.error.take() {
+                return Poll::Ready(Err(error));
+            }
+            if !write.in_flight && write.len < write.buf.len() {
+                let len = (write.buf.len() - write.len).min(buf.len());
+                write.buf[write.len..write.len + len].copy_from_slice(&buf[..len]);
+                write.len += len;
+                return Poll::Ready(Ok(len));
+            }
+            // The buffer is full, send it before accepting more
+            if !write.in_flight {
+                this.start_write();
+            }
+            ready!(this.poll_complete(WRITE, cx))?;
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(error) = this.write.error.take() {
+                return Poll::Ready(Err(error));
+            }
+            if this.write.len == 0 {
+                return Poll::Ready(Ok(()));
+            }
+            if !this.write.in_flight {
+                this.start_write();
+            }
+            ready!(this.poll_complete(WRITE, cx))?;
+        }
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.as_mut().poll_flush(cx))?;
+        Poll::Ready(self.socket.shutdown(Shutdown::Write))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -224,4 +632,168 @@ mod tests {
         let result = check_kernel_version();
         println!("Kernel version check: {result}");
     }
+
+    /// Accept a single connection, echoing what it receives until the client shuts down.
+    fn echo_server() -> (std::net::SocketAddr, std::thread::JoinHandle<()>) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            let _ = io::copy(&mut socket.try_clone().unwrap(), &mut socket).unwrap();
+        });
+        (addr, server)
+    }
+
+    /// Connect through io_uring, `None` if the kernel does not support it.
+    fn connect(addr: std::net::SocketAddr) -> Option<UringStream> {
+        if !is_iouring_available() {
+            eprintln!("io_uring is not available, skipping");
+            return None;
+        }
+        let socket = std::net::TcpStream::connect(addr).unwrap();
+        Some(UringStream::try_from_std(socket).unwrap())
+    }
+
+    /// Larger than a registered buffer, to exercise full buffers and partial reads
+    fn payload() -> Vec<u8> {
+        (0..URING_BUFFER_SIZE * 3).map(|i| u8::try_from(i % 251).unwrap()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_uring_stream_round_trip() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let (addr, server) = echo_server();
+        let Some(mut stream) = connect(addr) else { return };
+
+        stream.write_all(b"ping").await.unwrap();
+        stream.flush().await.unwrap();
+        let mut ping = [0_u8; 4];
+        let _ = stream.read_exact(&mut ping).await.unwrap();
+        assert_eq!(&ping, b"ping");
+
+        let payload = payload();
+        stream.write_all(&payload).await.unwrap();
+        stream.shutdown().await.unwrap();
+        let mut echoed = Vec::new();
+        let _ = stream.read_to_end(&mut echoed).await.unwrap();
+        assert_eq!(echoed, payload);
+
+        server.join().unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_uring_stream_split_halves() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let (addr, server) = echo_server();
+        let Some(stream) = connect(addr) else { return };
+
+        // Halves polled from different tasks must wake each other's completions
+        let (mut reader, mut writer) = tokio::io::split(stream);
+        let payload = payload();
+        let expected = payload.len();
+        let reads = crate::spawn::SpawnedTask::spawn(async move {
+            let mut echoed = Vec::new();
+            let _ = reader.read_to_end(&mut echoed).await.unwrap();
+            echoed
+        });
+        for chunk in payload.chunks(1000) {
+            writer.write_all(chunk).await.unwrap();
+        }
+        writer.shutdown().await.unwrap();
+
+        let echoed = reads.await.unwrap();
+        assert_eq!(echoed.len(), expected);
+        assert_eq!(echoed, payload);
+        server.join().unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_uring_stream_split_read_woken_after_write() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // More than the socket buffers hold, so the writer waits on the ring
+        const LEN: usize = 32 * 1024 * 1024;
+
+        // Reads once the writer waits, then replies once it is done
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            use std::io::{Read, Write};
+
+            let (mut socket, _) = listener.accept().unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            let _ = io::copy(&mut (&mut socket).take(LEN as u64), &mut io::sink()).unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            socket.write_all(b"pong").unwrap();
+        });
+        let Some(stream) = connect(addr) else { return };
+
+        // The reader waits first and the writer last, then goes idle, the reply completing the
+        // read must still wake the reader's task
+        let (mut reader, mut writer) = tokio::io::split(stream);
+        let reads = crate::spawn::SpawnedTask::spawn(async move {
+            let mut pong = [0_u8; 4];
+            let _ = reader.read_exact(&mut pong).await.unwrap();
+            pong
+        });
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        writer.write_all(&vec![0_u8; LEN]).await.unwrap();
+        writer.flush().await.unwrap();
+
+        let pong = tokio::time::timeout(std::time::Duration::from_secs(5), reads)
+            .await
+            .expect("read half should be woken")
+            .unwrap();
+        assert_eq!(&pong, b"pong");
+
+        writer.shutdown().await.unwrap();
+        server.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_uring_stream_peer_closed() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let Some(mut stream) = connect(listener.local_addr().unwrap()) else { return };
+        drop(listener.accept().unwrap());
+
+        // End of stream once the peer closes
+        let mut buf = [0_u8; 16];
+        assert_eq!(stream.read(&mut buf).await.unwrap(), 0);
+
+        // Writes fail once the peer has reset the connection
+        let mut result = Ok(());
+        for _ in 0..16 {
+            result = async {
+                stream.write_all(&payload()).await?;
+                stream.flush().await
+            }
+            .await;
+            if result.is_err() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_uring_stream_drop_with_pending_read() {
+        use tokio::io::AsyncReadExt;
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let Some(mut stream) = connect(listener.local_addr().unwrap()) else { return };
+        let (_peer, _) = listener.accept().unwrap();
+
+        // The peer sends nothing, leaving the read in flight when the stream is dropped
+        let mut buf = [0_u8; 16];
+        let read =
+            tokio::time::timeout(std::time::Duration::from_millis(50), stream.read(&mut buf));
+        assert!(read.await.is_err());
+        assert!(stream.read.in_flight);
+        drop(stream);
+    }
 }
@@ -225,6 +225,7 @@ mod io;
 #[cfg(all(target_os = "linux", feature = "io-uring"))]
 pub mod io_uring;
 pub mod limits;
+pub mod metrics;
 pub mod native;
 #[cfg(feature = "pool")]
 mod pool;
@@ -237,6 +238,7 @@ pub mod spawn;
 pub mod telemetry;
 #[cfg(any(feature = "test-utils", feature = "tmpfs-size"))]
 pub mod test_utils;
+mod transport;
 
 #[cfg(feature = "derive")]
 /// Derive macro for the [Row] trait.
@@ -276,17 +278,20 @@ pub use formats::{ArrowFormat, ClientFormat, NativeFormat};
 /// Contains useful top-level traits to interface with [`crate::prelude::NativeFormat`]
 pub use native::convert::*;
 pub use native::progress::Progress;
-pub use native::protocol::{ChunkedProtocolMode, ProfileEvent};
+pub use native::protocol::{
+    ChunkedProtocolMode, LogData, ProfileEvent, ProfileInfo, ServerLogLevel, TableStatus,
+};
 /// Represents the types that `ClickHouse` supports internally.
 pub use native::types::*;
 /// Contains useful top-level structures to interface with [`crate::prelude::NativeFormat`]
 pub use native::values::*;
-pub use native::{CompressionMethod, ServerError, Severity};
+pub use native::{CompressionMethod, CompressionOptions, ServerError, Severity};
 #[cfg(feature = "pool")]
 pub use pool::*;
 pub use query::{ParamValue, ParsedQuery, Qid, QueryParams};
 pub use schema::CreateOptions;
 pub use settings::{Setting, SettingValue, Settings};
+pub use transport::{ArrowTransport, RecordBatchStream};
 
 mod aliases {
     /// A non-cryptographically secure [`std::hash::BuildHasherDefault`] using
@@ -303,8 +308,10 @@ mod reexports {
     pub use bb8;
     pub use chrono_tz::Tz;
     pub use indexmap::IndexMap;
+    pub use rustc_hash;
+    pub use tokio_rustls::rustls;
+    pub use tracing;
     pub use uuid::Uuid;
-    pub use {rustc_hash, tracing};
 }
 /// Re-exports
 ///
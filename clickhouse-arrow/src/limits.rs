@@ -279,6 +279,9 @@ where
     pub fn truncation_reason(&self) -> Option<TruncationReason> {
         self.stream.state.truncation_reason
     }
+
+    /// Get a reference to the wrapped response, ie to read totals once the stream is consumed.
+    pub fn get_ref(&self) -> &S { &self.stream.inner }
 }
 
 impl<S> Stream for LimitedResponse<S>
@@ -0,0 +1,380 @@
+//! ## Metrics for queries, connections and pools.
+//!
+//! With the `metrics` feature, `clickhouse-arrow` records metrics through the [`metrics`] facade.
+//! Install a recorder, e.g. a Prometheus exporter, before building clients, then optionally
+//! describe the metrics:
+//!
+//! ```rust,ignore
+//! metrics_exporter_prometheus::PrometheusBuilder::new().install()?;
+//! clickhouse_arrow::metrics::describe();
+//!
+//! let client = Client::builder().with_endpoint("localhost:9000").build_arrow().await?;
+//! ```
+//!
+//! Every metric is labeled with [`LABEL_CLIENT_ID`]. Query metrics are further labeled with
+//! [`LABEL_OPERATION`], either `query` or `insert`. Metrics of a `bb8` pool are recorded by
+//! calling [`record_pool_state`] periodically.
+//!
+//! Without the feature, nothing is recorded and the `metrics` crate is not a dependency.
+//!
+//! [`metrics`]: https://docs.rs/metrics
+use std::time::Instant;
+
+use crate::native::progress::Progress;
+use crate::native::protocol::CompressionMethod;
+
+/// Duration of queries and inserts, from sending the query to the end of its response.
+#[cfg(feature = "metrics")]
+pub const QUERY_DURATION: &str = "clickhouse_query_duration_seconds";
+/// Rows read by the server, as reported by progress packets.
+#[cfg(feature = "metrics")]
+pub const ROWS_READ: &str = "clickhouse_rows_read_total";
+/// Bytes read by the server, as reported by progress packets.
+#[cfg(feature = "metrics")]
+pub const BYTES_READ: &str = "clickhouse_bytes_read_total";
+/// Rows written by the server, as reported by progress packets.
+#[cfg(feature = "metrics")]
+pub const ROWS_WRITTEN: &str = "clickhouse_rows_written_total";
+/// Bytes written by the server, as reported by progress packets.
+#[cfg(feature = "metrics")]
+pub const BYTES_WRITTEN: &str = "clickhouse_bytes_written_total";
+/// Ratio of uncompressed to compressed size of each block sent, labeled with [`LABEL_METHOD`].
+#[cfg(feature = "metrics")]
+pub const COMPRESSION_RATIO: &str = "clickhouse_compression_ratio";
+/// Queries queued or executing on a connection, labeled with [`LABEL_CONNECTION`].
+#[cfg(feature = "metrics")]
+pub const IN_FLIGHT: &str = "clickhouse_connection_in_flight";
+/// Reconnects of lost connections, labeled with [`LABEL_STATUS`].
+#[cfg(feature = "metrics")]
+pub const RECONNECTS: &str = "clickhouse_reconnects_total";
+/// Connections of a pool, idle or checked out.
+#[cfg(all(feature = "metrics", feature = "pool"))]
+pub const POOL_CONNECTIONS: &str = "clickhouse_pool_connections";
+/// Idle connections of a pool.
+#[cfg(all(feature = "metrics", feature = "pool"))]
+pub const POOL_IDLE_CONNECTIONS: &str = "clickhouse_pool_idle_connections";
+/// Checkouts of a pool that had to wait for a connection.
+#[cfg(all(feature = "metrics", feature = "pool"))]
+pub const POOL_GETS_WAITED: &str = "clickhouse_pool_gets_waited_total";
+/// Total time spent waiting for a connection of a pool.
+#[cfg(all(feature = "metrics", feature = "pool"))]
+pub const POOL_WAIT_TIME: &str = "clickhouse_pool_wait_microseconds_total";
+
+/// Client id, see [`crate::Client::client_id`].
+#[cfg(feature = "metrics")]
+pub const LABEL_CLIENT_ID: &str = "client_id";
+/// Kind of operation, `query` or `insert`.
+#[cfg(feature = "metrics")]
+pub const LABEL_OPERATION: &str = "operation";
+/// Outcome, `ok` or `error` for queries and `ok` or `failed` for reconnects.
+#[cfg(feature = "metrics")]
+pub const LABEL_STATUS: &str = "status";
+/// Compression method of [`COMPRESSION_RATIO`].
+#[cfg(feature = "metrics")]
+pub const LABEL_METHOD: &str = "method";
+/// Connection id of [`IN_FLIGHT`], `{client_id}.{connection}`.
+#[cfg(feature = "metrics")]
+pub const LABEL_CONNECTION: &str = "connection";
+/// Name of a pool, as given to [`record_pool_state`].
+#[cfg(all(feature = "metrics", feature = "pool"))]
+pub const LABEL_POOL: &str = "pool";
+
+/// Register units and descriptions of the metrics with the installed recorder.
+#[cfg(feature = "metrics")]
+pub fn describe() {
+    use ::metrics::{Unit, describe_counter, describe_gauge, describe_histogram};
+
+    describe_histogram!(QUERY_DURATION, Unit::Seconds, "Duration of queries and inserts");
+    describe_counter!(ROWS_READ, Unit::Count, "Rows read by the server");
+    describe_counter!(BYTES_READ, Unit::Bytes, "Bytes read by the server");
+    describe_counter!(ROWS_WRITTEN, Unit::Count, "Rows written by the server");
+    describe_counter!(BYTES_WRITTEN, Unit::Bytes, "Bytes written by the server");
+    describe_histogram!(COMPRESSION_RATIO, "Ratio of uncompressed to compressed block size");
+    describe_gauge!(IN_FLIGHT, Unit::Count, "Queries queued or executing on a connection");
+    describe_counter!(RECONNECTS, Unit::Count, "Reconnects of lost connections");
+    #[cfg(feature = "pool")]
+    {
+        describe_gauge!(POOL_CONNECTIONS, Unit::Count, "Connections of the pool");
+        describe_gauge!(POOL_IDLE_CONNECTIONS, Unit::Count, "Idle connections of the pool");
+        describe_counter!(POOL_GETS_WAITED, Unit::Count, "Checkouts that waited for a connection");
+        describe_counter!(
+            POOL_WAIT_TIME,
+            Unit::Microseconds,
+            "Time spent waiting for a connection"
+        );
+    }
+}
+
+/// Record the state of a `bb8` pool, labeled with `name`. Call periodically, e.g. from the task
+/// exporting metrics.
+///
+/// # Parameters
+/// - `pool`: The pool to record.
+/// - `name`: Value of [`LABEL_POOL`], to tell pools apart.
+#[cfg(all(feature = "metrics", feature = "pool"))]
+pub fn record_pool_state<T: crate::ClientFormat>(pool: &crate::ConnectionPool<T>, name: &str) {
+    let state = pool.state();
+    let labels = [(LABEL_POOL, name.to_string())];
+    ::metrics::gauge!(POOL_CONNECTIONS, &labels).set(f64::from(state.connections));
+    ::metrics::gauge!(POOL_IDLE_CONNECTIONS, &labels).set(f64::from(state.idle_connections));
+    ::metrics::counter!(POOL_GETS_WAITED, &labels).absolute(state.statistics.get_waited);
+    let wait = u64::try_from(state.statistics.get_wait_time.as_micros()).unwrap_or(u64::MAX);
+    ::metrics::counter!(POOL_WAIT_TIME, &labels).absolute(wait);
+}
+
+/// Kind of operation executing on a connection.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum OperationKind {
+    #[default]
+    Query,
+    Insert,
+}
+
+impl OperationKind {
+    #[cfg(feature = "metrics")]
+    fn as_str(self) -> &'static str {
+        match self {
+            OperationKind::Query => "query",
+            OperationKind::Insert => "insert",
+        }
+    }
+}
+
+/// Handles of the query metrics of one kind of operation.
+#[cfg(feature = "metrics")]
+struct OperationMetrics {
+    ok:            ::metrics::Histogram,
+    error:         ::metrics::Histogram,
+    rows_read:     ::metrics::Counter,
+    bytes_read:    ::metrics::Counter,
+    rows_written:  ::metrics::Counter,
+    bytes_written: ::metrics::Counter,
+}
+
+#[cfg(feature = "metrics")]
+impl OperationMetrics {
+    fn new(client_id: &str, kind: OperationKind) -> Self {
+        let labels =
+            [(LABEL_CLIENT_ID, client_id.to_string()), (LABEL_OPERATION, kind.as_str().into())];
+        let duration = |status: &'static str| {
+            let labels = [labels[0].clone(), labels[1].clone(), (LABEL_STATUS, status.into())];
+            ::metrics::histogram!(QUERY_DURATION, &labels)
+        };
+        OperationMetrics {
+            ok:            duration("ok"),
+            error:         duration("error"),
+            rows_read:     ::metrics::counter!(ROWS_READ, &labels),
+            bytes_read:    ::metrics::counter!(BYTES_READ, &labels),
+            rows_written:  ::metrics::counter!(ROWS_WRITTEN, &labels),
+            bytes_written: ::metrics::counter!(BYTES_WRITTEN, &labels),
+        }
+    }
+}
+
+/// Metrics of a single connection, registered once when the connection is established.
+pub(crate) struct ConnectionMetrics {
+    #[cfg(feature = "metrics")]
+    in_flight:  ::metrics::Gauge,
+    #[cfg(feature = "metrics")]
+    operations: [OperationMetrics; 2],
+}
+
+impl ConnectionMetrics {
+    #[cfg_attr(not(feature = "metrics"), expect(unused_variables))]
+    pub(crate) fn new(client_id: u16, connection: &str) -> Self {
+        #[cfg(feature = "metrics")]
+        {
+            let client_id = client_id.to_string();
+            let in_flight = ::metrics::gauge!(
+                IN_FLIGHT,
+                LABEL_CLIENT_ID => client_id.clone(),
+                LABEL_CONNECTION => connection.to_string()
+            );
+            let operations = [
+                OperationMetrics::new(&client_id, OperationKind::Query),
+                OperationMetrics::new(&client_id, OperationKind::Insert),
+            ];
+            ConnectionMetrics { in_flight, operations }
+        }
+        #[cfg(not(feature = "metrics"))]
+        ConnectionMetrics {}
+    }
+
+    /// Set the number of queries queued or executing.
+    #[cfg_attr(not(feature = "metrics"), expect(unused_variables, clippy::unused_self))]
+    #[cfg_attr(feature = "metrics", expect(clippy::cast_precision_loss))]
+    pub(crate) fn in_flight(&self, count: usize) {
+        #[cfg(feature = "metrics")]
+        self.in_flight.set(count as f64);
+    }
+
+    /// Record a progress packet of an executing operation.
+    #[cfg_attr(not(feature = "metrics"), expect(unused_variables, clippy::unused_self))]
+    pub(crate) fn progress(&self, kind: OperationKind, progress: &Progress) {
+        #[cfg(feature = "metrics")]
+        {
+            let metrics = &self.operations[kind as usize];
+            metrics.rows_read.increment(progress.read_rows);
+            metrics.bytes_read.increment(progress.read_bytes);
+            metrics.rows_written.increment(progress.written_rows.unwrap_or_default());
+            metrics.bytes_written.increment(progress.written_bytes.unwrap_or_default());
+        }
+    }
+
+    /// Record the duration of an operation that started at `started`.
+    #[cfg_attr(not(feature = "metrics"), expect(unused_variables, clippy::unused_self))]
+    pub(crate) fn finished(&self, kind: OperationKind, started: Instant, ok: bool) {
+        #[cfg(feature = "metrics")]
+        {
+            let metrics = &self.operations[kind as usize];
+            let duration = if ok { &metrics.ok } else { &metrics.error };
+            duration.record(started.elapsed());
+        }
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl Drop for ConnectionMetrics {
+    fn drop(&mut self) { self.in_flight.set(0.0); }
+}
+
+/// Record the compression ratio of a block of `raw_len` bytes compressed to `compressed_len`.
+#[cfg_attr(not(feature = "metrics"), expect(unused_variables))]
+#[cfg_attr(feature = "metrics", expect(clippy::cast_precision_loss))]
+pub(crate) fn record_compression(
+    client_id: u16,
+    method: CompressionMethod,
+    raw_len: usize,
+    compressed_len: usize,
+) {
+    #[cfg(feature = "metrics")]
+    if compressed_len > 0 {
+        ::metrics::histogram!(
+            COMPRESSION_RATIO,
+            LABEL_CLIENT_ID => client_id.to_string(),
+            LABEL_METHOD => method.to_string()
+        )
+        .record(raw_len as f64 / compressed_len as f64);
+    }
+}
+
+/// Record the outcome of reconnecting a lost connection.
+#[cfg_attr(not(feature = "metrics"), expect(unused_variables))]
+pub(crate) fn record_reconnect(client_id: u16, ok: bool) {
+    #[cfg(feature = "metrics")]
+    ::metrics::counter!(
+        RECONNECTS,
+        LABEL_CLIENT_ID => client_id.to_string(),
+        LABEL_STATUS => if ok { "ok" } else { "failed" }
+    )
+    .increment(1);
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use std::time::Duration;
+
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder, Snapshotter};
+
+    use super::*;
+
+    /// Name, labels and value of a metric.
+    type Metric = (String, Vec<(String, String)>, DebugValue);
+
+    /// Values of the metrics. Taking a snapshot resets the values.
+    fn snapshot(snapshotter: &Snapshotter) -> Vec<Metric> {
+        snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .map(|(key, _, _, value)| {
+                let key = key.key();
+                let labels =
+                    key.labels().map(|l| (l.key().to_string(), l.value().to_string())).collect();
+                (key.name().to_string(), labels, value)
+            })
+            .collect()
+    }
+
+    fn value<'a>(
+        snapshot: &'a [Metric],
+        name: &str,
+        labels: &[(&str, &str)],
+    ) -> Option<&'a DebugValue> {
+        snapshot.iter().find_map(|(n, l, value)| {
+            let matches = n == name
+                && labels.iter().all(|(k, v)| l.iter().any(|(lk, lv)| lk == k && lv == v));
+            matches.then_some(value)
+        })
+    }
+
+    #[test]
+    fn test_connection_metrics() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        ::metrics::with_local_recorder(&recorder, || {
+            let metrics = ConnectionMetrics::new(7, "7.1");
+            metrics.in_flight(3);
+            let progress = Progress {
+                read_rows: 10,
+                read_bytes: 80,
+                written_rows: Some(2),
+                ..Default::default()
+            };
+            metrics.progress(OperationKind::Query, &progress);
+            metrics.progress(OperationKind::Query, &progress);
+            metrics.progress(OperationKind::Insert, &progress);
+            let started = Instant::now().checked_sub(Duration::from_secs(1)).unwrap();
+            metrics.finished(OperationKind::Insert, started, false);
+
+            let snapshot = snapshot(&snapshotter);
+            let query = [("client_id", "7"), ("operation", "query")];
+            assert_eq!(value(&snapshot, ROWS_READ, &query), Some(&DebugValue::Counter(20)));
+            assert_eq!(value(&snapshot, BYTES_READ, &query), Some(&DebugValue::Counter(160)));
+            assert_eq!(value(&snapshot, ROWS_WRITTEN, &query), Some(&DebugValue::Counter(4)));
+
+            let insert = [("operation", "insert"), ("status", "error")];
+            let Some(DebugValue::Histogram(durations)) = value(&snapshot, QUERY_DURATION, &insert)
+            else {
+                panic!("expected a histogram");
+            };
+            assert_eq!(durations.len(), 1);
+            assert!(durations[0].into_inner() >= 1.0);
+            let insert_ok = [("operation", "insert"), ("status", "ok")];
+            assert_eq!(
+                value(&snapshot, QUERY_DURATION, &insert_ok),
+                Some(&DebugValue::Histogram(vec![]))
+            );
+
+            let connection = [("client_id", "7"), ("connection", "7.1")];
+            assert_eq!(
+                value(&snapshot, IN_FLIGHT, &connection),
+                Some(&DebugValue::Gauge(3.0.into()))
+            );
+        });
+    }
+
+    #[test]
+    fn test_record_compression_and_reconnect() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        ::metrics::with_local_recorder(&recorder, || {
+            record_compression(1, CompressionMethod::LZ4, 400, 100);
+            record_compression(1, CompressionMethod::LZ4, 400, 0);
+            record_reconnect(1, true);
+            record_reconnect(1, false);
+            record_reconnect(1, false);
+
+            let snapshot = snapshot(&snapshotter);
+            let lz4 = [("client_id", "1"), ("method", "LZ4")];
+            assert_eq!(
+                value(&snapshot, COMPRESSION_RATIO, &lz4),
+                Some(&DebugValue::Histogram(vec![4.0.into()]))
+            );
+            let ok = [("status", "ok")];
+            assert_eq!(value(&snapshot, RECONNECTS, &ok), Some(&DebugValue::Counter(1)));
+            let failed = [("status", "failed")];
+            assert_eq!(value(&snapshot, RECONNECTS, &failed), Some(&DebugValue::Counter(2)));
+        });
+    }
+}
@@ -11,4 +11,4 @@ pub mod types;
 pub mod values;
 
 pub use self::error_codes::{ServerError, Severity};
-pub use self::protocol::CompressionMethod;
+pub use self::protocol::{CompressionMethod, CompressionOptions};
@@ -1,10 +1,15 @@
 use std::str::FromStr;
 
 use indexmap::IndexMap;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 use super::block_info::BlockInfo;
 use super::protocol::DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION;
+use super::sparse::{
+    SERIALIZATION_KIND_DEFAULT, SERIALIZATION_KIND_SPARSE, SparseDeserializeState,
+    expand_sparse_values, put_serialization_kind, put_sparse_offsets, read_serialization_kind,
+    read_serialization_kind_sync, read_sparse_offsets, read_sparse_offsets_sync, sparse_positions,
+    unsupported_kind, write_serialization_kind, write_sparse_offsets,
+};
 use crate::deserialize::ClickHouseNativeDeserializer;
 use crate::formats::protocol_data::ProtocolData;
 use crate::formats::{DeserializerState, SerializerState};
@@ -156,6 +161,17 @@ impl Block {
     }
 }
 
+/// Keep the values at `positions`, in order.
+fn take_values(values: Vec<Value>, positions: &[usize]) -> Vec<Value> {
+    let mut positions = positions.iter().peekable();
+    values
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| positions.next_if_eq(&i).is_some())
+        .map(|(_, value)| value)
+        .collect()
+}
+
 impl ProtocolData<Self, ()> for Block {
     type Options = ();
 
@@ -165,6 +181,7 @@ impl ProtocolData<Self, ()> for Block {
         revision: u64,
         _header: Option<&[(String, Type)]>,
         _options: (),
+        sparse_ratio: Option<f64>,
     ) -> Result<()> {
         if revision > 0 {
             self.info.write_async(writer).await?;
@@ -175,6 +192,10 @@ impl ProtocolData<Self, ()> for Block {
         #[allow(clippy::cast_possible_truncation)]
         let rows = self.rows as usize;
 
+        // Sparse serialization is negotiated along with custom serialization
+        let sparse_ratio = sparse_ratio
+            .filter(|_| revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION);
+
         writer.write_var_uint(columns as u64).await?;
         writer.write_var_uint(self.rows).await?;
 
@@ -194,13 +215,18 @@ impl ProtocolData<Self, ()> for Block {
             writer.write_string(&name).await?;
             writer.write_string(type_.to_string()).await?;
 
-            if self.rows > 0 {
-                if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION {
-                    writer.write_u8(0).await?;
-                }
+            let positions = sparse_positions(&type_, &values, sparse_ratio);
+            write_serialization_kind(writer, revision, positions.is_some()).await?;
 
-                let mut state = SerializerState::default();
+            if self.rows > 0 {
+                let mut state = SerializerState::default().with_revision(revision);
                 type_.serialize_prefix_async(writer, &mut state).await?;
+                let values = if let Some(positions) = positions {
+                    write_sparse_offsets(writer, &positions, rows).await?;
+                    take_values(values, &positions)
+                } else {
+                    values
+                };
                 type_.serialize_column(values, writer, &mut state).await?;
             }
         }
@@ -213,6 +239,7 @@ impl ProtocolData<Self, ()> for Block {
         revision: u64,
         _header: Option<&[(String, Type)]>,
         _options: (),
+        sparse_ratio: Option<f64>,
     ) -> Result<()> {
         if revision > 0 {
             self.info.write(writer)?;
@@ -223,6 +250,10 @@ impl ProtocolData<Self, ()> for Block {
         #[allow(clippy::cast_possible_truncation)]
         let rows = self.rows as usize;
 
+        // Sparse serialization is negotiated along with custom serialization
+        let sparse_ratio = sparse_ratio
+            .filter(|_| revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION);
+
         writer.put_var_uint(columns as u64)?;
         writer.put_var_uint(self.rows)?;
 
@@ -242,13 +273,18 @@ impl ProtocolData<Self, ()> for Block {
             writer.put_string(&name)?;
             writer.put_string(type_.to_string())?;
 
-            if self.rows > 0 {
-                if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION {
-                    writer.put_u8(0);
-                }
+            let positions = sparse_positions(&type_, &values, sparse_ratio);
+            put_serialization_kind(writer, revision, positions.is_some());
 
-                let mut state = SerializerState::default();
+            if self.rows > 0 {
+                let mut state = SerializerState::default().with_revision(revision);
                 type_.serialize_prefix(writer, &mut state);
+                let values = if let Some(positions) = positions {
+                    put_sparse_offsets(writer, &positions, rows)?;
+                    take_values(values, &positions)
+                } else {
+                    values
+                };
                 type_.serialize_column_sync(values, writer, &mut state)?;
             }
         }
@@ -286,47 +322,37 @@ impl ProtocolData<Self, ()> for Block {
                 .await
                 .inspect_err(|e| error!("reading column type (name {name}): {e}"))?;
 
-            // Check for sparse/custom serialization
-            // Protocol: if has_custom=1, next byte is KindStackBinarySerializationType
-            // See: https://github.com/ClickHouse/ClickHouse/blob/master/src/DataTypes/Serializations/SerializationInfo.cpp
-            let serialization_kind =
-                if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION {
-                    let has_custom = reader.read_u8().await? != 0;
-                    if has_custom {
-                        // KindStackBinarySerializationType enum:
-                        // 0 = DEFAULT, 1 = SPARSE, 2 = DETACHED, 3 = DETACHED_OVER_SPARSE,
-                        // 4 = REPLICATED, 5 = COMBINATION
-                        reader.read_u8().await?
-                    } else {
-                        0 // DEFAULT
-                    }
-                } else {
-                    0 // DEFAULT (no custom serialization support)
-                };
-
-            if serialization_kind != 0 {
-                // Sparse or other custom serialization not supported for native format
-                return Err(Error::Unimplemented(format!(
-                    "Custom serialization kind {serialization_kind} not yet supported for column \
-                     '{name}' (type: {type_name}). Workaround: Set \
-                     `ratio_of_defaults_for_sparse_serialization = 1.0` in your ClickHouse server \
-                     settings to disable sparse serialization, or use ArrowFormat instead of \
-                     NativeFormat."
-                )));
+            let kind = read_serialization_kind(reader, revision).await?;
+            if kind != SERIALIZATION_KIND_DEFAULT && kind != SERIALIZATION_KIND_SPARSE {
+                return Err(unsupported_kind(kind, &name, &type_name));
             }
 
             let type_ = Type::from_str(&type_name).inspect_err(|error| {
                 error!(?error, "Type deserialize failed: name={name}, type={type_name}");
             })?;
 
+            #[allow(clippy::cast_possible_truncation)]
             let mut row_data = if rows > 0 {
                 type_.deserialize_prefix_async(reader, state).await?;
 
-                #[allow(clippy::cast_possible_truncation)]
-                type_
-                    .deserialize_column(reader, rows as usize, state)
-                    .await
-                    .inspect_err(|e| error!("deserialize (name {name}): {e}"))?
+                if kind == SERIALIZATION_KIND_SPARSE {
+                    let offsets = read_sparse_offsets(
+                        reader,
+                        rows as usize,
+                        &mut SparseDeserializeState::default(),
+                    )
+                    .await?;
+                    let values = type_
+                        .deserialize_column(reader, offsets.len(), state)
+                        .await
+                        .inspect_err(|e| error!("sparse deserialize (name {name}): {e}"))?;
+                    expand_sparse_values(&type_, values, &offsets, rows as usize)?
+                } else {
+                    type_
+                        .deserialize_column(reader, rows as usize, state)
+                        .await
+                        .inspect_err(|e| error!("deserialize (name {name}): {e}"))?
+                }
             } else {
                 vec![]
             };
@@ -372,33 +398,9 @@ impl ProtocolData<Self, ()> for Block {
                     .to_vec(),
             )?;
 
-            // Check for sparse/custom serialization
-            // Protocol: if has_custom=1, next byte is KindStackBinarySerializationType
-            // See: https://github.com/ClickHouse/ClickHouse/blob/master/src/DataTypes/Serializations/SerializationInfo.cpp
-            let serialization_kind =
-                if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION {
-                    let has_custom = reader.try_get_u8()? != 0;
-                    if has_custom {
-                        // KindStackBinarySerializationType enum:
-                        // 0 = DEFAULT, 1 = SPARSE, 2 = DETACHED, 3 = DETACHED_OVER_SPARSE,
-                        // 4 = REPLICATED, 5 = COMBINATION
-                        reader.try_get_u8()?
-                    } else {
-                        0 // DEFAULT
-                    }
-                } else {
-                    0 // DEFAULT (no custom serialization support)
-                };
-
-            if serialization_kind != 0 {
-                // Sparse or other custom serialization not supported for native format
-                return Err(Error::Unimplemented(format!(
-                    "Custom serialization kind {serialization_kind} not yet supported for column \
-                     '{name}' (type: {type_name}). Workaround: Set \
-                     `ratio_of_defaults_for_sparse_serialization = 1.0` in your ClickHouse server \
-                     settings to disable sparse serialization, or use ArrowFormat instead of \
-                     NativeFormat."
-                )));
+            let kind = read_serialization_kind_sync(reader, revision)?;
+            if kind != SERIALIZATION_KIND_DEFAULT && kind != SERIALIZATION_KIND_SPARSE {
+                return Err(unsupported_kind(kind, &name, &type_name));
             }
 
             let type_ = Type::from_str(&type_name).inspect_err(|error| {
@@ -408,9 +410,21 @@ impl ProtocolData<Self, ()> for Block {
             #[allow(clippy::cast_possible_truncation)]
             let mut row_data = if rows > 0 {
                 type_.deserialize_prefix(reader)?;
-                type_
-                    .deserialize_column_sync(reader, rows as usize, state)
-                    .inspect_err(|e| error!("deserialize (name {name}): {e}"))?
+                if kind == SERIALIZATION_KIND_SPARSE {
+                    let offsets = read_sparse_offsets_sync(
+                        reader,
+                        rows as usize,
+                        &mut SparseDeserializeState::default(),
+                    )?;
+                    let values = type_
+                        .deserialize_column_sync(reader, offsets.len(), state)
+                        .inspect_err(|e| error!("sparse deserialize (name {name}): {e}"))?;
+                    expand_sparse_values(&type_, values, &offsets, rows as usize)?
+                } else {
+                    type_
+                        .deserialize_column_sync(reader, rows as usize, state)
+                        .inspect_err(|e| error!("deserialize (name {name}): {e}"))?
+                }
             } else {
                 vec![]
             };
@@ -422,3 +436,41 @@ impl ProtocolData<Self, ()> for Block {
         Ok(block)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests that the custom serialization flag follows each column's type on revisions that
+    /// support it, including blocks without rows such as insert headers.
+    #[tokio::test]
+    async fn test_write_custom_serialization_flag() {
+        let block = |rows: u64| Block {
+            rows,
+            column_types: vec![("n".into(), Type::UInt8)],
+            column_data: (0..rows).map(|_| Value::UInt8(7)).collect(),
+            ..Default::default()
+        };
+        let revisions = [
+            (DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION - 1, &[][..]),
+            (DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION, &[0][..]),
+        ];
+        for rows in [0_u8, 1] {
+            for (revision, flag) in revisions {
+                let data = vec![7; usize::from(rows)];
+                let expected = [&[1, rows, 1, b'n', 5][..], b"UInt8", flag, &data].concat();
+
+                let mut bytes = Vec::new();
+                block(rows.into()).write(&mut bytes, revision, None, (), None).unwrap();
+                assert!(bytes.ends_with(&expected), "rows {rows}, revision {revision}: {bytes:?}");
+
+                let mut async_bytes = Vec::new();
+                block(rows.into())
+                    .write_async(&mut async_bytes, revision, None, (), None)
+                    .await
+                    .unwrap();
+                assert_eq!(bytes, async_bytes);
+            }
+        }
+    }
+}
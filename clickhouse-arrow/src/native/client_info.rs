@@ -1,5 +1,4 @@
 use tokio::io::AsyncWriteExt;
-use uuid::Uuid;
 
 use super::protocol::{
     DBMS_MIN_REVISION_WITH_JWT_IN_INTERSERVER, DBMS_MIN_REVISION_WITH_QUERY_AND_LINE_NUMBERS,
@@ -13,6 +12,7 @@ use crate::native::protocol::{
     DBMS_TCP_PROTOCOL_VERSION,
 };
 use crate::prelude::*;
+use crate::telemetry::TraceParent;
 
 #[repr(u8)]
 #[derive(PartialEq, Clone, Copy, Debug)]
@@ -25,12 +25,23 @@ pub(crate) enum QueryKind {
 
 #[derive(Debug)]
 pub(crate) struct OpenTelemetry<'a> {
-    trace_id:    Uuid,
+    trace_id:    u128,
     span_id:     u64,
     tracestate:  &'a str,
     trace_flags: u8,
 }
 
+impl<'a> From<&'a TraceParent> for OpenTelemetry<'a> {
+    fn from(trace: &'a TraceParent) -> Self {
+        OpenTelemetry {
+            trace_id:    trace.trace_id,
+            span_id:     trace.span_id,
+            tracestate:  &trace.tracestate,
+            trace_flags: trace.trace_flags,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct ClientInfo<'a> {
     pub kind:                        QueryKind,
@@ -118,9 +129,13 @@ impl ClientInfo<'_> {
         }
         if revision >= DBMS_MIN_REVISION_WITH_OPENTELEMETRY {
             if let Some(telemetry) = &self.open_telemetry {
+                // The trace ID is a UUID, high then low half
+                #[expect(clippy::cast_possible_truncation)]
+                let (high, low) = ((telemetry.trace_id >> 64) as u64, telemetry.trace_id as u64);
                 to.write_u8(1u8).await?;
-                to.write_all(&telemetry.trace_id.as_bytes()[..]).await?;
-                to.write_u64(telemetry.span_id).await?;
+                to.write_u64_le(high).await?;
+                to.write_u64_le(low).await?;
+                to.write_u64_le(telemetry.span_id).await?;
                 to.write_string(telemetry.tracestate).await?;
                 to.write_u8(telemetry.trace_flags).await?;
             } else {
@@ -147,3 +162,36 @@ impl ClientInfo<'_> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_write_open_telemetry() {
+        let trace = TraceParent::new(
+            0x0102_0304_0506_0708_090a_0b0c_0d0e_0f10,
+            0x1112_1314_1516_1718,
+            true,
+        )
+        .with_tracestate("a=b");
+        let info = ClientInfo { open_telemetry: Some((&trace).into()), ..Default::default() };
+        let mut buffer = Vec::new();
+        info.write(&mut buffer, DBMS_MIN_REVISION_WITH_OPENTELEMETRY).await.unwrap();
+
+        let mut expected = vec![1u8];
+        expected.extend(0x0102_0304_0506_0708_u64.to_le_bytes());
+        expected.extend(0x090a_0b0c_0d0e_0f10_u64.to_le_bytes());
+        expected.extend(0x1112_1314_1516_1718_u64.to_le_bytes());
+        expected.extend([3, b'a', b'=', b'b', TraceParent::SAMPLED]);
+        assert!(buffer.ends_with(&expected));
+
+        let mut without = Vec::new();
+        ClientInfo::default()
+            .write(&mut without, DBMS_MIN_REVISION_WITH_OPENTELEMETRY)
+            .await
+            .unwrap();
+        assert_eq!(without.last(), Some(&0));
+        assert_eq!(buffer.len() - without.len(), expected.len() - 1);
+    }
+}
@@ -171,6 +171,7 @@ impl FromSql for String {
                 | Type::Binary
                 | Type::FixedSizedBinary(_)
                 | Type::Object
+                | Type::Json(_)
         ) {
             return Err(unexpected_type(type_));
         }
@@ -218,7 +219,7 @@ impl<T: FromSql + Hash + Eq, Y: FromSql, S: ::std::hash::BuildHasher + Default>
         match value {
             Value::Map(x, y) => {
                 let mut out = HashMap::default();
-                for (x, y) in x.into_iter().zip(y.into_iter()) {
+                for (x, y) in x.into_iter().zip(y) {
                     drop(out.insert(T::from_sql(x_type, x)?, Y::from_sql(y_type, y)?));
                 }
                 Ok(out)
@@ -239,7 +240,7 @@ impl<T: FromSql + Ord, Y: FromSql> FromSql for BTreeMap<T, Y> {
         match value {
             Value::Map(x, y) => {
                 let mut out = BTreeMap::new();
-                for (x, y) in x.into_iter().zip(y.into_iter()) {
+                for (x, y) in x.into_iter().zip(y) {
                     drop(out.insert(T::from_sql(x_type, x)?, Y::from_sql(y_type, y)?));
                 }
                 Ok(out)
@@ -262,7 +263,7 @@ impl<T: FromSql + Hash + Eq, Y: FromSql, S: ::std::hash::BuildHasher + Default>
         match value {
             Value::Map(x, y) => {
                 let mut out = IndexMap::<T, Y, S>::with_capacity_and_hasher(x.len(), S::default());
-                for (x, y) in x.into_iter().zip(y.into_iter()) {
+                for (x, y) in x.into_iter().zip(y) {
                     drop(out.insert(T::from_sql(x_type, x)?, Y::from_sql(y_type, y)?));
                 }
                 Ok(out)
@@ -275,7 +276,7 @@ impl<T: FromSql + Hash + Eq, Y: FromSql, S: ::std::hash::BuildHasher + Default>
 #[cfg(feature = "serde")]
 impl FromSql for serde_json::Value {
     fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::Object | Type::String) {
+        if !matches!(type_, Type::Object | Type::Json(_) | Type::String) {
             return Err(unexpected_type(type_));
         }
         match value {
@@ -7,7 +7,7 @@ use super::block::Block;
 use super::error_codes::map_exception_to_error;
 use super::progress::Progress;
 use crate::prelude::*;
-use crate::{Error, FxIndexMap, Result, ServerError};
+use crate::{Error, Result, ServerError};
 
 pub(crate) const DBMS_MIN_REVISION_WITH_CLIENT_INFO: u64 = 54032;
 pub(crate) const DBMS_MIN_REVISION_WITH_SERVER_TIMEZONE: u64 = 54058;
@@ -45,15 +45,14 @@ pub(crate) const DBMS_MIN_PROTOCOL_VERSION_WITH_TOTAL_BYTES_IN_PROGRESS: u64 = 5
 // pub(crate) const DBMS_MIN_REVISION_WITH_SPARSE_SERIALIZATION: u64 = 54465;
 // pub(crate) const DBMS_MIN_REVISION_WITH_SSH_AUTHENTICATION: u64 = 54466;
 /// Send read-only flag for Replicated tables as well
-// pub(crate) const DBMS_MIN_REVISION_WITH_TABLE_READ_ONLY_CHECK: u64 = 54467;
+pub(crate) const DBMS_MIN_REVISION_WITH_TABLE_READ_ONLY_CHECK: u64 = 54467;
 // pub(crate) const DBMS_MIN_REVISION_WITH_SYSTEM_KEYWORDS_TABLE: u64 = 54468;
 pub(crate) const DBMS_MIN_REVISION_WITH_ROWS_BEFORE_AGGREGATION: u64 = 54469;
 pub(crate) const DBMS_MIN_PROTOCOL_VERSION_WITH_CHUNKED_PACKETS: u64 = 54470;
 pub(crate) const DBMS_MIN_REVISION_WITH_VERSIONED_PARALLEL_REPLICAS_PROTOCOL: u64 = 54471;
 /// Push externally granted roles to other nodes
 pub(crate) const DBMS_MIN_PROTOCOL_VERSION_WITH_INTERSERVER_EXTERNALLY_GRANTED_ROLES: u64 = 54472;
-// TODO: Implement other types of json deserialization
-// pub(crate) const DBMS_MIN_REVISION_WITH_V2_DYNAMIC_AND_JSON_SERIALIZATION: u64 = 54473;
+pub(crate) const DBMS_MIN_REVISION_WITH_V2_DYNAMIC_AND_JSON_SERIALIZATION: u64 = 54473;
 pub(crate) const DBMS_MIN_REVISION_WITH_SERVER_SETTINGS: u64 = 54474;
 pub(crate) const DBMS_MIN_REVISION_WITH_QUERY_AND_LINE_NUMBERS: u64 = 54475;
 pub(crate) const DBMS_MIN_REVISION_WITH_JWT_IN_INTERSERVER: u64 = 54476;
@@ -246,17 +245,21 @@ impl ServerException {
     pub(crate) fn emit(self) -> ServerError { map_exception_to_error(self) }
 }
 
-#[expect(unused)]
-#[derive(Debug, Clone)]
-pub(crate) struct ProfileInfo {
-    pub(crate) rows:                         u64,
-    pub(crate) blocks:                       u64,
-    pub(crate) bytes:                        u64,
-    pub(crate) applied_limit:                bool,
-    pub(crate) rows_before_limit:            u64,
-    pub(crate) calculated_rows_before_limit: bool,
-    pub(crate) applied_aggregation:          bool,
-    pub(crate) rows_before_aggregation:      u64,
+/// Execution summary sent by `ClickHouse` once a query's result has been produced.
+///
+/// `rows_before_limit` is the number of rows the query would have returned without `LIMIT`,
+/// which is the total count needed for pagination. It is a lower bound ("at least") unless
+/// `calculated_rows_before_limit` is set, and only meaningful when `applied_limit` is set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProfileInfo {
+    pub rows:                         u64,
+    pub blocks:                       u64,
+    pub bytes:                        u64,
+    pub applied_limit:                bool,
+    pub rows_before_limit:            u64,
+    pub calculated_rows_before_limit: bool,
+    pub applied_aggregation:          bool,
+    pub rows_before_aggregation:      u64,
 }
 
 #[expect(unused)]
@@ -266,31 +269,108 @@ pub(crate) struct TableColumns {
     pub(crate) description: String,
 }
 
-#[expect(unused)]
-#[derive(Debug, Clone)]
-pub(crate) struct TableStatus {
-    pub(crate) is_replicated:  bool,
-    pub(crate) absolute_delay: u32,
+/// Replication status of a table, as reported by [`crate::Client::tables_status`].
+///
+/// This is the same check the `Distributed` engine uses to skip stale replicas, see
+/// `max_replica_delay_for_distributed_queries`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TableStatus {
+    pub database:       String,
+    pub table:          String,
+    /// Whether the table uses a `Replicated*` engine. The other fields are only set if so.
+    pub is_replicated:  bool,
+    /// Seconds the replica lags behind the most recent replica of the table.
+    pub absolute_delay: u32,
+    /// Whether the replica is read-only, e.g. lost its `ZooKeeper` session. Not reported by older
+    /// servers.
+    pub is_readonly:    bool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub(crate) struct TablesStatusResponse {
-    pub(crate) database_tables: FxIndexMap<String, FxIndexMap<String, TableStatus>>,
+    pub(crate) tables: Vec<TableStatus>,
+}
+
+/// The minimum level of server log lines sent to the client, the `send_logs_level` setting.
+#[derive(Clone, Default, Copy, Debug, PartialEq, Eq, Hash, AsRefStr)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[strum(serialize_all = "lowercase")]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum ServerLogLevel {
+    #[default]
+    None,
+    Fatal,
+    Error,
+    Warning,
+    Information,
+    Debug,
+    Trace,
+    Test,
+}
+
+impl From<ServerLogLevel> for SettingValue {
+    fn from(level: ServerLogLevel) -> Self { SettingValue::String(level.as_ref().to_string()) }
 }
 
+/// A server log line, sent while a query runs when `send_logs_level` is enabled.
 #[derive(Debug, Clone, Default)]
-pub(crate) struct LogData {
-    pub(crate) time:       String,
-    pub(crate) time_micro: u32,
-    pub(crate) host_name:  String,
-    pub(crate) query_id:   String,
-    pub(crate) thread_id:  u64,
-    pub(crate) priority:   i8,
-    pub(crate) source:     String,
-    pub(crate) text:       String,
+#[non_exhaustive]
+pub struct LogData {
+    /// Time the line was logged, to the second.
+    pub time:       String,
+    /// Microseconds within the second of `time`.
+    pub time_micro: u32,
+    /// Host name of the server that logged the line.
+    pub host_name:  String,
+    /// Id of the query the line belongs to.
+    pub query_id:   String,
+    /// Id of the server thread that logged the line.
+    pub thread_id:  u64,
+    /// Poco message priority, from 1 (fatal) to 8 (trace).
+    pub priority:   i8,
+    /// Logger the line comes from, ie `executeQuery`.
+    pub source:     String,
+    /// The logged message.
+    pub text:       String,
 }
 
 impl LogData {
+    /// The `tracing` level matching the line's priority.
+    pub fn level(&self) -> tracing::Level {
+        match self.priority {
+            ..=3 => tracing::Level::ERROR,
+            4 => tracing::Level::WARN,
+            5 | 6 => tracing::Level::INFO,
+            7 => tracing::Level::DEBUG,
+            _ => tracing::Level::TRACE,
+        }
+    }
+
+    /// Re-emit the line as a `tracing` event at its matching level.
+    pub(crate) fn emit(&self, qid: Qid) {
+        macro_rules! emit {
+            ($level:expr) => {
+                tracing::event!(
+                    target: "clickhouse_arrow::server",
+                    $level,
+                    { ATT_QID } = %qid,
+                    host = %self.host_name,
+                    thread_id = self.thread_id,
+                    source = %self.source,
+                    "{}",
+                    self.text
+                )
+            };
+        }
+        match self.level() {
+            tracing::Level::ERROR => emit!(tracing::Level::ERROR),
+            tracing::Level::WARN => emit!(tracing::Level::WARN),
+            tracing::Level::INFO => emit!(tracing::Level::INFO),
+            tracing::Level::DEBUG => emit!(tracing::Level::DEBUG),
+            tracing::Level::TRACE => emit!(tracing::Level::TRACE),
+        }
+    }
+
     fn update_value(&mut self, name: &str, value: Value, type_: &Type) -> Result<()> {
         match name {
             "time" => self.time = value.to_string(),
@@ -445,10 +525,15 @@ impl FromStr for ChunkedProtocolMode {
 
 #[derive(Clone, Default, Copy, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub enum CompressionMethod {
     None,
     #[default]
     LZ4,
+    /// LZ4 in high compression mode: slower to compress, with a better ratio. Decompression is
+    /// as fast as `LZ4`. Requires the `lz4hc` feature.
+    #[cfg(feature = "lz4hc")]
+    LZ4HC,
     ZSTD,
 }
 
@@ -457,15 +542,59 @@ impl CompressionMethod {
         match self {
             CompressionMethod::None => 0x02,
             CompressionMethod::LZ4 => 0x82,
+            #[cfg(feature = "lz4hc")]
+            CompressionMethod::LZ4HC => 0x82,
             CompressionMethod::ZSTD => 0x90,
         }
     }
 }
 
+/// Tuning of the compression of data sent to `ClickHouse`, see [`CompressionMethod`].
+///
+/// # Examples
+/// ```rust,ignore
+/// use clickhouse_arrow::prelude::*;
+///
+/// // High ratio for slow links, in blocks of at most 1 MiB
+/// let options = CompressionOptions::new().with_level(9).with_max_block_size(1024 * 1024);
+/// ```
+#[derive(Clone, Default, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CompressionOptions {
+    /// Compression level. `ZSTD` accepts levels up to 22 (default 1), negative levels trade ratio
+    /// for speed. `LZ4HC` accepts 1 to 12 (default 9). Ignored by `LZ4`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub level:          Option<i32>,
+    /// Maximum size of the data compressed into a single block, larger data is split into
+    /// several blocks (see the `max_compress_block_size` setting of `ClickHouse`). By default
+    /// each data packet is compressed into one block.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_block_size: Option<usize>,
+}
+
+impl CompressionOptions {
+    #[must_use]
+    pub fn new() -> Self { Self::default() }
+
+    #[must_use]
+    pub fn with_level(mut self, level: i32) -> Self {
+        self.level = Some(level);
+        self
+    }
+
+    #[must_use]
+    pub fn with_max_block_size(mut self, size: usize) -> Self {
+        self.max_block_size = Some(size);
+        self
+    }
+}
+
 impl From<&str> for CompressionMethod {
     fn from(value: &str) -> Self {
         match value {
             "lz4" | "LZ4" => CompressionMethod::LZ4,
+            #[cfg(feature = "lz4hc")]
+            "lz4hc" | "LZ4HC" => CompressionMethod::LZ4HC,
             "zstd" | "ZSTD" => CompressionMethod::ZSTD,
             _ => CompressionMethod::None,
         }
@@ -477,6 +606,8 @@ impl std::fmt::Display for CompressionMethod {
         match self {
             CompressionMethod::None => write!(f, "None"),
             CompressionMethod::LZ4 => write!(f, "LZ4"),
+            #[cfg(feature = "lz4hc")]
+            CompressionMethod::LZ4HC => write!(f, "LZ4HC"),
             CompressionMethod::ZSTD => write!(f, "ZSTD"),
         }
     }
@@ -500,7 +631,30 @@ impl AsRef<str> for CompressionMethod {
         match self {
             CompressionMethod::None => "None",
             CompressionMethod::LZ4 => "LZ4",
+            #[cfg(feature = "lz4hc")]
+            CompressionMethod::LZ4HC => "LZ4HC",
             CompressionMethod::ZSTD => "ZSTD",
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_data_level() {
+        let level = |priority| LogData { priority, ..Default::default() }.level();
+        assert_eq!(level(1), tracing::Level::ERROR);
+        assert_eq!(level(4), tracing::Level::WARN);
+        assert_eq!(level(6), tracing::Level::INFO);
+        assert_eq!(level(7), tracing::Level::DEBUG);
+        assert_eq!(level(8), tracing::Level::TRACE);
+    }
+
+    #[test]
+    fn test_server_log_level_setting() {
+        assert_eq!(SettingValue::from(ServerLogLevel::Information).to_string(), "information");
+        assert_eq!(SettingValue::from(ServerLogLevel::None).to_string(), "none");
+    }
+}
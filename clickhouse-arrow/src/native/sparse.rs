@@ -8,18 +8,96 @@
 //! 2. Values: Only the non-default values
 //!
 //! Example: `[0, 0, 5, 0, 3, 0, 0, 0]` → offsets [2, 1, 3|END], values [5, 3]
+//!
+//! The serialization of each column is announced after its type, from protocol revision
+//! `DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION`: a `has_custom` byte, followed by the
+//! serialization kind if set. Inserts use sparse serialization for columns whose ratio of default
+//! values reaches the configured threshold.
 
 use std::sync::Arc;
 
 use arrow::array::*;
+use arrow::buffer::MutableBuffer;
+use arrow::compute::kernels::{boolean, cmp, length};
 use arrow::datatypes::*;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
-use crate::Result;
-use crate::io::ClickHouseRead;
+use super::protocol::DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION;
+use crate::io::{ClickHouseBytesRead, ClickHouseBytesWrite, ClickHouseRead, ClickHouseWrite};
+use crate::{Error, Result, Type, Value};
 
 /// End-of-granule marker (bit 62). When set, this is the final VarUInt in the offsets stream.
 pub(crate) const END_OF_GRANULE_FLAG: u64 = 1 << 62;
 
+// KindStackBinarySerializationType:
+// 0 = DEFAULT, 1 = SPARSE, 2 = DETACHED, 3 = DETACHED_OVER_SPARSE, 4 = REPLICATED, 5 = COMBINATION
+// See: https://github.com/ClickHouse/ClickHouse/blob/master/src/DataTypes/Serializations/SerializationInfo.cpp
+pub(crate) const SERIALIZATION_KIND_DEFAULT: u8 = 0;
+pub(crate) const SERIALIZATION_KIND_SPARSE: u8 = 1;
+pub(crate) const SERIALIZATION_KIND_COMBINATION: u8 = 5;
+
+/// Read the serialization kind of a column, sent after its type.
+///
+/// A combination is reported as sparse if its stack contains sparse serialization, as default if
+/// it only holds default serialization, otherwise as its first other kind.
+pub(crate) async fn read_serialization_kind<R: ClickHouseRead>(
+    reader: &mut R,
+    revision: u64,
+) -> Result<u8> {
+    if revision < DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION
+        || reader.read_u8().await? == 0
+    {
+        return Ok(SERIALIZATION_KIND_DEFAULT);
+    }
+    let kind = reader.read_u8().await?;
+    if kind != SERIALIZATION_KIND_COMBINATION {
+        return Ok(kind);
+    }
+    let count = reader.read_var_uint().await?;
+    let mut stack = Vec::new();
+    for _ in 0..count {
+        stack.push(reader.read_u8().await?);
+    }
+    Ok(combined_kind(&stack))
+}
+
+/// Sync version of [`read_serialization_kind`] for `bytes::Buf` readers.
+pub(crate) fn read_serialization_kind_sync<R: ClickHouseBytesRead>(
+    reader: &mut R,
+    revision: u64,
+) -> Result<u8> {
+    if revision < DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION || reader.try_get_u8()? == 0 {
+        return Ok(SERIALIZATION_KIND_DEFAULT);
+    }
+    let kind = reader.try_get_u8()?;
+    if kind != SERIALIZATION_KIND_COMBINATION {
+        return Ok(kind);
+    }
+    let count = reader.try_get_var_uint()?;
+    let mut stack = Vec::new();
+    for _ in 0..count {
+        stack.push(reader.try_get_u8()?);
+    }
+    Ok(combined_kind(&stack))
+}
+
+fn combined_kind(stack: &[u8]) -> u8 {
+    if stack.contains(&SERIALIZATION_KIND_SPARSE) {
+        SERIALIZATION_KIND_SPARSE
+    } else {
+        stack.iter().copied().find(|k| *k != SERIALIZATION_KIND_DEFAULT).unwrap_or_default()
+    }
+}
+
+/// Error for serialization kinds that can't be read.
+pub(crate) fn unsupported_kind(kind: u8, name: &str, type_name: &str) -> Error {
+    Error::Unimplemented(format!(
+        "Custom serialization kind {kind} not yet supported for column '{name}' (type: \
+         {type_name}). Workaround: Set `ratio_of_defaults_for_sparse_serialization = 1.0` in your \
+         ClickHouse server settings to disable sparse serialization."
+    ))
+}
+
 /// State for sparse deserialisation across multiple reads.
 #[derive(Debug, Default, Clone)]
 pub(crate) struct SparseDeserializeState {
@@ -93,9 +171,8 @@ pub(crate) async fn read_sparse_offsets<R: ClickHouseRead>(
 }
 
 /// Sync version of read_sparse_offsets for bytes::Buf readers.
-#[allow(dead_code)]
 #[allow(clippy::cast_possible_truncation)] // row counts fit in usize
-pub(crate) fn read_sparse_offsets_sync<R: crate::io::ClickHouseBytesRead>(
+pub(crate) fn read_sparse_offsets_sync<R: ClickHouseBytesRead>(
     reader: &mut R,
     num_rows: usize,
     state: &mut SparseDeserializeState,
@@ -153,6 +230,201 @@ pub(crate) fn read_sparse_offsets_sync<R: crate::io::ClickHouseBytesRead>(
     Ok(offsets)
 }
 
+/// Expand the non-default values of a sparse column to `total_rows`, filling the other rows with
+/// the default value of the type.
+pub(crate) fn expand_sparse_values(
+    type_: &Type,
+    values: Vec<Value>,
+    offsets: &[usize],
+    total_rows: usize,
+) -> Result<Vec<Value>> {
+    if values.len() != offsets.len() {
+        return Err(Error::DeserializeError(format!(
+            "sparse column holds {} values for {} offsets",
+            values.len(),
+            offsets.len()
+        )));
+    }
+    let default = match type_ {
+        Type::FixedSizedString(n) | Type::FixedSizedBinary(n) => Value::String(vec![0; *n]),
+        _ => type_.default_value(),
+    };
+    let mut out = vec![default; total_rows];
+    for (offset, value) in offsets.iter().zip(values) {
+        out[*offset] = value;
+    }
+    Ok(out)
+}
+
+/// Whether `ClickHouse` supports sparse serialization of columns of the type.
+pub(crate) fn supports_sparse(type_: &Type) -> bool {
+    matches!(
+        type_,
+        Type::Int8
+            | Type::Int16
+            | Type::Int32
+            | Type::Int64
+            | Type::Int128
+            | Type::Int256
+            | Type::UInt8
+            | Type::UInt16
+            | Type::UInt32
+            | Type::UInt64
+            | Type::UInt128
+            | Type::UInt256
+            | Type::Float32
+            | Type::Float64
+            | Type::Decimal32(_)
+            | Type::Decimal64(_)
+            | Type::Decimal128(_)
+            | Type::Decimal256(_)
+            | Type::String
+            | Type::Binary
+            | Type::Uuid
+            | Type::Date
+            | Type::Date32
+            | Type::DateTime(_)
+            | Type::DateTime64(..)
+            | Type::Ipv4
+            | Type::Ipv6
+    )
+}
+
+/// Whether a column with `defaults` default values out of `rows` is inserted sparse. As with the
+/// server setting `ratio_of_defaults_for_sparse_serialization`, a ratio of 1.0 or more disables
+/// sparse serialization.
+#[expect(clippy::cast_precision_loss)]
+fn use_sparse(defaults: usize, rows: usize, ratio: f64) -> bool {
+    rows > 0 && ratio < 1.0 && defaults as f64 >= ratio * rows as f64
+}
+
+/// Positions of the non-default values of a column, if it is inserted with sparse serialization.
+pub(crate) fn sparse_positions(
+    type_: &Type,
+    values: &[Value],
+    ratio: Option<f64>,
+) -> Option<Vec<usize>> {
+    let ratio = ratio.filter(|_| supports_sparse(type_))?;
+    let default = type_.default_value();
+    let positions = values
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| **v != default)
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    use_sparse(values.len() - positions.len(), values.len(), ratio).then_some(positions)
+}
+
+/// Positions and values of the non-default rows of an array, if it is inserted with sparse
+/// serialization. Nulls count as default values.
+pub(crate) fn sparse_array(
+    type_: &Type,
+    array: &ArrayRef,
+    ratio: Option<f64>,
+) -> Result<Option<(Vec<usize>, ArrayRef)>> {
+    let Some(ratio) = ratio.filter(|_| supports_sparse(type_)) else {
+        return Ok(None);
+    };
+    let Some(defaults) = default_mask(array)? else {
+        return Ok(None);
+    };
+    let defaults = boolean::or_kleene(&defaults, &arrow::compute::is_null(array)?)?;
+    if !use_sparse(defaults.true_count(), array.len(), ratio) {
+        return Ok(None);
+    }
+    let non_defaults = boolean::not(&defaults)?;
+    let positions = non_defaults.values().set_indices().collect();
+    Ok(Some((positions, arrow::compute::filter(array, &non_defaults)?)))
+}
+
+/// Which values of an array are the default value of its type, `None` for unsupported types.
+fn default_mask(array: &ArrayRef) -> Result<Option<BooleanArray>> {
+    let data_type = array.data_type();
+    let (values, width) = match data_type {
+        DataType::Boolean => return Ok(Some(boolean::not(array.as_boolean())?)),
+        DataType::Utf8 | DataType::Binary => (length::length(array)?, 4),
+        DataType::LargeUtf8 | DataType::LargeBinary => (length::length(array)?, 8),
+        _ if data_type.is_primitive() => {
+            (Arc::clone(array), data_type.primitive_width().unwrap_or(0))
+        }
+        _ => return Ok(None),
+    };
+    let zero = ArrayData::try_new(
+        values.data_type().clone(),
+        1,
+        None,
+        0,
+        vec![MutableBuffer::from_len_zeroed(width).into()],
+        vec![],
+    )?;
+    Ok(cmp::eq(&values, &Scalar::new(make_array(zero))).ok())
+}
+
+/// Group sizes of the offsets stream for the non-default `positions` out of `rows`.
+fn sparse_groups(positions: &[usize], rows: usize) -> Vec<u64> {
+    let mut groups = Vec::with_capacity(positions.len() + 1);
+    let mut start = 0;
+    for &position in positions {
+        groups.push((position - start) as u64);
+        start = position + 1;
+    }
+    groups.push((rows - start) as u64 | END_OF_GRANULE_FLAG);
+    groups
+}
+
+/// Write the offsets stream of a sparse column.
+pub(crate) async fn write_sparse_offsets<W: ClickHouseWrite>(
+    writer: &mut W,
+    positions: &[usize],
+    rows: usize,
+) -> Result<()> {
+    for group in sparse_groups(positions, rows) {
+        writer.write_var_uint(group).await?;
+    }
+    Ok(())
+}
+
+/// Sync version of [`write_sparse_offsets`] for `bytes::BufMut` writers.
+pub(crate) fn put_sparse_offsets<W: ClickHouseBytesWrite>(
+    writer: &mut W,
+    positions: &[usize],
+    rows: usize,
+) -> Result<()> {
+    for group in sparse_groups(positions, rows) {
+        writer.put_var_uint(group)?;
+    }
+    Ok(())
+}
+
+/// Write the serialization kind of a column, sent after its type.
+pub(crate) async fn write_serialization_kind<W: ClickHouseWrite>(
+    writer: &mut W,
+    revision: u64,
+    sparse: bool,
+) -> Result<()> {
+    if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION {
+        writer.write_u8(u8::from(sparse)).await?;
+        if sparse {
+            writer.write_u8(SERIALIZATION_KIND_SPARSE).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Sync version of [`write_serialization_kind`] for `bytes::BufMut` writers.
+pub(crate) fn put_serialization_kind<W: ClickHouseBytesWrite>(
+    writer: &mut W,
+    revision: u64,
+    sparse: bool,
+) {
+    if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION {
+        writer.put_u8(u8::from(sparse));
+        if sparse {
+            writer.put_u8(SERIALIZATION_KIND_SPARSE);
+        }
+    }
+}
+
 /// Expand sparse array to full size, filling non-offset positions with defaults.
 pub(crate) fn expand_sparse_array(
     sparse_array: &ArrayRef,
@@ -204,7 +476,7 @@ pub(crate) fn expand_sparse_array(
             expand_fixed_size_binary(sparse_array, offsets, total_rows, *size)
         }
         _ => {
-            return Err(crate::Error::Unimplemented(format!(
+            return Err(Error::Unimplemented(format!(
                 "Sparse expansion not implemented for type: {data_type:?}"
             )));
         }
@@ -438,6 +710,86 @@ mod tests {
         assert_eq!(offsets, vec![0, 3]);
     }
 
+    #[test]
+    fn test_sparse_offsets_roundtrip() {
+        for (positions, rows) in [(vec![2, 4], 8), (vec![], 4), (vec![0, 1, 2], 3), (vec![0, 3], 4)]
+        {
+            let mut data = Vec::new();
+            put_sparse_offsets(&mut data, &positions, rows).unwrap();
+            let mut bytes = Bytes::from(data);
+            let mut state = SparseDeserializeState::default();
+            let offsets = read_sparse_offsets_sync(&mut bytes, rows, &mut state).unwrap();
+            assert_eq!(offsets, positions);
+            assert!(bytes.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_sparse_positions_threshold() {
+        let values = [0_u32, 7, 0, 0].map(Value::UInt32);
+        assert_eq!(sparse_positions(&Type::UInt32, &values, Some(0.75)), Some(vec![1]));
+        assert_eq!(sparse_positions(&Type::UInt32, &values, Some(0.9)), None);
+        assert_eq!(sparse_positions(&Type::UInt32, &values, Some(1.0)), None);
+        assert_eq!(sparse_positions(&Type::UInt32, &values, None), None);
+        let nullable = Type::Nullable(Box::new(Type::UInt32));
+        assert_eq!(sparse_positions(&nullable, &values, Some(0.5)), None);
+    }
+
+    #[test]
+    fn test_read_serialization_kind() {
+        let revision = DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION;
+        for (data, kind) in [
+            (vec![0], SERIALIZATION_KIND_DEFAULT),
+            (vec![1, SERIALIZATION_KIND_SPARSE], SERIALIZATION_KIND_SPARSE),
+            (vec![1, SERIALIZATION_KIND_COMBINATION, 2, 0, 1], SERIALIZATION_KIND_SPARSE),
+            (vec![1, SERIALIZATION_KIND_COMBINATION, 2, 0, 4], 4),
+        ] {
+            let mut bytes = Bytes::from(data);
+            assert_eq!(read_serialization_kind_sync(&mut bytes, revision).unwrap(), kind);
+            assert!(bytes.is_empty());
+        }
+        let mut bytes = Bytes::from(vec![1]);
+        assert_eq!(read_serialization_kind_sync(&mut bytes, revision - 1).unwrap(), 0);
+        assert_eq!(bytes.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_native_block_sparse_roundtrip() {
+        use crate::formats::DeserializerState;
+        use crate::formats::protocol_data::ProtocolData;
+        use crate::native::block::Block;
+        use crate::native::protocol::DBMS_TCP_PROTOCOL_VERSION;
+
+        let ints = [0_i64, 0, 5, 0, 0, 0, 3, 0].map(Value::Int64);
+        let strings = ["", "", "", "x", "", "", "", ""].map(|s| Value::String(s.into()));
+        let block = Block {
+            rows: 8,
+            column_types: vec![("count".into(), Type::Int64), ("label".into(), Type::String)],
+            column_data: ints.iter().chain(&strings).cloned().collect(),
+            ..Default::default()
+        };
+
+        let revision = DBMS_TCP_PROTOCOL_VERSION;
+        let mut full = Vec::new();
+        block.clone().write_async(&mut full, revision, None, (), None).await.unwrap();
+        let mut sparse = Vec::new();
+        block.clone().write_async(&mut sparse, revision, None, (), Some(0.5)).await.unwrap();
+        assert!(sparse.len() < full.len());
+        let mut synced = Vec::new();
+        block.clone().write(&mut synced, revision, None, (), Some(0.5)).unwrap();
+        assert_eq!(synced, sparse);
+
+        let mut state = DeserializerState::default();
+        let read =
+            Block::read_async(&mut sparse.as_slice(), revision, (), &mut state).await.unwrap();
+        assert_eq!(read.column_types, block.column_types);
+        assert_eq!(read.column_data, block.column_data);
+
+        let mut bytes = Bytes::from(synced);
+        let read = Block::read(&mut bytes, revision, (), &mut state).unwrap();
+        assert_eq!(read.column_data, block.column_data);
+    }
+
     #[test]
     fn test_expand_sparse_int64_array() {
         // Sparse values at positions [1, 3]: values [10, 30]
@@ -3,8 +3,10 @@
 // Complex type parsing function is well-documented despite length
 #![allow(clippy::too_many_lines)]
 
+pub(crate) mod binary;
 pub(crate) mod deserialize;
 pub mod geo;
+pub(crate) mod json_type;
 pub(crate) mod low_cardinality;
 pub mod map;
 pub(crate) mod serialize;
@@ -19,6 +21,7 @@ use futures_util::FutureExt;
 use tokio::io::AsyncWriteExt;
 use uuid::Uuid;
 
+pub use self::json_type::JsonType;
 use super::protocol::MAX_STRING_SIZE;
 use super::values::{
     Date, DateTime, DynDateTime64, Ipv4, Ipv6, MultiPolygon, Point, Polygon, Ring, Value, i256,
@@ -87,8 +90,12 @@ pub enum Type {
     Tuple(Vec<Type>),
     Map(Box<Type>, Box<Type>),
 
+    /// The deprecated `Object('json')` type, transferred as one JSON string per row.
     Object,
 
+    /// The `JSON` type, stored as typed, dynamic, and shared path subcolumns.
+    Json(Box<JsonType>),
+
     // === DFE Fork: New types for ClickHouse 24.x+ ===
     /// Variant type - discriminated union of types
     /// Example: Variant(String, `UInt64`, Array(String))
@@ -201,6 +208,22 @@ impl Type {
 
     pub fn is_nullable(&self) -> bool { matches!(self, Type::Nullable(_)) }
 
+    /// Whether the type is, or contains, a `JSON` or `Dynamic` type.
+    ///
+    /// The stream prefix of these types describes the paths and types present in the data, so
+    /// they are read and written together with their data instead of through the prefix hooks.
+    pub(crate) fn has_dynamic_structure(&self) -> bool {
+        match self {
+            Type::Json(_) | Type::Dynamic { .. } => true,
+            Type::Array(inner) | Type::Nullable(inner) | Type::LowCardinality(inner) => {
+                inner.has_dynamic_structure()
+            }
+            Type::Map(_, value) => value.has_dynamic_structure(),
+            Type::Tuple(inner) => inner.iter().any(Type::has_dynamic_structure),
+            _ => false,
+        }
+    }
+
     pub fn strip_low_cardinality(&self) -> &Type {
         match self {
             Type::LowCardinality(x) => x,
@@ -259,7 +282,7 @@ impl Type {
             Type::Polygon => Value::Polygon(Polygon::default()),
             Type::MultiPolygon => Value::MultiPolygon(MultiPolygon::default()),
             Type::Uuid => Value::Uuid(Uuid::from_u128(0)),
-            Type::Object => Value::Object("{}".as_bytes().to_vec()),
+            Type::Object | Type::Json(_) => Value::Object("{}".as_bytes().to_vec()),
             // DFE Fork: New types
             Type::Variant(_) => Value::Null, // Variant defaults to NULL (discriminator 255)
             Type::Dynamic { .. } => Value::Null, // Dynamic defaults to NULL
@@ -349,7 +372,8 @@ impl Display for Type {
             ),
             Type::Nullable(inner) => write!(f, "Nullable({inner})"),
             Type::Map(key, value) => write!(f, "Map({key},{value})"),
-            Type::Object => write!(f, "JSON"),
+            Type::Object => write!(f, "Object('json')"),
+            Type::Json(json) => write!(f, "{json}"),
             // DFE Fork: New types
             Type::Variant(variants) => write!(
                 f,
@@ -409,6 +433,11 @@ impl Type {
                 )));
             }
 
+            // The stream prefix of these columns describes their data, see `dynamic`
+            if self.has_dynamic_structure() {
+                return dynamic::read_column(self, reader, rows).await;
+            }
+
             Ok(match self {
                 Type::Int8
                 | Type::Int16
@@ -462,8 +491,9 @@ impl Type {
                         .await?
                 }
                 Type::Object => object::ObjectDeserializer::read(self, reader, rows, state).await?,
+                Type::Json(_) | Type::Dynamic { .. } => unreachable!("handled above"),
                 // DFE Fork: New types - not yet implemented for native Value deserialization
-                Type::Variant(_) | Type::Dynamic { .. } | Type::Nested(_) => {
+                Type::Variant(_) | Type::Nested(_) => {
                     return Err(Error::Unimplemented(format!(
                         "Native Value deserialization not implemented for {self}"
                     )));
@@ -559,7 +589,7 @@ impl Type {
             }
             Type::Object => object::ObjectDeserializer::read_sync(self, reader, rows, state)?,
             // DFE Fork: New types - not yet implemented for native Value deserialization
-            Type::Variant(_) | Type::Dynamic { .. } | Type::Nested(_) => {
+            Type::Variant(_) | Type::Dynamic { .. } | Type::Json(_) | Type::Nested(_) => {
                 return Err(Error::Unimplemented(format!(
                     "Native Value deserialization not implemented for {self}"
                 )));
@@ -597,6 +627,14 @@ impl Type {
     ) -> impl Future<Output = Result<()>> + Send + 'a {
         use serialize::*;
         async move {
+            // The stream prefix of these columns depends on the values, see `dynamic`
+            if self.has_dynamic_structure() {
+                let mut buffer = Vec::with_capacity(values.len() * self.estimate_capacity());
+                dynamic::write_column(self, values, &mut buffer, state)?;
+                writer.write_all(&buffer).await?;
+                return Ok(());
+            }
+
             match self {
                 Type::Int8
                 | Type::Int16
@@ -662,9 +700,7 @@ impl Type {
                 Type::Variant(_) => {
                     variant::VariantSerializer::write(self, values, writer, state).await?;
                 }
-                Type::Dynamic { .. } => {
-                    dynamic::DynamicSerializer::write(self, values, writer, state).await?;
-                }
+                Type::Dynamic { .. } | Type::Json(_) => unreachable!("handled above"),
                 Type::Nested(_) => {
                     nested::NestedSerializer::write(self, values, writer, state).await?;
                 }
@@ -707,6 +743,12 @@ impl Type {
         state: &mut SerializerState,
     ) -> Result<()> {
         use serialize::*;
+
+        // The stream prefix of these columns depends on the values, see `dynamic`
+        if self.has_dynamic_structure() {
+            return dynamic::write_column(self, values, writer, state);
+        }
+
         match self {
             Type::Int8
             | Type::Int16
@@ -768,9 +810,7 @@ impl Type {
             Type::Variant(_) => {
                 variant::VariantSerializer::write_sync(self, values, writer, state)?;
             }
-            Type::Dynamic { .. } => {
-                dynamic::DynamicSerializer::write_sync(self, values, writer, state)?;
-            }
+            Type::Dynamic { .. } | Type::Json(_) => unreachable!("handled above"),
             Type::Nested(_) => {
                 nested::NestedSerializer::write_sync(self, values, writer, state)?;
             }
@@ -807,39 +847,33 @@ impl Type {
     #[expect(clippy::too_many_lines)]
     pub(crate) fn validate(&self) -> Result<()> {
         match self {
-            Type::Decimal32(scale) => {
-                if *scale == 0 || *scale > 9 {
-                    return Err(Error::TypeParseError(format!(
-                        "scale out of bounds for Decimal32({}) must be in range (1..=9)",
-                        *scale
-                    )));
-                }
+            Type::Decimal32(scale) if (*scale == 0 || *scale > 9) => {
+                return Err(Error::TypeParseError(format!(
+                    "scale out of bounds for Decimal32({}) must be in range (1..=9)",
+                    *scale
+                )));
             }
 
-            Type::Decimal128(scale) => {
-                if *scale == 0 || *scale > 38 {
-                    return Err(Error::TypeParseError(format!(
-                        "scale out of bounds for Decimal128({}) must be in range (1..=38)",
-                        *scale
-                    )));
-                }
+            Type::Decimal128(scale) if (*scale == 0 || *scale > 38) => {
+                return Err(Error::TypeParseError(format!(
+                    "scale out of bounds for Decimal128({}) must be in range (1..=38)",
+                    *scale
+                )));
             }
-            Type::Decimal256(scale) => {
-                if *scale == 0 || *scale > 76 {
-                    return Err(Error::TypeParseError(format!(
-                        "scale out of bounds for Decimal256({}) must be in range (1..=76)",
-                        *scale
-                    )));
-                }
+            Type::Decimal256(scale) if (*scale == 0 || *scale > 76) => {
+                return Err(Error::TypeParseError(format!(
+                    "scale out of bounds for Decimal256({}) must be in range (1..=76)",
+                    *scale
+                )));
             }
-            Type::DateTime64(precision, _) | Type::Decimal64(precision) => {
-                if *precision == 0 || *precision > 18 {
-                    return Err(Error::TypeParseError(format!(
-                        "precision out of bounds for Decimal64/DateTime64({}) must be in range \
-                         (1..=18)",
-                        *precision
-                    )));
-                }
+            Type::DateTime64(precision, _) | Type::Decimal64(precision)
+                if (*precision == 0 || *precision > 18) =>
+            {
+                return Err(Error::TypeParseError(format!(
+                    "precision out of bounds for Decimal64/DateTime64({}) must be in range \
+                     (1..=18)",
+                    *precision
+                )));
             }
             Type::LowCardinality(inner) => match inner.strip_null() {
                 Type::String
@@ -1012,6 +1046,7 @@ impl Type {
             ) => types.first().is_some_and(|t| t.inner_validate_value(inner)),
             (Type::Variant(_), Value::Variant(_, _) | Value::Null) => true,
             (Type::Dynamic { .. }, Value::Dynamic(_, _) | Value::Null) => true,
+            (Type::Json(_), Value::Object(_) | Value::String(_) | Value::Null) => true,
             _ => false,
         }
     }
@@ -1056,7 +1091,7 @@ impl Type {
             Type::Variant(variants) => {
                 1 + variants.iter().map(Type::estimate_capacity).max().unwrap_or(8)
             }
-            Type::Dynamic { .. } => 64,
+            Type::Dynamic { .. } | Type::Json(_) => 64,
             Type::Nested(fields) => fields.iter().map(|(_, t)| t.estimate_capacity()).sum(),
 
             // Placeholder for unsupported types
@@ -0,0 +1,460 @@
+//! `ClickHouse` binary encoding of data types and single values.
+//!
+//! Values that do not get a subcolumn of their own, in the shared variant of `Dynamic` and the
+//! shared data of `JSON`, are stored as the binary encoding of their type followed by the value
+//! itself.
+//!
+//! Reference: `ClickHouse/src/DataTypes/DataTypesBinaryEncoding.cpp`
+use std::str::FromStr;
+
+use super::{JsonType, Type};
+use crate::formats::{DeserializerState, SerializerState};
+use crate::io::{ClickHouseBytesRead, ClickHouseBytesWrite};
+use crate::{Error, Result, Value};
+
+const NOTHING: u8 = 0x00;
+const UINT8: u8 = 0x01;
+const UINT16: u8 = 0x02;
+const UINT32: u8 = 0x03;
+const UINT64: u8 = 0x04;
+const UINT128: u8 = 0x05;
+const UINT256: u8 = 0x06;
+const INT8: u8 = 0x07;
+const INT16: u8 = 0x08;
+const INT32: u8 = 0x09;
+const INT64: u8 = 0x0A;
+const INT128: u8 = 0x0B;
+const INT256: u8 = 0x0C;
+const FLOAT32: u8 = 0x0D;
+const FLOAT64: u8 = 0x0E;
+const DATE: u8 = 0x0F;
+const DATE32: u8 = 0x10;
+const DATETIME_UTC: u8 = 0x11;
+const DATETIME_TZ: u8 = 0x12;
+const DATETIME64_UTC: u8 = 0x13;
+const DATETIME64_TZ: u8 = 0x14;
+const STRING: u8 = 0x15;
+const FIXED_STRING: u8 = 0x16;
+const ENUM8: u8 = 0x17;
+const ENUM16: u8 = 0x18;
+const DECIMAL32: u8 = 0x19;
+const DECIMAL64: u8 = 0x1A;
+const DECIMAL128: u8 = 0x1B;
+const DECIMAL256: u8 = 0x1C;
+const UUID: u8 = 0x1D;
+const ARRAY: u8 = 0x1E;
+const TUPLE: u8 = 0x1F;
+const NAMED_TUPLE: u8 = 0x20;
+const NULLABLE: u8 = 0x23;
+const LOW_CARDINALITY: u8 = 0x26;
+const MAP: u8 = 0x27;
+const IPV4: u8 = 0x28;
+const IPV6: u8 = 0x29;
+const VARIANT: u8 = 0x2A;
+const DYNAMIC: u8 = 0x2B;
+const CUSTOM: u8 = 0x2C;
+const BOOL: u8 = 0x2D;
+const JSON: u8 = 0x30;
+const BFLOAT16: u8 = 0x31;
+
+/// Decode a binary encoded data type into its type name.
+///
+/// The name is returned rather than a [`Type`] since it preserves `Bool`.
+pub(crate) fn decode_type(reader: &mut impl ClickHouseBytesRead) -> Result<String> {
+    let code = reader.try_get_u8()?;
+    Ok(match code {
+        NOTHING => "Nothing".into(),
+        UINT8 => "UInt8".into(),
+        UINT16 => "UInt16".into(),
+        UINT32 => "UInt32".into(),
+        UINT64 => "UInt64".into(),
+        UINT128 => "UInt128".into(),
+        UINT256 => "UInt256".into(),
+        INT8 => "Int8".into(),
+        INT16 => "Int16".into(),
+        INT32 => "Int32".into(),
+        INT64 => "Int64".into(),
+        INT128 => "Int128".into(),
+        INT256 => "Int256".into(),
+        FLOAT32 => "Float32".into(),
+        FLOAT64 => "Float64".into(),
+        BFLOAT16 => "BFloat16".into(),
+        DATE => "Date".into(),
+        DATE32 => "Date32".into(),
+        DATETIME_UTC => "DateTime".into(),
+        DATETIME_TZ => format!("DateTime('{}')", read_name(reader)?),
+        DATETIME64_UTC => format!("DateTime64({})", reader.try_get_u8()?),
+        DATETIME64_TZ => {
+            let precision = reader.try_get_u8()?;
+            format!("DateTime64({precision}, '{}')", read_name(reader)?)
+        }
+        STRING => "String".into(),
+        FIXED_STRING => format!("FixedString({})", reader.try_get_var_uint()?),
+        ENUM8 | ENUM16 => {
+            let count = reader.try_get_var_uint()?;
+            let mut items = Vec::new();
+            for _ in 0..count {
+                let name = read_name(reader)?.replace('\'', "\\'");
+                let value = if code == ENUM8 {
+                    i16::from(reader.try_get_i8()?)
+                } else {
+                    reader.try_get_i16_le()?
+                };
+                items.push(format!("'{name}' = {value}"));
+            }
+            format!("Enum{}({})", if code == ENUM8 { 8 } else { 16 }, items.join(", "))
+        }
+        DECIMAL32 | DECIMAL64 | DECIMAL128 | DECIMAL256 => {
+            let precision = reader.try_get_u8()?;
+            format!("Decimal({precision}, {})", reader.try_get_u8()?)
+        }
+        UUID => "UUID".into(),
+        ARRAY => format!("Array({})", decode_type(reader)?),
+        TUPLE | NAMED_TUPLE => {
+            let count = reader.try_get_var_uint()?;
+            let mut items = Vec::new();
+            for _ in 0..count {
+                if code == NAMED_TUPLE {
+                    let name = read_name(reader)?;
+                    items.push(format!("{name} {}", decode_type(reader)?));
+                } else {
+                    items.push(decode_type(reader)?);
+                }
+            }
+            format!("Tuple({})", items.join(", "))
+        }
+        NULLABLE => format!("Nullable({})", decode_type(reader)?),
+        LOW_CARDINALITY => format!("LowCardinality({})", decode_type(reader)?),
+        MAP => {
+            let key = decode_type(reader)?;
+            format!("Map({key}, {})", decode_type(reader)?)
+        }
+        IPV4 => "IPv4".into(),
+        IPV6 => "IPv6".into(),
+        VARIANT => {
+            let count = reader.try_get_var_uint()?;
+            let items = (0..count).map(|_| decode_type(reader)).collect::<Result<Vec<_>>>()?;
+            format!("Variant({})", items.join(", "))
+        }
+        DYNAMIC => format!("Dynamic(max_types={})", reader.try_get_u8()?),
+        CUSTOM => read_name(reader)?,
+        BOOL => "Bool".into(),
+        JSON => {
+            let _version = reader.try_get_u8()?;
+            let mut json = JsonType::new()
+                .with_max_dynamic_paths(usize::try_from(reader.try_get_var_uint()?).unwrap_or(0))
+                .with_max_dynamic_types(usize::from(reader.try_get_u8()?));
+            for _ in 0..reader.try_get_var_uint()? {
+                let path = read_name(reader)?;
+                json = json.with_typed_path(path, Type::from_str(&decode_type(reader)?)?);
+            }
+            for _ in 0..reader.try_get_var_uint()? {
+                json = json.with_skip_path(read_name(reader)?);
+            }
+            for _ in 0..reader.try_get_var_uint()? {
+                json = json.with_skip_regexp(read_name(reader)?);
+            }
+            json.to_string()
+        }
+        code => {
+            return Err(Error::DeserializeError(format!(
+                "unsupported binary type encoding: 0x{code:02X}"
+            )));
+        }
+    })
+}
+
+fn read_name(reader: &mut impl ClickHouseBytesRead) -> Result<String> {
+    String::from_utf8(reader.try_get_string()?.to_vec())
+        .map_err(|e| Error::DeserializeError(format!("invalid type name: {e}")))
+}
+
+/// Decode a single binary encoded value of the given type.
+pub(crate) fn decode_value(type_: &Type, reader: &mut impl ClickHouseBytesRead) -> Result<Value> {
+    Ok(match type_ {
+        Type::Nullable(inner) => {
+            if reader.try_get_u8()? == 0 {
+                decode_value(inner, reader)?
+            } else {
+                Value::Null
+            }
+        }
+        Type::LowCardinality(inner) => decode_value(inner, reader)?,
+        Type::Array(inner) => {
+            let len = reader.try_get_var_uint()?;
+            Value::Array((0..len).map(|_| decode_value(inner, reader)).collect::<Result<_>>()?)
+        }
+        Type::Map(key, value) => {
+            let len = reader.try_get_var_uint()?;
+            let (mut keys, mut values) = (Vec::new(), Vec::new());
+            for _ in 0..len {
+                keys.push(decode_value(key, reader)?);
+                values.push(decode_value(value, reader)?);
+            }
+            Value::Map(keys, values)
+        }
+        Type::Tuple(inner) => {
+            Value::Tuple(inner.iter().map(|t| decode_value(t, reader)).collect::<Result<_>>()?)
+        }
+        Type::Variant(variants) => {
+            let discriminator = reader.try_get_u8()?;
+            match variants.get(usize::from(discriminator)) {
+                Some(inner) => {
+                    Value::Variant(discriminator, Box::new(decode_value(inner, reader)?))
+                }
+                None => Value::Null,
+            }
+        }
+        Type::Dynamic { .. } => {
+            let name = decode_type(reader)?;
+            if name == "Nothing" {
+                Value::Null
+            } else {
+                let value = decode_value(&Type::from_str(&name)?, reader)?;
+                Value::Dynamic(name, Box::new(value))
+            }
+        }
+        Type::Json(json) => {
+            let mut object = super::json_type::JsonObjectBuilder::default();
+            for _ in 0..reader.try_get_var_uint()? {
+                let path = read_name(reader)?;
+                if let Some(typed) = json.typed_path(&path) {
+                    object.insert(&path, &decode_value(typed, reader)?, false);
+                } else {
+                    let name = decode_type(reader)?;
+                    let value = decode_value(&Type::from_str(&name)?, reader)?;
+                    object.insert(&path, &value, super::json_type::is_bool_type(&name));
+                }
+            }
+            Value::Object(object.finish().into_bytes())
+        }
+        // Fixed width and string values are encoded like a single row column
+        _ => {
+            let mut state = DeserializerState::default();
+            type_.deserialize_column_sync(reader, 1, &mut state)?.pop().ok_or_else(|| {
+                Error::DeserializeError(format!("missing binary encoded value for {type_}"))
+            })?
+        }
+    })
+}
+
+/// Binary encode a data type.
+///
+/// `bool_leaf` encodes the innermost `UInt8` as `Bool`, see [`super::json_type::is_bool_type`].
+pub(crate) fn encode_type(
+    type_: &Type,
+    bool_leaf: bool,
+    writer: &mut impl ClickHouseBytesWrite,
+) -> Result<()> {
+    match type_ {
+        Type::UInt8 if bool_leaf => writer.put_u8(BOOL),
+        Type::UInt8 => writer.put_u8(UINT8),
+        Type::UInt16 => writer.put_u8(UINT16),
+        Type::UInt32 => writer.put_u8(UINT32),
+        Type::UInt64 => writer.put_u8(UINT64),
+        Type::UInt128 => writer.put_u8(UINT128),
+        Type::UInt256 => writer.put_u8(UINT256),
+        Type::Int8 => writer.put_u8(INT8),
+        Type::Int16 => writer.put_u8(INT16),
+        Type::Int32 => writer.put_u8(INT32),
+        Type::Int64 => writer.put_u8(INT64),
+        Type::Int128 => writer.put_u8(INT128),
+        Type::Int256 => writer.put_u8(INT256),
+        Type::Float32 => writer.put_u8(FLOAT32),
+        Type::Float64 => writer.put_u8(FLOAT64),
+        Type::BFloat16 => writer.put_u8(BFLOAT16),
+        Type::Date => writer.put_u8(DATE),
+        Type::Date32 => writer.put_u8(DATE32),
+        Type::DateTime(tz) => {
+            writer.put_u8(DATETIME_TZ);
+            writer.put_string(tz.name())?;
+        }
+        #[expect(clippy::cast_possible_truncation)]
+        Type::DateTime64(precision, tz) => {
+            writer.put_u8(DATETIME64_TZ);
+            writer.put_u8(*precision as u8);
+            writer.put_string(tz.name())?;
+        }
+        Type::String | Type::Binary => writer.put_u8(STRING),
+        Type::FixedSizedString(n) | Type::FixedSizedBinary(n) => {
+            writer.put_u8(FIXED_STRING);
+            writer.put_var_uint(*n as u64)?;
+        }
+        Type::Enum8(items) => {
+            writer.put_u8(ENUM8);
+            writer.put_var_uint(items.len() as u64)?;
+            for (name, value) in items {
+                writer.put_string(name)?;
+                writer.put_i8(*value);
+            }
+        }
+        Type::Enum16(items) => {
+            writer.put_u8(ENUM16);
+            writer.put_var_uint(items.len() as u64)?;
+            for (name, value) in items {
+                writer.put_string(name)?;
+                writer.put_i16_le(*value);
+            }
+        }
+        #[expect(clippy::cast_possible_truncation)]
+        Type::Decimal32(scale)
+        | Type::Decimal64(scale)
+        | Type::Decimal128(scale)
+        | Type::Decimal256(scale) => {
+            let (code, precision) = match type_ {
+                Type::Decimal32(_) => (DECIMAL32, 9),
+                Type::Decimal64(_) => (DECIMAL64, 18),
+                Type::Decimal128(_) => (DECIMAL128, 38),
+                _ => (DECIMAL256, 76),
+            };
+            writer.put_u8(code);
+            writer.put_u8(precision);
+            writer.put_u8(*scale as u8);
+        }
+        Type::Uuid => writer.put_u8(UUID),
+        Type::Ipv4 => writer.put_u8(IPV4),
+        Type::Ipv6 => writer.put_u8(IPV6),
+        Type::Array(inner) => {
+            writer.put_u8(ARRAY);
+            encode_type(inner, bool_leaf, writer)?;
+        }
+        Type::Nullable(inner) => {
+            writer.put_u8(NULLABLE);
+            encode_type(inner, bool_leaf, writer)?;
+        }
+        Type::LowCardinality(inner) => {
+            writer.put_u8(LOW_CARDINALITY);
+            encode_type(inner, bool_leaf, writer)?;
+        }
+        Type::Tuple(inner) => {
+            writer.put_u8(TUPLE);
+            writer.put_var_uint(inner.len() as u64)?;
+            for t in inner {
+                encode_type(t, false, writer)?;
+            }
+        }
+        Type::Map(key, value) => {
+            writer.put_u8(MAP);
+            encode_type(key, false, writer)?;
+            encode_type(value, bool_leaf, writer)?;
+        }
+        #[expect(clippy::cast_possible_truncation)]
+        Type::Dynamic { max_types } => {
+            writer.put_u8(DYNAMIC);
+            writer.put_u8(max_types.unwrap_or(JsonType::DEFAULT_MAX_DYNAMIC_TYPES) as u8);
+        }
+        Type::Point | Type::Ring | Type::Polygon | Type::MultiPolygon => {
+            writer.put_u8(CUSTOM);
+            writer.put_string(type_.to_string())?;
+        }
+        _ => {
+            return Err(Error::SerializeError(format!(
+                "binary type encoding not supported for {type_}"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Binary encode a single value of the given type.
+pub(crate) fn encode_value(
+    type_: &Type,
+    value: Value,
+    writer: &mut impl ClickHouseBytesWrite,
+) -> Result<()> {
+    match (type_, value) {
+        (Type::Nullable(_), Value::Null) => writer.put_u8(1),
+        (Type::Nullable(inner), value) => {
+            writer.put_u8(0);
+            encode_value(inner, value, writer)?;
+        }
+        (Type::LowCardinality(inner), value) => encode_value(inner, value, writer)?,
+        (Type::Array(inner), Value::Array(items)) => {
+            writer.put_var_uint(items.len() as u64)?;
+            for item in items {
+                encode_value(inner, item, writer)?;
+            }
+        }
+        (Type::Map(key, value), Value::Map(keys, values)) => {
+            writer.put_var_uint(keys.len() as u64)?;
+            for (k, v) in keys.into_iter().zip(values) {
+                encode_value(key, k, writer)?;
+                encode_value(value, v, writer)?;
+            }
+        }
+        (Type::Tuple(inner), Value::Tuple(items)) => {
+            for (t, item) in inner.iter().zip(items) {
+                encode_value(t, item, writer)?;
+            }
+        }
+        (Type::Dynamic { .. }, Value::Null) => writer.put_u8(NOTHING),
+        (Type::Dynamic { .. }, value) => {
+            let (name, value) = match value {
+                Value::Dynamic(name, value) => (name, *value),
+                value => (super::serialize::dynamic::infer_type_name(&value), value),
+            };
+            let inner = Type::from_str(&name)?;
+            encode_type(&inner, super::json_type::is_bool_type(&name), writer)?;
+            encode_value(&inner, value, writer)?;
+        }
+        (Type::Json(_), _) => {
+            return Err(Error::Unimplemented("binary encoding of JSON values".into()));
+        }
+        (Type::Array(_) | Type::Map(..) | Type::Tuple(_), value) => {
+            return Err(Error::SerializeError(format!("cannot encode {value:?} as {type_}")));
+        }
+        // Fixed width and string values are encoded like a single row column
+        (_, value) => {
+            let mut state = SerializerState::default();
+            type_.serialize_column_sync(vec![value], writer, &mut state)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(name: &str, value: Value) -> (String, Value) {
+        let type_ = Type::from_str(name).unwrap();
+        let mut buffer = Vec::new();
+        encode_type(&type_, super::super::json_type::is_bool_type(name), &mut buffer).unwrap();
+        encode_value(&type_, value, &mut buffer).unwrap();
+        let mut reader = buffer.as_slice();
+        let decoded_name = decode_type(&mut reader).unwrap();
+        let value = decode_value(&Type::from_str(&decoded_name).unwrap(), &mut reader).unwrap();
+        assert!(reader.is_empty());
+        (decoded_name, value)
+    }
+
+    #[test]
+    fn test_binary_encoding_roundtrip() {
+        assert_eq!(roundtrip("Int64", Value::Int64(-3)), ("Int64".into(), Value::Int64(-3)));
+        assert_eq!(roundtrip("Bool", Value::UInt8(1)), ("Bool".into(), Value::UInt8(1)));
+        assert_eq!(
+            roundtrip("String", Value::String(b"abc".to_vec())),
+            ("String".into(), Value::String(b"abc".to_vec()))
+        );
+        let array = Value::Array(vec![Value::Float64(1.5), Value::Null]);
+        assert_eq!(
+            roundtrip("Array(Nullable(Float64))", array.clone()),
+            ("Array(Nullable(Float64))".into(), array)
+        );
+        let map = Value::Map(vec![Value::String(b"k".to_vec())], vec![Value::UInt32(7)]);
+        assert_eq!(
+            roundtrip("Map(String, UInt32)", map.clone()),
+            ("Map(String, UInt32)".into(), map)
+        );
+    }
+
+    #[test]
+    fn test_decode_type_names() {
+        let mut reader: &[u8] = &[TUPLE, 2, STRING, DATETIME64_UTC, 3];
+        assert_eq!(decode_type(&mut reader).unwrap(), "Tuple(String, DateTime64(3))");
+        let mut reader: &[u8] = &[DECIMAL64, 18, 4];
+        assert_eq!(decode_type(&mut reader).unwrap(), "Decimal(18, 4)");
+        let mut reader: &[u8] = &[0x7F];
+        assert!(decode_type(&mut reader).is_err());
+    }
+}
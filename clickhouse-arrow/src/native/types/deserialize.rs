@@ -1,5 +1,7 @@
 pub(crate) mod array;
+pub(crate) mod dynamic;
 pub(crate) mod geo;
+pub(crate) mod json;
 pub(crate) mod low_cardinality;
 pub(crate) mod map;
 pub(crate) mod nullable;
@@ -32,6 +34,10 @@ impl ClickHouseNativeDeserializer for Type {
     ) -> impl Future<Output = Result<()>> + Send + 'a {
         use deserialize::*;
         async move {
+            // Read together with the data, see `Type::has_dynamic_structure`
+            if self.has_dynamic_structure() {
+                return Ok(());
+            }
             match self {
                 Type::Int8
                 | Type::Int16
@@ -93,9 +99,9 @@ impl ClickHouseNativeDeserializer for Type {
                 Type::Object => {
                     object::ObjectDeserializer::read_prefix(self, reader, state).await?;
                 }
+                Type::Json(_) | Type::Dynamic { .. } => {}
                 // DFE Fork: New types - no special prefix needed
                 Type::Variant(_)
-                | Type::Dynamic { .. }
                 | Type::Nested(_)
                 | Type::BFloat16
                 | Type::Time
@@ -532,6 +538,18 @@ impl FromStr for Type {
                         Type::Dynamic { max_types }
                     }
                 }
+                "Object" | "OBJECT" => {
+                    let (args, count) = parse_fixed_args::<1>(following)?;
+                    if count != 1 || !args[0].eq_ignore_ascii_case("'json'") {
+                        return Err(Error::TypeParseError(format!(
+                            "Object expects the 'json' schema format: {args:?}"
+                        )));
+                    }
+                    Type::Object
+                }
+                "JSON" | "Json" => {
+                    Type::Json(Box::new(JsonType::from_args(parse_variable_args(following)?)?))
+                }
                 "Nested" => {
                     // Parse Nested(name1 Type1, name2 Type2, ...)
                     let args = parse_variable_args(following)?;
@@ -589,7 +607,8 @@ impl FromStr for Type {
             "Ring" => Type::Ring,
             "Polygon" => Type::Polygon,
             "MultiPolygon" => Type::MultiPolygon,
-            "Object" | "Json" | "OBJECT" | "JSON" => Type::Object,
+            "Object" | "OBJECT" => Type::Object,
+            "JSON" | "Json" => Type::Json(Box::default()),
             // DFE Fork: Dynamic without parameters
             "Dynamic" => Type::Dynamic { max_types: None },
             // DFE Fork: New simple types
@@ -953,8 +972,18 @@ mod tests {
             Type::from_str("Map(String, Int32)").unwrap(),
             Type::Map(Box::new(Type::String), Box::new(Type::Int32))
         );
-        assert_eq!(Type::from_str("JSON").unwrap(), Type::Object);
+        assert_eq!(Type::from_str("JSON").unwrap(), Type::Json(Box::default()));
         assert_eq!(Type::from_str("Object").unwrap(), Type::Object);
+        assert_eq!(Type::from_str("Object('json')").unwrap(), Type::Object);
+        assert_eq!(
+            Type::from_str("JSON(max_dynamic_paths=8, a.b UInt32, SKIP c)").unwrap(),
+            Type::Json(Box::new(
+                JsonType::new()
+                    .with_max_dynamic_paths(8)
+                    .with_typed_path("a.b", Type::UInt32)
+                    .with_skip_path("c")
+            ))
+        );
 
         assert!(Type::from_str("LowCardinality()").is_err()); // Missing arg
         assert!(Type::from_str("Array(Int32, String)").is_err()); // Too many args
@@ -991,6 +1020,13 @@ mod tests {
             Type::Nullable(Box::new(Type::Int32)),
             Type::Map(Box::new(Type::String), Box::new(Type::Int32)),
             Type::Object,
+            Type::Json(Box::default()),
+            Type::Json(Box::new(
+                JsonType::new()
+                    .with_max_dynamic_types(4)
+                    .with_typed_path("a.b", Type::Array(Box::new(Type::String)))
+                    .with_skip_regexp("x.*"),
+            )),
         ];
 
         for ty in types {
@@ -0,0 +1,268 @@
+//! Deserializer for `Dynamic` columns and for columns containing `Dynamic` or `JSON`.
+//!
+//! The stream prefix of `Dynamic` lists the types present in the data and the prefix of `JSON`
+//! lists its dynamic paths, so the data of these columns cannot be read without the structure
+//! described by the prefix. Block columns are read by reading the prefix tree of the whole column
+//! type first, then the data with that structure.
+//!
+//! Binary format of `Dynamic`:
+//! - Prefix: version (u64), `max_dynamic_types` (varuint, V1 only), number of types (varuint), type
+//!   names, then the prefix of `Variant(types..., SharedVariant)`: discriminators mode (u64) and
+//!   each variant's prefix.
+//! - Data: discriminators (u8 per row, 255 for NULL) followed by each variant's column.
+//!
+//! Reference: `ClickHouse/src/DataTypes/Serializations/SerializationDynamic.cpp`
+use std::str::FromStr;
+
+use futures_util::FutureExt;
+use futures_util::future::BoxFuture;
+use tokio::io::AsyncReadExt;
+
+use super::json::{self as json_deserialize, JsonPrefix};
+use super::{ClickHouseNativeDeserializer, DeserializerState, Type};
+use crate::io::ClickHouseRead;
+use crate::native::types::binary;
+use crate::native::types::serialize::variant::NULL_DISCRIMINATOR;
+use crate::{Error, Result, Value};
+
+pub(crate) const DYNAMIC_SERIALIZATION_V1: u64 = 1;
+pub(crate) const DYNAMIC_SERIALIZATION_V2: u64 = 2;
+pub(crate) const DYNAMIC_SERIALIZATION_FLATTENED: u64 = 3;
+
+/// Variant holding values whose type has no variant of its own, binary encoded.
+pub(crate) const SHARED_VARIANT: &str = "SharedVariant";
+
+pub(crate) const VARIANT_DISCRIMINATORS_BASIC: u64 = 0;
+
+/// Structure read from the stream prefix of a column.
+pub(crate) enum StructurePrefix {
+    /// The prefix was read through the regular prefix hooks.
+    Plain,
+    Dynamic(DynamicPrefix),
+    Json(Box<JsonPrefix>),
+    Array(Box<StructurePrefix>),
+    Tuple(Vec<StructurePrefix>),
+}
+
+pub(crate) struct DynamicPrefix {
+    /// Variant names and types, sorted by name, including the shared variant.
+    pub(crate) variants: Vec<(String, Type, StructurePrefix)>,
+}
+
+/// Read a column whose type contains `Dynamic` or `JSON`, prefix included.
+pub(crate) async fn read_column<R: ClickHouseRead>(
+    type_: &Type,
+    reader: &mut R,
+    rows: usize,
+) -> Result<Vec<Value>> {
+    let prefix = read_prefix(type_, reader).await?;
+    read_data(type_, &prefix, reader, rows).await
+}
+
+pub(crate) fn read_prefix<'a, R: ClickHouseRead>(
+    type_: &'a Type,
+    reader: &'a mut R,
+) -> BoxFuture<'a, Result<StructurePrefix>> {
+    async move {
+        if !type_.has_dynamic_structure() {
+            type_.deserialize_prefix_async(reader, &mut DeserializerState::default()).await?;
+            return Ok(StructurePrefix::Plain);
+        }
+        Ok(match type_ {
+            Type::Dynamic { .. } => StructurePrefix::Dynamic(read_dynamic_prefix(reader).await?),
+            Type::Json(json) => StructurePrefix::Json(Box::new(
+                json_deserialize::read_json_prefix(json, reader).await?,
+            )),
+            Type::Array(inner) => {
+                StructurePrefix::Array(Box::new(read_prefix(inner, reader).await?))
+            }
+            Type::Map(key, value) => {
+                let entry = Type::Tuple(vec![(**key).clone(), (**value).clone()]);
+                StructurePrefix::Array(Box::new(read_prefix(&entry, reader).await?))
+            }
+            Type::Tuple(inner) => {
+                let mut prefixes = Vec::with_capacity(inner.len());
+                for inner_type in inner {
+                    prefixes.push(read_prefix(inner_type, reader).await?);
+                }
+                StructurePrefix::Tuple(prefixes)
+            }
+            _ => {
+                return Err(Error::Unimplemented(format!(
+                    "Dynamic or JSON values are not supported inside {type_}"
+                )));
+            }
+        })
+    }
+    .boxed()
+}
+
+pub(crate) fn read_data<'a, R: ClickHouseRead>(
+    type_: &'a Type,
+    prefix: &'a StructurePrefix,
+    reader: &'a mut R,
+    rows: usize,
+) -> BoxFuture<'a, Result<Vec<Value>>> {
+    async move {
+        Ok(match (type_, prefix) {
+            (_, StructurePrefix::Plain) => {
+                type_.deserialize_column(reader, rows, &mut DeserializerState::default()).await?
+            }
+            (Type::Dynamic { .. }, StructurePrefix::Dynamic(prefix)) => {
+                read_dynamic_data(prefix, reader, rows).await?
+            }
+            (Type::Json(json), StructurePrefix::Json(prefix)) => {
+                json_deserialize::read_json_data(json, prefix, reader, rows).await?
+            }
+            (Type::Array(_) | Type::Map(..), StructurePrefix::Array(inner_prefix)) => {
+                let inner_type = match type_ {
+                    Type::Map(key, value) => Type::Tuple(vec![(**key).clone(), (**value).clone()]),
+                    _ => type_.unwrap_array()?.clone(),
+                };
+                let mut offsets = Vec::with_capacity(rows);
+                for _ in 0..rows {
+                    offsets.push(reader.read_u64_le().await?);
+                }
+                let total = offsets.last().copied().unwrap_or(0);
+                let total = usize::try_from(total)
+                    .map_err(|_| Error::DeserializeError(format!("invalid offset {total}")))?;
+                let mut items =
+                    read_data(&inner_type, inner_prefix, reader, total).await?.into_iter();
+                let mut out = Vec::with_capacity(rows);
+                let mut last = 0;
+                for offset in offsets {
+                    #[expect(clippy::cast_possible_truncation)]
+                    let chunk: Vec<Value> = (&mut items).take((offset - last) as usize).collect();
+                    last = offset;
+                    out.push(if matches!(type_, Type::Map(..)) {
+                        let (keys, values) = chunk
+                            .into_iter()
+                            .map(|entry| match entry {
+                                Value::Tuple(mut kv) if kv.len() == 2 => {
+                                    let value = kv.pop().unwrap_or(Value::Null);
+                                    (kv.pop().unwrap_or(Value::Null), value)
+                                }
+                                other => (other, Value::Null),
+                            })
+                            .unzip();
+                        Value::Map(keys, values)
+                    } else {
+                        Value::Array(chunk)
+                    });
+                }
+                out
+            }
+            (Type::Tuple(inner), StructurePrefix::Tuple(prefixes)) => {
+                let mut columns = Vec::with_capacity(inner.len());
+                for (inner_type, inner_prefix) in inner.iter().zip(prefixes) {
+                    columns
+                        .push(read_data(inner_type, inner_prefix, reader, rows).await?.into_iter());
+                }
+                (0..rows)
+                    .map(|_| {
+                        Value::Tuple(
+                            columns.iter_mut().map(|c| c.next().unwrap_or(Value::Null)).collect(),
+                        )
+                    })
+                    .collect()
+            }
+            _ => {
+                return Err(Error::DeserializeError(format!(
+                    "stream prefix does not match column type {type_}"
+                )));
+            }
+        })
+    }
+    .boxed()
+}
+
+pub(crate) async fn read_dynamic_prefix<R: ClickHouseRead>(
+    reader: &mut R,
+) -> Result<DynamicPrefix> {
+    match reader.read_u64_le().await? {
+        DYNAMIC_SERIALIZATION_V1 => {
+            let _max_dynamic_types = reader.read_var_uint().await?;
+        }
+        DYNAMIC_SERIALIZATION_V2 => {}
+        DYNAMIC_SERIALIZATION_FLATTENED => {
+            return Err(Error::Unimplemented(
+                "flattened Dynamic serialization is not supported".to_string(),
+            ));
+        }
+        version => {
+            return Err(Error::DeserializeError(format!(
+                "unknown Dynamic serialization version: {version}"
+            )));
+        }
+    }
+
+    let count = reader.read_var_uint().await?;
+    let mut names = Vec::new();
+    for _ in 0..count {
+        names.push(reader.read_utf8_string().await?);
+    }
+    names.push(SHARED_VARIANT.to_string());
+    names.sort();
+
+    let mode = reader.read_u64_le().await?;
+    if mode != VARIANT_DISCRIMINATORS_BASIC {
+        return Err(Error::Unimplemented(format!(
+            "Variant discriminators serialization mode {mode} is not supported"
+        )));
+    }
+
+    let mut variants = Vec::with_capacity(names.len());
+    for name in names {
+        let type_ = if name == SHARED_VARIANT { Type::String } else { Type::from_str(&name)? };
+        let prefix = read_prefix(&type_, reader).await?;
+        variants.push((name, type_, prefix));
+    }
+    Ok(DynamicPrefix { variants })
+}
+
+pub(crate) async fn read_dynamic_data<R: ClickHouseRead>(
+    prefix: &DynamicPrefix,
+    reader: &mut R,
+    rows: usize,
+) -> Result<Vec<Value>> {
+    let mut discriminators = vec![0u8; rows];
+    let _ = reader.read_exact(&mut discriminators).await?;
+
+    let mut counts = vec![0usize; prefix.variants.len()];
+    for &discriminator in &discriminators {
+        if let Some(count) = counts.get_mut(usize::from(discriminator)) {
+            *count += 1;
+        } else if discriminator != NULL_DISCRIMINATOR {
+            return Err(Error::DeserializeError(format!(
+                "invalid Dynamic discriminator {discriminator}"
+            )));
+        }
+    }
+
+    let mut columns = Vec::with_capacity(counts.len());
+    for ((_, type_, variant_prefix), count) in prefix.variants.iter().zip(counts) {
+        columns.push(read_data(type_, variant_prefix, reader, count).await?.into_iter());
+    }
+
+    discriminators
+        .into_iter()
+        .map(|discriminator| {
+            let Some(column) = columns.get_mut(usize::from(discriminator)) else {
+                return Ok(Value::Null);
+            };
+            let value = column.next().unwrap_or(Value::Null);
+            let name = &prefix.variants[usize::from(discriminator)].0;
+            if name == SHARED_VARIANT {
+                let Value::String(encoded) = value else {
+                    return Err(Error::DeserializeError("invalid shared variant value".into()));
+                };
+                let mut encoded = encoded.as_slice();
+                let name = binary::decode_type(&mut encoded)?;
+                let value = binary::decode_value(&Type::from_str(&name)?, &mut encoded)?;
+                Ok(Value::Dynamic(name, Box::new(value)))
+            } else {
+                Ok(Value::Dynamic(name.clone(), Box::new(value)))
+            }
+        })
+        .collect()
+}
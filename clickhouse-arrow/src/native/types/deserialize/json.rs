@@ -0,0 +1,143 @@
+//! Deserializer for the `JSON` type.
+//!
+//! Binary format (V1 and V2):
+//! - Prefix: version (u64), `max_dynamic_paths` (varuint, V1 only), number of dynamic paths
+//!   (varuint), dynamic paths sorted by name, the prefix of each typed path, then the `Dynamic`
+//!   prefix of each dynamic path.
+//! - Data: each typed path column, each dynamic path column, then the shared data as
+//!   `Array(Tuple(String, String))` of paths and binary encoded values.
+//!
+//! The STRING version transfers one JSON document per row instead.
+//!
+//! Reference: `ClickHouse/src/DataTypes/Serializations/SerializationObject.cpp`
+use std::str::FromStr;
+
+use tokio::io::AsyncReadExt;
+
+use super::dynamic::{self, DynamicPrefix, StructurePrefix};
+use super::{DeserializerState, Type};
+use crate::io::ClickHouseRead;
+use crate::native::types::binary;
+use crate::native::types::json_type::{
+    JsonObjectBuilder, JsonType, is_bool_type, shared_data_type,
+};
+use crate::{Error, Result, Value};
+
+pub(crate) const OBJECT_SERIALIZATION_V1: u64 = 0;
+pub(crate) const OBJECT_SERIALIZATION_STRING: u64 = 1;
+pub(crate) const OBJECT_SERIALIZATION_V2: u64 = 2;
+pub(crate) const OBJECT_SERIALIZATION_FLATTENED: u64 = 3;
+
+/// Structure read from the stream prefix of a `JSON` column.
+pub(crate) struct JsonPrefix {
+    /// Documents are transferred as JSON strings.
+    pub(crate) as_string: bool,
+    /// Prefixes of the typed paths, in the order of [`JsonType::typed_paths`].
+    typed:                Vec<StructurePrefix>,
+    dynamic:              Vec<(String, DynamicPrefix)>,
+}
+
+pub(crate) async fn read_json_prefix<R: ClickHouseRead>(
+    json: &JsonType,
+    reader: &mut R,
+) -> Result<JsonPrefix> {
+    match reader.read_u64_le().await? {
+        OBJECT_SERIALIZATION_V1 => {
+            let _max_dynamic_paths = reader.read_var_uint().await?;
+        }
+        OBJECT_SERIALIZATION_V2 => {}
+        OBJECT_SERIALIZATION_STRING => {
+            return Ok(JsonPrefix { as_string: true, typed: vec![], dynamic: vec![] });
+        }
+        OBJECT_SERIALIZATION_FLATTENED => {
+            return Err(Error::Unimplemented(
+                "flattened JSON serialization is not supported".to_string(),
+            ));
+        }
+        version => {
+            return Err(Error::DeserializeError(format!(
+                "unknown JSON serialization version: {version}"
+            )));
+        }
+    }
+
+    let count = reader.read_var_uint().await?;
+    let mut paths = Vec::new();
+    for _ in 0..count {
+        paths.push(reader.read_utf8_string().await?);
+    }
+
+    let mut typed = Vec::with_capacity(json.typed_paths.len());
+    for (_, type_) in &json.typed_paths {
+        typed.push(dynamic::read_prefix(type_, reader).await?);
+    }
+
+    let mut dynamic = Vec::with_capacity(paths.len());
+    for path in paths {
+        dynamic.push((path, dynamic::read_dynamic_prefix(reader).await?));
+    }
+    Ok(JsonPrefix { as_string: false, typed, dynamic })
+}
+
+pub(crate) async fn read_json_data<R: ClickHouseRead>(
+    json: &JsonType,
+    prefix: &JsonPrefix,
+    reader: &mut R,
+    rows: usize,
+) -> Result<Vec<Value>> {
+    if prefix.as_string {
+        let mut out = Vec::with_capacity(rows);
+        for _ in 0..rows {
+            out.push(Value::Object(reader.read_string().await?));
+        }
+        return Ok(out);
+    }
+
+    let mut objects: Vec<JsonObjectBuilder> =
+        (0..rows).map(|_| JsonObjectBuilder::default()).collect();
+    for ((path, type_), typed_prefix) in json.typed_paths.iter().zip(&prefix.typed) {
+        let values = dynamic::read_data(type_, typed_prefix, reader, rows).await?;
+        for (object, value) in objects.iter_mut().zip(&values) {
+            object.insert(path, value, false);
+        }
+    }
+    read_json_remainder(prefix, reader, &mut objects).await?;
+    Ok(objects.into_iter().map(|o| Value::Object(o.finish().into_bytes())).collect())
+}
+
+/// Read the dynamic paths and shared data of a `JSON` column into `objects`, one per row.
+pub(crate) async fn read_json_remainder<R: ClickHouseRead>(
+    prefix: &JsonPrefix,
+    reader: &mut R,
+    objects: &mut [JsonObjectBuilder],
+) -> Result<()> {
+    let rows = objects.len();
+    for (path, dynamic_prefix) in &prefix.dynamic {
+        let values = dynamic::read_dynamic_data(dynamic_prefix, reader, rows).await?;
+        for (object, value) in objects.iter_mut().zip(&values) {
+            if !matches!(value, Value::Null) {
+                object.insert(path, value, false);
+            }
+        }
+    }
+
+    let shared_type = shared_data_type();
+    let shared =
+        shared_type.deserialize_column(reader, rows, &mut DeserializerState::default()).await?;
+    for (object, entries) in objects.iter_mut().zip(shared) {
+        let Value::Array(entries) = entries else { continue };
+        for entry in entries {
+            let Value::Tuple(mut entry) = entry else { continue };
+            let (Some(Value::String(encoded)), Some(Value::String(path))) =
+                (entry.pop(), entry.pop())
+            else {
+                continue;
+            };
+            let mut encoded = encoded.as_slice();
+            let name = binary::decode_type(&mut encoded)?;
+            let value = binary::decode_value(&Type::from_str(&name)?, &mut encoded)?;
+            object.insert(&String::from_utf8_lossy(&path), &value, is_bool_type(&name));
+        }
+    }
+    Ok(())
+}
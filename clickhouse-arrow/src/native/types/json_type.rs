@@ -0,0 +1,491 @@
+//! The `ClickHouse` `JSON` type and helpers for rendering its values as JSON text.
+//!
+//! A `JSON` column stores every path of an object as its own subcolumn. Paths declared in the type
+//! (`JSON(a.b UInt32)`) are *typed* and always present, all other paths are *dynamic* and stored as
+//! `Dynamic` columns, up to `max_dynamic_paths`. Paths beyond that limit end up in a shared data
+//! column holding binary encoded values.
+use std::collections::BTreeMap;
+use std::fmt::{self, Write as _};
+use std::str::FromStr;
+
+use chrono::{NaiveDate, Timelike};
+use chrono_tz::Tz;
+
+use super::Type;
+use crate::{Error, Result, Value};
+
+/// Parameters of a `ClickHouse` `JSON` type.
+///
+/// Mirrors the type definition `JSON(max_dynamic_paths=N, max_dynamic_types=M, a.b UInt32, SKIP
+/// a.c, SKIP REGEXP 'x.*')`. Typed paths are kept sorted by path, the order `ClickHouse` uses for
+/// their subcolumns on the wire.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JsonType {
+    /// Maximum number of paths stored as separate subcolumns, 1024 when unset.
+    pub max_dynamic_paths: Option<usize>,
+    /// Maximum number of types stored separately in each dynamic path, 32 when unset.
+    pub max_dynamic_types: Option<usize>,
+    /// Paths with a declared type.
+    pub typed_paths:       Vec<(String, Type)>,
+    /// Paths that are not stored.
+    pub skip_paths:        Vec<String>,
+    /// Regular expressions of paths that are not stored.
+    pub skip_regexps:      Vec<String>,
+}
+
+impl JsonType {
+    /// Default value of `max_dynamic_paths` when the type does not specify one.
+    pub const DEFAULT_MAX_DYNAMIC_PATHS: usize = 1024;
+    /// Default value of `max_dynamic_types` when the type does not specify one.
+    pub const DEFAULT_MAX_DYNAMIC_TYPES: usize = 32;
+
+    #[must_use]
+    pub fn new() -> Self { Self::default() }
+
+    #[must_use]
+    pub fn with_max_dynamic_paths(mut self, max: usize) -> Self {
+        self.max_dynamic_paths = Some(max);
+        self
+    }
+
+    #[must_use]
+    pub fn with_max_dynamic_types(mut self, max: usize) -> Self {
+        self.max_dynamic_types = Some(max);
+        self
+    }
+
+    /// Declare a typed path. Replaces the type if the path is already declared.
+    #[must_use]
+    pub fn with_typed_path(mut self, path: impl Into<String>, type_: Type) -> Self {
+        let path = path.into();
+        match self.typed_paths.binary_search_by(|(p, _)| p.as_str().cmp(&path)) {
+            Ok(i) => self.typed_paths[i].1 = type_,
+            Err(i) => self.typed_paths.insert(i, (path, type_)),
+        }
+        self
+    }
+
+    #[must_use]
+    pub fn with_skip_path(mut self, path: impl Into<String>) -> Self {
+        self.skip_paths.push(path.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_skip_regexp(mut self, regexp: impl Into<String>) -> Self {
+        self.skip_regexps.push(regexp.into());
+        self
+    }
+
+    /// The declared type of a path, if it is typed.
+    pub fn typed_path(&self, path: &str) -> Option<&Type> {
+        self.typed_paths
+            .binary_search_by(|(p, _)| p.as_str().cmp(path))
+            .ok()
+            .map(|i| &self.typed_paths[i].1)
+    }
+
+    pub fn max_dynamic_paths(&self) -> usize {
+        self.max_dynamic_paths.unwrap_or(Self::DEFAULT_MAX_DYNAMIC_PATHS)
+    }
+
+    pub fn max_dynamic_types(&self) -> usize {
+        self.max_dynamic_types.unwrap_or(Self::DEFAULT_MAX_DYNAMIC_TYPES)
+    }
+
+    /// Whether a path, or one of its parents, is listed in `SKIP`.
+    ///
+    /// `SKIP REGEXP` is evaluated by the server only.
+    #[cfg_attr(not(feature = "serde"), expect(dead_code))]
+    pub(crate) fn is_skipped(&self, path: &str) -> bool {
+        self.skip_paths.iter().any(|skip| {
+            path.strip_prefix(skip.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+        })
+    }
+
+    /// Parse the individual arguments of `JSON(...)`.
+    pub(crate) fn from_args(args: impl IntoIterator<Item = impl AsRef<str>>) -> Result<Self> {
+        let mut json = Self::default();
+        for arg in args {
+            let arg = arg.as_ref().trim();
+            if let Some((key, value)) = arg.split_once('=')
+                && matches!(key.trim(), "max_dynamic_paths" | "max_dynamic_types")
+            {
+                let value = value.trim().parse().map_err(|e| {
+                    Error::TypeParseError(format!("invalid JSON parameter '{arg}': {e}"))
+                })?;
+                if key.trim() == "max_dynamic_paths" {
+                    json.max_dynamic_paths = Some(value);
+                } else {
+                    json.max_dynamic_types = Some(value);
+                }
+            } else if let Some(regexp) =
+                strip_keyword(arg, "SKIP").and_then(|rest| strip_keyword(rest, "REGEXP"))
+            {
+                json.skip_regexps.push(unquote_literal(regexp)?);
+            } else if let Some(path) = strip_keyword(arg, "SKIP") {
+                json.skip_paths.push(parse_path(path)?.0);
+            } else {
+                let (path, rest) = parse_path(arg)?;
+                if rest.is_empty() {
+                    return Err(Error::TypeParseError(format!(
+                        "JSON typed path '{path}' is missing a type"
+                    )));
+                }
+                json = json.with_typed_path(path, Type::from_str(rest)?);
+            }
+        }
+        Ok(json)
+    }
+}
+
+impl fmt::Display for JsonType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut args = Vec::new();
+        if let Some(max) = self.max_dynamic_paths {
+            args.push(format!("max_dynamic_paths={max}"));
+        }
+        if let Some(max) = self.max_dynamic_types {
+            args.push(format!("max_dynamic_types={max}"));
+        }
+        for (path, type_) in &self.typed_paths {
+            args.push(format!("{} {type_}", quote_path(path)));
+        }
+        for path in &self.skip_paths {
+            args.push(format!("SKIP {}", quote_path(path)));
+        }
+        for regexp in &self.skip_regexps {
+            args.push(format!(
+                "SKIP REGEXP '{}'",
+                regexp.replace('\\', "\\\\").replace('\'', "\\'")
+            ));
+        }
+        if args.is_empty() { write!(f, "JSON") } else { write!(f, "JSON({})", args.join(", ")) }
+    }
+}
+
+fn strip_keyword<'a>(input: &'a str, keyword: &str) -> Option<&'a str> {
+    let rest = input.get(..keyword.len()).filter(|k| k.eq_ignore_ascii_case(keyword))?;
+    let rest = &input[rest.len()..];
+    rest.starts_with(char::is_whitespace).then(|| rest.trim_start())
+}
+
+/// Split a possibly backquoted path from the remainder of the argument.
+fn parse_path(input: &str) -> Result<(String, &str)> {
+    if let Some(quoted) = input.strip_prefix('`') {
+        let mut path = String::new();
+        let mut chars = quoted.char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' => {
+                    if let Some((_, escaped)) = chars.next() {
+                        path.push(escaped);
+                    }
+                }
+                '`' if quoted[i + 1..].starts_with('`') => {
+                    path.push('`');
+                    let _ = chars.next();
+                }
+                '`' => return Ok((path, quoted[i + 1..].trim())),
+                c => path.push(c),
+            }
+        }
+        Err(Error::TypeParseError(format!("unterminated JSON path: {input}")))
+    } else {
+        let (path, rest) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+        if path.is_empty() {
+            return Err(Error::TypeParseError("empty JSON path".to_string()));
+        }
+        Ok((path.to_string(), rest.trim()))
+    }
+}
+
+fn unquote_literal(input: &str) -> Result<String> {
+    let inner = input
+        .strip_prefix('\'')
+        .and_then(|s| s.strip_suffix('\''))
+        .ok_or_else(|| Error::TypeParseError(format!("expected a quoted string: {input}")))?;
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(escaped) = chars.next() {
+                out.push(escaped);
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    Ok(out)
+}
+
+fn quote_path(path: &str) -> String {
+    let plain = path.split('.').all(|part| {
+        part.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    });
+    if plain { path.to_string() } else { format!("`{}`", path.replace('`', "``")) }
+}
+
+/// Type of the shared data column of `JSON`, paths with their binary encoded values.
+pub(crate) fn shared_data_type() -> Type {
+    Type::Array(Box::new(Type::Tuple(vec![Type::String, Type::String])))
+}
+
+// ---
+// JSON text rendering
+// ---
+
+/// Whether a type name describes `Bool` values, possibly wrapped in `Array`, `Nullable` or
+/// `LowCardinality`.
+///
+/// `Bool` is parsed as `UInt8`, so the original type name is the only place the distinction
+/// survives.
+pub(crate) fn is_bool_type(mut name: &str) -> bool {
+    loop {
+        let Some(inner) = ["Array(", "Nullable(", "LowCardinality("]
+            .iter()
+            .find_map(|wrapper| name.strip_prefix(wrapper).and_then(|n| n.strip_suffix(')')))
+        else {
+            return name == "Bool";
+        };
+        name = inner;
+    }
+}
+
+/// Builds a JSON object from `path -> value` pairs, nesting dotted paths.
+#[derive(Default)]
+pub(crate) struct JsonObjectBuilder {
+    root: BTreeMap<String, JsonNode>,
+}
+
+enum JsonNode {
+    Leaf(String),
+    Object(BTreeMap<String, JsonNode>),
+}
+
+impl JsonObjectBuilder {
+    /// Insert already rendered JSON text at a dotted path.
+    pub(crate) fn insert_raw(&mut self, path: &str, json: String) {
+        let mut parts = path.split('.').peekable();
+        let mut node = &mut self.root;
+        while let Some(part) = parts.next() {
+            if parts.peek().is_none() {
+                // Objects win over scalars stored at a prefix of their path
+                let _ = node.entry(part.to_string()).or_insert(JsonNode::Leaf(json));
+                return;
+            }
+            let entry =
+                node.entry(part.to_string()).or_insert_with(|| JsonNode::Object(BTreeMap::new()));
+            if let JsonNode::Leaf(_) = entry {
+                *entry = JsonNode::Object(BTreeMap::new());
+            }
+            let JsonNode::Object(next) = entry else { unreachable!() };
+            node = next;
+        }
+    }
+
+    /// Insert a value at a dotted path.
+    pub(crate) fn insert(&mut self, path: &str, value: &Value, bool_leaf: bool) {
+        let mut json = String::new();
+        write_json_value(value, bool_leaf, &mut json);
+        self.insert_raw(path, json);
+    }
+
+    pub(crate) fn finish(self) -> String {
+        let mut out = String::new();
+        write_node_map(&self.root, &mut out);
+        out
+    }
+}
+
+fn write_node_map(map: &BTreeMap<String, JsonNode>, out: &mut String) {
+    out.push('{');
+    for (i, (key, node)) in map.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        write_json_string(key.as_bytes(), out);
+        out.push(':');
+        match node {
+            JsonNode::Leaf(json) => out.push_str(json),
+            JsonNode::Object(inner) => write_node_map(inner, out),
+        }
+    }
+    out.push('}');
+}
+
+pub(crate) fn write_json_string(bytes: &[u8], out: &mut String) {
+    out.push('"');
+    for c in String::from_utf8_lossy(bytes).chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Render a value as JSON text, the way `ClickHouse` prints `JSON` columns.
+pub(crate) fn write_json_value(value: &Value, bool_leaf: bool, out: &mut String) {
+    match value {
+        Value::UInt8(x) if bool_leaf => out.push_str(if *x == 0 { "false" } else { "true" }),
+        Value::Int8(x) => push_display(out, x),
+        Value::Int16(x) => push_display(out, x),
+        Value::Int32(x) => push_display(out, x),
+        Value::Int64(x) => push_display(out, x),
+        Value::Int128(x) => push_display(out, x),
+        Value::Int256(x) => push_display(out, x),
+        Value::UInt8(x) => push_display(out, x),
+        Value::UInt16(x) => push_display(out, x),
+        Value::UInt32(x) => push_display(out, x),
+        Value::UInt64(x) => push_display(out, x),
+        Value::UInt128(x) => push_display(out, x),
+        Value::UInt256(x) => push_display(out, x),
+        Value::Float32(x) if x.is_finite() => push_display(out, x),
+        Value::Float64(x) if x.is_finite() => push_display(out, x),
+        Value::Float32(_) | Value::Float64(_) | Value::Null => out.push_str("null"),
+        Value::Decimal32(..)
+        | Value::Decimal64(..)
+        | Value::Decimal128(..)
+        | Value::Decimal256(..) => push_display(out, value),
+        Value::String(bytes) | Value::AggregateFunction(bytes) => write_json_string(bytes, out),
+        Value::Enum8(name, _) | Value::Enum16(name, _) => write_json_string(name.as_bytes(), out),
+        Value::Uuid(uuid) => write_json_string(uuid.to_string().as_bytes(), out),
+        Value::Ipv4(ip) => write_json_string(ip.0.to_string().as_bytes(), out),
+        Value::Ipv6(ip) => write_json_string(ip.0.to_string().as_bytes(), out),
+        Value::Date(date) => {
+            let date: NaiveDate = (*date).into();
+            write_json_string(date.to_string().as_bytes(), out);
+        }
+        Value::Date32(date) => {
+            let date: NaiveDate = (*date).into();
+            write_json_string(date.to_string().as_bytes(), out);
+        }
+        Value::DateTime(datetime) => match chrono::DateTime::<Tz>::try_from(*datetime) {
+            Ok(dt) => write_json_string(dt.format("%Y-%m-%d %H:%M:%S").to_string().as_bytes(), out),
+            Err(_) => out.push_str("null"),
+        },
+        Value::DateTime64(datetime) => match chrono::DateTime::<chrono::Utc>::try_from(*datetime) {
+            Ok(dt) => {
+                let dt = dt.with_timezone(&datetime.0);
+                let mut text = dt.format("%Y-%m-%d %H:%M:%S").to_string();
+                let precision = datetime.2.min(9);
+                if precision > 0 {
+                    #[expect(clippy::cast_possible_truncation)]
+                    let fraction =
+                        dt.nanosecond() % 1_000_000_000 / 10u32.pow(9 - precision as u32);
+                    let _ = write!(text, ".{fraction:0precision$}");
+                }
+                write_json_string(text.as_bytes(), out);
+            }
+            Err(_) => out.push_str("null"),
+        },
+        Value::Array(items) | Value::Tuple(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_json_value(item, bool_leaf, out);
+            }
+            out.push(']');
+        }
+        Value::Map(keys, values) => {
+            out.push('{');
+            for (i, (key, value)) in keys.iter().zip(values).enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                if let Value::String(key) = key {
+                    write_json_string(key, out);
+                } else {
+                    let mut rendered = String::new();
+                    write_json_value(key, false, &mut rendered);
+                    write_json_string(rendered.trim_matches('"').as_bytes(), out);
+                }
+                out.push(':');
+                write_json_value(value, bool_leaf, out);
+            }
+            out.push('}');
+        }
+        Value::Object(json) if json.is_empty() => out.push_str("{}"),
+        Value::Object(json) => out.push_str(&String::from_utf8_lossy(json)),
+        Value::Dynamic(name, inner) => write_json_value(inner, is_bool_type(name), out),
+        Value::Variant(_, inner) | Value::SimpleAggregateFunction(inner) => {
+            write_json_value(inner, bool_leaf, out);
+        }
+        other => write_json_string(other.to_string().as_bytes(), out),
+    }
+}
+
+fn push_display(out: &mut String, value: impl fmt::Display) { let _ = write!(out, "{value}"); }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_type_args() {
+        let args = [
+            "max_dynamic_paths=10",
+            "max_dynamic_types = 2",
+            "b.c UInt32",
+            "`a b` Array(String)",
+            "SKIP d.e",
+            "SKIP REGEXP 'x\\'.*'",
+        ];
+        let json = JsonType::from_args(args).unwrap();
+        assert_eq!(json.max_dynamic_paths, Some(10));
+        assert_eq!(json.max_dynamic_types, Some(2));
+        assert_eq!(json.typed_paths, vec![
+            ("a b".to_string(), Type::Array(Box::new(Type::String))),
+            ("b.c".to_string(), Type::UInt32),
+        ]);
+        assert_eq!(json.skip_paths, vec!["d.e".to_string()]);
+        assert_eq!(json.skip_regexps, vec!["x'.*".to_string()]);
+        assert_eq!(
+            json.to_string(),
+            "JSON(max_dynamic_paths=10, max_dynamic_types=2, `a b` Array(String), b.c UInt32, \
+             SKIP d.e, SKIP REGEXP 'x\\'.*')"
+        );
+        assert!(JsonType::from_args(["a.b"]).is_err());
+        assert!(JsonType::from_args(["max_dynamic_paths=x"]).is_err());
+    }
+
+    #[test]
+    fn test_json_skip_paths() {
+        let json = JsonType::new().with_skip_path("a.b");
+        assert!(json.is_skipped("a.b"));
+        assert!(json.is_skipped("a.b.c"));
+        assert!(!json.is_skipped("a.bc"));
+        assert!(!json.is_skipped("a"));
+    }
+
+    #[test]
+    fn test_is_bool_type() {
+        assert!(is_bool_type("Bool"));
+        assert!(is_bool_type("Array(Nullable(Bool))"));
+        assert!(!is_bool_type("UInt8"));
+        assert!(!is_bool_type("Array(String)"));
+    }
+
+    #[test]
+    fn test_json_object_builder() {
+        let mut builder = JsonObjectBuilder::default();
+        builder.insert("b", &Value::String(b"x\"y".to_vec()), false);
+        builder.insert("a.c", &Value::Array(vec![Value::Int64(1), Value::Null]), false);
+        builder.insert("a.b", &Value::UInt8(1), true);
+        builder.insert("d", &Value::Float64(1.5), false);
+        assert_eq!(builder.finish(), r#"{"a":{"b":true,"c":[1,null]},"b":"x\"y","d":1.5}"#);
+    }
+}
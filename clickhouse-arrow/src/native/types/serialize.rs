@@ -1,6 +1,7 @@
 pub(crate) mod array;
 pub(crate) mod dynamic;
 pub(crate) mod geo;
+pub(crate) mod json;
 pub(crate) mod low_cardinality;
 pub(crate) mod map;
 pub(crate) mod nested;
@@ -37,6 +38,10 @@ impl ClickHouseNativeSerializer for Type {
     ) -> impl Future<Output = Result<()>> + Send + 'a {
         use serialize::*;
         async move {
+            // Written together with the data, see `Type::has_dynamic_structure`
+            if self.has_dynamic_structure() {
+                return Ok(());
+            }
             match self {
                 Type::Int8
                 | Type::Int16
@@ -96,9 +101,7 @@ impl ClickHouseNativeSerializer for Type {
                 Type::Variant(_) => {
                     variant::VariantSerializer::write_prefix(self, writer, state).await?;
                 }
-                Type::Dynamic { .. } => {
-                    dynamic::DynamicSerializer::write_prefix(self, writer, state).await?;
-                }
+                Type::Json(_) | Type::Dynamic { .. } => {}
                 Type::Nested(_) => {
                     nested::NestedSerializer::write_prefix(self, writer, state).await?;
                 }
@@ -121,6 +124,9 @@ impl ClickHouseNativeSerializer for Type {
         writer: &mut W,
         state: &mut SerializerState,
     ) {
+        if self.has_dynamic_structure() {
+            return;
+        }
         let type_ = match self {
             Type::Nullable(inner) | Type::Array(inner) => inner,
             Type::Map(key, value) => &super::map::normalize_map_type(key, value),
@@ -6,256 +6,308 @@
 // License:   LicenseRef-HyperSec-EULA
 // Copyright: (c) 2025 HyperSec
 
-// ClickHouse Dynamic type uses u8 discriminators, max 255 types
-#![allow(clippy::cast_possible_truncation)]
-
-//! Serializer for `ClickHouse` Dynamic type (introduced in `ClickHouse` 24.x).
+//! Serializer for `Dynamic` columns and for columns containing `Dynamic` or `JSON`.
 //!
-//! Dynamic is similar to Variant but with runtime-discovered types.
-//! The type information is stored in the structure prefix.
+//! Dynamic is similar to Variant but with runtime-discovered types. The types, like the dynamic
+//! paths of `JSON`, are listed in the stream prefix, so the structure of the whole column is built
+//! from the values first and then written as prefix followed by data.
 //!
 //! Binary format:
-//! - Structure prefix: version (u64) + `num_types` (varuint) + `type_names`...
+//! - Structure prefix: version (u64), `max_types` (varuint, V1 only), number of types (varuint),
+//!   type names, then the prefix of `Variant(types..., SharedVariant)`: discriminators mode (u64)
+//!   and each variant's prefix.
 //! - Data: variant discriminators + variant columns
 //!
+//! Types beyond `max_types` are stored in `SharedVariant` as binary encoded type and value.
+//!
 //! Reference: ClickHouse/src/DataTypes/Serializations/SerializationDynamic.cpp
+use std::collections::HashMap;
+use std::str::FromStr;
 
-use tokio::io::AsyncWriteExt;
-
+use super::json::JsonColumn;
 use super::variant::NULL_DISCRIMINATOR;
-use super::{Serializer, SerializerState, Type};
-use crate::io::{ClickHouseBytesWrite, ClickHouseWrite};
+use super::{ClickHouseNativeSerializer, SerializerState, Type};
+use crate::io::ClickHouseBytesWrite;
+use crate::native::protocol::DBMS_MIN_REVISION_WITH_V2_DYNAMIC_AND_JSON_SERIALIZATION;
+use crate::native::types::binary;
+use crate::native::types::deserialize::dynamic::{
+    DYNAMIC_SERIALIZATION_V1, DYNAMIC_SERIALIZATION_V2, SHARED_VARIANT,
+    VARIANT_DISCRIMINATORS_BASIC,
+};
+use crate::native::types::json_type::{JsonType, is_bool_type};
 use crate::{Error, Result, Value};
 
-/// Dynamic serialization version
-const SERIALIZATION_VERSION_V2: u64 = 1;
+/// Write a column whose type contains `Dynamic` or `JSON`, prefix included.
+pub(crate) fn write_column(
+    type_: &Type,
+    values: Vec<Value>,
+    writer: &mut impl ClickHouseBytesWrite,
+    state: &mut SerializerState,
+) -> Result<()> {
+    let column = StructureColumn::new(type_, values, state.revision)?;
+    column.write_prefix(writer, state)?;
+    column.write_data(writer, state)
+}
 
-pub(crate) struct DynamicSerializer;
+/// Values of a column split up according to the structure written in its stream prefix.
+pub(crate) enum StructureColumn {
+    /// Column without dynamic structure, written through the regular hooks.
+    Plain(Type, Vec<Value>),
+    Dynamic(DynamicColumn),
+    Json(Box<JsonColumn>),
+    /// Offsets and the flattened items of an `Array` or `Map`.
+    Array(Vec<u64>, Box<StructureColumn>),
+    Tuple(Vec<StructureColumn>),
+}
 
-impl Serializer for DynamicSerializer {
-    async fn write_prefix<W: ClickHouseWrite>(
-        type_: &Type,
-        writer: &mut W,
-        _state: &mut SerializerState,
-    ) -> Result<()> {
-        let max_types = match type_ {
-            Type::Dynamic { max_types } => max_types.unwrap_or(0),
+impl StructureColumn {
+    pub(crate) fn new(type_: &Type, values: Vec<Value>, revision: u64) -> Result<Self> {
+        if !type_.has_dynamic_structure() {
+            return Ok(Self::Plain(type_.clone(), values));
+        }
+        Ok(match type_ {
+            Type::Dynamic { max_types } => Self::Dynamic(DynamicColumn::new(
+                values,
+                max_types.unwrap_or(JsonType::DEFAULT_MAX_DYNAMIC_TYPES),
+                revision,
+            )?),
+            Type::Json(json) => Self::Json(Box::new(JsonColumn::new(json, values, revision)?)),
+            Type::Array(inner) => {
+                let (offsets, items) = flatten_items(values, |value| match value {
+                    Value::Array(items) => Ok(items),
+                    Value::Null => Ok(vec![]),
+                    other => Err(Error::SerializeError(format!(
+                        "expected array for {type_}, got {other:?}"
+                    ))),
+                })?;
+                Self::Array(offsets, Box::new(Self::new(inner, items, revision)?))
+            }
+            Type::Map(key, value) => {
+                let entry = Type::Tuple(vec![(**key).clone(), (**value).clone()]);
+                let (offsets, items) = flatten_items(values, |value| match value {
+                    Value::Map(keys, values) => Ok(keys
+                        .into_iter()
+                        .zip(values)
+                        .map(|(k, v)| Value::Tuple(vec![k, v]))
+                        .collect()),
+                    Value::Null => Ok(vec![]),
+                    other => Err(Error::SerializeError(format!(
+                        "expected map for {type_}, got {other:?}"
+                    ))),
+                })?;
+                Self::Array(offsets, Box::new(Self::new(&entry, items, revision)?))
+            }
+            Type::Tuple(inner) => {
+                let mut columns = vec![Vec::with_capacity(values.len()); inner.len()];
+                for value in values {
+                    let Value::Tuple(items) = value else {
+                        return Err(Error::SerializeError(format!(
+                            "expected tuple for {type_}, got {value:?}"
+                        )));
+                    };
+                    for (column, item) in columns.iter_mut().zip(items) {
+                        column.push(item);
+                    }
+                }
+                Self::Tuple(
+                    inner
+                        .iter()
+                        .zip(columns)
+                        .map(|(t, column)| Self::new(t, column, revision))
+                        .collect::<Result<_>>()?,
+                )
+            }
             _ => {
-                return Err(Error::SerializeError(format!(
-                    "DynamicSerializer called with non-dynamic type: {type_:?}"
+                return Err(Error::Unimplemented(format!(
+                    "Dynamic or JSON values are not supported inside {type_}"
                 )));
             }
-        };
-
-        // Write structure version (V2 by default)
-        writer.write_u64_le(SERIALIZATION_VERSION_V2).await?;
-
-        // For now, we write 0 types in prefix - types will be discovered from values
-        // In practice, the types are written during the data phase
-        write_varuint(writer, 0).await?;
-
-        // Optionally write max_types hint
-        if max_types > 0 {
-            // This is used by ClickHouse to pre-allocate variant slots
-        }
-
-        Ok(())
+        })
     }
 
-    async fn write<W: ClickHouseWrite>(
-        type_: &Type,
-        values: Vec<Value>,
-        writer: &mut W,
-        _state: &mut SerializerState,
+    pub(crate) fn write_prefix(
+        &self,
+        writer: &mut impl ClickHouseBytesWrite,
+        state: &mut SerializerState,
     ) -> Result<()> {
-        let _max_types = match type_ {
-            Type::Dynamic { max_types } => max_types.unwrap_or(0),
-            _ => {
-                return Err(Error::SerializeError(format!(
-                    "DynamicSerializer called with non-dynamic type: {type_:?}"
-                )));
-            }
-        };
-
-        // Collect unique types and map values to discriminators
-        let mut type_names: Vec<String> = Vec::new();
-        let mut type_to_discr: std::collections::HashMap<String, u8> =
-            std::collections::HashMap::new();
-        let mut discriminators: Vec<u8> = Vec::with_capacity(values.len());
-        let mut variant_columns: Vec<(String, Vec<Value>)> = Vec::new();
-
-        for value in &values {
-            match value {
-                Value::Null => {
-                    discriminators.push(NULL_DISCRIMINATOR);
-                }
-                Value::Dynamic(type_name, _inner_value) => {
-                    let discr = if let Some(&d) = type_to_discr.get(type_name) {
-                        d
-                    } else {
-                        let d = type_names.len() as u8;
-                        type_names.push(type_name.clone());
-                        let _ = type_to_discr.insert(type_name.clone(), d);
-                        variant_columns.push((type_name.clone(), Vec::new()));
-                        d
-                    };
-                    discriminators.push(discr);
-                }
-                _ => {
-                    // Infer type name from value
-                    let type_name = infer_type_name(value);
-                    let discr = if let Some(&d) = type_to_discr.get(&type_name) {
-                        d
-                    } else {
-                        let d = type_names.len() as u8;
-                        type_names.push(type_name.clone());
-                        let _ = type_to_discr.insert(type_name.clone(), d);
-                        variant_columns.push((type_name, Vec::new()));
-                        d
-                    };
-                    discriminators.push(discr);
+        match self {
+            Self::Plain(type_, _) => type_.serialize_prefix(writer, state),
+            Self::Dynamic(column) => column.write_prefix(writer, state)?,
+            Self::Json(column) => column.write_prefix(writer, state)?,
+            Self::Array(_, inner) => inner.write_prefix(writer, state)?,
+            Self::Tuple(inner) => {
+                for column in inner {
+                    column.write_prefix(writer, state)?;
                 }
             }
         }
+        Ok(())
+    }
 
-        // Build variant columns with actual values
-        for value in values {
-            match value {
-                Value::Null => {}
-                Value::Dynamic(type_name, inner_value) => {
-                    let discr = type_to_discr[&type_name] as usize;
-                    variant_columns[discr].1.push(*inner_value);
-                }
-                _ => {
-                    let type_name = infer_type_name(&value);
-                    let discr = type_to_discr[&type_name] as usize;
-                    variant_columns[discr].1.push(value);
+    pub(crate) fn write_data(
+        self,
+        writer: &mut impl ClickHouseBytesWrite,
+        state: &mut SerializerState,
+    ) -> Result<()> {
+        match self {
+            Self::Plain(type_, values) => type_.serialize_column_sync(values, writer, state)?,
+            Self::Dynamic(column) => column.write_data(writer, state)?,
+            Self::Json(column) => column.write_data(writer, state)?,
+            Self::Array(offsets, inner) => {
+                for offset in offsets {
+                    writer.put_u64_le(offset);
                 }
+                inner.write_data(writer, state)?;
             }
-        }
-
-        // Write number of discovered types
-        write_varuint(writer, type_names.len() as u64).await?;
-
-        // Write type names
-        for type_name in &type_names {
-            write_string(writer, type_name).await?;
-        }
-
-        // Write discriminators
-        writer.write_all(&discriminators).await?;
-
-        // Write each variant's values
-        for (_type_name, column_values) in variant_columns {
-            if !column_values.is_empty() {
-                // For Dynamic, we need to serialize values based on their actual types
-                // This is a simplified implementation - full impl needs type inference
-                for value in column_values {
-                    serialize_value_async(value, writer).await?;
+            Self::Tuple(inner) => {
+                for column in inner {
+                    column.write_data(writer, state)?;
                 }
             }
         }
-
         Ok(())
     }
+}
 
-    fn write_sync(
-        type_: &Type,
-        values: Vec<Value>,
-        writer: &mut impl ClickHouseBytesWrite,
-        _state: &mut SerializerState,
-    ) -> Result<()> {
-        let _max_types = match type_ {
-            Type::Dynamic { max_types } => max_types.unwrap_or(0),
-            _ => {
-                return Err(Error::SerializeError(format!(
-                    "DynamicSerializer called with non-dynamic type: {type_:?}"
-                )));
-            }
-        };
+fn flatten_items(
+    values: Vec<Value>,
+    mut items_of: impl FnMut(Value) -> Result<Vec<Value>>,
+) -> Result<(Vec<u64>, Vec<Value>)> {
+    let mut offsets = Vec::with_capacity(values.len());
+    let mut items = Vec::new();
+    for value in values {
+        items.extend(items_of(value)?);
+        offsets.push(items.len() as u64);
+    }
+    Ok((offsets, items))
+}
 
-        // Collect unique types and map values to discriminators
-        let mut type_names: Vec<String> = Vec::new();
-        let mut type_to_discr: std::collections::HashMap<String, u8> =
-            std::collections::HashMap::new();
-        let mut discriminators: Vec<u8> = Vec::with_capacity(values.len());
-        let mut variant_columns: Vec<(String, Vec<Value>)> = Vec::new();
+/// A `Dynamic` column split into its variants.
+pub(crate) struct DynamicColumn {
+    version:        u64,
+    max_types:      usize,
+    /// Variants sorted by name, including the shared variant.
+    variants:       Vec<(String, StructureColumn)>,
+    discriminators: Vec<u8>,
+}
 
-        for value in &values {
-            match value {
-                Value::Null => {
-                    discriminators.push(NULL_DISCRIMINATOR);
-                }
-                Value::Dynamic(type_name, _inner_value) => {
-                    let discr = if let Some(&d) = type_to_discr.get(type_name) {
-                        d
-                    } else {
-                        let d = type_names.len() as u8;
-                        type_names.push(type_name.clone());
-                        let _ = type_to_discr.insert(type_name.clone(), d);
-                        variant_columns.push((type_name.clone(), Vec::new()));
-                        d
-                    };
-                    discriminators.push(discr);
-                }
-                _ => {
-                    let type_name = infer_type_name(value);
-                    let discr = if let Some(&d) = type_to_discr.get(&type_name) {
-                        d
-                    } else {
-                        let d = type_names.len() as u8;
-                        type_names.push(type_name.clone());
-                        let _ = type_to_discr.insert(type_name.clone(), d);
-                        variant_columns.push((type_name, Vec::new()));
-                        d
-                    };
-                    discriminators.push(discr);
-                }
-            }
-        }
+impl DynamicColumn {
+    /// Group values by type. Values are either [`Value::Dynamic`] carrying their type name, or
+    /// plain values whose type is inferred. The most frequent `max_types` types get a variant of
+    /// their own, the remaining values go to the shared variant.
+    pub(crate) fn new(values: Vec<Value>, max_types: usize, revision: u64) -> Result<Self> {
+        let version = if revision >= DBMS_MIN_REVISION_WITH_V2_DYNAMIC_AND_JSON_SERIALIZATION {
+            DYNAMIC_SERIALIZATION_V2
+        } else {
+            DYNAMIC_SERIALIZATION_V1
+        };
 
-        // Build variant columns with actual values
-        for value in values {
-            match value {
-                Value::Null => {}
-                Value::Dynamic(type_name, inner_value) => {
-                    let discr = type_to_discr[&type_name] as usize;
-                    variant_columns[discr].1.push(*inner_value);
+        let entries = values
+            .into_iter()
+            .map(|value| match value {
+                Value::Null => None,
+                Value::Dynamic(name, value) => Some((name, *value)),
+                value => Some((infer_type_name(&value), value)),
+            })
+            .collect::<Vec<_>>();
+
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for (name, _) in entries.iter().flatten() {
+            *counts.entry(name.as_str()).or_default() += 1;
+        }
+        let mut by_count = counts.into_iter().collect::<Vec<_>>();
+        by_count.sort_by(|(a_name, a), (b_name, b)| b.cmp(a).then(a_name.cmp(b_name)));
+        let mut names = by_count
+            .into_iter()
+            .take(max_types)
+            .map(|(name, _)| name.to_string())
+            .collect::<Vec<_>>();
+        names.push(SHARED_VARIANT.to_string());
+        names.sort();
+
+        // A Dynamic column holds at most 255 variants, NULL_DISCRIMINATOR is reserved
+        if names.len() > usize::from(NULL_DISCRIMINATOR) {
+            return Err(Error::SerializeError(format!("too many Dynamic types: {}", names.len())));
+        }
+        let shared = names.iter().position(|n| n == SHARED_VARIANT).unwrap_or_default();
+        let index =
+            names.iter().enumerate().map(|(i, n)| (n.clone(), i)).collect::<HashMap<_, _>>();
+
+        let mut columns = vec![Vec::new(); names.len()];
+        let mut discriminators = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let Some((name, value)) = entry else {
+                discriminators.push(NULL_DISCRIMINATOR);
+                continue;
+            };
+            let discriminator = match index.get(&name) {
+                Some(&i) if i != shared => {
+                    columns[i].push(value);
+                    i
                 }
                 _ => {
-                    let type_name = infer_type_name(&value);
-                    let discr = type_to_discr[&type_name] as usize;
-                    variant_columns[discr].1.push(value);
+                    let type_ = Type::from_str(&name)?;
+                    let mut encoded = Vec::new();
+                    binary::encode_type(&type_, is_bool_type(&name), &mut encoded)?;
+                    binary::encode_value(&type_, value, &mut encoded)?;
+                    columns[shared].push(Value::String(encoded));
+                    shared
                 }
-            }
+            };
+            #[expect(clippy::cast_possible_truncation)]
+            discriminators.push(discriminator as u8);
         }
 
-        // Write number of discovered types
-        write_varuint_sync(writer, type_names.len() as u64);
+        let variants = names
+            .into_iter()
+            .zip(columns)
+            .map(|(name, values)| {
+                let type_ =
+                    if name == SHARED_VARIANT { Type::String } else { Type::from_str(&name)? };
+                Ok((name, StructureColumn::new(&type_, values, revision)?))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self { version, max_types, variants, discriminators })
+    }
 
-        // Write type names
-        for type_name in &type_names {
-            write_string_sync(writer, type_name);
+    pub(crate) fn write_prefix(
+        &self,
+        writer: &mut impl ClickHouseBytesWrite,
+        state: &mut SerializerState,
+    ) -> Result<()> {
+        writer.put_u64_le(self.version);
+        if self.version == DYNAMIC_SERIALIZATION_V1 {
+            writer.put_var_uint(self.max_types as u64)?;
         }
-
-        // Write discriminators
-        writer.put_slice(&discriminators);
-
-        // Write each variant's values
-        for (_type_name, column_values) in variant_columns {
-            if !column_values.is_empty() {
-                for value in column_values {
-                    serialize_value_sync(value, writer);
-                }
+        writer.put_var_uint(self.variants.len() as u64 - 1)?;
+        for (name, _) in &self.variants {
+            if name != SHARED_VARIANT {
+                writer.put_string(name)?;
             }
         }
+        writer.put_u64_le(VARIANT_DISCRIMINATORS_BASIC);
+        for (_, column) in &self.variants {
+            column.write_prefix(writer, state)?;
+        }
+        Ok(())
+    }
 
+    pub(crate) fn write_data(
+        self,
+        writer: &mut impl ClickHouseBytesWrite,
+        state: &mut SerializerState,
+    ) -> Result<()> {
+        writer.put_slice(&self.discriminators);
+        for (_, column) in self.variants {
+            column.write_data(writer, state)?;
+        }
         Ok(())
     }
 }
 
 /// Infer `ClickHouse` type name from a Value
-fn infer_type_name(value: &Value) -> String {
+pub(crate) fn infer_type_name(value: &Value) -> String {
     match value {
         Value::Int8(_) => "Int8".to_string(),
         Value::Int16(_) => "Int16".to_string(),
@@ -289,7 +341,7 @@ fn infer_type_name(value: &Value) -> String {
         Value::Ring(_) => "Ring".to_string(),
         Value::Polygon(_) => "Polygon".to_string(),
         Value::MultiPolygon(_) => "MultiPolygon".to_string(),
-        Value::Object(_) => "Object('json')".to_string(),
+        Value::Object(_) => "JSON".to_string(),
         Value::Decimal32(scale, _) => format!("Decimal32({scale})"),
         Value::Decimal64(scale, _) => format!("Decimal64({scale})"),
         Value::Decimal128(scale, _) => format!("Decimal128({scale})"),
@@ -307,103 +359,10 @@ fn infer_type_name(value: &Value) -> String {
     }
 }
 
-/// Write variable-length unsigned integer (async)
-async fn write_varuint<W: ClickHouseWrite>(writer: &mut W, mut value: u64) -> Result<()> {
-    loop {
-        let mut byte = (value & 0x7F) as u8;
-        value >>= 7;
-        if value != 0 {
-            byte |= 0x80;
-        }
-        writer.write_u8(byte).await?;
-        if value == 0 {
-            break;
-        }
-    }
-    Ok(())
-}
-
-/// Write variable-length unsigned integer (sync)
-fn write_varuint_sync(writer: &mut impl ClickHouseBytesWrite, mut value: u64) {
-    loop {
-        let mut byte = (value & 0x7F) as u8;
-        value >>= 7;
-        if value != 0 {
-            byte |= 0x80;
-        }
-        writer.put_u8(byte);
-        if value == 0 {
-            break;
-        }
-    }
-}
-
-/// Write string with length prefix (async)
-async fn write_string<W: ClickHouseWrite>(writer: &mut W, s: &str) -> Result<()> {
-    write_varuint(writer, s.len() as u64).await?;
-    writer.write_all(s.as_bytes()).await?;
-    Ok(())
-}
-
-/// Write string with length prefix (sync)
-fn write_string_sync(writer: &mut impl ClickHouseBytesWrite, s: &str) {
-    write_varuint_sync(writer, s.len() as u64);
-    writer.put_slice(s.as_bytes());
-}
-
-/// Serialize a single value (async) - simplified implementation
-async fn serialize_value_async<W: ClickHouseWrite>(value: Value, writer: &mut W) -> Result<()> {
-    // This is a simplified implementation
-    // Full implementation would use the type system to serialize properly
-    match value {
-        Value::Int8(v) => writer.write_i8(v).await?,
-        Value::Int16(v) => writer.write_i16_le(v).await?,
-        Value::Int32(v) => writer.write_i32_le(v).await?,
-        Value::Int64(v) => writer.write_i64_le(v).await?,
-        Value::UInt8(v) => writer.write_u8(v).await?,
-        Value::UInt16(v) => writer.write_u16_le(v).await?,
-        Value::UInt32(v) => writer.write_u32_le(v).await?,
-        Value::UInt64(v) => writer.write_u64_le(v).await?,
-        Value::Float32(v) => writer.write_f32_le(v).await?,
-        Value::Float64(v) => writer.write_f64_le(v).await?,
-        Value::String(v) => {
-            write_varuint(writer, v.len() as u64).await?;
-            writer.write_all(&v).await?;
-        }
-        _ => {
-            return Err(Error::SerializeError(format!(
-                "Unsupported value type in Dynamic: {value:?}"
-            )));
-        }
-    }
-    Ok(())
-}
-
-/// Serialize a single value (sync) - simplified implementation
-fn serialize_value_sync(value: Value, writer: &mut impl ClickHouseBytesWrite) {
-    match value {
-        Value::Int8(v) => writer.put_i8(v),
-        Value::Int16(v) => writer.put_i16_le(v),
-        Value::Int32(v) => writer.put_i32_le(v),
-        Value::Int64(v) => writer.put_i64_le(v),
-        Value::UInt8(v) => writer.put_u8(v),
-        Value::UInt16(v) => writer.put_u16_le(v),
-        Value::UInt32(v) => writer.put_u32_le(v),
-        Value::UInt64(v) => writer.put_u64_le(v),
-        Value::Float32(v) => writer.put_f32_le(v),
-        Value::Float64(v) => writer.put_f64_le(v),
-        Value::String(v) => {
-            write_varuint_sync(writer, v.len() as u64);
-            writer.put_slice(&v);
-        }
-        _ => {
-            // Skip unsupported types for now
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
+    use bytes::BufMut;
+
     use super::*;
 
     #[test]
@@ -414,21 +373,45 @@ mod tests {
     }
 
     #[test]
-    fn test_varuint_encoding() {
-        let mut buf = bytes::BytesMut::new();
-        write_varuint_sync(&mut buf, 0);
-        assert_eq!(&buf[..], &[0]);
-
-        buf.clear();
-        write_varuint_sync(&mut buf, 127);
-        assert_eq!(&buf[..], &[127]);
-
-        buf.clear();
-        write_varuint_sync(&mut buf, 128);
-        assert_eq!(&buf[..], &[0x80, 0x01]);
+    fn test_dynamic_prefix() {
+        let values = vec![Value::Int64(1), Value::Null, Value::String(b"a".to_vec())];
+        let mut state = SerializerState::default()
+            .with_revision(DBMS_MIN_REVISION_WITH_V2_DYNAMIC_AND_JSON_SERIALIZATION);
+        let mut buffer = Vec::new();
+        write_column(&Type::Dynamic { max_types: None }, values, &mut buffer, &mut state).unwrap();
+
+        let mut expected = Vec::new();
+        expected.put_u64_le(DYNAMIC_SERIALIZATION_V2);
+        expected.put_var_uint(2).unwrap();
+        expected.put_string("Int64").unwrap();
+        expected.put_string("String").unwrap();
+        expected.put_u64_le(VARIANT_DISCRIMINATORS_BASIC);
+        // Variants sorted: Int64, SharedVariant, String
+        expected.put_slice(&[0, NULL_DISCRIMINATOR, 2]);
+        expected.put_i64_le(1);
+        expected.put_string("a").unwrap();
+        assert_eq!(buffer, expected);
+    }
 
-        buf.clear();
-        write_varuint_sync(&mut buf, 300);
-        assert_eq!(&buf[..], &[0xAC, 0x02]);
+    #[test]
+    fn test_dynamic_shared_variant() {
+        let values = vec![Value::Int64(1), Value::Int64(2), Value::UInt8(3)];
+        let mut state = SerializerState::default();
+        let mut buffer = Vec::new();
+        write_column(&Type::Dynamic { max_types: Some(1) }, values, &mut buffer, &mut state)
+            .unwrap();
+
+        let mut expected = Vec::new();
+        expected.put_u64_le(DYNAMIC_SERIALIZATION_V1);
+        expected.put_var_uint(1).unwrap();
+        expected.put_var_uint(1).unwrap();
+        expected.put_string("Int64").unwrap();
+        expected.put_u64_le(VARIANT_DISCRIMINATORS_BASIC);
+        expected.put_slice(&[0, 0, 1]);
+        expected.put_i64_le(1);
+        expected.put_i64_le(2);
+        // Shared variant: UInt8 type id followed by the value
+        expected.put_string([0x01, 3]).unwrap();
+        assert_eq!(buffer, expected);
     }
 }
@@ -0,0 +1,489 @@
+//! Serializer for the `JSON` type.
+//!
+//! Documents are inserted as [`Value::Object`] (or [`Value::String`]) holding JSON text. Each
+//! document is flattened into its leaf paths: declared paths are converted to their type, the
+//! most frequent other paths become `Dynamic` subcolumns with inferred types, and the remaining
+//! paths beyond `max_dynamic_paths` are binary encoded into the shared data.
+//!
+//! See [`crate::native::types::deserialize::json`] for the binary format.
+#[cfg(feature = "serde")]
+use self::document::{flatten, infer_json, json_to_value, parse_document};
+use super::SerializerState;
+#[cfg(feature = "serde")]
+use super::Type;
+use super::dynamic::{DynamicColumn, StructureColumn};
+use crate::io::ClickHouseBytesWrite;
+#[cfg(feature = "serde")]
+use crate::native::protocol::DBMS_MIN_REVISION_WITH_V2_DYNAMIC_AND_JSON_SERIALIZATION;
+use crate::native::types::deserialize::json::OBJECT_SERIALIZATION_V1;
+#[cfg(feature = "serde")]
+use crate::native::types::deserialize::json::OBJECT_SERIALIZATION_V2;
+use crate::native::types::json_type::{JsonType, shared_data_type};
+use crate::{Result, Value};
+
+/// A `JSON` column split into its typed paths, dynamic paths and shared data.
+pub(crate) struct JsonColumn {
+    version:           u64,
+    max_dynamic_paths: usize,
+    /// Typed path columns, in the order of [`JsonType::typed_paths`].
+    typed:             Vec<StructureColumn>,
+    /// Dynamic path columns, sorted by path.
+    dynamic:           Vec<(String, DynamicColumn)>,
+    /// Shared data column, `Array(Tuple(String, String))`.
+    shared:            Vec<Value>,
+}
+
+impl JsonColumn {
+    #[cfg(not(feature = "serde"))]
+    pub(crate) fn new(_json: &JsonType, _values: Vec<Value>, _revision: u64) -> Result<Self> {
+        Err(crate::Error::Unimplemented(
+            "inserting JSON columns requires the `serde` feature".into(),
+        ))
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn new(json: &JsonType, values: Vec<Value>, revision: u64) -> Result<Self> {
+        use std::collections::{BTreeMap, HashMap};
+        use std::str::FromStr;
+
+        use crate::native::types::binary;
+        use crate::native::types::json_type::is_bool_type;
+
+        let version = if revision >= DBMS_MIN_REVISION_WITH_V2_DYNAMIC_AND_JSON_SERIALIZATION {
+            OBJECT_SERIALIZATION_V2
+        } else {
+            OBJECT_SERIALIZATION_V1
+        };
+        let rows = values.len();
+
+        let mut documents = Vec::with_capacity(rows);
+        for value in values {
+            let mut leaves = BTreeMap::new();
+            flatten(json, "", parse_document(value)?, &mut leaves);
+            documents.push(leaves);
+        }
+
+        let mut typed = Vec::with_capacity(json.typed_paths.len());
+        for (path, type_) in &json.typed_paths {
+            let values = documents
+                .iter_mut()
+                .map(|leaves| match leaves.remove(path) {
+                    Some(leaf) => json_to_value(&leaf, type_, path),
+                    None => Ok(type_.default_value()),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            typed.push(StructureColumn::new(type_, values, revision)?);
+        }
+
+        // The most frequent paths become subcolumns
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for leaves in &documents {
+            for path in leaves.keys() {
+                *counts.entry(path.as_str()).or_default() += 1;
+            }
+        }
+        let mut by_count = counts.into_iter().collect::<Vec<_>>();
+        by_count.sort_by(|(a_path, a), (b_path, b)| b.cmp(a).then(a_path.cmp(b_path)));
+        let mut paths = by_count
+            .into_iter()
+            .take(json.max_dynamic_paths())
+            .map(|(path, _)| path.to_string())
+            .collect::<Vec<_>>();
+        paths.sort();
+
+        let mut dynamic_values = vec![Vec::with_capacity(rows); paths.len()];
+        let mut shared = Vec::with_capacity(rows);
+        for leaves in documents {
+            let mut entries = Vec::new();
+            let mut leaves = leaves.into_iter().peekable();
+            for (i, path) in paths.iter().enumerate() {
+                // Leaves are sorted, so shared entries sort before the next kept path
+                while let Some((leaf_path, _)) = leaves.peek()
+                    && leaf_path < path
+                {
+                    let Some((leaf_path, leaf)) = leaves.next() else { break };
+                    entries.push((leaf_path, leaf));
+                }
+                match leaves.next_if(|(leaf_path, _)| leaf_path == path) {
+                    Some((_, leaf)) => {
+                        let (name, value) = infer_json(&leaf, json)?;
+                        dynamic_values[i].push(Value::Dynamic(name, Box::new(value)));
+                    }
+                    None => dynamic_values[i].push(Value::Null),
+                }
+            }
+            entries.extend(leaves);
+
+            let mut encoded_entries = Vec::with_capacity(entries.len());
+            for (path, leaf) in entries {
+                let (name, value) = infer_json(&leaf, json)?;
+                let leaf_type = Type::from_str(&name)?;
+                let mut encoded = Vec::new();
+                binary::encode_type(&leaf_type, is_bool_type(&name), &mut encoded)?;
+                binary::encode_value(&leaf_type, value, &mut encoded)?;
+                encoded_entries.push(Value::Tuple(vec![
+                    Value::String(path.into_bytes()),
+                    Value::String(encoded),
+                ]));
+            }
+            shared.push(Value::Array(encoded_entries));
+        }
+
+        let dynamic = paths
+            .into_iter()
+            .zip(dynamic_values)
+            .map(|(path, values)| {
+                Ok((path, DynamicColumn::new(values, json.max_dynamic_types(), revision)?))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self { version, max_dynamic_paths: json.max_dynamic_paths(), typed, dynamic, shared })
+    }
+
+    pub(crate) fn write_prefix(
+        &self,
+        writer: &mut impl ClickHouseBytesWrite,
+        state: &mut SerializerState,
+    ) -> Result<()> {
+        self.write_paths(writer)?;
+        for column in &self.typed {
+            column.write_prefix(writer, state)?;
+        }
+        self.write_dynamic_prefix(writer, state)
+    }
+
+    pub(crate) fn write_data(
+        mut self,
+        writer: &mut impl ClickHouseBytesWrite,
+        state: &mut SerializerState,
+    ) -> Result<()> {
+        for column in std::mem::take(&mut self.typed) {
+            column.write_data(writer, state)?;
+        }
+        self.write_dynamic_data(writer, state)
+    }
+
+    /// Write the start of the prefix, the version and the dynamic paths. The prefixes of the
+    /// typed paths follow.
+    pub(crate) fn write_paths(&self, writer: &mut impl ClickHouseBytesWrite) -> Result<()> {
+        writer.put_u64_le(self.version);
+        if self.version == OBJECT_SERIALIZATION_V1 {
+            writer.put_var_uint(self.max_dynamic_paths as u64)?;
+        }
+        writer.put_var_uint(self.dynamic.len() as u64)?;
+        for (path, _) in &self.dynamic {
+            writer.put_string(path)?;
+        }
+        Ok(())
+    }
+
+    /// Write the prefixes of the dynamic paths, following the prefixes of the typed paths.
+    pub(crate) fn write_dynamic_prefix(
+        &self,
+        writer: &mut impl ClickHouseBytesWrite,
+        state: &mut SerializerState,
+    ) -> Result<()> {
+        for (_, column) in &self.dynamic {
+            column.write_prefix(writer, state)?;
+        }
+        Ok(())
+    }
+
+    /// Write the dynamic paths and the shared data, following the data of the typed paths.
+    pub(crate) fn write_dynamic_data(
+        self,
+        writer: &mut impl ClickHouseBytesWrite,
+        state: &mut SerializerState,
+    ) -> Result<()> {
+        for (_, column) in self.dynamic {
+            column.write_data(writer, state)?;
+        }
+        shared_data_type().serialize_column_sync(self.shared, writer, state)
+    }
+}
+
+#[cfg(feature = "serde")]
+mod document {
+    use std::collections::BTreeMap;
+
+    use serde_json::Value as JsonValue;
+
+    use super::super::Type;
+    use crate::native::types::json_type::JsonType;
+    use crate::{Error, Result, Value};
+
+    pub(super) fn parse_document(value: Value) -> Result<serde_json::Map<String, JsonValue>> {
+        let text = match value {
+            Value::Null => return Ok(serde_json::Map::new()),
+            Value::Object(text) | Value::String(text) => text,
+            other => {
+                return Err(Error::SerializeError(format!(
+                    "expected a JSON document, got {other:?}"
+                )));
+            }
+        };
+        match serde_json::from_slice(&text) {
+            Ok(JsonValue::Object(object)) => Ok(object),
+            Ok(other) => {
+                Err(Error::SerializeError(format!("JSON document is not an object: {other}")))
+            }
+            Err(e) => Err(Error::SerializeError(format!("invalid JSON document: {e}"))),
+        }
+    }
+
+    /// Flatten an object into its leaf paths. `null` leaves and skipped paths are dropped.
+    pub(super) fn flatten(
+        json: &JsonType,
+        prefix: &str,
+        object: serde_json::Map<String, JsonValue>,
+        leaves: &mut BTreeMap<String, JsonValue>,
+    ) {
+        for (key, value) in object {
+            let path = if prefix.is_empty() { key } else { format!("{prefix}.{key}") };
+            if json.is_skipped(&path) {
+                continue;
+            }
+            match value {
+                JsonValue::Null => {}
+                // Typed paths keep their value even if it is an object
+                JsonValue::Object(object) if json.typed_path(&path).is_none() => {
+                    flatten(json, &path, object, leaves);
+                }
+                value => {
+                    drop(leaves.insert(path, value));
+                }
+            }
+        }
+    }
+
+    /// Convert the value of a typed path.
+    pub(super) fn json_to_value(value: &JsonValue, type_: &Type, path: &str) -> Result<Value> {
+        let invalid = || {
+            Error::SerializeError(format!("JSON path '{path}' of type {type_} cannot hold {value}"))
+        };
+        let integer = || -> Result<i128> {
+            match value {
+                JsonValue::Number(n) => n
+                    .as_i64()
+                    .map(i128::from)
+                    .or_else(|| n.as_u64().map(i128::from))
+                    .ok_or_else(invalid),
+                JsonValue::Bool(b) => Ok(i128::from(*b)),
+                JsonValue::String(s) => s.parse().map_err(|_| invalid()),
+                _ => Err(invalid()),
+            }
+        };
+        let float = || -> Result<f64> {
+            match value {
+                JsonValue::Number(n) => n.as_f64().ok_or_else(invalid),
+                JsonValue::String(s) => s.parse().map_err(|_| invalid()),
+                _ => Err(invalid()),
+            }
+        };
+        Ok(match type_ {
+            Type::Nullable(_) if value.is_null() => Value::Null,
+            Type::Nullable(inner) | Type::LowCardinality(inner) => {
+                json_to_value(value, inner, path)?
+            }
+            _ if value.is_null() => type_.default_value(),
+            Type::Int8 => Value::Int8(integer()?.try_into().map_err(|_| invalid())?),
+            Type::Int16 => Value::Int16(integer()?.try_into().map_err(|_| invalid())?),
+            Type::Int32 => Value::Int32(integer()?.try_into().map_err(|_| invalid())?),
+            Type::Int64 => Value::Int64(integer()?.try_into().map_err(|_| invalid())?),
+            Type::UInt8 => Value::UInt8(integer()?.try_into().map_err(|_| invalid())?),
+            Type::UInt16 => Value::UInt16(integer()?.try_into().map_err(|_| invalid())?),
+            Type::UInt32 => Value::UInt32(integer()?.try_into().map_err(|_| invalid())?),
+            Type::UInt64 => Value::UInt64(integer()?.try_into().map_err(|_| invalid())?),
+            #[expect(clippy::cast_possible_truncation)]
+            Type::Float32 => Value::Float32(float()? as f32),
+            Type::Float64 => Value::Float64(float()?),
+            Type::String | Type::Binary => match value {
+                JsonValue::String(s) => Value::String(s.clone().into_bytes()),
+                other => Value::String(other.to_string().into_bytes()),
+            },
+            Type::Array(inner) => match value {
+                JsonValue::Array(items) => Value::Array(
+                    items
+                        .iter()
+                        .map(|item| json_to_value(item, inner, path))
+                        .collect::<Result<_>>()?,
+                ),
+                _ => return Err(invalid()),
+            },
+            Type::Json(_) => Value::Object(value.to_string().into_bytes()),
+            _ => return Err(invalid()),
+        })
+    }
+
+    /// Infer the type of a dynamic leaf, returning the type name and the value.
+    pub(super) fn infer_json(value: &JsonValue, json: &JsonType) -> Result<(String, Value)> {
+        match value {
+            JsonValue::Array(items) => {
+                let (name, value) = infer_array(items, json)?;
+                Ok((name.unwrap_or_else(|| "Array(Nullable(String))".into()), value))
+            }
+            JsonValue::Object(_) => Ok(("JSON".into(), Value::Object(value.to_string().into()))),
+            scalar => infer_scalar(scalar).map(|(name, value)| (name.to_string(), value)),
+        }
+    }
+
+    fn infer_scalar(value: &JsonValue) -> Result<(&'static str, Value)> {
+        Ok(match value {
+            JsonValue::Bool(b) => ("Bool", Value::UInt8(u8::from(*b))),
+            JsonValue::Number(n) => {
+                if let Some(i) = n.as_i64() {
+                    ("Int64", Value::Int64(i))
+                } else if let Some(u) = n.as_u64() {
+                    ("UInt64", Value::UInt64(u))
+                } else {
+                    ("Float64", Value::Float64(n.as_f64().unwrap_or_default()))
+                }
+            }
+            JsonValue::String(s) => ("String", Value::String(s.clone().into_bytes())),
+            other => {
+                return Err(Error::SerializeError(format!("unexpected JSON value {other}")));
+            }
+        })
+    }
+
+    /// Infer the type of an array. The element type is `None` when the array has no non-null
+    /// elements, those arrays fit any array type.
+    fn infer_array(items: &[JsonValue], json: &JsonType) -> Result<(Option<String>, Value)> {
+        if items.iter().any(JsonValue::is_object) {
+            if !items.iter().all(|i| i.is_object() || i.is_null()) {
+                return Err(Error::SerializeError(format!(
+                    "JSON arrays mixing objects and other values are not supported: {items:?}"
+                )));
+            }
+            // Nested objects get a share of the parent's limits, like ClickHouse does
+            let name = format!(
+                "Array(JSON(max_dynamic_types={}, max_dynamic_paths={}))",
+                json.max_dynamic_types() / 2,
+                json.max_dynamic_paths() / 4
+            );
+            let values = items
+                .iter()
+                .map(|item| {
+                    Value::Object(if item.is_null() {
+                        b"{}".to_vec()
+                    } else {
+                        item.to_string().into()
+                    })
+                })
+                .collect();
+            return Ok((Some(name), Value::Array(values)));
+        }
+
+        if items.iter().any(JsonValue::is_array) {
+            let mut name: Option<String> = None;
+            let mut values = Vec::with_capacity(items.len());
+            for item in items {
+                match item {
+                    JsonValue::Array(inner) => {
+                        let (inner_name, value) = infer_array(inner, json)?;
+                        match (&name, inner_name) {
+                            (Some(a), Some(b)) if *a != b => {
+                                return Err(Error::SerializeError(format!(
+                                    "JSON array mixes element types {a} and {b}"
+                                )));
+                            }
+                            (None, Some(b)) => name = Some(b),
+                            _ => {}
+                        }
+                        values.push(value);
+                    }
+                    JsonValue::Null => values.push(Value::Array(vec![])),
+                    other => {
+                        return Err(Error::SerializeError(format!(
+                            "JSON array mixes arrays and scalars: {other}"
+                        )));
+                    }
+                }
+            }
+            let name = name.unwrap_or_else(|| "Array(Nullable(String))".into());
+            return Ok((Some(format!("Array({name})")), Value::Array(values)));
+        }
+
+        let scalars = items
+            .iter()
+            .map(|item| if item.is_null() { Ok(None) } else { infer_scalar(item).map(Some) })
+            .collect::<Result<Vec<_>>>()?;
+        let mut names = scalars.iter().flatten().map(|(name, _)| *name).collect::<Vec<_>>();
+        names.sort_unstable();
+        names.dedup();
+        let element = match names.as_slice() {
+            [] => return Ok((None, Value::Array(vec![Value::Null; items.len()]))),
+            [name] => *name,
+            _ if names.iter().all(|n| matches!(*n, "Int64" | "UInt64" | "Float64")) => "Float64",
+            // Mixed types are stored as Dynamic elements
+            _ => {
+                let values = scalars
+                    .into_iter()
+                    .map(|scalar| match scalar {
+                        Some((name, value)) => Value::Dynamic(name.into(), Box::new(value)),
+                        None => Value::Null,
+                    })
+                    .collect();
+                return Ok((Some("Array(Dynamic)".into()), Value::Array(values)));
+            }
+        };
+        let values = items
+            .iter()
+            .zip(scalars)
+            .map(|(item, scalar)| match scalar {
+                None => Value::Null,
+                Some(_) if element == "Float64" => {
+                    Value::Float64(item.as_f64().unwrap_or_default())
+                }
+                Some((_, value)) => value,
+            })
+            .collect();
+        Ok((Some(format!("Array(Nullable({element}))")), Value::Array(values)))
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_infer_json() {
+        let json = JsonType::default();
+        assert_eq!(infer_json(&json!(true), &json).unwrap(), ("Bool".into(), Value::UInt8(1)));
+        assert_eq!(infer_json(&json!(-1), &json).unwrap(), ("Int64".into(), Value::Int64(-1)));
+        assert_eq!(
+            infer_json(&json!([1, null, 2.5]), &json).unwrap(),
+            (
+                "Array(Nullable(Float64))".into(),
+                Value::Array(vec![Value::Float64(1.0), Value::Null, Value::Float64(2.5)])
+            )
+        );
+        assert_eq!(
+            infer_json(&json!([[1], []]), &json).unwrap().0,
+            "Array(Array(Nullable(Int64)))"
+        );
+        assert_eq!(infer_json(&json!([1, "a"]), &json).unwrap().0, "Array(Dynamic)");
+        assert_eq!(
+            infer_json(&json!([{"a": 1}]), &json).unwrap().0,
+            "Array(JSON(max_dynamic_types=16, max_dynamic_paths=256))"
+        );
+    }
+
+    #[test]
+    fn test_json_typed_values() {
+        let int = Type::UInt32;
+        assert_eq!(json_to_value(&json!(7), &int, "a").unwrap(), Value::UInt32(7));
+        assert_eq!(json_to_value(&json!("7"), &int, "a").unwrap(), Value::UInt32(7));
+        assert!(json_to_value(&json!(-7), &int, "a").is_err());
+        assert_eq!(
+            json_to_value(&json!(null), &Type::Nullable(Box::new(int)), "a").unwrap(),
+            Value::Null
+        );
+        assert_eq!(
+            json_to_value(&json!(1), &Type::String, "a").unwrap(),
+            Value::String(b"1".to_vec())
+        );
+    }
+}
@@ -1,5 +1,7 @@
 use std::io::Cursor;
 use std::net::{Ipv4Addr, Ipv6Addr};
+#[cfg(feature = "serde")]
+use std::str::FromStr;
 
 use chrono_tz::Tz;
 use uuid::Uuid;
@@ -8,6 +10,7 @@ use super::Type;
 use super::deserialize::ClickHouseNativeDeserializer;
 use super::serialize::ClickHouseNativeSerializer;
 use crate::formats::{DeserializerState, SerializerState};
+use crate::native::protocol::DBMS_MIN_REVISION_WITH_V2_DYNAMIC_AND_JSON_SERIALIZATION;
 use crate::{
     Date, Date32, DateTime, DynDateTime64, MultiPolygon, Point, Polygon, Result, Ring, Value, i256,
     u256,
@@ -266,6 +269,89 @@ async fn roundtrip_object() {
     assert_eq!(&values[..], roundtrip_values(&Type::Object, &values[..]).await.unwrap());
 }
 
+async fn roundtrip_values_revision(
+    type_: &Type,
+    values: &[Value],
+    revision: u64,
+) -> Result<Vec<Value>> {
+    let mut output = vec![];
+
+    let mut state = SerializerState::default().with_revision(revision);
+    type_.serialize_prefix_async(&mut output, &mut state).await?;
+    type_.serialize_column(values.to_vec(), &mut output, &mut state).await?;
+    let mut input = Cursor::new(output);
+    let mut state = DeserializerState::default();
+    type_.deserialize_prefix_async(&mut input, &mut state).await?;
+    let deserialized = type_.deserialize_column(&mut input, values.len(), &mut state).await?;
+    assert_eq!(input.position(), input.get_ref().len() as u64, "column not fully read");
+
+    Ok(deserialized)
+}
+
+#[cfg(feature = "serde")]
+#[tokio::test]
+async fn roundtrip_json() {
+    let type_ = Type::from_str("JSON(max_dynamic_paths=2, a.b UInt32, SKIP c)").unwrap();
+    let values = &[
+        Value::Object(br#"{"a":{"b":1},"c":5,"d":"x","e":[1,2],"f":true}"#.to_vec()),
+        Value::Object(br#"{"d":"y","g":{"h":1.5}}"#.to_vec()),
+        Value::Null,
+    ];
+    // `d` and `e` become dynamic paths, `f` and `g.h` go to the shared data
+    let expected = &[
+        Value::Object(br#"{"a":{"b":1},"d":"x","e":[1,2],"f":true}"#.to_vec()),
+        Value::Object(br#"{"a":{"b":0},"d":"y","g":{"h":1.5}}"#.to_vec()),
+        Value::Object(br#"{"a":{"b":0}}"#.to_vec()),
+    ];
+    for revision in [0, DBMS_MIN_REVISION_WITH_V2_DYNAMIC_AND_JSON_SERIALIZATION] {
+        assert_eq!(
+            &expected[..],
+            roundtrip_values_revision(&type_, values, revision).await.unwrap()
+        );
+    }
+
+    // Nested documents
+    let type_ = Type::from_str("Array(JSON)").unwrap();
+    let values = &[
+        Value::Array(vec![Value::Object(br#"{"a":[{"b":"c"}]}"#.to_vec())]),
+        Value::Array(vec![]),
+    ];
+    assert_eq!(&values[..], roundtrip_values_revision(&type_, values, 0).await.unwrap());
+}
+
+#[cfg(not(feature = "serde"))]
+#[tokio::test]
+async fn json_insert_requires_serde() {
+    let type_ = Type::Json(Box::default());
+    let mut state = SerializerState::default();
+    let result = type_.serialize_column(vec![Value::Null], &mut vec![], &mut state).await;
+    assert!(matches!(result, Err(crate::Error::Unimplemented(_))));
+}
+
+#[tokio::test]
+async fn roundtrip_dynamic() {
+    let dynamic = |name: &str, value| Value::Dynamic(name.into(), Box::new(value));
+    let values = &[
+        dynamic("Int64", Value::Int64(1)),
+        Value::Null,
+        dynamic("String", Value::string("a")),
+        dynamic("Array(Nullable(Int64))", Value::Array(vec![Value::Int64(2), Value::Null])),
+        dynamic("Bool", Value::UInt8(1)),
+    ];
+    // Only two types get a variant, the others are stored in the shared variant
+    let type_ = Type::Dynamic { max_types: Some(2) };
+    for revision in [0, DBMS_MIN_REVISION_WITH_V2_DYNAMIC_AND_JSON_SERIALIZATION] {
+        assert_eq!(&values[..], roundtrip_values_revision(&type_, values, revision).await.unwrap());
+    }
+
+    let type_ = Type::Map(Box::new(Type::String), Box::new(Type::Dynamic { max_types: None }));
+    let values = &[
+        Value::Map(vec![Value::string("k")], vec![dynamic("Float64", Value::Float64(0.5))]),
+        Value::Map(vec![], vec![]),
+    ];
+    assert_eq!(&values[..], roundtrip_values_revision(&type_, values, 0).await.unwrap());
+}
+
 #[tokio::test]
 async fn roundtrip_uuid() {
     let values = &[
@@ -370,7 +370,7 @@ impl Value {
             Value::Ring(_) => Type::Ring,
             Value::Polygon(_) => Type::Polygon,
             Value::MultiPolygon(_) => Type::MultiPolygon,
-            Value::Object(_) => Type::Object,
+            Value::Object(_) => Type::Json(Box::default()),
             // DFE Fork: New types
             Value::Variant(discr, inner) => {
                 // Create a single-variant Variant type with the inner type
@@ -522,7 +522,7 @@ fn test_value_methods() {
         Type::Map(Box::new(Type::String), Box::new(Type::String)),
         Type::Ipv4,
         Type::Ipv6,
-        Type::Object,
+        Type::Json(Box::default()),
     ];
 
     for type_ in val_types {
@@ -217,6 +217,10 @@ impl<T: ClientFormat> ManageConnection for ConnectionManager<T> {
                 warn!("Connection validation failed: Closed");
                 Err(Error::ConnectionGone("Connection in closed state"))
             }
+            ConnectionStatus::Reconnecting => {
+                warn!("Connection validation failed: Reconnecting");
+                Err(Error::ConnectionGone("Connection is reconnecting"))
+            }
             ConnectionStatus::Open => {
                 let id = conn.client_id;
                 let timeout_duration = Duration::from_secs(2);
@@ -239,44 +243,3 @@ impl<T: ClientFormat> ManageConnection for ConnectionManager<T> {
         matches!(conn.status(), ConnectionStatus::Error | ConnectionStatus::Closed)
     }
 }
-
-#[derive(Debug, Clone, Copy)]
-pub struct ExponentialBackoff {
-    current_interval: Duration,
-    factor:           f64,
-    max_interval:     Duration,
-    max_elapsed_time: Option<Duration>,
-    attempts:         u32,
-}
-
-impl ExponentialBackoff {
-    pub fn new() -> Self {
-        ExponentialBackoff {
-            current_interval: Duration::from_millis(10), // Start with 100ms
-            factor:           2.0,
-            max_interval:     Duration::from_secs(60),
-            max_elapsed_time: Some(Duration::from_secs(900)), // 15 minutes
-            attempts:         0,
-        }
-    }
-
-    pub fn next_backoff(&mut self) -> Option<Duration> {
-        self.attempts += 1;
-
-        if let Some(max_time) = self.max_elapsed_time
-            && self.current_interval * self.attempts > max_time
-        {
-            return None;
-        }
-
-        #[expect(clippy::cast_possible_wrap)]
-        let next_interval =
-            self.current_interval.mul_f64(self.factor.powi(self.attempts as i32 - 1));
-
-        Some(next_interval.min(self.max_interval))
-    }
-}
-
-impl Default for ExponentialBackoff {
-    fn default() -> Self { Self::new() }
-}
@@ -15,7 +15,11 @@ pub use crate::query::{ParamValue, ParsedQuery, Qid, QueryParams};
 pub use crate::schema::*;
 pub use crate::settings::*;
 pub use crate::telemetry::*;
-pub use crate::{ArrowClient, Client, ClientBuilder, CompressionMethod, NativeClient, Row, Type};
+pub use crate::transport::{ArrowTransport, RecordBatchStream};
+pub use crate::{
+    ArrowClient, Client, ClientBuilder, CompressionMethod, CompressionOptions, Inserter,
+    InserterOptions, NativeClient, Row, Type,
+};
 
 // TODO: Encrypt
 /// Newtype to protect secrets from being logged
@@ -1,9 +1,13 @@
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 
+use chrono::{NaiveDate, NaiveDateTime, TimeZone, Utc};
 use uuid::Uuid;
 
 use crate::Result;
 use crate::io::ClickHouseWrite;
+use crate::native::values::{DateTime, DynDateTime64, MultiPolygon, Point, Polygon, Ring, Value};
 use crate::prelude::SettingValue;
 use crate::settings::SETTING_FLAG_CUSTOM;
 
@@ -57,8 +61,443 @@ impl fmt::Display for Qid {
     }
 }
 
-/// Type alias to help distinguish settings from params
-pub type ParamValue = SettingValue;
+/// A typed value bound to a query parameter.
+///
+/// `ClickHouse` parses parameter values from their text representation using the type given in
+/// the query, e.g. `{ids:Array(UInt64)}` or `{ts:DateTime64(3)}`. `ParamValue` renders each
+/// variant in the text format expected by the server, quoting nested strings, dates and other
+/// quoted types as required, so composite values like arrays of tuples or maps of nullable
+/// values can be bound directly.
+///
+/// Conversions exist from Rust primitives, `Option` (`None` binds `NULL`), `Vec` and slices
+/// (arrays), tuples, `HashMap`/`BTreeMap` (maps), `chrono` dates and times, [`Uuid`], IP
+/// addresses, `rust_decimal::Decimal` (with the `rust_decimal` feature) and any native
+/// [`Value`].
+///
+/// Top level strings are not quoted, only backslashes and control characters are escaped, so a
+/// pre-formatted string such as `"[1, 2, 3]"` can still be bound to any type. Use
+/// [`ParamValue::Raw`] to send text without any escaping.
+///
+/// # Example
+/// ```rust,ignore
+/// use clickhouse_arrow::prelude::*;
+///
+/// let params = QueryParams::from(vec![
+///     ("ids", ParamValue::from(vec![1_u64, 2, 3])),
+///     ("name", ParamValue::from(Some("it's"))),
+///     ("ts", ParamValue::from(chrono::Utc::now())),
+/// ]);
+/// let query = "SELECT * FROM t WHERE id IN {ids:Array(UInt64)} AND name = {name:Nullable(String)} \
+///              AND ts < {ts:DateTime64(3)}";
+/// ```
+///
+/// See:
+/// [Queries with parameters](https://clickhouse.com/docs/interfaces/cli#cli-queries-with-parameters)
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamValue {
+    /// `NULL`, for `Nullable` types.
+    Null,
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    /// A string. At the top level it is escaped, nested it is also quoted.
+    String(String),
+    /// A decimal, represented by its unscaled value and scale.
+    Decimal {
+        value: i128,
+        scale: u32,
+    },
+    Date(NaiveDate),
+    /// A date and time without timezone, interpreted in the timezone of the parameter's type.
+    DateTime(NaiveDateTime),
+    /// A point in time, passed as a unix timestamp so it is independent of timezones. Sub-second
+    /// precision is only sent when non-zero, truncate it when binding to `DateTime`.
+    Timestamp(chrono::DateTime<Utc>),
+    Uuid(Uuid),
+    Ipv4(Ipv4Addr),
+    Ipv6(Ipv6Addr),
+    Array(Vec<ParamValue>),
+    Tuple(Vec<ParamValue>),
+    Map(Vec<(ParamValue, ParamValue)>),
+    /// Pre-formatted text, written as is at any nesting level.
+    Raw(String),
+}
+
+impl ParamValue {
+    /// Writes the value in `ClickHouse`'s text format. Top level values use the escaped format,
+    /// nested values the quoted format.
+    fn write_text(&self, out: &mut String, nested: bool) {
+        use fmt::Write as _;
+
+        // Types that are quoted when nested
+        let quoted = |out: &mut String, text: fmt::Arguments<'_>| {
+            if nested {
+                let _ = write!(out, "'{text}'");
+            } else {
+                let _ = out.write_fmt(text);
+            }
+        };
+
+        match self {
+            ParamValue::Null if nested => out.push_str("NULL"),
+            ParamValue::Null => out.push_str("\\N"),
+            ParamValue::Bool(b) => {
+                let _ = write!(out, "{b}");
+            }
+            ParamValue::Int(i) => {
+                let _ = write!(out, "{i}");
+            }
+            ParamValue::UInt(u) => {
+                let _ = write!(out, "{u}");
+            }
+            ParamValue::Float(f) if f.is_nan() => out.push_str("nan"),
+            ParamValue::Float(f) => {
+                let _ = write!(out, "{f}");
+            }
+            ParamValue::String(s) if nested => {
+                out.push('\'');
+                escape_text(out, s, true);
+                out.push('\'');
+            }
+            ParamValue::String(s) => escape_text(out, s, false),
+            ParamValue::Decimal { value, scale } => write_decimal(out, &value.to_string(), *scale),
+            ParamValue::Date(date) => quoted(out, format_args!("{}", date.format("%Y-%m-%d"))),
+            ParamValue::DateTime(datetime) => {
+                quoted(out, format_args!("{}", datetime.format("%Y-%m-%d %H:%M:%S%.f")));
+            }
+            ParamValue::Timestamp(ts) => {
+                let mut text = String::new();
+                write_timestamp(&mut text, ts.timestamp(), ts.timestamp_subsec_nanos());
+                quoted(out, format_args!("{text}"));
+            }
+            ParamValue::Uuid(uuid) => quoted(out, format_args!("{uuid}")),
+            ParamValue::Ipv4(ip) => quoted(out, format_args!("{ip}")),
+            ParamValue::Ipv6(ip) => quoted(out, format_args!("{ip}")),
+            ParamValue::Array(values) => {
+                out.push('[');
+                write_list(out, values);
+                out.push(']');
+            }
+            ParamValue::Tuple(values) => {
+                out.push('(');
+                write_list(out, values);
+                out.push(')');
+            }
+            ParamValue::Map(entries) => {
+                out.push('{');
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    key.write_text(out, true);
+                    out.push(':');
+                    value.write_text(out, true);
+                }
+                out.push('}');
+            }
+            ParamValue::Raw(raw) => out.push_str(raw),
+        }
+    }
+}
+
+/// Formats the value as the server parses it, e.g. `[1,2,3]` or `('a',NULL)`.
+impl fmt::Display for ParamValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut text = String::new();
+        self.write_text(&mut text, false);
+        f.write_str(&text)
+    }
+}
+
+fn write_list(out: &mut String, values: &[ParamValue]) {
+    for (i, value) in values.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        value.write_text(out, true);
+    }
+}
+
+/// Escapes a string for `ClickHouse`'s escaped (top level) or quoted (nested) text format.
+fn escape_text(out: &mut String, s: &str, quoted: bool) {
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\'' if quoted => out.push_str("\\'"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\0' => out.push_str("\\0"),
+            '\x08' => out.push_str("\\b"),
+            '\x0C' => out.push_str("\\f"),
+            c => out.push(c),
+        }
+    }
+}
+
+/// Writes an unscaled decimal, given as its integer digits, with `scale` fractional digits.
+fn write_decimal(out: &mut String, digits: &str, scale: u32) {
+    let (sign, digits) = digits.strip_prefix('-').map_or(("", digits), |d| ("-", d));
+    let scale = scale as usize;
+    if scale == 0 {
+        out.push_str(sign);
+        out.push_str(digits);
+        return;
+    }
+    let padded = format!("{digits:0>width$}", width = scale + 1);
+    let (int, fraction) = padded.split_at(padded.len() - scale);
+    out.push_str(sign);
+    out.push_str(int);
+    out.push('.');
+    out.push_str(fraction);
+}
+
+/// Writes a unix timestamp with an optional fraction, trimming trailing zeros.
+fn write_timestamp(out: &mut String, secs: i64, nanos: u32) {
+    use fmt::Write as _;
+
+    if nanos == 0 {
+        let _ = write!(out, "{secs}");
+        return;
+    }
+    // chrono keeps nanoseconds positive, negative timestamps count back from the next second
+    let (sign, secs, nanos) = if secs < 0 {
+        ("-", (secs + 1).unsigned_abs(), 1_000_000_000 - nanos)
+    } else {
+        ("", secs.unsigned_abs(), nanos)
+    };
+    let fraction = format!("{nanos:09}");
+    let _ = write!(out, "{sign}{secs}.{}", fraction.trim_end_matches('0'));
+}
+
+macro_rules! param_value {
+    ($ty:ident, $inner:ty) => {
+        impl From<$inner> for ParamValue {
+            fn from(value: $inner) -> Self { ParamValue::$ty(value.into()) }
+        }
+    };
+    ($ty:ident, $inner:ty, $v:tt =>  { $override:expr }) => {
+        impl From<$inner> for ParamValue {
+            fn from($v: $inner) -> Self { ParamValue::$ty($override) }
+        }
+    };
+}
+
+param_value!(Bool, bool);
+param_value!(Int, i8);
+param_value!(Int, i16);
+param_value!(Int, i32);
+param_value!(Int, i64);
+param_value!(UInt, u8);
+param_value!(UInt, u16);
+param_value!(UInt, u32);
+param_value!(UInt, u64);
+param_value!(Float, f32);
+param_value!(Float, f64);
+param_value!(Raw, i128, v => { v.to_string() });
+param_value!(Raw, u128, v => { v.to_string() });
+param_value!(String, &str, v => { v.to_string() });
+param_value!(String, &String, v => { v.clone() });
+param_value!(String, String);
+param_value!(String, Box<str>, v => { v.to_string() });
+param_value!(String, std::sync::Arc<str>, v => { v.to_string() });
+param_value!(Date, NaiveDate);
+param_value!(DateTime, NaiveDateTime);
+param_value!(Uuid, Uuid);
+param_value!(Ipv4, Ipv4Addr);
+param_value!(Ipv6, Ipv6Addr);
+
+impl<Tz: TimeZone> From<chrono::DateTime<Tz>> for ParamValue {
+    fn from(value: chrono::DateTime<Tz>) -> Self { ParamValue::Timestamp(value.to_utc()) }
+}
+
+impl From<IpAddr> for ParamValue {
+    fn from(value: IpAddr) -> Self {
+        match value {
+            IpAddr::V4(ip) => ParamValue::Ipv4(ip),
+            IpAddr::V6(ip) => ParamValue::Ipv6(ip),
+        }
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+impl From<rust_decimal::Decimal> for ParamValue {
+    fn from(value: rust_decimal::Decimal) -> Self {
+        ParamValue::Decimal { value: value.mantissa(), scale: value.scale() }
+    }
+}
+
+impl<T: Into<ParamValue>> From<Option<T>> for ParamValue {
+    fn from(value: Option<T>) -> Self { value.map_or(ParamValue::Null, Into::into) }
+}
+
+impl<T: Into<ParamValue>> From<Vec<T>> for ParamValue {
+    fn from(value: Vec<T>) -> Self {
+        ParamValue::Array(value.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Clone + Into<ParamValue>> From<&[T]> for ParamValue {
+    fn from(value: &[T]) -> Self {
+        ParamValue::Array(value.iter().cloned().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<ParamValue>, const N: usize> From<[T; N]> for ParamValue {
+    fn from(value: [T; N]) -> Self {
+        ParamValue::Array(value.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<K: Into<ParamValue>, V: Into<ParamValue>, S> From<HashMap<K, V, S>> for ParamValue {
+    fn from(value: HashMap<K, V, S>) -> Self {
+        ParamValue::Map(value.into_iter().map(|(k, v)| (k.into(), v.into())).collect())
+    }
+}
+
+impl<K: Into<ParamValue>, V: Into<ParamValue>> From<BTreeMap<K, V>> for ParamValue {
+    fn from(value: BTreeMap<K, V>) -> Self {
+        ParamValue::Map(value.into_iter().map(|(k, v)| (k.into(), v.into())).collect())
+    }
+}
+
+macro_rules! param_value_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: Into<ParamValue>),+> From<($($name,)+)> for ParamValue {
+            #[allow(non_snake_case)]
+            fn from(($($name,)+): ($($name,)+)) -> Self {
+                ParamValue::Tuple(vec![$($name.into()),+])
+            }
+        }
+    };
+}
+
+param_value_tuple!(A);
+param_value_tuple!(A, B);
+param_value_tuple!(A, B, C);
+param_value_tuple!(A, B, C, D);
+param_value_tuple!(A, B, C, D, E);
+param_value_tuple!(A, B, C, D, E, F);
+param_value_tuple!(A, B, C, D, E, F, G);
+param_value_tuple!(A, B, C, D, E, F, G, H);
+
+impl From<SettingValue> for ParamValue {
+    fn from(value: SettingValue) -> Self {
+        match value {
+            SettingValue::Int(i) => ParamValue::Int(i),
+            SettingValue::Bool(b) => ParamValue::Bool(b),
+            SettingValue::Float(f) => ParamValue::Float(f),
+            SettingValue::String(s) => ParamValue::String(s),
+        }
+    }
+}
+
+impl From<Value> for ParamValue {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Int8(x) => x.into(),
+            Value::Int16(x) => x.into(),
+            Value::Int32(x) => x.into(),
+            Value::Int64(x) => x.into(),
+            Value::Int128(x) => x.into(),
+            Value::Int256(x) => ParamValue::Raw(x.to_string()),
+            Value::UInt8(x) => x.into(),
+            Value::UInt16(x) => x.into(),
+            Value::UInt32(x) => x.into(),
+            Value::UInt64(x) => x.into(),
+            Value::UInt128(x) => x.into(),
+            Value::UInt256(x) => ParamValue::Raw(x.to_string()),
+            Value::Float32(x) => x.into(),
+            Value::Float64(x) => x.into(),
+            Value::BFloat16(bits) => f32::from_bits(u32::from(bits) << 16).into(),
+            #[expect(clippy::cast_possible_truncation)]
+            Value::Decimal32(scale, value) => {
+                ParamValue::Decimal { value: value.into(), scale: scale as u32 }
+            }
+            #[expect(clippy::cast_possible_truncation)]
+            Value::Decimal64(scale, value) => {
+                ParamValue::Decimal { value: value.into(), scale: scale as u32 }
+            }
+            #[expect(clippy::cast_possible_truncation)]
+            Value::Decimal128(scale, value) => ParamValue::Decimal { value, scale: scale as u32 },
+            #[expect(clippy::cast_possible_truncation)]
+            Value::Decimal256(scale, value) => {
+                let mut text = String::new();
+                write_decimal(&mut text, &value.to_string(), scale as u32);
+                ParamValue::Raw(text)
+            }
+            Value::String(bytes) | Value::Object(bytes) | Value::AggregateFunction(bytes) => {
+                ParamValue::String(String::from_utf8_lossy(&bytes).into_owned())
+            }
+            Value::Uuid(uuid) => ParamValue::Uuid(uuid),
+            Value::Date(date) => ParamValue::Date(date.into()),
+            Value::Date32(date) => ParamValue::Date(date.into()),
+            Value::DateTime(DateTime(_, secs)) => chrono::DateTime::from_timestamp(secs.into(), 0)
+                .map_or(ParamValue::Raw(secs.to_string()), ParamValue::Timestamp),
+            Value::DateTime64(DynDateTime64(_, value, precision)) => {
+                #[expect(clippy::cast_possible_truncation)]
+                let precision = precision.min(9) as u32;
+                let divisor = 10_u64.pow(precision);
+                #[expect(clippy::cast_possible_truncation)]
+                let nanos = ((value % divisor) * 10_u64.pow(9 - precision)) as u32;
+                i64::try_from(value / divisor)
+                    .ok()
+                    .and_then(|secs| chrono::DateTime::from_timestamp(secs, nanos))
+                    .map_or_else(
+                        || {
+                            let mut text = String::new();
+                            write_decimal(&mut text, &value.to_string(), precision);
+                            ParamValue::Raw(text)
+                        },
+                        ParamValue::Timestamp,
+                    )
+            }
+            Value::Time(secs) => {
+                let (hours, mins, secs) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+                ParamValue::Raw(format!("{hours:02}:{mins:02}:{secs:02}"))
+            }
+            #[expect(clippy::cast_possible_truncation)]
+            Value::Time64(precision, value) => {
+                let mut text = String::new();
+                write_decimal(&mut text, &value.to_string(), precision as u32);
+                ParamValue::Raw(text)
+            }
+            Value::Enum8(name, _) | Value::Enum16(name, _) => ParamValue::String(name),
+            Value::Array(values) => values.into(),
+            Value::Tuple(values) => ParamValue::Tuple(values.into_iter().map(Into::into).collect()),
+            Value::Map(keys, values) => ParamValue::Map(
+                keys.into_iter().zip(values).map(|(k, v)| (k.into(), v.into())).collect(),
+            ),
+            Value::Null => ParamValue::Null,
+            Value::Ipv4(ip) => ParamValue::Ipv4(ip.into()),
+            Value::Ipv6(ip) => ParamValue::Ipv6(ip.into()),
+            Value::Point(point) => point.into(),
+            Value::Ring(ring) => ring.into(),
+            Value::Polygon(polygon) => polygon.into(),
+            Value::MultiPolygon(multi) => multi.into(),
+            Value::Variant(_, inner)
+            | Value::Dynamic(_, inner)
+            | Value::SimpleAggregateFunction(inner) => (*inner).into(),
+        }
+    }
+}
+
+impl From<Point> for ParamValue {
+    fn from(Point([x, y]): Point) -> Self { (x, y).into() }
+}
+
+impl From<Ring> for ParamValue {
+    fn from(ring: Ring) -> Self { ring.0.into() }
+}
+
+impl From<Polygon> for ParamValue {
+    fn from(polygon: Polygon) -> Self { polygon.0.into() }
+}
+
+impl From<MultiPolygon> for ParamValue {
+    fn from(multi: MultiPolygon) -> Self { multi.0.into() }
+}
 
 /// Represent parameters that can be passed to bind values during queries.
 ///
@@ -105,12 +544,7 @@ impl QueryParams {
     /// - flags (varuint) - 0x02 (`settingFlagCustom`) for params
     /// - value (string) - encoded as "field dump" format
     ///
-    /// Field dump format follows `ClickHouse's` `Field::restoreFromDump`:
-    /// - Strings: `'value'` with escaped single quotes
-    /// - Numbers: raw numeric string (e.g., "42", "3.14")
-    /// - Booleans: "true" or "false"
-    ///
-    /// See: <https://github.com/ClickHouse/ClickHouse/blob/master/src/Core/Field.cpp#L312>
+    /// See [`encode_field_dump`] for how values are encoded.
     ///
     /// # Errors
     /// Returns an error if writing to the stream fails.
@@ -132,37 +566,41 @@ impl QueryParams {
     }
 }
 
-/// Encodes a `SettingValue` as a `ClickHouse` field dump string for query parameters.
+/// Encodes a [`ParamValue`] as a `ClickHouse` field dump string for query parameters.
 ///
 /// **IMPORTANT**: `ClickHouse's` native protocol only supports **string** parameters!
-/// Non-string values are converted to their string representation.
 ///
 /// This is because `ClickHouse` calls `Settings::toNameToNameMap()` on received parameters,
 /// which requires all values to be parseable as quoted strings. The `{param:Type}` syntax
-/// in queries tells `ClickHouse` how to cast the string parameter to the desired type.
+/// in queries tells `ClickHouse` how to parse the string parameter as the desired type, using
+/// the type's escaped text format.
 ///
-/// Field dump format for parameters:
-/// - All values are encoded as quoted strings: `'value'`
-/// - Single quotes within strings are escaped: `'` -> `\'`
+/// The value is first rendered in the escaped text format (see [`ParamValue`]'s `Display`), then
+/// wrapped in single quotes with backslashes and single quotes escaped.
 ///
 /// # Examples
 /// ```rust,ignore
-/// encode_field_dump(&SettingValue::String("hello"))    // "'hello'"
-/// encode_field_dump(&SettingValue::String("it's"))     // "'it\\'s'"
-/// encode_field_dump(&SettingValue::Int(42))             // "'42'"
-/// encode_field_dump(&SettingValue::Float(3.14))         // "'3.14'"
-/// encode_field_dump(&SettingValue::Bool(true))          // "'true'"
+/// encode_field_dump(&ParamValue::from("hello"))       // "'hello'"
+/// encode_field_dump(&ParamValue::from("it's"))        // "'it\\'s'"
+/// encode_field_dump(&ParamValue::from(42))            // "'42'"
+/// encode_field_dump(&ParamValue::from(vec!["a"]))     // "'[\\'a\\']'"
+/// encode_field_dump(&ParamValue::Null)                // "'\\\\N'"
 /// ```
 ///
-/// See: <https://github.com/ClickHouse/ClickHouse/blob/master/src/Server/TCPHandler.cpp>
-fn encode_field_dump(value: &SettingValue) -> String {
-    // All parameter values must be strings for toNameToNameMap() to work
-    match value {
-        SettingValue::String(s) => format!("'{}'", s.replace('\'', "\\'")),
-        SettingValue::Int(i) => format!("'{i}'"),
-        SettingValue::Float(f) => format!("'{f}'"),
-        SettingValue::Bool(b) => format!("'{b}'"),
+/// See: <https://github.com/ClickHouse/ClickHouse/blob/master/src/Core/Field.cpp#L312>
+fn encode_field_dump(value: &ParamValue) -> String {
+    let text = value.to_string();
+    let mut dump = String::with_capacity(text.len() + 2);
+    dump.push('\'');
+    for c in text.chars() {
+        match c {
+            '\\' => dump.push_str("\\\\"),
+            '\'' => dump.push_str("\\'"),
+            c => dump.push(c),
+        }
     }
+    dump.push('\'');
+    dump
 }
 
 /// Represents a parsed query.
@@ -194,3 +632,110 @@ impl From<&str> for ParsedQuery {
 impl From<&String> for ParsedQuery {
     fn from(q: &String) -> ParsedQuery { ParsedQuery(q.trim().to_string()) }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use chrono::{NaiveDate, TimeZone, Utc};
+
+    use super::*;
+
+    fn text(value: impl Into<ParamValue>) -> String { value.into().to_string() }
+
+    #[test]
+    fn test_param_value_scalars() {
+        assert_eq!(text(42_i32), "42");
+        assert_eq!(text(u64::MAX), u64::MAX.to_string());
+        assert_eq!(text(-1_i128), "-1");
+        assert_eq!(text(1.5_f64), "1.5");
+        assert_eq!(text(f64::NAN), "nan");
+        assert_eq!(text(true), "true");
+        assert_eq!(text(None::<i32>), "\\N");
+        assert_eq!(text(Some(7_u8)), "7");
+        assert_eq!(text("it's"), "it's");
+        assert_eq!(text("a\tb\\c\n"), "a\\tb\\\\c\\n");
+        assert_eq!(text(Uuid::nil()), "00000000-0000-0000-0000-000000000000");
+        assert_eq!(text(Ipv4Addr::LOCALHOST), "127.0.0.1");
+        assert_eq!(text(IpAddr::V6(Ipv6Addr::LOCALHOST)), "::1");
+    }
+
+    #[test]
+    fn test_param_value_dates() {
+        let date = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+        assert_eq!(text(date), "2024-02-29");
+
+        let datetime = date.and_hms_opt(1, 2, 3).unwrap();
+        assert_eq!(text(datetime), "2024-02-29 01:02:03");
+        let datetime = date.and_hms_milli_opt(1, 2, 3, 450).unwrap();
+        assert_eq!(text(datetime), "2024-02-29 01:02:03.450");
+
+        let ts = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        assert_eq!(text(ts), "1700000000");
+        let ts = Utc.timestamp_opt(1_700_000_000, 123_000_000).unwrap();
+        assert_eq!(text(ts), "1700000000.123");
+        let ts = Utc.timestamp_opt(-2, 500_000_000).unwrap();
+        assert_eq!(text(ts), "-1.5");
+        let ts = Utc.timestamp_opt(-1, 750_000_000).unwrap();
+        assert_eq!(text(ts), "-0.25");
+
+        // Nested dates are quoted
+        assert_eq!(text(vec![date]), "['2024-02-29']");
+    }
+
+    #[test]
+    fn test_param_value_decimals() {
+        assert_eq!(ParamValue::Decimal { value: 12345, scale: 2 }.to_string(), "123.45");
+        assert_eq!(ParamValue::Decimal { value: -5, scale: 3 }.to_string(), "-0.005");
+        assert_eq!(ParamValue::Decimal { value: 7, scale: 0 }.to_string(), "7");
+        assert_eq!(text(Value::Decimal64(2, -150)), "-1.50");
+    }
+
+    #[test]
+    fn test_param_value_composites() {
+        assert_eq!(text(vec![1_u64, 2, 3]), "[1,2,3]");
+        assert_eq!(text(&["a", "it's"][..]), "['a','it\\'s']");
+        assert_eq!(text(vec![Some(1_i32), None]), "[1,NULL]");
+        assert_eq!(text((1_u8, "a", None::<String>)), "(1,'a',NULL)");
+        assert_eq!(text(BTreeMap::from([("a", 1), ("b", 2)])), "{'a':1,'b':2}");
+        assert_eq!(text(vec![vec![(1_i32, "x")], vec![]]), "[[(1,'x')],[]]");
+        assert_eq!(text(Vec::<i32>::new()), "[]");
+        // Top level strings are not quoted, only escaped
+        assert_eq!(text("['a', 'b']"), "['a', 'b']");
+        assert_eq!(text("a\tb\\c"), "a\\tb\\\\c");
+    }
+
+    #[test]
+    fn test_param_value_from_value() {
+        assert_eq!(text(Value::string("x")), "x");
+        assert_eq!(text(Value::Array(vec![Value::string("x"), Value::Null])), "['x',NULL]");
+        assert_eq!(text(Value::Map(vec![Value::string("k")], vec![Value::UInt8(1)])), "{'k':1}");
+        assert_eq!(text(Value::Tuple(vec![Value::Int64(-1), Value::Float64(0.5)])), "(-1,0.5)");
+        assert_eq!(text(Value::Date(crate::Date(1))), "1970-01-02");
+        assert_eq!(text(Value::DateTime(DateTime(chrono_tz::UTC, 60))), "60");
+        assert_eq!(text(Value::DateTime64(DynDateTime64(chrono_tz::UTC, 1_500, 3))), "1.5");
+        assert_eq!(text(Value::Point(Point([1.0, 2.5]))), "(1,2.5)");
+        assert_eq!(text(Value::Enum8("a".into(), 1)), "a");
+        assert_eq!(text(Value::Variant(0, Box::new(Value::UInt32(3)))), "3");
+    }
+
+    #[test]
+    fn test_encode_field_dump() {
+        assert_eq!(encode_field_dump(&"hello".into()), "'hello'");
+        assert_eq!(encode_field_dump(&"it's".into()), "'it\\'s'");
+        assert_eq!(encode_field_dump(&42.into()), "'42'");
+        assert_eq!(encode_field_dump(&vec!["a"].into()), "'[\\'a\\']'");
+        assert_eq!(encode_field_dump(&ParamValue::Null), "'\\\\N'");
+        assert_eq!(encode_field_dump(&"a\\b".into()), "'a\\\\\\\\b'");
+    }
+
+    #[test]
+    fn test_query_params_from() {
+        let params = QueryParams::from(vec![("a", 1_i32), ("b", 2_i32)]);
+        assert_eq!(params.len(), 2);
+        assert_eq!(params.0[0], ("a".to_string(), ParamValue::Int(1)));
+
+        let params = QueryParams::from([("s", ParamValue::from(SettingValue::from("x")))]);
+        assert_eq!(params.0[0].1, ParamValue::String("x".into()));
+    }
+}
@@ -248,6 +248,9 @@ pub struct Setting {
 }
 
 impl Setting {
+    #[cfg(feature = "test-utils")]
+    pub(crate) fn value(&self) -> &SettingValue { &self.value }
+
     /// Encodes the setting to the `ClickHouse` native protocol.
     ///
     /// For legacy revisions (≤ 54429), only integer and boolean settings are supported,
@@ -488,6 +491,30 @@ impl Settings {
         self
     }
 
+    /// Return these settings with `overrides` applied on top.
+    ///
+    /// Settings in `overrides` replace settings with the same name, all others are appended.
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// use clickhouse_arrow::query::settings::Settings;
+    ///
+    /// let defaults = Settings::from([("max_threads", 8_i32), ("max_execution_time", 60)]);
+    /// let merged = defaults.with_overrides(&Settings::from([("max_threads", 2_i32)]));
+    /// assert_eq!(merged.encode_to_strings(), vec!["max_threads = 2", "max_execution_time = 60"]);
+    /// ```
+    #[must_use]
+    pub fn with_overrides(mut self, overrides: &Settings) -> Self {
+        for setting in &overrides.0 {
+            if let Some(current) = self.0.iter_mut().find(|s| s.key == setting.key) {
+                *current = setting.clone();
+            } else {
+                self.0.push(setting.clone());
+            }
+        }
+        self
+    }
+
     /// Converts settings to a vector of key-value string pairs.
     ///
     /// Each setting is represented as a tuple of `(key, value.to_string())`.
@@ -868,6 +895,20 @@ mod tests {
         Setting { key: key.to_string(), value: value.into(), important, custom }
     }
 
+    #[test]
+    fn test_settings_with_overrides() {
+        let defaults = Settings::from([("max_threads", 8_i32), ("max_execution_time", 60)]);
+        let merged = defaults.with_overrides(&Settings::from([
+            ("max_threads", SettingValue::from(2_i32)),
+            ("async_insert", SettingValue::from(true)),
+        ]));
+        assert_eq!(merged.encode_to_strings(), vec![
+            "max_threads = 2",
+            "max_execution_time = 60",
+            "async_insert = true"
+        ]);
+    }
+
     #[test]
     fn test_setting_value_from_primitives() {
         // Test all supported From implementations for SettingValue
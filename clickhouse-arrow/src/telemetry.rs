@@ -3,7 +3,7 @@
 //! `clickhouse-arrow` uses the `tracing` crate to emit spans and events for debugging and
 //! monitoring. To enable tracing, add a subscriber in your application:
 //!
-//! ```rust
+//! ```rust,ignore
 //! use tracing_subscriber;
 //!
 //! tracing_subscriber::fmt()
@@ -11,11 +11,15 @@
 //!     .init();
 //! // Use clickhouse_arrow
 //! ```
+use std::fmt;
 use std::num::NonZeroU64;
+use std::str::FromStr;
 
 pub use opentelemetry_semantic_conventions::*;
 use tracing::Span;
 
+use crate::{Error, Result};
+
 /// Commonly used attribute names
 pub const ATT_CID: &str = "clickhouse.client.id";
 pub const ATT_CON: &str = "clickhouse.connection.id";
@@ -51,3 +55,210 @@ impl From<NonZeroU64> for TraceContext {
 impl From<Option<NonZeroU64>> for TraceContext {
     fn from(id: Option<NonZeroU64>) -> Self { Self(id) }
 }
+
+/// A W3C trace context (`traceparent` and `tracestate`) sent with a query.
+///
+/// `ClickHouse` continues the trace: the spans of the query in `system.opentelemetry_span_log`
+/// are children of the span identified here. The server records them if the trace is sampled,
+/// or as decided by its `opentelemetry_start_trace_probability` setting otherwise.
+///
+/// The trace context of a query is set with [`crate::explain::QueryOptions::with_trace_parent`].
+/// With the `opentelemetry` feature, queries without one use the current `OpenTelemetry`
+/// context, see [`TraceParent::current`].
+///
+/// # Examples
+/// ```rust,ignore
+/// use clickhouse_arrow::prelude::*;
+///
+/// let trace = TraceParent::from_str("00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01")?
+///     .with_tracestate("congo=t61rcWkgMzE");
+/// let options = QueryOptions::new().with_trace_parent(trace);
+/// let batches = client.query_with_options("SELECT 1", options).await?;
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TraceParent {
+    /// ID of the trace, must not be zero.
+    pub trace_id:    u128,
+    /// ID of the parent span, must not be zero.
+    pub span_id:     u64,
+    /// Trace flags, `0x01` if the trace is sampled.
+    pub trace_flags: u8,
+    /// Vendor specific trace state, the `tracestate` header. Empty if none.
+    pub tracestate:  String,
+}
+
+impl TraceParent {
+    /// Flag of sampled traces.
+    pub const SAMPLED: u8 = 0x01;
+
+    #[must_use]
+    pub fn new(trace_id: u128, span_id: u64, sampled: bool) -> Self {
+        let trace_flags = if sampled { Self::SAMPLED } else { 0 };
+        Self { trace_id, span_id, trace_flags, tracestate: String::new() }
+    }
+
+    #[must_use]
+    pub fn with_tracestate(mut self, tracestate: impl Into<String>) -> Self {
+        self.tracestate = tracestate.into();
+        self
+    }
+
+    /// Whether the trace is sampled.
+    pub fn is_sampled(&self) -> bool { self.trace_flags & Self::SAMPLED != 0 }
+
+    /// The trace context of the current `OpenTelemetry` context, `None` if it has no valid span.
+    ///
+    /// When spans are created with `tracing-opentelemetry`, the `OpenTelemetry` context is only
+    /// current if attached, e.g. with `Span::current().context().attach()`. Alternatively, the
+    /// span context can be converted and set explicitly:
+    ///
+    /// ```rust,ignore
+    /// use opentelemetry::trace::TraceContextExt;
+    /// use tracing_opentelemetry::OpenTelemetrySpanExt;
+    ///
+    /// let context = tracing::Span::current().context();
+    /// let trace = TraceParent::from(context.span().span_context());
+    /// ```
+    #[cfg(feature = "opentelemetry")]
+    pub fn current() -> Option<Self> {
+        use opentelemetry::trace::TraceContextExt;
+
+        let context = opentelemetry::Context::current();
+        let span = context.span();
+        let span_context = span.span_context();
+        span_context.is_valid().then(|| Self::from(span_context))
+    }
+
+    /// The trace context sent with a query, the explicit one or the current one, if any.
+    pub(crate) fn resolve(explicit: Option<&Self>) -> Option<Self> {
+        #[cfg(feature = "opentelemetry")]
+        {
+            explicit.cloned().or_else(Self::current)
+        }
+        #[cfg(not(feature = "opentelemetry"))]
+        {
+            explicit.cloned()
+        }
+    }
+}
+
+/// Formats the `traceparent` header, e.g.
+/// `00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01`.
+impl fmt::Display for TraceParent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "00-{:032x}-{:016x}-{:02x}", self.trace_id, self.span_id, self.trace_flags)
+    }
+}
+
+/// Parses a `traceparent` header, without trace state.
+impl FromStr for TraceParent {
+    type Err = Error;
+
+    fn from_str(traceparent: &str) -> Result<Self> {
+        let invalid = || Error::Client(format!("Invalid traceparent: {traceparent}"));
+        let hex = |field: &str, len: usize| {
+            if field.len() != len || !field.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return Err(invalid());
+            }
+            u128::from_str_radix(field, 16).map_err(|_| invalid())
+        };
+
+        let mut fields = traceparent.trim().split('-');
+        let (Some(version), Some(trace_id), Some(span_id), Some(trace_flags)) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            return Err(invalid());
+        };
+        // Version 00 has exactly four fields, later versions may append more
+        let version = hex(version, 2)?;
+        if version == 0xff || (version == 0 && fields.next().is_some()) {
+            return Err(invalid());
+        }
+
+        let trace_id = hex(trace_id, 32)?;
+        let span_id = u64::try_from(hex(span_id, 16)?).map_err(|_| invalid())?;
+        let trace_flags = u8::try_from(hex(trace_flags, 2)?).map_err(|_| invalid())?;
+        if trace_id == 0 || span_id == 0 {
+            return Err(invalid());
+        }
+        Ok(Self { trace_id, span_id, trace_flags, tracestate: String::new() })
+    }
+}
+
+#[cfg(feature = "opentelemetry")]
+impl From<&opentelemetry::trace::SpanContext> for TraceParent {
+    fn from(context: &opentelemetry::trace::SpanContext) -> Self {
+        Self {
+            trace_id:    u128::from_be_bytes(context.trace_id().to_bytes()),
+            span_id:     u64::from_be_bytes(context.span_id().to_bytes()),
+            trace_flags: context.trace_flags().to_u8(),
+            tracestate:  context.trace_state().header(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRACEPARENT: &str = "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01";
+
+    #[test]
+    fn test_trace_parent_round_trip() {
+        let trace = TraceParent::from_str(TRACEPARENT).unwrap();
+        assert_eq!(trace.trace_id, 0x0af7_6519_16cd_43dd_8448_eb21_1c80_319c);
+        assert_eq!(trace.span_id, 0xb7ad_6b71_6920_3331);
+        assert!(trace.is_sampled());
+        assert!(trace.tracestate.is_empty());
+        assert_eq!(trace.to_string(), TRACEPARENT);
+
+        let unsampled = TraceParent::new(1, 2, false);
+        assert_eq!(unsampled.to_string(), format!("00-{:032x}-{:016x}-00", 1, 2));
+        assert_eq!(TraceParent::from_str(&unsampled.to_string()).unwrap(), unsampled);
+    }
+
+    #[test]
+    fn test_trace_parent_invalid() {
+        for traceparent in [
+            "",
+            "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331",
+            "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01-extra",
+            "ff-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01",
+            "00-00000000000000000000000000000000-b7ad6b7169203331-01",
+            "00-0af7651916cd43dd8448eb211c80319c-0000000000000000-01",
+            "00-0af7651916cd43dd8448eb211c8031-b7ad6b7169203331-01",
+            "00-0af7651916cd43dd8448eb211c80319g-b7ad6b7169203331-01",
+        ] {
+            assert!(TraceParent::from_str(traceparent).is_err(), "{traceparent}");
+        }
+        // Later versions may carry more fields
+        assert!(TraceParent::from_str(&format!("01{}-future", &TRACEPARENT[2..])).is_ok());
+    }
+
+    #[cfg(feature = "opentelemetry")]
+    #[test]
+    fn test_trace_parent_from_opentelemetry() {
+        use opentelemetry::trace::{
+            SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState,
+        };
+
+        let span_context = SpanContext::new(
+            TraceId::from(0x0af7_6519_16cd_43dd_8448_eb21_1c80_319c),
+            SpanId::from(0xb7ad_6b71_6920_3331),
+            TraceFlags::SAMPLED,
+            true,
+            TraceState::from_str("congo=t61rcWkgMzE").unwrap(),
+        );
+        let expected =
+            TraceParent::from_str(TRACEPARENT).unwrap().with_tracestate("congo=t61rcWkgMzE");
+        assert_eq!(TraceParent::from(&span_context), expected);
+
+        assert_eq!(TraceParent::current(), None);
+        let context = opentelemetry::Context::new().with_remote_span_context(span_context);
+        let _guard = context.attach();
+        assert_eq!(TraceParent::current(), Some(expected.clone()));
+        assert_eq!(TraceParent::resolve(None), Some(expected));
+        let explicit = TraceParent::new(1, 2, true);
+        assert_eq!(TraceParent::resolve(Some(&explicit)), Some(explicit));
+    }
+}
@@ -15,6 +15,10 @@ use tracing::{debug, error};
 use tracing_subscriber::EnvFilter;
 use tracing_subscriber::prelude::*;
 
+pub mod mock;
+
+pub use self::mock::{MockException, MockQuery, MockResponse, MockServer};
+
 pub const ENDPOINT_ENV: &str = "CLICKHOUSE_ENDPOINT";
 pub const HOST_ENV: &str = "CLICKHOUSE_HOST";
 pub const VERSION_ENV: &str = "CLICKHOUSE_VERSION";
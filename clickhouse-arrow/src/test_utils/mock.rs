@@ -0,0 +1,1307 @@
+//! An in-process mock `ClickHouse` server speaking the native protocol.
+//!
+//! [`MockServer`] listens on a local port and answers queries with scripted [`MockResponse`]s, so
+//! retries, cancellation and error handling can be tested deterministically without a `ClickHouse`
+//! instance. Responses can return data, progress and profile info, inject exceptions and delays,
+//! or drop the connection. Every query received is recorded and available via
+//! [`MockServer::queries`].
+//!
+//! The server supports the handshake, `Query`, `Data`, `Cancel` and `Ping` packets from the client
+//! and replies with `Hello`, `Data`, `Exception`, `Progress`, `ProfileInfo`, `Pong` and
+//! `EndOfStream`. The chunked protocol is not supported, the server always negotiates `notchunked`.
+//!
+//! # Example
+//! ```rust,ignore
+//! use clickhouse_arrow::test_utils::mock::{MockResponse, MockServer};
+//!
+//! let server = MockServer::start().await?;
+//! server.on_query("SELECT", MockResponse::new().with_block(block));
+//! server.on_query("DROP", MockResponse::exception(60, "Table default.t does not exist"));
+//!
+//! let client = Client::<ArrowFormat>::builder()
+//!     .with_endpoint(server.endpoint())
+//!     .build()
+//!     .await?;
+//! ```
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt, BufReader, BufWriter, ReadBuf};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+use tracing::{debug, error, trace};
+
+use crate::client::connection::ClientMetadata;
+use crate::formats::DeserializerState;
+use crate::formats::sealed::ClientFormatImpl;
+use crate::io::{ClickHouseRead, ClickHouseWrite};
+use crate::native::block::Block;
+use crate::native::protocol::{
+    ClientPacketId, DBMS_MIN_PROTOCOL_VERSION_WITH_ADDENDUM,
+    DBMS_MIN_PROTOCOL_VERSION_WITH_CHUNKED_PACKETS,
+    DBMS_MIN_PROTOCOL_VERSION_WITH_DISTRIBUTED_DEPTH,
+    DBMS_MIN_PROTOCOL_VERSION_WITH_INTERSERVER_EXTERNALLY_GRANTED_ROLES,
+    DBMS_MIN_PROTOCOL_VERSION_WITH_PARALLEL_REPLICAS, DBMS_MIN_PROTOCOL_VERSION_WITH_PARAMETERS,
+    DBMS_MIN_PROTOCOL_VERSION_WITH_PASSWORD_COMPLEXITY_RULES,
+    DBMS_MIN_PROTOCOL_VERSION_WITH_QUERY_START_TIME,
+    DBMS_MIN_PROTOCOL_VERSION_WITH_SERVER_QUERY_TIME_IN_PROGRESS,
+    DBMS_MIN_PROTOCOL_VERSION_WITH_TOTAL_BYTES_IN_PROGRESS, DBMS_MIN_REVISION_WITH_CLIENT_INFO,
+    DBMS_MIN_REVISION_WITH_CLIENT_WRITE_INFO, DBMS_MIN_REVISION_WITH_INTERSERVER_SECRET,
+    DBMS_MIN_REVISION_WITH_INTERSERVER_SECRET_V2, DBMS_MIN_REVISION_WITH_JWT_IN_INTERSERVER,
+    DBMS_MIN_REVISION_WITH_OPENTELEMETRY, DBMS_MIN_REVISION_WITH_QUERY_AND_LINE_NUMBERS,
+    DBMS_MIN_REVISION_WITH_QUERY_PLAN_SERIALIZATION,
+    DBMS_MIN_REVISION_WITH_QUOTA_KEY_IN_CLIENT_INFO,
+    DBMS_MIN_REVISION_WITH_ROWS_BEFORE_AGGREGATION, DBMS_MIN_REVISION_WITH_SERVER_DISPLAY_NAME,
+    DBMS_MIN_REVISION_WITH_SERVER_LOGS, DBMS_MIN_REVISION_WITH_SERVER_SETTINGS,
+    DBMS_MIN_REVISION_WITH_SERVER_TIMEZONE, DBMS_MIN_REVISION_WITH_VERSION_PATCH,
+    DBMS_MIN_REVISION_WITH_VERSIONED_CLUSTER_FUNCTION_PROTOCOL,
+    DBMS_MIN_REVISION_WITH_VERSIONED_PARALLEL_REPLICAS_PROTOCOL,
+    DBMS_PARALLEL_REPLICAS_PROTOCOL_VERSION, DBMS_TCP_PROTOCOL_VERSION, ServerPacketId,
+};
+use crate::spawn::SpawnedTask;
+use crate::{
+    ArrowOptions, CompressionMethod, CompressionOptions, Error, NativeFormat, ProfileInfo,
+    Progress, Qid, Result, Settings, Type,
+};
+
+const MOCK_SERVER_NAME: &str = "ClickHouse";
+const MOCK_SERVER_VERSION: (u64, u64, u64) = (25, 8, 0);
+const MOCK_SERVER_TIMEZONE: &str = "UTC";
+const MOCK_EXCEPTION_NAME: &str = "DB::Exception";
+
+/// An exception sent to the client in place of a result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockException {
+    pub code:    i32,
+    pub name:    String,
+    pub message: String,
+}
+
+impl MockException {
+    pub fn new(code: i32, message: impl Into<String>) -> Self {
+        Self { code, name: MOCK_EXCEPTION_NAME.into(), message: message.into() }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum MockAction {
+    Data(Block),
+    Progress(Progress),
+    ProfileInfo(ProfileInfo),
+    Delay(Duration),
+    Exception(MockException),
+    Disconnect,
+}
+
+/// A scripted response to a query, played back in order by the [`MockServer`].
+///
+/// The default response completes the query without returning data. Unless the response ends
+/// with an exception or a dropped connection, `EndOfStream` is sent once all actions have run.
+#[derive(Debug, Clone, Default)]
+pub struct MockResponse {
+    header:  Option<Vec<(String, Type)>>,
+    insert:  bool,
+    actions: Vec<MockAction>,
+}
+
+impl MockResponse {
+    /// A response completing the query without returning data.
+    pub fn new() -> Self { Self::default() }
+
+    /// A response failing the query with an exception.
+    pub fn exception(code: i32, message: impl Into<String>) -> Self {
+        Self::new().with_exception(MockException::new(code, message))
+    }
+
+    /// A response dropping the connection without answering the query.
+    pub fn disconnect() -> Self { Self::new().with_disconnect() }
+
+    /// Set the columns sent in the header block.
+    ///
+    /// Defaults to the columns of the first block, no header is sent for responses without data.
+    #[must_use]
+    pub fn with_header(mut self, columns: Vec<(String, Type)>) -> Self {
+        self.header = Some(columns);
+        self
+    }
+
+    /// Accept an insert into the given columns.
+    ///
+    /// The header is sent and the client's data is read and recorded before the remaining
+    /// actions run.
+    #[must_use]
+    pub fn with_insert(mut self, columns: Vec<(String, Type)>) -> Self {
+        self.header = Some(columns);
+        self.insert = true;
+        self
+    }
+
+    /// Send a block of data.
+    #[must_use]
+    pub fn with_block(mut self, block: Block) -> Self {
+        self.actions.push(MockAction::Data(block));
+        self
+    }
+
+    /// Send a progress update.
+    #[must_use]
+    pub fn with_progress(mut self, progress: Progress) -> Self {
+        self.actions.push(MockAction::Progress(progress));
+        self
+    }
+
+    /// Send the query's profile info.
+    #[must_use]
+    pub fn with_profile_info(mut self, info: ProfileInfo) -> Self {
+        self.actions.push(MockAction::ProfileInfo(info));
+        self
+    }
+
+    /// Pause before the next action. A `Cancel` from the client ends the query during a delay.
+    #[must_use]
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.actions.push(MockAction::Delay(delay));
+        self
+    }
+
+    /// Fail the query with an exception, ending the response.
+    #[must_use]
+    pub fn with_exception(mut self, exception: MockException) -> Self {
+        self.actions.push(MockAction::Exception(exception));
+        self
+    }
+
+    /// Drop the connection, ending the response.
+    #[must_use]
+    pub fn with_disconnect(mut self) -> Self {
+        self.actions.push(MockAction::Disconnect);
+        self
+    }
+
+    // The header defaults to the columns of the first block
+    fn header(&self) -> Option<Vec<(String, Type)>> {
+        self.header.clone().or_else(|| {
+            self.actions.iter().find_map(|action| match action {
+                MockAction::Data(block) => Some(block.column_types.clone()),
+                _ => None,
+            })
+        })
+    }
+}
+
+/// A query received by the [`MockServer`].
+#[derive(Debug, Clone, Default)]
+pub struct MockQuery {
+    pub qid:         String,
+    pub query:       String,
+    pub user:        String,
+    pub database:    String,
+    pub settings:    Settings,
+    pub params:      Settings,
+    pub compression: CompressionMethod,
+    /// Quota key sent in the client info.
+    pub quota_key:   String,
+    /// Blocks received for an insert, see [`MockResponse::with_insert`].
+    pub data:        Vec<Block>,
+    /// Whether the client cancelled the query before the response completed.
+    pub cancelled:   bool,
+}
+
+#[derive(Debug)]
+struct MockRule {
+    pattern:  String,
+    response: MockResponse,
+    once:     bool,
+}
+
+#[derive(Debug, Default)]
+struct MockScript {
+    rules:     Vec<MockRule>,
+    default:   MockResponse,
+    handshake: Option<MockException>,
+}
+
+impl MockScript {
+    // The first rule whose pattern the query contains, once rules are removed when matched
+    fn response(&mut self, query: &str) -> MockResponse {
+        let Some(index) = self.rules.iter().position(|rule| query.contains(&rule.pattern)) else {
+            return self.default.clone();
+        };
+        if self.rules[index].once {
+            return self.rules.remove(index).response;
+        }
+        self.rules[index].response.clone()
+    }
+}
+
+#[derive(Debug, Default)]
+struct MockShared {
+    script:      Mutex<MockScript>,
+    queries:     Mutex<Vec<MockQuery>>,
+    connections: AtomicUsize,
+    pings:       AtomicUsize,
+    received:    AtomicUsize,
+    tasks:       Mutex<JoinSet<()>>,
+}
+
+/// An in-process mock `ClickHouse` server, see the [module docs](self).
+///
+/// The server stops, dropping all connections, when dropped.
+#[derive(Debug)]
+pub struct MockServer {
+    addr:    SocketAddr,
+    shared:  Arc<MockShared>,
+    // Aborts the accept loop when dropped
+    _accept: SpawnedTask<()>,
+}
+
+impl MockServer {
+    /// Start a server listening on a random local port.
+    ///
+    /// # Errors
+    /// Returns an error if the listener cannot be bound.
+    pub async fn start() -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let shared = Arc::new(MockShared::default());
+        let accept = SpawnedTask::spawn(accept_loop(listener, Arc::clone(&shared)));
+        debug!(%addr, "Mock server listening");
+        Ok(Self { addr, shared, _accept: accept })
+    }
+
+    /// The address the server is listening on.
+    pub fn addr(&self) -> SocketAddr { self.addr }
+
+    /// The address the server is listening on, as an endpoint string.
+    pub fn endpoint(&self) -> String { self.addr.to_string() }
+
+    /// Respond to queries containing `pattern`.
+    ///
+    /// Rules are matched in the order they were added, queries matching no rule receive the
+    /// default response (see [`MockServer::set_default_response`]).
+    pub fn on_query(&self, pattern: impl Into<String>, response: MockResponse) {
+        self.add_rule(pattern.into(), response, false);
+    }
+
+    /// Respond to the next query containing `pattern`, the rule is removed once matched.
+    pub fn on_query_once(&self, pattern: impl Into<String>, response: MockResponse) {
+        self.add_rule(pattern.into(), response, true);
+    }
+
+    /// Set the response to queries matching no rule.
+    pub fn set_default_response(&self, response: MockResponse) {
+        self.shared.script.lock().default = response;
+    }
+
+    /// Fail new connections' handshakes with an exception, ie to simulate bad credentials.
+    pub fn set_handshake_exception(&self, exception: Option<MockException>) {
+        self.shared.script.lock().handshake = exception;
+    }
+
+    /// Drop all open connections. The server continues to accept new connections.
+    pub fn drop_connections(&self) { self.shared.tasks.lock().abort_all(); }
+
+    /// The queries received so far, in order.
+    pub fn queries(&self) -> Vec<MockQuery> { self.shared.queries.lock().clone() }
+
+    /// The number of connections accepted so far.
+    pub fn connections(&self) -> usize { self.shared.connections.load(Ordering::Acquire) }
+
+    /// The number of pings received so far.
+    pub fn pings(&self) -> usize { self.shared.pings.load(Ordering::Acquire) }
+
+    /// The number of bytes received so far over all connections, as sent on the wire.
+    pub fn bytes_received(&self) -> usize { self.shared.received.load(Ordering::Acquire) }
+
+    fn add_rule(&self, pattern: String, response: MockResponse, once: bool) {
+        self.shared.script.lock().rules.push(MockRule { pattern, response, once });
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) { self.shared.tasks.lock().abort_all(); }
+}
+
+async fn accept_loop(listener: TcpListener, shared: Arc<MockShared>) {
+    loop {
+        let (socket, peer) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(error) => {
+                error!(?error, "Mock server failed to accept connection");
+                continue;
+            }
+        };
+        let _ = shared.connections.fetch_add(1, Ordering::AcqRel);
+        trace!(%peer, "Mock server accepted connection");
+
+        let mut tasks = shared.tasks.lock();
+        while tasks.try_join_next().is_some() {}
+        let conn_shared = Arc::clone(&shared);
+        drop(tasks.spawn(async move {
+            if let Err(error) = serve(socket, conn_shared).await {
+                debug!(?error, %peer, "Mock server connection closed");
+            }
+        }));
+    }
+}
+
+/// A packet received from the client.
+enum ClientPacket {
+    Query(MockQuery),
+    Data(Option<Block>),
+    Cancel,
+    Ping,
+}
+
+/// The state of a single connection, after the handshake.
+struct MockConnection {
+    shared:   Arc<MockShared>,
+    writer:   BufWriter<OwnedWriteHalf>,
+    packets:  mpsc::Receiver<Result<ClientPacket>>,
+    revision: u64,
+    user:     String,
+    database: String,
+}
+
+async fn serve(socket: TcpStream, shared: Arc<MockShared>) -> Result<()> {
+    socket.set_nodelay(true)?;
+    let (reader, writer) = socket.into_split();
+    let mut reader = BufReader::new(CountingReader { inner: reader, shared: Arc::clone(&shared) });
+    let mut writer = BufWriter::new(writer);
+
+    // Handshake
+    let packet = reader.read_var_uint().await?;
+    if packet != ClientPacketId::Hello as u64 {
+        return Err(Error::Protocol(format!("Expected client hello, got packet {packet}")));
+    }
+    drop(reader.read_string().await?); // client name
+    let _ = reader.read_var_uint().await?; // major version
+    let _ = reader.read_var_uint().await?; // minor version
+    let revision = reader.read_var_uint().await?.min(DBMS_TCP_PROTOCOL_VERSION);
+    let database = reader.read_utf8_string().await?;
+    let user = reader.read_utf8_string().await?;
+    drop(reader.read_string().await?); // password
+
+    let handshake = shared.script.lock().handshake.clone();
+    if let Some(exception) = handshake {
+        write_exception(&mut writer, &exception).await?;
+        writer.flush().await?;
+        return Ok(());
+    }
+    write_hello(&mut writer, revision).await?;
+    writer.flush().await?;
+    read_addendum(&mut reader, revision).await?;
+
+    // Packets are read separately so a cancel is seen while a response is being written
+    let (packets_tx, packets) = mpsc::channel(16);
+    let mut reader_task = JoinSet::new();
+    drop(reader_task.spawn(read_packets(reader, revision, packets_tx)));
+
+    let mut conn = MockConnection { shared, writer, packets, revision, user, database };
+    while let Some(Ok(packet)) = conn.packets.recv().await {
+        match packet {
+            ClientPacket::Ping => {
+                let _ = conn.shared.pings.fetch_add(1, Ordering::AcqRel);
+                conn.writer.write_var_uint(ServerPacketId::Pong as u64).await?;
+                conn.writer.flush().await?;
+            }
+            ClientPacket::Query(query) => {
+                if !conn.respond(query).await? {
+                    break;
+                }
+            }
+            // Nothing is executing
+            ClientPacket::Data(_) | ClientPacket::Cancel => {}
+        }
+    }
+    Ok(())
+}
+
+impl MockConnection {
+    /// Play back the response to a query, returning `false` if the connection should be dropped.
+    async fn respond(&mut self, mut query: MockQuery) -> Result<bool> {
+        // The query is followed by an empty block, ending the (unsupported) external tables
+        loop {
+            match self.packets.recv().await {
+                Some(Ok(ClientPacket::Data(None))) => break,
+                Some(Ok(_)) => {}
+                Some(Err(_)) | None => return Ok(false),
+            }
+        }
+
+        let response = self.shared.script.lock().response(&query.query);
+        query.user.clone_from(&self.user);
+        query.database.clone_from(&self.database);
+        let metadata = mock_metadata(query.compression);
+        let index = {
+            let mut queries = self.shared.queries.lock();
+            queries.push(query);
+            queries.len() - 1
+        };
+
+        if let Some(columns) = response.header() {
+            let header = Block { column_types: columns, ..Default::default() };
+            self.write_block(header, metadata).await?;
+            self.writer.flush().await?;
+        }
+
+        if response.insert {
+            loop {
+                match self.packets.recv().await {
+                    Some(Ok(ClientPacket::Data(Some(block)))) => {
+                        self.shared.queries.lock()[index].data.push(block);
+                    }
+                    Some(Ok(ClientPacket::Data(None))) => break,
+                    Some(Ok(ClientPacket::Cancel)) => return self.cancelled(index).await,
+                    Some(Ok(_)) => {}
+                    Some(Err(_)) | None => return Ok(false),
+                }
+            }
+        }
+
+        for action in response.actions {
+            match action {
+                MockAction::Data(block) => self.write_block(block, metadata).await?,
+                MockAction::Progress(progress) => self.write_progress(&progress).await?,
+                MockAction::ProfileInfo(info) => self.write_profile_info(&info).await?,
+                MockAction::Delay(delay) => {
+                    self.writer.flush().await?;
+                    let sleep = tokio::time::sleep(delay);
+                    tokio::pin!(sleep);
+                    loop {
+                        tokio::select! {
+                            () = &mut sleep => break,
+                            packet = self.packets.recv() => match packet {
+                                Some(Ok(ClientPacket::Cancel)) => {
+                                    return self.cancelled(index).await;
+                                }
+                                Some(Ok(_)) => {}
+                                Some(Err(_)) | None => return Ok(false),
+                            },
+                        }
+                    }
+                }
+                MockAction::Exception(exception) => {
+                    write_exception(&mut self.writer, &exception).await?;
+                    self.writer.flush().await?;
+                    return Ok(true);
+                }
+                MockAction::Disconnect => return Ok(false),
+            }
+        }
+
+        self.writer.write_var_uint(ServerPacketId::EndOfStream as u64).await?;
+        self.writer.flush().await?;
+        Ok(true)
+    }
+
+    // A cancelled query ends with `EndOfStream`, the connection remains usable
+    async fn cancelled(&mut self, index: usize) -> Result<bool> {
+        self.shared.queries.lock()[index].cancelled = true;
+        self.writer.write_var_uint(ServerPacketId::EndOfStream as u64).await?;
+        self.writer.flush().await?;
+        Ok(true)
+    }
+
+    async fn write_block(&mut self, block: Block, metadata: ClientMetadata) -> Result<()> {
+        self.writer.write_var_uint(ServerPacketId::Data as u64).await?;
+        self.writer.write_string("").await?; // Table name
+        NativeFormat::write(&mut self.writer, block, Qid::default(), None, self.revision, metadata)
+            .await
+    }
+
+    async fn write_progress(&mut self, progress: &Progress) -> Result<()> {
+        let revision = self.revision;
+        let writer = &mut self.writer;
+        writer.write_var_uint(ServerPacketId::Progress as u64).await?;
+        writer.write_var_uint(progress.read_rows).await?;
+        writer.write_var_uint(progress.read_bytes).await?;
+        if revision >= DBMS_MIN_REVISION_WITH_SERVER_LOGS {
+            writer.write_var_uint(progress.total_rows_to_read).await?;
+        }
+        if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_TOTAL_BYTES_IN_PROGRESS {
+            writer.write_var_uint(progress.total_bytes_to_read.unwrap_or_default()).await?;
+        }
+        if revision >= DBMS_MIN_REVISION_WITH_CLIENT_WRITE_INFO {
+            writer.write_var_uint(progress.written_rows.unwrap_or_default()).await?;
+            writer.write_var_uint(progress.written_bytes.unwrap_or_default()).await?;
+        }
+        if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_SERVER_QUERY_TIME_IN_PROGRESS {
+            writer.write_var_uint(progress.elapsed_ns.unwrap_or_default()).await?;
+        }
+        Ok(())
+    }
+
+    async fn write_profile_info(&mut self, info: &ProfileInfo) -> Result<()> {
+        let writer = &mut self.writer;
+        writer.write_var_uint(ServerPacketId::ProfileInfo as u64).await?;
+        writer.write_var_uint(info.rows).await?;
+        writer.write_var_uint(info.blocks).await?;
+        writer.write_var_uint(info.bytes).await?;
+        writer.write_u8(u8::from(info.applied_limit)).await?;
+        writer.write_var_uint(info.rows_before_limit).await?;
+        writer.write_u8(u8::from(info.calculated_rows_before_limit)).await?;
+        if self.revision >= DBMS_MIN_REVISION_WITH_ROWS_BEFORE_AGGREGATION {
+            writer.write_u8(u8::from(info.applied_aggregation)).await?;
+            writer.write_var_uint(info.rows_before_aggregation).await?;
+        }
+        Ok(())
+    }
+}
+
+fn mock_metadata(compression: CompressionMethod) -> ClientMetadata {
+    ClientMetadata {
+        client_id: 0,
+        compression,
+        arrow_options: ArrowOptions::default(),
+        trace_server_logs: false,
+        compression_options: CompressionOptions::default(),
+        sparse_ratio: None,
+    }
+}
+
+async fn write_hello<W: ClickHouseWrite>(writer: &mut W, revision: u64) -> Result<()> {
+    let (major, minor, patch) = MOCK_SERVER_VERSION;
+    writer.write_var_uint(ServerPacketId::Hello as u64).await?;
+    writer.write_string(MOCK_SERVER_NAME).await?;
+    writer.write_var_uint(major).await?;
+    writer.write_var_uint(minor).await?;
+    writer.write_var_uint(DBMS_TCP_PROTOCOL_VERSION).await?;
+    if revision >= DBMS_MIN_REVISION_WITH_VERSIONED_PARALLEL_REPLICAS_PROTOCOL {
+        writer.write_var_uint(DBMS_PARALLEL_REPLICAS_PROTOCOL_VERSION).await?;
+    }
+    if revision >= DBMS_MIN_REVISION_WITH_SERVER_TIMEZONE {
+        writer.write_string(MOCK_SERVER_TIMEZONE).await?;
+    }
+    if revision >= DBMS_MIN_REVISION_WITH_SERVER_DISPLAY_NAME {
+        writer.write_string(MOCK_SERVER_NAME).await?;
+    }
+    if revision >= DBMS_MIN_REVISION_WITH_VERSION_PATCH {
+        writer.write_var_uint(patch).await?;
+    }
+    if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_CHUNKED_PACKETS {
+        writer.write_string("notchunked").await?; // send
+        writer.write_string("notchunked").await?; // recv
+    }
+    if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_PASSWORD_COMPLEXITY_RULES {
+        writer.write_var_uint(0).await?;
+    }
+    if revision >= DBMS_MIN_REVISION_WITH_INTERSERVER_SECRET_V2 {
+        writer.write_u64_le(0).await?; // nonce
+    }
+    if revision >= DBMS_MIN_REVISION_WITH_SERVER_SETTINGS {
+        writer.write_string("").await?; // end of settings
+    }
+    if revision >= DBMS_MIN_REVISION_WITH_QUERY_PLAN_SERIALIZATION {
+        writer.write_var_uint(0).await?;
+    }
+    if revision >= DBMS_MIN_REVISION_WITH_VERSIONED_CLUSTER_FUNCTION_PROTOCOL {
+        writer.write_var_uint(0).await?;
+    }
+    Ok(())
+}
+
+async fn write_exception<W: ClickHouseWrite>(
+    writer: &mut W,
+    exception: &MockException,
+) -> Result<()> {
+    writer.write_var_uint(ServerPacketId::Exception as u64).await?;
+    writer.write_i32_le(exception.code).await?;
+    writer.write_string(&exception.name).await?;
+    writer.write_string(&exception.message).await?;
+    writer.write_string("").await?; // stack trace
+    writer.write_u8(0).await?; // has nested
+    Ok(())
+}
+
+async fn read_addendum<R: ClickHouseRead>(reader: &mut R, revision: u64) -> Result<()> {
+    if revision < DBMS_MIN_PROTOCOL_VERSION_WITH_ADDENDUM {
+        return Ok(());
+    }
+    drop(reader.read_string().await?); // quota key
+    if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_CHUNKED_PACKETS {
+        drop(reader.read_string().await?); // send
+        drop(reader.read_string().await?); // recv
+    }
+    if revision >= DBMS_MIN_REVISION_WITH_VERSIONED_PARALLEL_REPLICAS_PROTOCOL {
+        let _ = reader.read_var_uint().await?;
+    }
+    Ok(())
+}
+
+/// Counts the bytes read from a connection, see [`MockServer::bytes_received`].
+struct CountingReader<R> {
+    inner:  R,
+    shared: Arc<MockShared>,
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for CountingReader<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let filled = buf.filled().len();
+        let result = Pin::new(&mut self.inner).poll_read(cx, buf);
+        let _ = self.shared.received.fetch_add(buf.filled().len() - filled, Ordering::AcqRel);
+        result
+    }
+}
+
+async fn read_packets(
+    mut reader: BufReader<CountingReader<OwnedReadHalf>>,
+    revision: u64,
+    packets: mpsc::Sender<Result<ClientPacket>>,
+) {
+    // Data blocks are compressed according to the query they belong to
+    let mut compression = CompressionMethod::None;
+    loop {
+        let packet = read_packet(&mut reader, revision, &mut compression).await;
+        let failed = packet.is_err();
+        if packets.send(packet).await.is_err() || failed {
+            return;
+        }
+    }
+}
+
+async fn read_packet<R: ClickHouseRead + 'static>(
+    reader: &mut R,
+    revision: u64,
+    compression: &mut CompressionMethod,
+) -> Result<ClientPacket> {
+    const QUERY: u64 = ClientPacketId::Query as u64;
+    const DATA: u64 = ClientPacketId::Data as u64;
+    const CANCEL: u64 = ClientPacketId::Cancel as u64;
+    const PING: u64 = ClientPacketId::Ping as u64;
+
+    Ok(match reader.read_var_uint().await? {
+        QUERY => {
+            let query = read_query(reader, revision).await?;
+            *compression = query.compression;
+            ClientPacket::Query(query)
+        }
+        DATA => {
+            drop(reader.read_string().await?); // Table name
+            let mut state = DeserializerState::default();
+            let metadata = mock_metadata(*compression);
+            ClientPacket::Data(NativeFormat::read(reader, revision, metadata, &mut state).await?)
+        }
+        CANCEL => ClientPacket::Cancel,
+        PING => ClientPacket::Ping,
+        packet => return Err(Error::Protocol(format!("Unsupported client packet {packet}"))),
+    })
+}
+
+async fn read_query<R: ClickHouseRead>(reader: &mut R, revision: u64) -> Result<MockQuery> {
+    let qid = reader.read_utf8_string().await?;
+    let quota_key = if revision >= DBMS_MIN_REVISION_WITH_CLIENT_INFO {
+        read_client_info(reader, revision).await?
+    } else {
+        String::new()
+    };
+    let settings = Settings::decode(reader).await?;
+    if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_INTERSERVER_EXTERNALLY_GRANTED_ROLES {
+        drop(reader.read_string().await?);
+    }
+    if revision >= DBMS_MIN_REVISION_WITH_INTERSERVER_SECRET {
+        drop(reader.read_string().await?);
+    }
+    let _ = reader.read_var_uint().await?; // stage
+    let compressed = reader.read_u8().await? != 0;
+    let query = reader.read_utf8_string().await?;
+    let params = if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_PARAMETERS {
+        Settings::decode(reader).await?
+    } else {
+        Settings::default()
+    };
+
+    let compression = if !compressed {
+        CompressionMethod::None
+    } else if settings
+        .get("network_compression_method")
+        .is_some_and(|s| s.value().to_string().eq_ignore_ascii_case("zstd"))
+    {
+        CompressionMethod::ZSTD
+    } else {
+        CompressionMethod::LZ4
+    };
+
+    Ok(MockQuery { qid, query, settings, params, compression, quota_key, ..Default::default() })
+}
+
+/// Read the client info of a query, returning its quota key.
+async fn read_client_info<R: ClickHouseRead>(reader: &mut R, revision: u64) -> Result<String> {
+    // Query kind, `NoQuery` carries no info
+    if reader.read_u8().await? == 0 {
+        return Ok(String::new());
+    }
+    drop(reader.read_string().await?); // initial user
+    drop(reader.read_string().await?); // initial query id
+    drop(reader.read_string().await?); // initial address
+    if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_QUERY_START_TIME {
+        let _ = reader.read_u64_le().await?;
+    }
+    let _ = reader.read_u8().await?; // interface
+    drop(reader.read_string().await?); // os user
+    drop(reader.read_string().await?); // client hostname
+    drop(reader.read_string().await?); // client name
+    let _ = reader.read_var_uint().await?; // major version
+    let _ = reader.read_var_uint().await?; // minor version
+    let _ = reader.read_var_uint().await?; // protocol version
+    let quota_key = if revision >= DBMS_MIN_REVISION_WITH_QUOTA_KEY_IN_CLIENT_INFO {
+        reader.read_utf8_string().await?
+    } else {
+        String::new()
+    };
+    if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_DISTRIBUTED_DEPTH {
+        let _ = reader.read_var_uint().await?;
+    }
+    if revision >= DBMS_MIN_REVISION_WITH_VERSION_PATCH {
+        let _ = reader.read_var_uint().await?;
+    }
+    if revision >= DBMS_MIN_REVISION_WITH_OPENTELEMETRY && reader.read_u8().await? != 0 {
+        let mut trace_id = [0_u8; 16];
+        let _ = reader.read_exact(&mut trace_id).await?;
+        let _ = reader.read_u64().await?; // span id
+        drop(reader.read_string().await?); // tracestate
+        let _ = reader.read_u8().await?; // trace flags
+    }
+    if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_PARALLEL_REPLICAS {
+        for _ in 0..3 {
+            let _ = reader.read_var_uint().await?;
+        }
+    }
+    if revision >= DBMS_MIN_REVISION_WITH_QUERY_AND_LINE_NUMBERS {
+        let _ = reader.read_var_uint().await?; // script query number
+        let _ = reader.read_var_uint().await?; // script line number
+    }
+    if revision >= DBMS_MIN_REVISION_WITH_JWT_IN_INTERSERVER && reader.read_u8().await? != 0 {
+        drop(reader.read_string().await?);
+    }
+    Ok(quota_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+
+    use super::*;
+    use crate::prelude::*;
+    use crate::{ClickHouseEvent, ConnectionStatus, Destination, Event, ReconnectPolicy, Value};
+
+    fn numbers(rows: u64) -> Block {
+        Block {
+            rows,
+            column_types: vec![("number".into(), Type::UInt64)],
+            column_data: (0..rows).map(Value::UInt64).collect(),
+            ..Default::default()
+        }
+    }
+
+    async fn client(server: &MockServer) -> NativeClient {
+        Client::<NativeFormat>::builder()
+            .with_endpoint(server.endpoint())
+            .with_username("mock")
+            .build_native()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_mock_query_data() {
+        let server = MockServer::start().await.unwrap();
+        server.on_query(
+            "numbers",
+            MockResponse::new()
+                .with_block(numbers(3))
+                .with_progress(Progress { read_rows: 3, ..Default::default() })
+                .with_block(numbers(2)),
+        );
+        let client = client(&server).await;
+
+        let qid = Qid::new();
+        let blocks = client
+            .query_raw::<QueryParams>("SELECT * FROM numbers(5)".into(), None, qid)
+            .await
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(blocks.iter().map(|b| b.rows).sum::<u64>(), 5);
+
+        let queries = server.queries();
+        assert_eq!(queries.len(), 1);
+        assert_eq!(queries[0].query, "SELECT * FROM numbers(5)");
+        assert_eq!(queries[0].user, "mock");
+        assert!(!queries[0].cancelled);
+        assert!(server.connections() >= 1);
+    }
+
+    #[tokio::test]
+    async fn test_mock_exception() {
+        let server = MockServer::start().await.unwrap();
+        server.on_query("missing", MockResponse::exception(60, "Table default.missing missing"));
+        let client = client(&server).await;
+
+        let result = client.execute("DROP TABLE missing", None).await;
+        let Err(Error::ServerException(error)) = result else {
+            panic!("Expected server exception, got {result:?}");
+        };
+        assert_eq!(error.code, 60);
+        assert_eq!(error.message, "Table default.missing missing");
+
+        // The connection remains usable
+        client.execute("SELECT 1", None).await.unwrap();
+        assert_eq!(server.queries().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_mock_quota_key() {
+        let server = MockServer::start().await.unwrap();
+        let client = client(&server).await;
+
+        let options = QueryOptions::new().with_quota_key("tenant_1");
+        client.execute_with_options("SELECT 1", options).await.unwrap();
+        client.execute("SELECT 1", None).await.unwrap();
+
+        let queries = server.queries();
+        assert_eq!(queries[0].quota_key, "tenant_1");
+        assert_eq!(queries[1].quota_key, "");
+    }
+
+    #[tokio::test]
+    async fn test_mock_insert() {
+        let server = MockServer::start().await.unwrap();
+        server.on_query(
+            "INSERT",
+            MockResponse::new().with_insert(vec![("number".into(), Type::UInt64)]),
+        );
+        let client = client(&server).await;
+
+        let mut response =
+            client.insert("INSERT INTO t FORMAT Native", numbers(4), None).await.unwrap();
+        while let Some(result) = response.next().await {
+            result.unwrap();
+        }
+
+        let queries = server.queries();
+        assert_eq!(queries.len(), 1);
+        assert_eq!(queries[0].data.iter().map(|b| b.rows).sum::<u64>(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_mock_insert_compression_options() {
+        let server = MockServer::start().await.unwrap();
+        server.on_query(
+            "INSERT",
+            MockResponse::new().with_insert(vec![("number".into(), Type::UInt64)]),
+        );
+        let client = Client::<NativeFormat>::builder()
+            .with_endpoint(server.endpoint())
+            .with_username("mock")
+            .with_compression(CompressionMethod::ZSTD)
+            .build_native()
+            .await
+            .unwrap();
+
+        let insert = async |options: QueryOptions| {
+            let before = server.bytes_received();
+            let mut response = client
+                .insert_many_with_options(
+                    "INSERT INTO t FORMAT Native",
+                    vec![numbers(1000)],
+                    options,
+                )
+                .await
+                .unwrap();
+            while let Some(result) = response.next().await {
+                result.unwrap();
+            }
+            server.bytes_received() - before
+        };
+        let default = insert(QueryOptions::new()).await;
+        // Each compressed block carries a checksum and header, so small blocks show in the size
+        let options = QueryOptions::new()
+            .with_compression(CompressionOptions::new().with_level(19).with_max_block_size(64));
+        let configured = insert(options).await;
+        assert!(configured > default + 1000, "{configured} <= {default} + 1000");
+
+        let queries = server.queries();
+        assert!(queries.iter().all(|q| q.compression == CompressionMethod::ZSTD));
+        assert!(queries.iter().all(|q| q.data.iter().map(|b| b.rows).sum::<u64>() == 1000));
+    }
+
+    #[tokio::test]
+    async fn test_mock_inserter_period() {
+        let server = MockServer::start().await.unwrap();
+        server.on_query(
+            "INSERT",
+            MockResponse::new().with_insert(vec![("number".into(), Type::UInt64)]),
+        );
+        let client = client(&server).await;
+
+        let options = InserterOptions::default()
+            .with_max_rows(None)
+            .with_max_bytes(None)
+            .with_period(Some(Duration::from_millis(50)));
+        let mut inserter = client.inserter("INSERT INTO t FORMAT Native", options).await.unwrap();
+
+        // The stream only ends once the server received the block, which requires the period
+        // to flush it while the inserter waits on the stream
+        let (tx, rx) = mpsc::channel(1);
+        tx.send(Ok(numbers(2))).await.unwrap();
+        let received = async {
+            while server.queries()[0].data.iter().map(|b| b.rows).sum::<u64>() < 2 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            drop(tx);
+        };
+        let write = inserter.write_stream(tokio_stream::wrappers::ReceiverStream::new(rx));
+        let (result, ()) =
+            tokio::time::timeout(Duration::from_secs(5), async { tokio::join!(write, received) })
+                .await
+                .expect("inserter did not flush on time");
+        result.unwrap();
+        assert_eq!(inserter.rows_sent(), 2);
+        inserter.commit().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_inserter_thresholds() {
+        let server = MockServer::start().await.unwrap();
+        server.on_query(
+            "INSERT",
+            MockResponse::new().with_insert(vec![("number".into(), Type::UInt64)]),
+        );
+        let client = client(&server).await;
+
+        // Rows
+        let options = InserterOptions::default().with_max_rows(Some(3)).with_max_bytes(None);
+        let mut inserter = client.inserter("INSERT INTO t FORMAT Native", options).await.unwrap();
+        inserter.write(numbers(2)).await.unwrap();
+        assert_eq!((inserter.rows_sent(), inserter.pending_rows()), (0, 2));
+        inserter.write(numbers(2)).await.unwrap();
+        assert_eq!((inserter.rows_sent(), inserter.pending_rows()), (4, 0));
+        inserter.commit().await.unwrap();
+
+        // Bytes
+        let max_bytes = crate::formats::DataSize::data_size(&numbers(4));
+        let options =
+            InserterOptions::default().with_max_rows(None).with_max_bytes(Some(max_bytes));
+        let mut inserter = client.inserter("INSERT INTO t FORMAT Native", options).await.unwrap();
+        inserter.write(numbers(2)).await.unwrap();
+        assert_eq!(inserter.rows_sent(), 0);
+        assert!(inserter.pending_bytes() > 0);
+        inserter.write(numbers(2)).await.unwrap();
+        assert_eq!((inserter.rows_sent(), inserter.pending_bytes()), (4, 0));
+        inserter.commit().await.unwrap();
+
+        // Time, checked when data is written
+        let options = InserterOptions::default()
+            .with_max_rows(None)
+            .with_max_bytes(None)
+            .with_period(Some(Duration::from_millis(50)));
+        let mut inserter = client.inserter("INSERT INTO t FORMAT Native", options).await.unwrap();
+        inserter.write(numbers(1)).await.unwrap();
+        assert_eq!(inserter.rows_sent(), 0);
+        tokio::time::sleep(inserter.time_left().unwrap()).await;
+        inserter.write(numbers(1)).await.unwrap();
+        assert_eq!((inserter.rows_sent(), inserter.pending_rows()), (2, 0));
+        inserter.commit().await.unwrap();
+
+        let queries = server.queries();
+        let rows = queries.iter().map(|q| q.data.iter().map(|b| b.rows).sum::<u64>());
+        assert_eq!(rows.collect::<Vec<_>>(), [4, 4, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_mock_inserter_abort() {
+        let server = MockServer::start().await.unwrap();
+        server.on_query(
+            "INSERT",
+            MockResponse::new().with_insert(vec![("number".into(), Type::UInt64)]),
+        );
+        let client = client(&server).await;
+
+        let options = InserterOptions::default().with_max_rows(None).with_max_bytes(None);
+        let mut inserter = client.inserter("INSERT INTO t FORMAT Native", options).await.unwrap();
+        inserter.write(numbers(2)).await.unwrap();
+        inserter.flush().await.unwrap();
+        // Buffered data is discarded
+        inserter.write(numbers(3)).await.unwrap();
+        inserter.abort().await.unwrap();
+
+        let queries = server.queries();
+        assert!(queries[0].cancelled);
+        assert_eq!(queries[0].data.iter().map(|b| b.rows).sum::<u64>(), 2);
+
+        // The connection remains usable
+        client.execute("SELECT 1", None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_inserter_reconnect() {
+        let server = MockServer::start().await.unwrap();
+        server.on_query(
+            "INSERT",
+            MockResponse::new().with_insert(vec![("number".into(), Type::UInt64)]),
+        );
+        let policy = ReconnectPolicy::new().with_initial_interval(Duration::from_millis(10));
+        let client = Client::<NativeFormat>::builder()
+            .with_endpoint(server.endpoint())
+            .with_username("mock")
+            .with_reconnect(policy)
+            .build_native()
+            .await
+            .unwrap();
+
+        let options = InserterOptions::default().with_max_rows(Some(1));
+        let mut inserter = client.inserter("INSERT INTO t FORMAT Native", options).await.unwrap();
+        inserter.write(numbers(1)).await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while server.queries()[0].data.is_empty() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("server should receive the first block");
+
+        // Another operation re-establishes the connection while the insert is open
+        server.drop_connections();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while client.status() == ConnectionStatus::Open {
+                let _ = client.execute("SELECT 1", None).await.ok();
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("lost connection should be detected");
+        client.execute("SELECT 1", None).await.unwrap();
+
+        // The new connection does not know the insert, its data is not sent there
+        let result = inserter.write(numbers(1)).await;
+        assert!(
+            matches!(result, Err(Error::ConnectionGone(e)) if e.contains("re-established")),
+            "{result:?}"
+        );
+        assert!(inserter.write(numbers(1)).await.is_err());
+        assert!(inserter.commit().await.is_err());
+
+        let queries = server.queries();
+        assert_eq!(queries[0].data.iter().map(|b| b.rows).sum::<u64>(), 1);
+        assert!(queries[1..].iter().all(|q| q.data.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn test_mock_inserter_sink() {
+        use futures_util::SinkExt;
+
+        let server = MockServer::start().await.unwrap();
+        server.on_query(
+            "INSERT",
+            MockResponse::new().with_insert(vec![("number".into(), Type::UInt64)]),
+        );
+        let client = client(&server).await;
+
+        let options = InserterOptions::default().with_max_rows(Some(3));
+        let inserter = client.inserter("INSERT INTO t FORMAT Native", options).await.unwrap();
+        let mut sink = inserter.into_sink();
+        // The second block reaches the threshold, the third waits for the sink to close
+        sink.feed(numbers(2)).await.unwrap();
+        sink.feed(numbers(2)).await.unwrap();
+        sink.feed(numbers(1)).await.unwrap();
+        sink.close().await.unwrap();
+        assert!(sink.feed(numbers(1)).await.is_err());
+
+        let queries = server.queries();
+        assert_eq!(queries[0].data.iter().map(|b| b.rows).sum::<u64>(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_mock_cancel_and_ping() {
+        let server = MockServer::start().await.unwrap();
+        server.on_query("sleep", MockResponse::new().with_delay(Duration::from_secs(30)));
+        let client = client(&server).await;
+
+        let qid = Qid::new();
+        let response =
+            client.query_raw::<QueryParams>("SELECT sleep(3)".into(), None, qid).await.unwrap();
+        client.cancel(qid).await.unwrap();
+        let results = tokio::time::timeout(Duration::from_secs(5), response.collect::<Vec<_>>())
+            .await
+            .expect("cancelled query should end");
+        assert!(results.iter().all(Result::is_ok));
+        assert!(server.queries()[0].cancelled);
+
+        client.health_check(true).await.unwrap();
+        assert!(server.pings() >= 1);
+    }
+
+    #[tokio::test]
+    async fn test_mock_cancel_on_drop() {
+        let server = MockServer::start().await.unwrap();
+        server.on_query("sleep", MockResponse::new().with_delay(Duration::from_secs(30)));
+        let client = client(&server).await;
+
+        let response = client.query_raw::<QueryParams>("SELECT sleep(3)".into(), None, Qid::new());
+        drop(response.await.unwrap());
+
+        // No further operation is sent, the dropped response alone must cancel the query
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !server.queries().first().is_some_and(|q| q.cancelled) {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("dropped response should cancel the query");
+    }
+
+    #[tokio::test]
+    async fn test_mock_handshake_exception() {
+        let server = MockServer::start().await.unwrap();
+        server.set_handshake_exception(Some(MockException::new(516, "Authentication failed")));
+        let result =
+            Client::<NativeFormat>::builder().with_endpoint(server.endpoint()).build_native().await;
+        assert!(matches!(result, Err(Error::ServerException(e)) if e.code == 516));
+    }
+
+    #[tokio::test]
+    async fn test_mock_disconnect() {
+        let server = MockServer::start().await.unwrap();
+        server.on_query_once("SELECT", MockResponse::disconnect());
+        let client = client(&server).await;
+
+        assert!(client.execute("SELECT 1", None).await.is_err());
+        assert_eq!(server.queries().len(), 1);
+
+        server.drop_connections();
+        assert!(client.execute("SELECT 1", None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_mock_replica_failover() {
+        // A replica that refuses the connection, one failing the handshake and a healthy one
+        let closed = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let closed_addr = closed.local_addr().unwrap();
+        drop(closed);
+        let failing = MockServer::start().await.unwrap();
+        failing.set_handshake_exception(Some(MockException::new(516, "Authentication failed")));
+        let healthy = MockServer::start().await.unwrap();
+
+        let destination = Destination::replicas([
+            Destination::from(closed_addr),
+            Destination::from(failing.addr()),
+            Destination::from(healthy.addr()),
+        ]);
+        let client = Client::<NativeFormat>::builder()
+            .with_destination(destination)
+            .with_username("mock")
+            .build_native()
+            .await
+            .unwrap();
+
+        client.execute("SELECT 1", None).await.unwrap();
+        assert!(failing.connections() >= 1);
+        assert!(failing.queries().is_empty());
+        assert_eq!(healthy.queries().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_mock_reconnect() {
+        let server = MockServer::start().await.unwrap();
+        let policy = ReconnectPolicy::new().with_initial_interval(Duration::from_millis(10));
+        let client = Client::<NativeFormat>::builder()
+            .with_endpoint(server.endpoint())
+            .with_username("mock")
+            .with_reconnect(policy)
+            .build_native()
+            .await
+            .unwrap();
+        let mut events = client.subscribe_events();
+        let connections = server.connections();
+
+        // The query in flight when the socket is dropped fails and is not retried
+        server.drop_connections();
+        assert!(client.execute("SELECT 1", None).await.is_err());
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while client.status() == ConnectionStatus::Open {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("lost connection should be detected");
+
+        // The next query re-establishes the connection first
+        client.execute("SELECT 1", None).await.unwrap();
+        assert_eq!(client.status(), ConnectionStatus::Open);
+        assert!(server.connections() > connections);
+
+        let mut reconnected = None;
+        while let Ok(Event { event, .. }) = events.try_recv() {
+            if let ClickHouseEvent::Reconnected { attempts } = event {
+                reconnected = Some(attempts);
+            }
+        }
+        assert_eq!(reconnected, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_mock_reconnect_failed_cooldown() {
+        let server = MockServer::start().await.unwrap();
+        let policy = ReconnectPolicy::new()
+            .with_max_attempts(Some(2))
+            .with_initial_interval(Duration::from_millis(10))
+            .with_cooldown(Duration::from_millis(300));
+        let client = Client::<NativeFormat>::builder()
+            .with_endpoint(server.endpoint())
+            .with_username("mock")
+            .with_reconnect(policy)
+            .build_native()
+            .await
+            .unwrap();
+        let mut events = client.subscribe_events();
+
+        server.set_handshake_exception(Some(MockException::new(516, "Authentication failed")));
+        server.drop_connections();
+        assert!(client.execute("SELECT 1", None).await.is_err());
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while client.status() == ConnectionStatus::Open {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("lost connection should be detected");
+
+        // The first operation exhausts the attempts
+        let connections = server.connections();
+        assert!(client.execute("SELECT 1", None).await.is_err());
+        assert_eq!(server.connections(), connections + 2);
+
+        // Later operations fail without attempting or emitting events again
+        let result = client.execute("SELECT 1", None).await;
+        assert!(matches!(result, Err(Error::Network(_))), "{result:?}");
+        assert_eq!(server.connections(), connections + 2);
+        let mut failed = 0;
+        while let Ok(Event { event, .. }) = events.try_recv() {
+            if let ClickHouseEvent::ReconnectFailed { .. } = event {
+                failed += 1;
+            }
+        }
+        assert_eq!(failed, 1);
+
+        // Once the cooldown has passed the connection is attempted again
+        server.set_handshake_exception(None);
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        client.execute("SELECT 1", None).await.unwrap();
+        assert_eq!(client.status(), ConnectionStatus::Open);
+    }
+}
@@ -0,0 +1,213 @@
+//! A common interface over the transports that speak Arrow to `ClickHouse`.
+//!
+//! [`ArrowTransport`] is implemented by the native [`ArrowClient`](crate::ArrowClient), the bb8
+//! [`ConnectionPool<ArrowFormat>`](crate::ConnectionPool) (feature `pool`) and the
+//! [`HttpClient`](crate::http::HttpClient) (feature `http`). The trait is object safe, so the
+//! transport can be chosen from configuration at runtime:
+//!
+//! ```rust,ignore
+//! use std::sync::Arc;
+//!
+//! use clickhouse_arrow::prelude::*;
+//!
+//! let transport: Arc<dyn ArrowTransport> = if use_http {
+//!     Arc::new(HttpClient::new(http_options)?)
+//! } else {
+//!     Arc::new(Client::builder().with_endpoint("localhost:9000").build_arrow().await?)
+//! };
+//!
+//! let batches = transport
+//!     .query("SELECT number FROM system.numbers LIMIT 10", QueryOptions::new())
+//!     .await?
+//!     .try_collect::<Vec<_>>()
+//!     .await?;
+//! transport.insert("my_table", batches, QueryOptions::new()).await?;
+//! ```
+use std::collections::HashMap;
+
+use arrow::array::RecordBatch;
+use arrow::datatypes::SchemaRef;
+use futures_util::future::BoxFuture;
+use futures_util::stream::BoxStream;
+use futures_util::{FutureExt, StreamExt};
+
+use crate::explain::QueryOptions;
+use crate::formats::ArrowFormat;
+use crate::query::Qid;
+use crate::{Client, Result};
+
+/// Stream of Arrow batches returned by [`ArrowTransport::query`].
+pub type RecordBatchStream = BoxStream<'static, Result<RecordBatch>>;
+
+/// Query, execute, insert and schema fetch over any Arrow capable transport.
+///
+/// Methods take [`QueryOptions`] for parameters, query ID and per-query settings. Options that a
+/// transport does not support are ignored, e.g. limits and explain over HTTP.
+pub trait ArrowTransport: Send + Sync {
+    /// Execute a query, returning a stream of Arrow batches.
+    fn query<'a>(
+        &'a self,
+        query: &'a str,
+        options: QueryOptions,
+    ) -> BoxFuture<'a, Result<RecordBatchStream>>;
+
+    /// Execute a query that returns no rows, e.g. DDL.
+    fn execute<'a>(&'a self, query: &'a str, options: QueryOptions) -> BoxFuture<'a, Result<()>>;
+
+    /// Insert batches into a table. No insert is issued if `batches` is empty.
+    fn insert<'a>(
+        &'a self,
+        table: &'a str,
+        batches: Vec<RecordBatch>,
+        options: QueryOptions,
+    ) -> BoxFuture<'a, Result<()>>;
+
+    /// Fetch the Arrow schemas of the tables in a database, or of a subset of its tables. Without
+    /// a database, the default database of the transport is used.
+    fn fetch_schema<'a>(
+        &'a self,
+        database: Option<&'a str>,
+        tables: &'a [&'a str],
+        qid: Option<Qid>,
+    ) -> BoxFuture<'a, Result<HashMap<String, SchemaRef>>>;
+}
+
+impl ArrowTransport for Client<ArrowFormat> {
+    fn query<'a>(
+        &'a self,
+        query: &'a str,
+        options: QueryOptions,
+    ) -> BoxFuture<'a, Result<RecordBatchStream>> {
+        async move { Ok(self.query_with_options(query, options).await?.boxed()) }.boxed()
+    }
+
+    fn execute<'a>(&'a self, query: &'a str, options: QueryOptions) -> BoxFuture<'a, Result<()>> {
+        self.execute_with_options(query, options).boxed()
+    }
+
+    fn insert<'a>(
+        &'a self,
+        table: &'a str,
+        batches: Vec<RecordBatch>,
+        options: QueryOptions,
+    ) -> BoxFuture<'a, Result<()>> {
+        async move {
+            if batches.is_empty() {
+                return Ok(());
+            }
+            let query = format!("INSERT INTO {table} VALUES");
+            let mut stream = self.insert_many_with_options(query, batches, options).await?;
+            while let Some(result) = stream.next().await {
+                result?;
+            }
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn fetch_schema<'a>(
+        &'a self,
+        database: Option<&'a str>,
+        tables: &'a [&'a str],
+        qid: Option<Qid>,
+    ) -> BoxFuture<'a, Result<HashMap<String, SchemaRef>>> {
+        Client::fetch_schema(self, database, tables, qid).boxed()
+    }
+}
+
+#[cfg(feature = "pool")]
+mod pool {
+    use super::*;
+    use crate::{ConnectionPool, Error};
+
+    async fn get(
+        pool: &ConnectionPool<ArrowFormat>,
+    ) -> Result<bb8::PooledConnection<'_, crate::ArrowConnectionManager>> {
+        pool.get().await.map_err(|error| match error {
+            bb8::RunError::User(error) => error,
+            bb8::RunError::TimedOut => {
+                Error::ConnectionTimeout("Timed out waiting for a pooled connection".into())
+            }
+        })
+    }
+
+    /// Each call checks out a connection for its duration. Query streams outlive the checkout,
+    /// the connection is multiplexed and stays usable by other callers.
+    impl ArrowTransport for ConnectionPool<ArrowFormat> {
+        fn query<'a>(
+            &'a self,
+            query: &'a str,
+            options: QueryOptions,
+        ) -> BoxFuture<'a, Result<RecordBatchStream>> {
+            async move { ArrowTransport::query(&*get(self).await?, query, options).await }.boxed()
+        }
+
+        fn execute<'a>(
+            &'a self,
+            query: &'a str,
+            options: QueryOptions,
+        ) -> BoxFuture<'a, Result<()>> {
+            async move { ArrowTransport::execute(&*get(self).await?, query, options).await }.boxed()
+        }
+
+        fn insert<'a>(
+            &'a self,
+            table: &'a str,
+            batches: Vec<RecordBatch>,
+            options: QueryOptions,
+        ) -> BoxFuture<'a, Result<()>> {
+            async move { ArrowTransport::insert(&*get(self).await?, table, batches, options).await }
+                .boxed()
+        }
+
+        fn fetch_schema<'a>(
+            &'a self,
+            database: Option<&'a str>,
+            tables: &'a [&'a str],
+            qid: Option<Qid>,
+        ) -> BoxFuture<'a, Result<HashMap<String, SchemaRef>>> {
+            async move { get(self).await?.fetch_schema(database, tables, qid).await }.boxed()
+        }
+    }
+}
+
+#[cfg(feature = "http")]
+impl ArrowTransport for crate::http::HttpClient {
+    fn query<'a>(
+        &'a self,
+        query: &'a str,
+        options: QueryOptions,
+    ) -> BoxFuture<'a, Result<RecordBatchStream>> {
+        async move { Ok(self.query_with_options(query, options).await?.boxed()) }.boxed()
+    }
+
+    fn execute<'a>(&'a self, query: &'a str, options: QueryOptions) -> BoxFuture<'a, Result<()>> {
+        self.execute_with_options(query, options).boxed()
+    }
+
+    fn insert<'a>(
+        &'a self,
+        table: &'a str,
+        batches: Vec<RecordBatch>,
+        options: QueryOptions,
+    ) -> BoxFuture<'a, Result<()>> {
+        let batches = futures_util::stream::iter(batches.into_iter().map(Ok));
+        self.insert_stream_with_options(table, batches, options).boxed()
+    }
+
+    fn fetch_schema<'a>(
+        &'a self,
+        database: Option<&'a str>,
+        tables: &'a [&'a str],
+        qid: Option<Qid>,
+    ) -> BoxFuture<'a, Result<HashMap<String, SchemaRef>>> {
+        crate::http::HttpClient::fetch_schema(self, database, tables, qid).boxed()
+    }
+}
+
+// Each transport must remain usable as a trait object
+const _: fn(&Client<ArrowFormat>) -> &dyn ArrowTransport = |c| c;
+#[cfg(feature = "pool")]
+const _: fn(&crate::ConnectionPool<ArrowFormat>) -> &dyn ArrowTransport = |c| c;
+#[cfg(feature = "http")]
+const _: fn(&crate::http::HttpClient) -> &dyn ArrowTransport = |c| c;
@@ -56,6 +56,42 @@ e2e_test!(e2e_arrow_schema, tests::arrow::test_schema_utils, TRACING_DIRECTIVES,
 #[cfg(feature = "test-utils")]
 e2e_test!(e2e_arrow_execute, tests::arrow::test_execute_queries, TRACING_DIRECTIVES, None);
 
+// Test query cancellation
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_arrow_cancel, tests::arrow::test_query_cancellation, TRACING_DIRECTIVES, None);
+
+// Test totals and extremes
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_arrow_totals, tests::arrow::test_totals_and_extremes, TRACING_DIRECTIVES, None);
+
+// Test profile info
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_arrow_profile_info, tests::arrow::test_profile_info, TRACING_DIRECTIVES, None);
+
+// Test server logs
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_arrow_server_logs, tests::arrow::test_server_logs, TRACING_DIRECTIVES, None);
+
+// Test per-query settings
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_arrow_query_settings, tests::arrow::test_query_settings, TRACING_DIRECTIVES, None);
+
+// Test tables status requests
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_arrow_tables_status, tests::arrow::test_tables_status, TRACING_DIRECTIVES, None);
+
+// Test external tables sent with a query
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_arrow_external_tables, tests::arrow::test_external_tables, TRACING_DIRECTIVES, None);
+
+// Test trace context propagation
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_arrow_trace_parent, tests::arrow::test_trace_parent, TRACING_DIRECTIVES, None);
+
+// Test streaming inserts
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_arrow_inserter, tests::arrow::test_inserter, TRACING_DIRECTIVES, None);
+
 // Test ClickHouse nullable array support
 #[cfg(feature = "test-utils")]
 e2e_test!(
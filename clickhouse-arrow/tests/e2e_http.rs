@@ -10,8 +10,12 @@ use arrow::array::{
 };
 use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
 use clickhouse_arrow::http::{HttpClient, HttpOptions};
-use clickhouse_arrow::prelude::ClientBuilder;
+use clickhouse_arrow::prelude::{
+    ArrowTransport, ClientBuilder, ParamValue, QueryOptions, QueryParams,
+};
 use clickhouse_arrow::test_utils::ClickHouseContainer;
+use clickhouse_arrow::{ArrowConnectionPoolBuilder, ArrowOptions, ClickHouseEvent, Error};
+use futures_util::{StreamExt, TryStreamExt};
 
 pub mod common;
 pub mod tests;
@@ -35,6 +39,18 @@ e2e_test!(e2e_http_round_trip, test_http_round_trip, TRACING_DIRECTIVES, None);
 #[cfg(feature = "test-utils")]
 e2e_test!(e2e_http_insert_batches, test_http_insert_batches, TRACING_DIRECTIVES, None);
 
+// HTTP streaming query and insert test
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_http_streaming, test_http_streaming, TRACING_DIRECTIVES, None);
+
+// HTTP query parameters, settings and sessions test
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_http_session, test_http_session, TRACING_DIRECTIVES, None);
+
+// Common transport trait over HTTP, native and pooled clients test
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_http_transport, test_http_transport, TRACING_DIRECTIVES, None);
+
 // HTTP ClientBuilder integration test
 #[cfg(feature = "test-utils")]
 e2e_test!(e2e_http_builder, test_http_builder, TRACING_DIRECTIVES, None);
@@ -89,7 +105,13 @@ pub async fn test_http_query(ch: Arc<ClickHouseContainer>) {
     let client = create_http_client(&ch);
 
     // Simple scalar query
-    let batches = client.query("SELECT 1 as value").await.expect("Query should succeed");
+    let batches = client
+        .query("SELECT 1 as value")
+        .await
+        .expect("Query should succeed")
+        .try_collect::<Vec<_>>()
+        .await
+        .expect("Query should succeed");
 
     assert_eq!(batches.len(), 1);
     assert_eq!(batches[0].num_rows(), 1);
@@ -120,8 +142,13 @@ pub async fn test_http_insert(ch: Arc<ClickHouseContainer>) {
     client.insert("http_test_insert", batch).await.expect("Insert should succeed");
 
     // Verify data was inserted
-    let batches =
-        client.query("SELECT count(*) as cnt FROM http_test_insert").await.expect("Count query");
+    let batches = client
+        .query("SELECT count(*) as cnt FROM http_test_insert")
+        .await
+        .expect("Count query")
+        .try_collect::<Vec<_>>()
+        .await
+        .expect("Count query");
 
     assert_eq!(batches.len(), 1);
     let count_col = batches[0]
@@ -166,6 +193,9 @@ pub async fn test_http_round_trip(ch: Arc<ClickHouseContainer>) {
     let batches = client
         .query("SELECT id, name FROM http_test_round_trip ORDER BY id")
         .await
+        .expect("Query should succeed")
+        .try_collect::<Vec<_>>()
+        .await
         .expect("Query should succeed");
 
     assert_eq!(batches.len(), 1);
@@ -236,8 +266,13 @@ pub async fn test_http_insert_batches(ch: Arc<ClickHouseContainer>) {
         .expect("Insert batches should succeed");
 
     // Verify total count
-    let batches =
-        client.query("SELECT count(*) as cnt FROM http_test_batches").await.expect("Count query");
+    let batches = client
+        .query("SELECT count(*) as cnt FROM http_test_batches")
+        .await
+        .expect("Count query")
+        .try_collect::<Vec<_>>()
+        .await
+        .expect("Count query");
 
     let count_col = batches[0]
         .column(0)
@@ -252,6 +287,190 @@ pub async fn test_http_insert_batches(ch: Arc<ClickHouseContainer>) {
     eprintln!("HTTP insert_batches test passed");
 }
 
+/// Test streaming a query into a streaming insert
+///
+/// # Panics
+/// Panics if assertions fail
+pub async fn test_http_streaming(ch: Arc<ClickHouseContainer>) {
+    let client = create_http_client(&ch);
+
+    client
+        .execute(
+            "CREATE TABLE IF NOT EXISTS http_test_streaming (
+                id UInt64
+            ) ENGINE = MergeTree() ORDER BY id",
+        )
+        .await
+        .expect("Create table");
+
+    // Small blocks so the response holds many batches
+    let batches = client
+        .query("SELECT number AS id FROM numbers(100000) SETTINGS max_block_size = 1000")
+        .await
+        .expect("Query should succeed");
+    let mut events = client.subscribe_events();
+    client.insert_stream("http_test_streaming", batches).await.expect("Streaming insert");
+
+    // The summary of the insert reports the written rows
+    let mut written_rows = 0;
+    while let Ok(event) = events.try_recv() {
+        if let ClickHouseEvent::Progress(progress) = event.event {
+            written_rows += progress.written_rows.unwrap_or_default();
+        }
+    }
+    assert_eq!(written_rows, 100_000);
+
+    let mut batches =
+        client.query("SELECT id FROM http_test_streaming").await.expect("Query should succeed");
+    let mut batch_count = 0;
+    let mut row_count = 0;
+    while let Some(batch) = batches.next().await {
+        batch_count += 1;
+        row_count += batch.expect("Batch should decode").num_rows();
+    }
+    assert!(batch_count > 1, "Expected several batches, got {batch_count}");
+    assert_eq!(row_count, 100_000);
+
+    // An empty stream sends nothing
+    client
+        .insert_stream("http_test_streaming", futures_util::stream::empty())
+        .await
+        .expect("Empty streaming insert");
+
+    // Cleanup
+    client.execute("DROP TABLE IF EXISTS http_test_streaming").await.expect("Drop table");
+
+    eprintln!("HTTP streaming test passed");
+}
+
+/// Test query parameters, per-request settings and temporary tables in a session
+///
+/// # Panics
+/// Panics if assertions fail
+pub async fn test_http_session(ch: Arc<ClickHouseContainer>) {
+    let url = format!("http://{}:{}", ch.endpoint, ch.http_port);
+    let options = HttpOptions::new(&url)
+        .expect("Valid URL")
+        .with_credentials(&ch.user, &ch.password)
+        .with_session(format!("http_test_{}", std::process::id()))
+        .with_session_timeout(std::time::Duration::from_secs(90))
+        .with_setting("max_threads", 2);
+    let client = HttpClient::new(options).expect("Create HTTP client");
+
+    // Temporary tables live as long as the session
+    client
+        .execute("CREATE TEMPORARY TABLE http_test_tmp (id Int64, name Nullable(String))")
+        .await
+        .expect("Create temporary table");
+    client.insert("http_test_tmp", test_batch()).await.expect("Insert into temporary table");
+
+    let params = QueryParams::from(vec![("min_id", ParamValue::from(2_i64))]);
+    let batches = client
+        .query_params("SELECT id FROM http_test_tmp WHERE id >= {min_id:Int64}", Some(params), None)
+        .await
+        .expect("Query with params")
+        .try_collect::<Vec<_>>()
+        .await
+        .expect("Query with params");
+    let rows: usize = batches.iter().map(RecordBatch::num_rows).sum();
+    assert_eq!(rows, 4);
+
+    // Per-request settings override the client's
+    let options = QueryOptions::new().with_setting("max_threads", 1);
+    let batches = client
+        .query_with_options("SELECT toUInt64(getSetting('max_threads')) AS threads", options)
+        .await
+        .expect("Query with settings")
+        .try_collect::<Vec<_>>()
+        .await
+        .expect("Query with settings");
+    let threads = batches[0]
+        .column(0)
+        .as_any()
+        .downcast_ref::<arrow::array::UInt64Array>()
+        .expect("threads column");
+    assert_eq!(threads.value(0), 1);
+
+    // Without the session the temporary table doesn't exist
+    let Err(err) = create_http_client(&ch).query("SELECT * FROM http_test_tmp").await else {
+        panic!("Temporary table should only exist in the session");
+    };
+    let err_str = err.to_string();
+    assert!(err_str.contains("UNKNOWN_TABLE"), "Unexpected error: {err_str}");
+
+    eprintln!("HTTP session test passed");
+}
+
+/// Test the same workload through `ArrowTransport` over HTTP, native and pooled clients
+///
+/// # Panics
+/// Panics if assertions fail
+pub async fn test_http_transport(ch: Arc<ClickHouseContainer>) {
+    let builder = ClientBuilder::new()
+        .with_endpoint(ch.get_native_url())
+        .with_username(&ch.user)
+        .with_password(&ch.password)
+        .with_ipv4_only(true)
+        .with_arrow_options(ArrowOptions::default().with_strings_as_strings(true));
+    let native = builder.clone().build_arrow().await.expect("Build native client");
+    let pool = ArrowConnectionPoolBuilder::with_client_builder(builder)
+        .configure_pool(|pool| pool.max_size(2))
+        .build()
+        .await
+        .expect("Build pool");
+
+    let transports: Vec<(&str, Arc<dyn ArrowTransport>)> = vec![
+        ("http", Arc::new(create_http_client(&ch))),
+        ("native", Arc::new(native)),
+        ("pool", Arc::new(pool)),
+    ];
+
+    for (name, transport) in transports {
+        let table = format!("http_test_transport_{name}");
+        transport
+            .execute(
+                &format!(
+                    "CREATE TABLE {table} (id Int64, name Nullable(String)) ENGINE = MergeTree() \
+                     ORDER BY id"
+                ),
+                QueryOptions::new(),
+            )
+            .await
+            .expect("Create table");
+
+        transport.insert(&table, vec![test_batch()], QueryOptions::new()).await.expect("Insert");
+        transport.insert(&table, vec![], QueryOptions::new()).await.expect("Empty insert");
+
+        let params = QueryParams::from(vec![("min", ParamValue::from(2_i64))]);
+        let batches = transport
+            .query(
+                &format!("SELECT id, name FROM {table} WHERE id >= {{min:Int64}} ORDER BY id"),
+                QueryOptions::new().with_params(params),
+            )
+            .await
+            .expect("Query")
+            .try_collect::<Vec<_>>()
+            .await
+            .expect("Collect");
+        let rows: usize = batches.iter().map(RecordBatch::num_rows).sum();
+        assert_eq!(rows, 4, "{name}");
+
+        let schemas =
+            transport.fetch_schema(None, &[table.as_str()], None).await.expect("Fetch schema");
+        let schema = schemas.get(&table).expect("Table schema");
+        assert_eq!(schema.fields().len(), 2, "{name}");
+        assert_eq!(schema.field(0).data_type(), &DataType::Int64, "{name}");
+        assert!(schema.field(1).is_nullable(), "{name}");
+
+        transport
+            .execute(&format!("DROP TABLE {table}"), QueryOptions::new())
+            .await
+            .expect("Drop");
+    }
+
+    eprintln!("HTTP transport test passed");
+}
+
 /// Test `ClientBuilder.build_http()` integration
 ///
 /// # Panics
@@ -268,7 +487,13 @@ pub async fn test_http_builder(ch: Arc<ClickHouseContainer>) {
         .expect("build_http should succeed");
 
     // Verify it works
-    let batches = client.query("SELECT 42 as answer").await.expect("Query should succeed");
+    let batches = client
+        .query("SELECT 42 as answer")
+        .await
+        .expect("Query should succeed")
+        .try_collect::<Vec<_>>()
+        .await
+        .expect("Query should succeed");
 
     assert_eq!(batches.len(), 1);
     assert_eq!(batches[0].num_rows(), 1);
@@ -320,8 +545,13 @@ pub async fn test_http_large_data(ch: Arc<ClickHouseContainer>) {
     client.insert("http_test_large", batch).await.expect("Insert large batch");
 
     // Verify count
-    let batches =
-        client.query("SELECT count(*) as cnt FROM http_test_large").await.expect("Count query");
+    let batches = client
+        .query("SELECT count(*) as cnt FROM http_test_large")
+        .await
+        .expect("Count query")
+        .try_collect::<Vec<_>>()
+        .await
+        .expect("Count query");
 
     let count_col = batches[0]
         .column(0)
@@ -334,6 +564,9 @@ pub async fn test_http_large_data(ch: Arc<ClickHouseContainer>) {
     let batches = client
         .query("SELECT id, value, text FROM http_test_large ORDER BY id LIMIT 100")
         .await
+        .expect("Query large data")
+        .try_collect::<Vec<_>>()
+        .await
         .expect("Query large data");
 
     assert_eq!(batches[0].num_rows(), 100);
@@ -410,6 +643,9 @@ pub async fn test_http_types(ch: Arc<ClickHouseContainer>) {
             "SELECT id, big_id, flag, ratio, name, optional_name FROM http_test_types ORDER BY id",
         )
         .await
+        .expect("Query types")
+        .try_collect::<Vec<_>>()
+        .await
         .expect("Query types");
 
     assert_eq!(batches.len(), 1);
@@ -434,14 +670,22 @@ pub async fn test_http_errors(ch: Arc<ClickHouseContainer>) {
     let client = create_http_client(&ch);
 
     // Test invalid SQL - should return server error
-    let result = client.query("SELECT * FROM nonexistent_table_12345").await;
-    assert!(result.is_err(), "Query to nonexistent table should fail");
-    let err = result.unwrap_err();
+    let Err(err) = client.query("SELECT * FROM nonexistent_table_12345").await else {
+        panic!("Query to nonexistent table should fail");
+    };
     let err_str = err.to_string();
-    assert!(
-        err_str.contains("Server error") || err_str.contains("UNKNOWN_TABLE"),
-        "Error should mention server error or unknown table: {err_str}"
-    );
+    assert!(err_str.contains("UNKNOWN_TABLE"), "Error should mention unknown table: {err_str}");
+
+    // Exceptions carry the same error code as over the native protocol
+    let Error::ServerException(server_error) = err else {
+        panic!("Expected a server exception: {err_str}");
+    };
+    assert_eq!(server_error.code, 60);
+    let Err(Error::ServerException(server_error)) = client.execute("CREATE INVALID SYNTAX").await
+    else {
+        panic!("Invalid DDL should fail with a server exception");
+    };
+    assert_eq!(server_error.code, 62);
 
     // Test invalid DDL
     let result = client.execute("CREATE INVALID SYNTAX").await;
@@ -8,3 +8,12 @@ const TRACING_DIRECTIVES: &[(&str, &str)] = &[("testcontainers", "debug")];
 // Test native e2e
 #[cfg(feature = "derive")]
 e2e_test!(e2e_native, tests::native::test_round_trip, TRACING_DIRECTIVES, None);
+
+// Test native external tables
+#[cfg(feature = "derive")]
+e2e_test!(
+    e2e_native_external_tables,
+    tests::native::test_external_tables,
+    TRACING_DIRECTIVES,
+    None
+);
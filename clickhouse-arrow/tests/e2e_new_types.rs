@@ -15,6 +15,7 @@ e2e_test!(e2e_variant_complex, tests::new_types::test_variant_complex, TRACING_D
 
 // Dynamic tests
 e2e_test!(e2e_dynamic_basic, tests::new_types::test_dynamic_basic, TRACING_DIRECTIVES, None);
+e2e_test!(e2e_dynamic_union, tests::new_types::test_dynamic_union, TRACING_DIRECTIVES, None);
 e2e_test!(
     e2e_dynamic_max_types,
     tests::new_types::test_dynamic_max_types,
@@ -22,6 +23,9 @@ e2e_test!(
     None
 );
 
+// JSON tests
+e2e_test!(e2e_json_basic, tests::new_types::test_json_basic, TRACING_DIRECTIVES, None);
+
 // Nested tests
 e2e_test!(e2e_nested_basic, tests::new_types::test_nested_basic, TRACING_DIRECTIVES, None);
 e2e_test!(e2e_nested_flatten, tests::new_types::test_nested_flatten, TRACING_DIRECTIVES, None);
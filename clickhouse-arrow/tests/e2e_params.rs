@@ -25,3 +25,6 @@ e2e_test!(e2e_params_array_int32, tests::params::test_params_array_int32, TRACIN
 
 // Test parameter functionality - mixed types
 e2e_test!(e2e_params_mixed_types, tests::params::test_params_mixed_types, TRACING_DIRECTIVES, None);
+
+// Test parameter functionality - typed values (arrays, maps, tuples, NULL, dates, decimals)
+e2e_test!(e2e_params_typed, tests::params::test_params_typed, TRACING_DIRECTIVES, None);
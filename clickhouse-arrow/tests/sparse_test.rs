@@ -889,3 +889,117 @@ async fn test_sparse_large_scale() {
 
     println!("Large-scale sparse test passed!");
 }
+
+/// Test inserting mostly default columns with sparse serialization, from both formats, and
+/// reading sparse columns with the native format.
+#[tokio::test]
+async fn test_sparse_insert_roundtrip() {
+    use std::sync::Arc;
+
+    use arrow::array::{Int64Array, RecordBatch, StringArray, UInt64Array};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use clickhouse_arrow::Value;
+    use clickhouse_arrow::native::block::Block;
+
+    init_tracing(None);
+
+    let ch = get_or_create_container(None).await;
+    let client = Client::<ArrowFormat>::builder()
+        .with_endpoint(ch.get_native_url())
+        .with_username(&ch.user)
+        .with_password(&ch.password)
+        .with_sparse_serialization_ratio(0.9)
+        .build_arrow()
+        .await
+        .expect("Failed to create client");
+    let native = Client::<NativeFormat>::builder()
+        .with_endpoint(ch.get_native_url())
+        .with_username(&ch.user)
+        .with_password(&ch.password)
+        .with_sparse_serialization_ratio(0.9)
+        .build_native()
+        .await
+        .expect("Failed to create native client");
+
+    client.execute("DROP TABLE IF EXISTS sparse_insert_test", None).await.unwrap();
+    client
+        .execute(
+            "CREATE TABLE sparse_insert_test (id UInt64, sparse_int Int64, sparse_string String) \
+             ENGINE = MergeTree() ORDER BY id",
+            None,
+        )
+        .await
+        .unwrap();
+
+    let rows = 1000_u64;
+    let sparse_int = |i: u64| if i.is_multiple_of(50) { i as i64 + 1 } else { 0 };
+    let sparse_string =
+        |i: u64| if i.is_multiple_of(100) { format!("value_{i}") } else { String::new() };
+
+    // Arrow insert of the first half
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::UInt64, false),
+        Field::new("sparse_int", DataType::Int64, false),
+        Field::new("sparse_string", DataType::Utf8, false),
+    ]));
+    let ids = 0..rows / 2;
+    let batch = RecordBatch::try_new(schema, vec![
+        Arc::new(UInt64Array::from_iter_values(ids.clone())),
+        Arc::new(Int64Array::from_iter_values(ids.clone().map(sparse_int))),
+        Arc::new(StringArray::from_iter_values(ids.map(sparse_string))),
+    ])
+    .unwrap();
+    let mut insert =
+        client.insert("INSERT INTO sparse_insert_test FORMAT Native", batch, None).await.unwrap();
+    while let Some(result) = insert.next().await {
+        result.expect("Arrow sparse insert");
+    }
+
+    // Native insert of the second half
+    let ids = rows / 2..rows;
+    let block = Block {
+        rows: rows / 2,
+        column_types: vec![
+            ("id".into(), Type::UInt64),
+            ("sparse_int".into(), Type::Int64),
+            ("sparse_string".into(), Type::String),
+        ],
+        column_data: ids
+            .clone()
+            .map(Value::UInt64)
+            .chain(ids.clone().map(|i| Value::Int64(sparse_int(i))))
+            .chain(ids.map(|i| Value::String(sparse_string(i).into_bytes())))
+            .collect(),
+        ..Default::default()
+    };
+    let mut insert =
+        native.insert("INSERT INTO sparse_insert_test FORMAT Native", block, None).await.unwrap();
+    while let Some(result) = insert.next().await {
+        result.expect("Native sparse insert");
+    }
+
+    // Read back through the native format, sparse columns included
+    let mut response = native
+        .query_raw(
+            "SELECT * FROM sparse_insert_test ORDER BY id".to_string(),
+            None::<QueryParams>,
+            Qid::new(),
+        )
+        .await
+        .unwrap();
+    let mut expected = 0_u64;
+    while let Some(block) = response.next().await {
+        let mut block: Block = block.expect("Native block");
+        for row in block.take_iter_rows() {
+            assert_eq!(row[0].2, Value::UInt64(expected));
+            assert_eq!(row[1].2, Value::Int64(sparse_int(expected)));
+            assert_eq!(row[2].2, Value::String(sparse_string(expected).into_bytes()));
+            expected += 1;
+        }
+    }
+    assert_eq!(expected, rows);
+
+    client.execute("DROP TABLE IF EXISTS sparse_insert_test", None).await.unwrap();
+    native.shutdown().await.unwrap();
+    client.shutdown().await.unwrap();
+}
@@ -6,8 +6,8 @@ use arrow::datatypes::*;
 use clickhouse_arrow::prelude::*;
 use clickhouse_arrow::test_utils::ClickHouseContainer;
 use clickhouse_arrow::{
-    ArrowOptions, CompressionMethod, ConnectionStatus, CreateOptions, Result as ClickHouseResult,
-    Type,
+    ArrowOptions, ClickHouseEvent, CompressionMethod, ConnectionStatus, CreateOptions,
+    Result as ClickHouseResult, Type,
 };
 use futures_util::StreamExt;
 use tracing::debug;
@@ -229,6 +229,473 @@ pub async fn test_execute_queries(ch: Arc<ClickHouseContainer>) {
     client.shutdown().await.unwrap();
 }
 
+/// Test cancelling queries, both by dropping the response and explicitly by query id.
+///
+/// # Panics
+pub async fn test_query_cancellation(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap(ch.as_ref(), None).await;
+
+    let query = "SELECT number FROM system.numbers";
+
+    let query_id = Qid::new();
+    header(query_id, "Cancel on drop");
+    let mut results = client.query(query, Some(query_id)).await.unwrap();
+    let batch = results.next().await.expect("Expected data").expect("Expected no error");
+    assert!(batch.num_rows() > 0);
+    drop(results);
+
+    // The connection is drained and remains usable
+    let query_id = Qid::new();
+    header(query_id, "Query after cancel on drop");
+    let column = client
+        .query_column("SELECT 1", Some(query_id))
+        .await
+        .unwrap()
+        .expect("Expected column after cancellation");
+    assert_eq!(column.len(), 1);
+
+    let query_id = Qid::new();
+    header(query_id, "Explicit cancel");
+    let mut results = client.query(query, Some(query_id)).await.unwrap();
+    let _batch = results.next().await.expect("Expected data").expect("Expected no error");
+    client.cancel(query_id).await.unwrap();
+
+    // The unbounded stream terminates once the server acknowledges the cancellation
+    while let Some(result) = results.next().await {
+        if let Err(error) = result {
+            debug!(?error, "Cancelled query ended with error");
+            break;
+        }
+    }
+
+    let query_id = Qid::new();
+    header(query_id, "Query after explicit cancel");
+    let column = client
+        .query_column("SELECT 1", Some(query_id))
+        .await
+        .unwrap()
+        .expect("Expected column after cancellation");
+    assert_eq!(column.len(), 1);
+
+    client.shutdown().await.unwrap();
+}
+
+/// Test reading `WITH TOTALS` and extremes blocks once a response is consumed.
+///
+/// # Panics
+pub async fn test_totals_and_extremes(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap(ch.as_ref(), None).await;
+
+    let query = "SELECT number % 3 AS n, count() AS c FROM numbers(10) GROUP BY n WITH TOTALS \
+                 ORDER BY n SETTINGS extremes = 1";
+
+    let query_id = Qid::new();
+    header(query_id, "Totals and extremes");
+    let mut response = client.query(query, Some(query_id)).await.unwrap();
+    assert!(response.totals().is_none(), "Totals are only available once consumed");
+
+    let mut rows = 0;
+    while let Some(batch) = response.next().await {
+        rows += batch.expect("Expected no error").num_rows();
+    }
+    assert_eq!(rows, 3);
+
+    let totals = response.totals().expect("Expected totals");
+    arrow::util::pretty::print_batches(std::slice::from_ref(&totals)).unwrap();
+    assert_eq!(totals.num_rows(), 1);
+    let counts = totals.column(1).as_primitive::<UInt64Type>();
+    assert_eq!(counts.value(0), 10);
+
+    let extremes = response.extremes().expect("Expected extremes");
+    arrow::util::pretty::print_batches(std::slice::from_ref(&extremes)).unwrap();
+    assert_eq!(extremes.num_rows(), 2);
+    assert!(response.totals_block().is_none());
+
+    // Queries without totals leave them empty
+    let query_id = Qid::new();
+    header(query_id, "No totals");
+    let mut response = client.query("SELECT 1", Some(query_id)).await.unwrap();
+    while let Some(batch) = response.next().await {
+        drop(batch.expect("Expected no error"));
+    }
+    assert!(response.totals().is_none());
+    assert!(response.extremes().is_none());
+
+    // Native clients expose the same blocks
+    let native: NativeClient = Client::<NativeFormat>::builder()
+        .with_endpoint(ch.get_native_url())
+        .with_username(&ch.user)
+        .with_password(&ch.password)
+        .with_ipv4_only(true)
+        .build()
+        .await
+        .expect("Building native client");
+    let query_id = Qid::new();
+    header(query_id, "Native totals and extremes");
+    let mut response =
+        native.query_raw(query.to_string(), None::<QueryParams>, query_id).await.unwrap();
+    while let Some(block) = response.next().await {
+        drop(block.expect("Expected no error"));
+    }
+    let totals = response.totals_block().expect("Expected native totals");
+    assert_eq!(totals.rows, 1);
+    let extremes = response.extremes_block().expect("Expected native extremes");
+    assert_eq!(extremes.rows, 2);
+    assert!(response.totals().is_none());
+
+    native.shutdown().await.unwrap();
+    client.shutdown().await.unwrap();
+}
+
+/// Test reading the profile info of a paginated query, from the response and as an event.
+///
+/// # Panics
+pub async fn test_profile_info(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap(ch.as_ref(), None).await;
+    let mut events = client.subscribe_events();
+
+    let query_id = Qid::new();
+    header(query_id, "Profile info");
+    let query = "SELECT number FROM numbers(1000) WHERE number % 2 = 0 LIMIT 10 OFFSET 20";
+    let mut response = client.query(query, Some(query_id)).await.unwrap();
+    let mut rows = 0;
+    while let Some(batch) = response.next().await {
+        rows += batch.expect("Expected no error").num_rows();
+    }
+    assert_eq!(rows, 10);
+
+    let info = response.profile_info().expect("Expected profile info");
+    debug!(?info, "Profile info");
+    assert!(info.applied_limit);
+    assert_eq!(info.rows_before_limit, 500);
+
+    let mut received = None;
+    while let Ok(event) = events.try_recv() {
+        if let ClickHouseEvent::ProfileInfo(info) = event.event {
+            assert_eq!(event.qid, query_id);
+            received = Some(info);
+        }
+    }
+    assert_eq!(received, Some(info));
+
+    client.shutdown().await.unwrap();
+}
+
+/// Test receiving server logs as events when `send_logs_level` is set per query.
+///
+/// # Panics
+pub async fn test_server_logs(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap_with_options(
+        ch.as_ref(),
+        None,
+        Some(|builder: ClientBuilder| builder.with_trace_server_logs(true)),
+    )
+    .await;
+    let mut events = client.subscribe_events();
+
+    let query_id = Qid::new();
+    header(query_id, "Server logs");
+    let options =
+        QueryOptions::new().with_qid(query_id).with_send_logs_level(ServerLogLevel::Trace);
+    let mut response =
+        client.query_with_options("SELECT count() FROM numbers(1000)", options).await.unwrap();
+    while let Some(batch) = response.next().await {
+        drop(batch.expect("Expected no error"));
+    }
+
+    let mut lines = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        if let ClickHouseEvent::Log(logs) = event.event {
+            assert_eq!(event.qid, query_id);
+            lines.extend(logs);
+        }
+    }
+    assert!(!lines.is_empty(), "Expected server log lines");
+    assert!(lines.iter().all(|log| log.query_id == query_id.to_string()));
+
+    // Without send_logs_level no logs are sent
+    let query_id = Qid::new();
+    header(query_id, "No server logs");
+    client.execute("SELECT 1", Some(query_id)).await.unwrap();
+    while let Ok(event) = events.try_recv() {
+        assert!(!matches!(event.event, ClickHouseEvent::Log(_)), "Unexpected server logs");
+    }
+
+    client.shutdown().await.unwrap();
+}
+
+/// Test settings applied to a single query on top of the client's settings.
+///
+/// # Panics
+pub async fn test_query_settings(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap(ch.as_ref(), None).await;
+
+    let query_id = Qid::new();
+    header(query_id, "Per-query settings");
+    let query = "SELECT toUInt64(getSetting('max_threads')) AS max_threads";
+    let options = QueryOptions::new().with_qid(query_id).with_setting("max_threads", 3);
+    let batches = client
+        .query_with_options(query, options)
+        .await
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<ClickHouseResult<Vec<_>>>()
+        .unwrap();
+    let column = batches[0].column(0).as_primitive::<UInt64Type>();
+    assert_eq!(column.value(0), 3);
+
+    // Settings are not carried over to the next query
+    let query_id = Qid::new();
+    header(query_id, "Client settings");
+    let batches = client
+        .query(query, Some(query_id))
+        .await
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<ClickHouseResult<Vec<_>>>()
+        .unwrap();
+    let column = batches[0].column(0).as_primitive::<UInt64Type>();
+    assert_ne!(column.value(0), 3);
+
+    // Settings are applied to statements too
+    let query_id = Qid::new();
+    header(query_id, "Execute with settings");
+    let options = QueryOptions::new()
+        .with_qid(query_id)
+        .with_setting("max_result_rows", 1)
+        .with_setting("result_overflow_mode", "throw");
+    let result = client.execute_with_options("SELECT number FROM numbers(10)", options).await;
+    assert!(result.is_err(), "Expected max_result_rows to be exceeded");
+
+    client.shutdown().await.unwrap();
+}
+
+/// Test requesting the replication status of tables, also while a query is executing.
+///
+/// # Panics
+pub async fn test_tables_status(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap(ch.as_ref(), None).await;
+
+    let query_id = Qid::new();
+    header(query_id, "Tables status");
+    client
+        .execute(
+            "CREATE TABLE IF NOT EXISTS default.status_test (id UInt64) ENGINE = MergeTree ORDER \
+             BY id",
+            Some(query_id),
+        )
+        .await
+        .unwrap();
+
+    let tables = [("default", "status_test"), ("default", "status_missing")];
+    let statuses = client.tables_status(&tables).await.unwrap();
+    assert_eq!(statuses, vec![TableStatus {
+        database: "default".into(),
+        table: "status_test".into(),
+        ..Default::default()
+    }]);
+
+    // Requests wait for the executing query
+    let query_id = Qid::new();
+    header(query_id, "Tables status during query");
+    let query = "SELECT number FROM numbers(100000)";
+    let response = client.query(query, Some(query_id)).await.unwrap();
+    let (rows, statuses) = tokio::join!(
+        response.map(|batch| batch.unwrap().num_rows()).collect::<Vec<_>>(),
+        client.tables_status(&tables)
+    );
+    assert_eq!(rows.into_iter().sum::<usize>(), 100_000);
+    assert_eq!(statuses.unwrap().len(), 1);
+
+    // The connection is usable afterwards
+    let query_id = Qid::new();
+    client.execute("DROP TABLE default.status_test", Some(query_id)).await.unwrap();
+    client.shutdown().await.unwrap();
+}
+
+/// Test sending external tables with a query instead of inlining values into the query text.
+///
+/// # Panics
+pub async fn test_external_tables(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap(ch.as_ref(), None).await;
+
+    let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::UInt64, false)]));
+    let ids = |range: std::ops::Range<u64>| {
+        let column = UInt64Array::from_iter_values(range.filter(|id| id % 2 == 0));
+        RecordBatch::try_new(Arc::clone(&schema), vec![Arc::new(column)]).unwrap()
+    };
+
+    let query_id = Qid::new();
+    header(query_id, "External table");
+    let query = "SELECT count() AS c FROM numbers(100000) WHERE number IN ids";
+    let options = QueryOptions::new().with_qid(query_id);
+    let batches = client
+        .query_with_external_tables(query, [("ids", ids(0..100_000))], options)
+        .await
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<ClickHouseResult<Vec<_>>>()
+        .unwrap();
+    assert_eq!(batches[0].column(0).as_primitive::<UInt64Type>().value(0), 50_000);
+
+    // Batches with the same name are appended, several tables can be joined
+    let query_id = Qid::new();
+    header(query_id, "Appended and joined external tables");
+    let query = "SELECT count() AS c FROM ids JOIN other USING (id)";
+    let tables = [("ids", ids(0..10)), ("ids", ids(10..20)), ("other", ids(5..15))];
+    let options = QueryOptions::new().with_qid(query_id);
+    let batches = client
+        .query_with_external_tables(query, tables, options)
+        .await
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<ClickHouseResult<Vec<_>>>()
+        .unwrap();
+    assert_eq!(batches[0].column(0).as_primitive::<UInt64Type>().value(0), 5);
+
+    // Empty names would end the external tables early
+    let result = client
+        .query_with_external_tables("SELECT 1", [("", ids(0..2))], QueryOptions::new())
+        .await;
+    assert!(result.is_err());
+
+    // Tables that fail to serialize only fail their query
+    let schema = Arc::new(Schema::new(vec![Field::new("v", DataType::Null, true)]));
+    let column = NullArray::new(1);
+    let batch = RecordBatch::try_new(schema, vec![Arc::new(column)]).unwrap();
+    let result = client
+        .query_with_external_tables("SELECT count() FROM t", [("t", batch)], QueryOptions::new())
+        .await;
+    assert!(result.is_err());
+
+    // The connection is usable afterwards
+    let query_id = Qid::new();
+    client.execute("SELECT 1", Some(query_id)).await.unwrap();
+    client.shutdown().await.unwrap();
+}
+
+/// Test continuing a W3C trace context server side, recorded in `system.opentelemetry_span_log`.
+///
+/// # Panics
+pub async fn test_trace_parent(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap(ch.as_ref(), None).await;
+
+    let trace: TraceParent =
+        "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01".parse().unwrap();
+    let query_id = Qid::new();
+    header(query_id, "Query with trace parent");
+    let options = QueryOptions::new().with_qid(query_id).with_trace_parent(trace);
+    client.execute_with_options("SELECT 1", options).await.unwrap();
+
+    client.execute("SYSTEM FLUSH LOGS", None).await.unwrap();
+    // The server's spans continue the trace, the first one is a child of the parent span
+    let query = format!(
+        "SELECT count(), countIf(parent_span_id = {}) FROM system.opentelemetry_span_log WHERE \
+         trace_id = toUUID('0af76519-16cd-43dd-8448-eb211c80319c')",
+        0xb7ad_6b71_6920_3331_u64
+    );
+    let batches = client
+        .query(query, None)
+        .await
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<ClickHouseResult<Vec<_>>>()
+        .unwrap();
+    assert!(batches[0].column(0).as_primitive::<UInt64Type>().value(0) > 0);
+    assert_eq!(batches[0].column(1).as_primitive::<UInt64Type>().value(0), 1);
+    client.shutdown().await.unwrap();
+}
+
+/// Test streaming inserts with an `Inserter`, committed and aborted.
+///
+/// # Panics
+pub async fn test_inserter(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap(ch.as_ref(), None).await;
+
+    let table_qid = Qid::new();
+    let table_name = format!("test_inserter_{table_qid}");
+    let query_id = Qid::new();
+    header(query_id, format!("Creating table: {table_name}"));
+    client
+        .execute(format!("CREATE TABLE {table_name} (id UInt64) ENGINE = Memory"), Some(query_id))
+        .await
+        .expect("Failed to create table");
+
+    let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::UInt64, false)]));
+    let batch = |start: u64| {
+        let ids = UInt64Array::from_iter_values(start..start + 10);
+        RecordBatch::try_new(Arc::clone(&schema), vec![Arc::new(ids)]).unwrap()
+    };
+    let count = async |client: &ArrowClient| {
+        let batches = client
+            .query(format!("SELECT count() FROM {table_name}"), None)
+            .await
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<ClickHouseResult<Vec<_>>>()
+            .unwrap();
+        batches[0].column(0).as_primitive::<UInt64Type>().value(0)
+    };
+
+    // Flushes every 25 rows, the remainder is sent on commit
+    let query_id = Qid::new();
+    header(query_id, "Inserter commit");
+    let options = InserterOptions::default()
+        .with_max_rows(Some(25))
+        .with_query_options(QueryOptions::new().with_qid(query_id));
+    let insert = format!("INSERT INTO {table_name} FORMAT Native");
+    let mut inserter = client.inserter(&insert, options).await.expect("Opening inserter");
+    let batches = futures_util::stream::iter((0..10).map(|i| Ok(batch(i * 10))));
+    inserter.write_stream(batches).await.expect("Writing stream");
+    assert_eq!(inserter.rows_sent(), 90);
+    assert_eq!(inserter.pending_rows(), 10);
+    inserter.commit().await.expect("Committing inserter");
+    assert_eq!(count(&client).await, 100);
+
+    // Aborting discards data that was never flushed
+    let query_id = Qid::new();
+    header(query_id, "Inserter abort");
+    let options = InserterOptions::default()
+        .with_max_rows(None)
+        .with_max_bytes(None)
+        .with_query_options(QueryOptions::new().with_qid(query_id));
+    let mut inserter = client.inserter(&insert, options).await.expect("Opening inserter");
+    inserter.write(batch(100)).await.unwrap();
+    inserter.abort().await.expect("Aborting inserter");
+    assert_eq!(count(&client).await, 100);
+
+    // Dropping an inserter cancels it and leaves the connection usable
+    let query_id = Qid::new();
+    header(query_id, "Inserter drop");
+    let options =
+        InserterOptions::default().with_query_options(QueryOptions::new().with_qid(query_id));
+    let mut inserter = client.inserter(&insert, options).await.expect("Opening inserter");
+    inserter.write(batch(200)).await.unwrap();
+    drop(inserter);
+    assert_eq!(count(&client).await, 100);
+
+    let query_id = Qid::new();
+    header(query_id, format!("Dropping table: {table_name}"));
+    client
+        .execute(format!("DROP TABLE {table_name}"), Some(query_id))
+        .await
+        .expect("Failed to drop table");
+
+    client.shutdown().await.unwrap();
+}
+
 /// Test named tuple field parsing (issue #85)
 /// `ClickHouse` supports `Tuple(name1 Type1, name2 Type2)` syntax which was not being parsed
 /// correctly.
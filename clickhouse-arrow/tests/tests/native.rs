@@ -1,5 +1,6 @@
 use std::sync::Arc;
 
+use clickhouse_arrow::native::block::Block;
 use clickhouse_arrow::prelude::*;
 use clickhouse_arrow::test_utils::ClickHouseContainer;
 use clickhouse_arrow::{CompressionMethod, CreateOptions, Result as ClickHouseResult};
@@ -130,3 +131,42 @@ pub async fn round_trip<T: Row + std::fmt::Debug + PartialEq + Clone + Send + Sy
 
     Ok(())
 }
+
+#[cfg(feature = "derive")]
+#[derive(Debug, Clone, PartialEq, Row)]
+struct IdRow {
+    id: u64,
+}
+
+/// # Panics
+#[cfg(feature = "derive")]
+pub async fn test_external_tables(ch: Arc<ClickHouseContainer>) {
+    let client: NativeClient = ClientBuilder::new()
+        .with_endpoint(ch.get_native_url())
+        .with_username(&ch.user)
+        .with_password(&ch.password)
+        .with_ipv4_only(true)
+        .build()
+        .await
+        .expect("Building client");
+
+    let rows = (0..10).map(|id| IdRow { id }).collect::<Vec<_>>();
+    let block = Block::from_rows(rows, vec![("id".into(), Type::UInt64)]).unwrap();
+
+    let query_id = Qid::new();
+    header(query_id, "Native external table");
+    let query = "SELECT id FROM ids WHERE id % 3 = 0 ORDER BY id";
+    let options = QueryOptions::new().with_qid(query_id);
+    let rows = client
+        .query_with_external_tables::<IdRow>(query, [("ids", block)], options)
+        .await
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<ClickHouseResult<Vec<_>>>()
+        .unwrap();
+    assert_eq!(rows, vec![IdRow { id: 0 }, IdRow { id: 3 }, IdRow { id: 6 }, IdRow { id: 9 }]);
+
+    client.shutdown().await.unwrap();
+}
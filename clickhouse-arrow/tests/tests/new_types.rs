@@ -1,6 +1,6 @@
 //! E2E integration tests for new `ClickHouse` types introduced in v24+
 //!
-//! Tests: `BFloat16`, Variant, Dynamic, Nested, Time/Time64
+//! Tests: `BFloat16`, Variant, Dynamic, JSON, Nested, Time/Time64
 //!
 //! These tests verify round-trip serialization/deserialization against a real `ClickHouse`
 //! instance. Uses `ArrowClient` for querying since it handles arbitrary result types better.
@@ -14,7 +14,7 @@ use std::sync::Arc;
 use arrow::record_batch::RecordBatch;
 use clickhouse_arrow::prelude::*;
 use clickhouse_arrow::test_utils::ClickHouseContainer;
-use clickhouse_arrow::{CompressionMethod, Result as ClickHouseResult};
+use clickhouse_arrow::{ArrowOptions, CompressionMethod, Result as ClickHouseResult};
 use futures_util::StreamExt;
 use tracing::{debug, info};
 
@@ -22,6 +22,18 @@ use crate::common::header;
 
 /// Helper to create an `ArrowClient` for testing
 async fn create_client(ch: &ClickHouseContainer) -> ArrowClient {
+    client_builder(ch).build().await.expect("Building client")
+}
+
+/// Helper to create an `ArrowClient` with the given Arrow options for testing
+async fn create_client_with_options(
+    ch: &ClickHouseContainer,
+    options: ArrowOptions,
+) -> ArrowClient {
+    client_builder(ch).with_arrow_options(options).build().await.expect("Building client")
+}
+
+fn client_builder(ch: &ClickHouseContainer) -> ClientBuilder {
     let native_url = ch.get_native_url();
     debug!("ClickHouse Native URL: {native_url}");
 
@@ -31,9 +43,6 @@ async fn create_client(ch: &ClickHouseContainer) -> ArrowClient {
         .with_password(&ch.password)
         .with_ipv4_only(true)
         .with_compression(CompressionMethod::LZ4)
-        .build()
-        .await
-        .expect("Building client")
 }
 
 /// Helper to query and collect results
@@ -296,6 +305,123 @@ pub async fn test_dynamic_basic(ch: Arc<ClickHouseContainer>) {
     info!("Dynamic basic test passed!");
 }
 
+/// Test Dynamic read as an Arrow dense union, and inserted from one
+pub async fn test_dynamic_union(ch: Arc<ClickHouseContainer>) {
+    use arrow::array::{
+        Array, ArrayRef, AsArray, Int64Array, StringArray, UInt32Array, UnionArray,
+    };
+    use arrow::buffer::ScalarBuffer;
+    use arrow::datatypes::{DataType, Field, Int64Type, Schema, UnionFields};
+    use clickhouse_arrow::arrow::block::{DYNAMIC_NULL_FIELD_NAME, DYNAMIC_TYPES_METADATA_KEY};
+
+    let options = ArrowOptions::new().with_strings_as_strings(true).with_dynamic_as_union(true);
+    let client = create_client_with_options(&ch, options).await;
+
+    let qid = Qid::new();
+    let db = format!("test_dynamic_union_{qid}");
+    let table = "dynamic_union_test";
+
+    header(qid, format!("Creating database: {db}"));
+    client.execute(format!("CREATE DATABASE IF NOT EXISTS {db}"), Some(qid)).await.unwrap();
+
+    header(qid, "Creating Dynamic table");
+    let create_sql = format!(
+        "CREATE TABLE {db}.{table} (
+            id UInt32,
+            dyn_col Dynamic
+        ) ENGINE = MergeTree() ORDER BY id
+        SETTINGS allow_experimental_dynamic_type = 1"
+    );
+    client.execute(&create_sql, Some(qid)).await.unwrap();
+
+    header(qid, "Inserting Dynamic test data");
+    let insert_sql =
+        format!("INSERT INTO {db}.{table} VALUES (1, 42::Int64), (2, 'text'::String), (3, NULL)");
+    client.execute(&insert_sql, Some(qid)).await.unwrap();
+
+    header(qid, "Inserting Dynamic union through Arrow");
+    let fields = [
+        (0, Arc::new(Field::new("Int64", DataType::Int64, true))),
+        (1, Arc::new(Field::new("String", DataType::Utf8, true))),
+    ]
+    .into_iter()
+    .collect::<UnionFields>();
+    let union = UnionArray::try_new(
+        fields,
+        ScalarBuffer::from(vec![0, 1]),
+        Some(ScalarBuffer::from(vec![0, 0])),
+        vec![Arc::new(Int64Array::from(vec![7])), Arc::new(StringArray::from(vec!["arrow"]))],
+    )
+    .unwrap();
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::UInt32, false),
+        Field::new("dyn_col", union.data_type().clone(), true),
+    ]));
+    let batch = RecordBatch::try_new(schema, vec![
+        Arc::new(UInt32Array::from(vec![4, 5])) as ArrayRef,
+        Arc::new(union),
+    ])
+    .unwrap();
+    let query = format!("INSERT INTO {db}.{table} FORMAT Native");
+    drop(
+        client
+            .insert(&query, batch, Some(qid))
+            .await
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<ClickHouseResult<Vec<_>>>()
+            .unwrap(),
+    );
+
+    header(qid, "Querying Dynamic as union");
+    let query = format!("SELECT dyn_col FROM {db}.{table} ORDER BY id");
+    let batches = query_all(&client, &query, qid).await;
+    assert_eq!(count_rows(&batches), 5, "Expected 5 rows");
+
+    let mut ints = vec![];
+    let mut strings = vec![];
+    let mut nulls = 0;
+    for batch in &batches {
+        let field = batch.schema().field(0).clone();
+        assert!(field.metadata().contains_key(DYNAMIC_TYPES_METADATA_KEY));
+        let column = batch.column(0).as_union();
+        for row in 0..column.len() {
+            let value = column.value(row);
+            let name = field_name(column, row);
+            match name.as_str() {
+                "Int64" => ints.push(value.as_primitive::<Int64Type>().value(0)),
+                "String" => strings.push(value.as_string::<i32>().value(0).to_string()),
+                DYNAMIC_NULL_FIELD_NAME => nulls += 1,
+                other => panic!("Unexpected Dynamic type {other}"),
+            }
+        }
+    }
+    ints.sort_unstable();
+    strings.sort();
+    assert_eq!(ints, vec![7, 42]);
+    assert_eq!(strings, vec!["arrow", "text"]);
+    assert_eq!(nulls, 1);
+
+    // Cleanup
+    client.execute(format!("DROP TABLE {db}.{table}"), None).await.unwrap();
+    client.execute(format!("DROP DATABASE {db}"), None).await.unwrap();
+
+    info!("Dynamic union test passed!");
+}
+
+/// Name of the union field holding a row
+fn field_name(union: &arrow::array::UnionArray, row: usize) -> String {
+    use arrow::array::Array;
+
+    let arrow::datatypes::DataType::Union(fields, _) = union.data_type() else {
+        panic!("Expected union");
+    };
+    let type_id = union.type_id(row);
+    fields.iter().find(|(id, _)| *id == type_id).map(|(_, f)| f.name().clone()).unwrap()
+}
+
 /// Test Dynamic with `max_types` limit
 ///
 /// Note: Dynamic Arrow deserialization returns data as UTF-8 strings.
@@ -348,6 +474,89 @@ pub async fn test_dynamic_max_types(ch: Arc<ClickHouseContainer>) {
     info!("Dynamic max_types test passed!");
 }
 
+// =============================================================================
+// JSON Tests
+// =============================================================================
+
+/// Test JSON with typed paths: SQL inserts and Arrow inserts read back as a struct of the typed
+/// paths and the remaining paths as JSON text.
+pub async fn test_json_basic(ch: Arc<ClickHouseContainer>) {
+    use arrow::array::{Array, ArrayRef, AsArray, StringArray, UInt32Array};
+    use arrow::datatypes::{DataType, Field, Schema, UInt32Type};
+    use clickhouse_arrow::arrow::block::JSON_DYNAMIC_FIELD_NAME;
+
+    let client = create_client(&ch).await;
+
+    let qid = Qid::new();
+    let db = format!("test_json_{qid}");
+    let table = "json_test";
+
+    header(qid, format!("Creating database: {db}"));
+    client.execute(format!("CREATE DATABASE IF NOT EXISTS {db}"), Some(qid)).await.unwrap();
+
+    header(qid, "Creating JSON table");
+    let create_sql = format!(
+        "CREATE TABLE {db}.{table} (
+            id UInt32,
+            doc JSON(max_dynamic_paths=2, a.b UInt32, SKIP skipped)
+        ) ENGINE = MergeTree() ORDER BY id
+        SETTINGS allow_experimental_json_type = 1"
+    );
+    client.execute(&create_sql, Some(qid)).await.unwrap();
+
+    header(qid, "Inserting JSON test data");
+    let insert_sql = format!(
+        r#"INSERT INTO {db}.{table} VALUES
+        (1, '{{"a":{{"b":1}},"c":"x","skipped":1}}'),
+        (2, '{{"c":"y","d":[1,2],"e":{{"f":true}}}}')"#
+    );
+    client.execute(&insert_sql, Some(qid)).await.unwrap();
+
+    header(qid, "Inserting JSON through Arrow");
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::UInt32, false),
+        Field::new("doc", DataType::Utf8, false),
+    ]));
+    let batch = RecordBatch::try_new(schema, vec![
+        Arc::new(UInt32Array::from(vec![3])) as ArrayRef,
+        Arc::new(StringArray::from(vec![r#"{"a":{"b":3},"g":1.5}"#])),
+    ])
+    .unwrap();
+    let query = format!("INSERT INTO {db}.{table} FORMAT Native");
+    drop(
+        client
+            .insert(&query, batch, Some(qid))
+            .await
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<ClickHouseResult<Vec<_>>>()
+            .unwrap(),
+    );
+
+    header(qid, "Querying JSON");
+    let query = format!("SELECT doc FROM {db}.{table} ORDER BY id");
+    let batches = query_all(&client, &query, qid).await;
+    let batch = arrow::compute::concat_batches(&batches[0].schema(), &batches).unwrap();
+    assert_eq!(batch.num_rows(), 3);
+
+    let doc = batch.column(0).as_struct();
+    let typed = doc.column_by_name("a.b").unwrap().as_primitive::<UInt32Type>();
+    assert_eq!(typed.values(), &[1, 0, 3]);
+    let remainder = doc.column_by_name(JSON_DYNAMIC_FIELD_NAME).unwrap().as_string::<i32>();
+    assert_eq!(remainder.value(0), r#"{"c":"x"}"#);
+    assert_eq!(remainder.value(1), r#"{"c":"y","d":[1,2],"e":{"f":true}}"#);
+    assert_eq!(remainder.value(2), r#"{"g":1.5}"#);
+    assert_eq!(remainder.null_count(), 0);
+
+    // Cleanup
+    client.execute(format!("DROP TABLE {db}.{table}"), None).await.unwrap();
+    client.execute(format!("DROP DATABASE {db}"), None).await.unwrap();
+
+    info!("JSON basic test passed!");
+}
+
 // =============================================================================
 // Nested Tests
 // =============================================================================
@@ -384,3 +384,69 @@ pub async fn test_params_mixed_types(ch: Arc<ClickHouseContainer>) {
 
     header(query_id, "Mixed parameter types test completed");
 }
+
+/// Test typed parameters: arrays, maps, tuples, NULL, dates, decimals, UUIDs and IPs
+///
+/// Each comparison is checked server side with `throwIf`, so a value that is rendered
+/// incorrectly either fails to parse or fails the query.
+///
+/// # Panics
+pub async fn test_params_typed(ch: Arc<ClickHouseContainer>) {
+    use std::collections::BTreeMap;
+    use std::net::Ipv4Addr;
+
+    use chrono::{NaiveDate, TimeZone, Utc};
+
+    let native_url = ch.get_native_url();
+    debug!("ClickHouse Native URL: {native_url}");
+
+    let client: NativeClient = ClientBuilder::new()
+        .with_endpoint(native_url)
+        .with_username(&ch.user)
+        .with_password(&ch.password)
+        .with_ipv4_only(true)
+        .build()
+        .await
+        .expect("Building client");
+
+    let query_id = Qid::new();
+    header(query_id, "Testing typed parameters");
+
+    let uuid = clickhouse_arrow::Uuid::new_v4();
+    let params = QueryParams::from(vec![
+        ("ids", ParamValue::from(vec![1_u64, 2, 3])),
+        ("names", ParamValue::from(vec![Some("it's"), None, Some("back\\slash")])),
+        ("missing", ParamValue::from(None::<String>)),
+        ("text", ParamValue::from("tab\there")),
+        ("pairs", ParamValue::from(BTreeMap::from([("a", 1_u8), ("b", 2)]))),
+        ("tuple", ParamValue::from((7_i32, "x'y", vec![1.5_f64]))),
+        ("date", ParamValue::from(NaiveDate::from_ymd_opt(2024, 2, 29).unwrap())),
+        ("ts", ParamValue::from(Utc.timestamp_millis_opt(1_700_000_000_123).unwrap())),
+        ("dec", ParamValue::Decimal { value: -12345, scale: 2 }),
+        ("uuid", ParamValue::from(uuid)),
+        ("ip", ParamValue::from(Ipv4Addr::LOCALHOST)),
+        ("nested", ParamValue::from(vec![vec![(1_u8, "a")], vec![]])),
+    ]);
+    let query = format!(
+        "SELECT throwIf(NOT (
+            {{ids:Array(UInt64)}} = [1, 2, 3]
+            AND {{names:Array(Nullable(String))}} = ['it\\'s', NULL, 'back\\\\slash']
+            AND {{missing:Nullable(String)}} IS NULL
+            AND {{text:String}} = 'tab\\there'
+            AND {{pairs:Map(String, UInt8)}} = map('a', 1, 'b', 2)
+            AND {{tuple:Tuple(Int32, String, Array(Float64))}} = (7, 'x\\'y', [1.5])
+            AND {{date:Date}} = toDate('2024-02-29')
+            AND {{ts:DateTime64(3, 'UTC')}} = toDateTime64('2023-11-14 22:13:20.123', 3, 'UTC')
+            AND {{dec:Decimal(10, 2)}} = toDecimal64('-123.45', 2)
+            AND {{uuid:UUID}} = toUUID('{uuid}')
+            AND {{ip:IPv4}} = toIPv4('127.0.0.1')
+            AND {{nested:Array(Array(Tuple(UInt8, String)))}} = [[(1, 'a')], []]
+        ), 'Typed parameters did not round trip')"
+    );
+    client
+        .execute_params(query, Some(params), Some(query_id))
+        .await
+        .expect("Typed params should be parsed by the server");
+
+    header(query_id, "Typed parameter test completed");
+}